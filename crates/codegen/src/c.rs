@@ -0,0 +1,388 @@
+use std::fmt::Write;
+
+use vmm_ir::{
+	BlockInstruction, CellWidth, EofPolicy, HasIo as _, Instruction, Offset, ScaleAnd,
+	SuperInstruction,
+};
+use vmm_utils::GetOrZero as _;
+
+use super::{Codegen, CodegenError, CodegenOptions, writer::CodeWriter};
+
+const CURRENT: &str = "tape[ptr]";
+
+/// Emits a program as a single C translation unit, with `main` running the program on stdin and
+/// stdout.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CCodegen {
+	options: CodegenOptions,
+}
+
+impl CCodegen {
+	#[must_use]
+	pub const fn new(options: CodegenOptions) -> Self {
+		Self { options }
+	}
+}
+
+impl Codegen for CCodegen {
+	fn options(&self) -> CodegenOptions {
+		self.options
+	}
+
+	fn emit(&self, program: &[Instruction], out: &mut dyn Write) -> Result<(), CodegenError> {
+		if matches!(self.options.tape_len, 0) {
+			return Err(CodegenError::EmptyTape);
+		}
+
+		let mut emitter = Emitter {
+			w: CodeWriter::new(out, "\t"),
+			options: self.options,
+		};
+
+		emitter.preamble(program.has_read())?;
+
+		emitter.w.line(format_args!("int main(void) {{"))?;
+		emitter.w.indent();
+
+		emitter.block(program)?;
+
+		emitter.w.line(format_args!("return 0;"))?;
+		emitter.w.dedent();
+		emitter.w.line(format_args!("}}"))?;
+
+		Ok(())
+	}
+}
+
+struct Emitter<'a> {
+	w: CodeWriter<'a>,
+	options: CodegenOptions,
+}
+
+impl Emitter<'_> {
+	fn preamble(&mut self, has_read: bool) -> Result<(), CodegenError> {
+		let cell_type = match self.options.cell_width {
+			CellWidth::U8 => "uint8_t",
+			CellWidth::U16 => "uint16_t",
+			CellWidth::U32 => "uint32_t",
+		};
+
+		let on_eof = match self.options.eof {
+			EofPolicy::Zero => "\t} else {\n\t\ttape[ptr] = 0;\n",
+			EofPolicy::Max => "\t} else {\n\t\ttape[ptr] = (cell)-1;\n",
+			EofPolicy::Unchanged => "",
+		};
+
+		self.w.raw("#include <stdint.h>\n#include <stdio.h>\n\n")?;
+		self.w
+			.line(format_args!("#define TAPE_LEN {}", self.options.tape_len))?;
+		self.w.blank()?;
+		self.w.line(format_args!("typedef {cell_type} cell;"))?;
+		self.w.raw(concat!(
+			"\n",
+			"static cell tape[TAPE_LEN];\n",
+			"static size_t ptr;\n",
+			"\n",
+			"/* The index of the cell `offset` cells after the pointer, wrapping around the tape. */\n",
+			"static size_t at(size_t offset) {\n",
+			"\tsize_t index = ptr + offset;\n",
+			"\treturn index >= TAPE_LEN ? index - TAPE_LEN : index;\n",
+			"}\n",
+			"\n",
+		))?;
+
+		if has_read {
+			self.w.raw(concat!(
+				"static void read_cell(void) {\n",
+				"\tint c = getchar();\n",
+				"\n",
+				"\tif (c != EOF) {\n",
+				"\t\ttape[ptr] = (cell)c;\n",
+			))?;
+			self.w.raw(on_eof)?;
+			self.w.raw("\t}\n}\n\n")?;
+		}
+
+		Ok(())
+	}
+
+	fn block(&mut self, instructions: &[Instruction]) -> Result<(), CodegenError> {
+		instructions
+			.iter()
+			.try_for_each(|instr| self.instruction(instr))
+	}
+
+	fn instruction(&mut self, instr: &Instruction) -> Result<(), CodegenError> {
+		match instr {
+			Instruction::Boundary => {}
+			Instruction::IncVal { value, offset } => {
+				let cell = self.cell(*offset);
+				let value = self.options.cell_width.wrap_signed(*value);
+
+				match value {
+					0 => {}
+					1.. => self.w.line(format_args!("{cell} += {value}u;"))?,
+					_ => self
+						.w
+						.line(format_args!("{cell} -= {}u;", value.unsigned_abs()))?,
+				}
+			}
+			Instruction::SetVal { value, offset } => {
+				let cell = self.cell(*offset);
+				let value = self.constant(value.get_or_zero());
+
+				self.w.line(format_args!("{cell} = {value};"))?;
+			}
+			Instruction::SubCell { offset } => {
+				if self.is_current(*offset) {
+					self.w
+						.line(format_args!("{CURRENT} = (cell)(0u - {CURRENT});"))?;
+				} else {
+					let cell = self.cell(*offset);
+
+					self.w.line(format_args!("{cell} -= {CURRENT};"))?;
+					self.w.line(format_args!("{CURRENT} = 0;"))?;
+				}
+			}
+			Instruction::ScaleVal { factor } => {
+				let scaled = self.scaled(CURRENT, *factor);
+
+				if scaled != CURRENT {
+					self.w.line(format_args!("{CURRENT} = {scaled};"))?;
+				}
+			}
+			Instruction::MoveVal(offset) => self.scale_and_move(*offset, 1)?,
+			Instruction::FetchVal(offset) => self.scale_and_fetch(*offset, 1)?,
+			Instruction::TakeVal(offset) => self.scale_and_take(*offset, 1)?,
+			Instruction::ReplaceVal(offset) => {
+				if !self.is_current(*offset) {
+					let cell = self.cell(*offset);
+
+					self.w.line(format_args!("{CURRENT} = {cell};"))?;
+					self.w.line(format_args!("{cell} = 0;"))?;
+				}
+			}
+			Instruction::MovePtr(offset) => self.move_ptr(*offset)?,
+			Instruction::FindZero(jump_by) => self.find_zero(*jump_by)?,
+			Instruction::Read => self.w.line(format_args!("read_cell();"))?,
+			Instruction::Write { offset } => {
+				let cell = self.cell(*offset);
+
+				if matches!(self.options.cell_width, CellWidth::U8) {
+					self.w.line(format_args!("putchar({cell});"))?;
+				} else {
+					self.w
+						.line(format_args!("putchar((unsigned char){cell});"))?;
+				}
+			}
+			Instruction::Block(BlockInstruction::DynamicLoop(body)) => {
+				self.while_nz(|this| this.block(body))?;
+			}
+			Instruction::Block(BlockInstruction::IfNz(body)) => {
+				self.w.line(format_args!("if ({CURRENT}) {{"))?;
+				self.w.indent();
+
+				self.block(body)?;
+				self.w.line(format_args!("{CURRENT} = 0;"))?;
+
+				self.w.dedent();
+				self.w.line(format_args!("}}"))?;
+			}
+			Instruction::Super(s) => self.super_instruction(*s)?,
+			i => return Err(CodegenError::Unsupported(i.clone())),
+		}
+
+		Ok(())
+	}
+
+	fn super_instruction(&mut self, instr: SuperInstruction) -> Result<(), CodegenError> {
+		match instr {
+			SuperInstruction::ScaleAnd {
+				action,
+				offset,
+				factor,
+			} => match action {
+				ScaleAnd::Move => self.scale_and_move(offset, factor)?,
+				ScaleAnd::Fetch => self.scale_and_fetch(offset, factor)?,
+				ScaleAnd::Take => self.scale_and_take(offset, factor)?,
+				ScaleAnd::Set(value) => {
+					let scaled = self.scaled(CURRENT, factor);
+					let value = self.constant(value.get());
+
+					if self.is_current(offset) {
+						self.w
+							.line(format_args!("{CURRENT} = (cell)({value} + {scaled});"))?;
+					} else {
+						let cell = self.cell(offset);
+
+						self.w.line(format_args!("{cell} += {scaled};"))?;
+						self.w.line(format_args!("{CURRENT} = {value};"))?;
+					}
+				}
+				_ => return Err(CodegenError::Unsupported(instr.into())),
+			},
+			SuperInstruction::FindAndSetZero { offset, value } => {
+				self.find_zero(offset)?;
+
+				let value = self.constant(value.get());
+				self.w.line(format_args!("{CURRENT} = {value};"))?;
+			}
+			SuperInstruction::SetUntilZero { value, offset } => {
+				let value = self.constant(value.get_or_zero());
+
+				self.while_nz(|this| {
+					this.w.line(format_args!("{CURRENT} = {value};"))?;
+					this.move_ptr(offset)
+				})?;
+			}
+			SuperInstruction::FindCellByZero { jump_by, offset } => {
+				self.find_zero(jump_by)?;
+				self.move_ptr(offset)?;
+			}
+			SuperInstruction::ShiftVals { jump_by, offset } => {
+				self.while_nz(|this| {
+					this.scale_and_move(offset, 1)?;
+					this.move_ptr(jump_by)
+				})?;
+			}
+			i => return Err(CodegenError::Unsupported(i.into())),
+		}
+
+		Ok(())
+	}
+
+	fn scale_and_move(&mut self, offset: Offset, factor: u32) -> Result<(), CodegenError> {
+		let scaled = self.scaled(CURRENT, factor);
+
+		if self.is_current(offset) {
+			if scaled != CURRENT {
+				self.w.line(format_args!("{CURRENT} = {scaled};"))?;
+			}
+		} else {
+			let cell = self.cell(offset);
+
+			self.w.line(format_args!("{cell} += {scaled};"))?;
+			self.w.line(format_args!("{CURRENT} = 0;"))?;
+		}
+
+		Ok(())
+	}
+
+	fn scale_and_fetch(&mut self, offset: Offset, factor: u32) -> Result<(), CodegenError> {
+		if self.is_current(offset) {
+			return self.scale_and_move(offset, factor);
+		}
+
+		let cell = self.cell(offset);
+		let scaled = self.scaled(&cell, factor);
+
+		self.w.line(format_args!("{CURRENT} += {scaled};"))?;
+		self.w.line(format_args!("{cell} = 0;"))?;
+
+		Ok(())
+	}
+
+	fn scale_and_take(&mut self, offset: Offset, factor: u32) -> Result<(), CodegenError> {
+		self.scale_and_move(offset, factor)?;
+		self.move_ptr(offset)
+	}
+
+	fn find_zero(&mut self, jump_by: Offset) -> Result<(), CodegenError> {
+		self.while_nz(|this| this.move_ptr(jump_by))
+	}
+
+	fn move_ptr(&mut self, offset: Offset) -> Result<(), CodegenError> {
+		let offset = self.options.wrap_offset(offset);
+
+		if !matches!(offset, 0) {
+			self.w.line(format_args!("ptr = at({offset});"))?;
+		}
+
+		Ok(())
+	}
+
+	fn while_nz(
+		&mut self,
+		body: impl FnOnce(&mut Self) -> Result<(), CodegenError>,
+	) -> Result<(), CodegenError> {
+		self.w.line(format_args!("while ({CURRENT}) {{"))?;
+		self.w.indent();
+
+		body(self)?;
+
+		self.w.dedent();
+		self.w.line(format_args!("}}"))?;
+
+		Ok(())
+	}
+
+	const fn is_current(&self, offset: Offset) -> bool {
+		matches!(self.options.wrap_offset(offset), 0)
+	}
+
+	fn cell(&self, offset: Offset) -> String {
+		match self.options.wrap_offset(offset) {
+			0 => CURRENT.to_owned(),
+			offset => format!("tape[at({offset})]"),
+		}
+	}
+
+	fn constant(&self, value: u32) -> String {
+		format!("{}u", self.options.cell_width.wrap_unsigned(value))
+	}
+
+	// `value * factor`, truncated back to the cell width.
+	fn scaled(&self, value: &str, factor: u32) -> String {
+		match self.options.cell_width.wrap_unsigned(factor) {
+			1 => value.to_owned(),
+			factor => format!("(cell)({value} * {factor}u)"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use vmm_ir::{CellWidth, EofPolicy, Instruction, SuperInstruction};
+
+	use super::CCodegen;
+	use crate::{Codegen as _, CodegenOptions};
+
+	#[test]
+	fn honours_options() {
+		let options = CodegenOptions::new()
+			.and_with_tape_len(100)
+			.and_with_cell_width(CellWidth::U16)
+			.and_with_eof_policy(EofPolicy::Unchanged);
+
+		let code = CCodegen::new(options)
+			.generate(&[Instruction::Read])
+			.unwrap();
+
+		assert!(code.contains("#define TAPE_LEN 100\n"));
+		assert!(code.contains("typedef uint16_t cell;\n"));
+		assert!(code.contains("read_cell();"));
+		assert!(!code.contains("else"));
+	}
+
+	#[test]
+	fn wraps_offsets() {
+		let code = CCodegen::new(CodegenOptions::new().and_with_tape_len(10))
+			.generate(&[
+				Instruction::move_ptr(-1),
+				Instruction::inc_val_at(-1, 12),
+				Instruction::dynamic_loop([Instruction::Super(
+					SuperInstruction::scale_and_move_val(3, 1),
+				)]),
+			])
+			.unwrap();
+
+		assert!(code.contains(concat!(
+			"\tptr = at(9);\n",
+			"\ttape[at(2)] -= 1u;\n",
+			"\twhile (tape[ptr]) {\n",
+			"\t\ttape[at(1)] += (cell)(tape[ptr] * 3u);\n",
+			"\t\ttape[ptr] = 0;\n",
+			"\t}\n",
+		)));
+	}
+}
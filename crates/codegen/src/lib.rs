@@ -0,0 +1,115 @@
+#![cfg_attr(docsrs, feature(doc_auto_cfg, doc_cfg))]
+
+mod c;
+mod rust;
+mod wasm;
+mod writer;
+
+use std::{
+	error::Error as StdError,
+	fmt::{Debug, Display, Error as FmtError, Formatter, Result as FmtResult, Write},
+};
+
+use vmm_ir::{CellWidth, EofPolicy, Instruction, Offset};
+use vmm_tape::TAPE_SIZE;
+
+pub use self::{c::*, rust::*, wasm::*};
+
+/// Settings shared by every code generator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodegenOptions {
+	pub tape_len: usize,
+	pub cell_width: CellWidth,
+	pub eof: EofPolicy,
+}
+
+impl CodegenOptions {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			tape_len: TAPE_SIZE,
+			cell_width: CellWidth::U8,
+			eof: EofPolicy::Zero,
+		}
+	}
+
+	#[must_use]
+	pub const fn and_with_tape_len(mut self, tape_len: usize) -> Self {
+		self.tape_len = tape_len;
+		self
+	}
+
+	#[must_use]
+	pub const fn and_with_cell_width(mut self, cell_width: CellWidth) -> Self {
+		self.cell_width = cell_width;
+		self
+	}
+
+	#[must_use]
+	pub const fn and_with_eof_policy(mut self, eof: EofPolicy) -> Self {
+		self.eof = eof;
+		self
+	}
+
+	/// The distance `offset` moves the pointer forward, wrapping around the tape.
+	#[must_use]
+	pub const fn wrap_offset(self, offset: Offset) -> usize {
+		offset.value().rem_euclid(self.tape_len as isize) as usize
+	}
+}
+
+impl Default for CodegenOptions {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Turns a program into source code for another language.
+pub trait Codegen {
+	fn options(&self) -> CodegenOptions;
+
+	fn emit(&self, program: &[Instruction], out: &mut dyn Write) -> Result<(), CodegenError>;
+
+	fn generate(&self, program: &[Instruction]) -> Result<String, CodegenError> {
+		let mut out = String::new();
+
+		self.emit(program, &mut out)?;
+
+		Ok(out)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodegenError {
+	Unsupported(Instruction),
+	EmptyTape,
+	TapeTooLarge(usize),
+	Fmt,
+}
+
+impl Display for CodegenError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::Unsupported(instr) => {
+				f.write_str("instruction ")?;
+				Debug::fmt(&instr, f)?;
+				f.write_str(" is not supported by the code generator")
+			}
+			Self::EmptyTape => f.write_str("cannot generate code for an empty tape"),
+			Self::TapeTooLarge(len) => {
+				f.write_str("a tape of ")?;
+				Display::fmt(&len, f)?;
+				f.write_str(" cells is too large for the target")
+			}
+			Self::Fmt => f.write_str("an error occurred when writing the generated code"),
+		}
+	}
+}
+
+impl StdError for CodegenError {}
+
+impl From<FmtError> for CodegenError {
+	fn from(_: FmtError) -> Self {
+		Self::Fmt
+	}
+}
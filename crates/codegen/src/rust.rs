@@ -0,0 +1,468 @@
+use std::fmt::Write;
+
+use vmm_ir::{
+	BlockInstruction, CellWidth, EofPolicy, HasIo as _, Instruction, Offset, ScaleAnd,
+	SuperInstruction,
+};
+use vmm_utils::GetOrZero as _;
+
+use super::{Codegen, CodegenError, CodegenOptions, writer::CodeWriter};
+
+const CURRENT: &str = "tape[ptr]";
+
+/// The kind of Rust source file to generate.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RustEntry {
+	/// A standalone `main.rs`, running the program on stdin and stdout.
+	#[default]
+	Main,
+	/// A library exposing `pub fn run(input: &mut impl Read, output: &mut impl Write)`.
+	Library,
+}
+
+/// Emits a program as safe Rust, using wrapping arithmetic on a fixed-size array.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RustCodegen {
+	options: CodegenOptions,
+	entry: RustEntry,
+}
+
+impl RustCodegen {
+	#[must_use]
+	pub const fn new(options: CodegenOptions) -> Self {
+		Self {
+			options,
+			entry: RustEntry::Main,
+		}
+	}
+
+	#[must_use]
+	pub const fn and_with_entry(mut self, entry: RustEntry) -> Self {
+		self.entry = entry;
+		self
+	}
+
+	#[must_use]
+	pub const fn entry(self) -> RustEntry {
+		self.entry
+	}
+}
+
+impl Codegen for RustCodegen {
+	fn options(&self) -> CodegenOptions {
+		self.options
+	}
+
+	fn emit(&self, program: &[Instruction], out: &mut dyn Write) -> Result<(), CodegenError> {
+		if matches!(self.options.tape_len, 0) {
+			return Err(CodegenError::EmptyTape);
+		}
+
+		let mut body = String::new();
+
+		let mut emitter = Emitter {
+			w: CodeWriter::new(&mut body, "\t"),
+			options: self.options,
+			uses_at: false,
+			moves_ptr: false,
+		};
+
+		emitter.w.indent();
+		emitter.block(program)?;
+
+		let uses_at = emitter.uses_at;
+		let ptr = if emitter.moves_ptr { "mut ptr" } else { "ptr" };
+		let has_read = program.has_read();
+
+		let mut w = CodeWriter::new(out, "\t");
+
+		let cell_type = match self.options.cell_width {
+			CellWidth::U8 => "u8",
+			CellWidth::U16 => "u16",
+			CellWidth::U32 => "u32",
+		};
+
+		w.line(format_args!("use std::io::{{self, Read, Write}};"))?;
+		w.blank()?;
+		w.line(format_args!(
+			"const TAPE_LEN: usize = {};",
+			self.options.tape_len
+		))?;
+		w.blank()?;
+		w.line(format_args!("type Cell = {cell_type};"))?;
+		w.blank()?;
+
+		if matches!(self.entry, RustEntry::Main) {
+			w.raw(concat!(
+				"fn main() -> io::Result<()> {\n",
+				"\trun(&mut io::stdin().lock(), &mut io::stdout().lock())\n",
+				"}\n",
+				"\n",
+			))?;
+		}
+
+		let visibility = match self.entry {
+			RustEntry::Main => "",
+			RustEntry::Library => "pub ",
+		};
+
+		let input = if has_read { "input" } else { "_input" };
+
+		w.line(format_args!(
+			"{visibility}fn run({input}: &mut impl Read, output: &mut impl Write) -> io::Result<()> {{"
+		))?;
+		w.line(format_args!(
+			"\tlet mut tape: [Cell; TAPE_LEN] = [0; TAPE_LEN];"
+		))?;
+		w.line(format_args!("\tlet {ptr} = 0;"))?;
+		w.blank()?;
+		w.raw(&body)?;
+		w.raw(concat!("\n", "\toutput.flush()\n", "}\n"))?;
+
+		if uses_at {
+			w.raw(concat!(
+				"\n",
+				"/// The index of the cell `offset` cells after `ptr`, wrapping around the tape.\n",
+				"const fn at(ptr: usize, offset: usize) -> usize {\n",
+				"\tlet index = ptr + offset;\n",
+				"\n",
+				"\tif index >= TAPE_LEN { index - TAPE_LEN } else { index }\n",
+				"}\n",
+			))?;
+		}
+
+		if has_read {
+			let on_eof = match self.options.eof {
+				EofPolicy::Zero => "*cell = 0",
+				EofPolicy::Max => "*cell = Cell::MAX",
+				EofPolicy::Unchanged => "{}",
+			};
+
+			w.raw(concat!(
+				"\n",
+				"fn read_cell(input: &mut impl Read, cell: &mut Cell) -> io::Result<()> {\n",
+				"\tlet mut buf = [0];\n",
+				"\n",
+				"\tmatch input.read_exact(&mut buf) {\n",
+				"\t\tOk(()) => *cell = Cell::from(buf[0]),\n",
+			))?;
+			w.line(format_args!(
+				"\t\tErr(e) if e.kind() == io::ErrorKind::UnexpectedEof => {on_eof},"
+			))?;
+			w.raw(concat!(
+				"\t\tErr(e) => return Err(e),\n",
+				"\t}\n",
+				"\n",
+				"\tOk(())\n",
+				"}\n",
+			))?;
+		}
+
+		Ok(())
+	}
+}
+
+struct Emitter<'a> {
+	w: CodeWriter<'a>,
+	options: CodegenOptions,
+	uses_at: bool,
+	moves_ptr: bool,
+}
+
+impl Emitter<'_> {
+	fn block(&mut self, instructions: &[Instruction]) -> Result<(), CodegenError> {
+		instructions
+			.iter()
+			.try_for_each(|instr| self.instruction(instr))
+	}
+
+	fn instruction(&mut self, instr: &Instruction) -> Result<(), CodegenError> {
+		match instr {
+			Instruction::Boundary => {}
+			Instruction::IncVal { value, offset } => {
+				let cell = self.cell(*offset);
+				let value = self.options.cell_width.wrap_signed(*value);
+
+				match value {
+					0 => {}
+					1.. => self
+						.w
+						.line(format_args!("{cell} = {cell}.wrapping_add({value});"))?,
+					_ => self.w.line(format_args!(
+						"{cell} = {cell}.wrapping_sub({});",
+						value.unsigned_abs()
+					))?,
+				}
+			}
+			Instruction::SetVal { value, offset } => {
+				let cell = self.cell(*offset);
+				let value = self.constant(value.get_or_zero());
+
+				self.w.line(format_args!("{cell} = {value};"))?;
+			}
+			Instruction::SubCell { offset } => {
+				if self.is_current(*offset) {
+					self.w
+						.line(format_args!("{CURRENT} = {CURRENT}.wrapping_neg();"))?;
+				} else {
+					let cell = self.cell(*offset);
+
+					self.w
+						.line(format_args!("{cell} = {cell}.wrapping_sub({CURRENT});"))?;
+					self.w.line(format_args!("{CURRENT} = 0;"))?;
+				}
+			}
+			Instruction::ScaleVal { factor } => {
+				let scaled = self.scaled(CURRENT, *factor);
+
+				if scaled != CURRENT {
+					self.w.line(format_args!("{CURRENT} = {scaled};"))?;
+				}
+			}
+			Instruction::MoveVal(offset) => self.scale_and_move(*offset, 1)?,
+			Instruction::FetchVal(offset) => self.scale_and_fetch(*offset, 1)?,
+			Instruction::TakeVal(offset) => self.scale_and_take(*offset, 1)?,
+			Instruction::ReplaceVal(offset) => {
+				if !self.is_current(*offset) {
+					let cell = self.cell(*offset);
+
+					self.w.line(format_args!("{CURRENT} = {cell};"))?;
+					self.w.line(format_args!("{cell} = 0;"))?;
+				}
+			}
+			Instruction::MovePtr(offset) => self.move_ptr(*offset)?,
+			Instruction::FindZero(jump_by) => self.find_zero(*jump_by)?,
+			Instruction::Read => self
+				.w
+				.line(format_args!("read_cell(input, &mut {CURRENT})?;"))?,
+			Instruction::Write { offset } => {
+				let cell = self.cell(*offset);
+
+				if matches!(self.options.cell_width, CellWidth::U8) {
+					self.w.line(format_args!("output.write_all(&[{cell}])?;"))?;
+				} else {
+					self.w
+						.line(format_args!("output.write_all(&[{cell} as u8])?;"))?;
+				}
+			}
+			Instruction::Block(BlockInstruction::DynamicLoop(body)) => {
+				self.while_nz(|this| this.block(body))?;
+			}
+			Instruction::Block(BlockInstruction::IfNz(body)) => {
+				self.w.line(format_args!("if {CURRENT} != 0 {{"))?;
+				self.w.indent();
+
+				self.block(body)?;
+				self.w.line(format_args!("{CURRENT} = 0;"))?;
+
+				self.w.dedent();
+				self.w.line(format_args!("}}"))?;
+			}
+			Instruction::Super(s) => self.super_instruction(*s)?,
+			i => return Err(CodegenError::Unsupported(i.clone())),
+		}
+
+		Ok(())
+	}
+
+	fn super_instruction(&mut self, instr: SuperInstruction) -> Result<(), CodegenError> {
+		match instr {
+			SuperInstruction::ScaleAnd {
+				action,
+				offset,
+				factor,
+			} => match action {
+				ScaleAnd::Move => self.scale_and_move(offset, factor)?,
+				ScaleAnd::Fetch => self.scale_and_fetch(offset, factor)?,
+				ScaleAnd::Take => self.scale_and_take(offset, factor)?,
+				ScaleAnd::Set(value) => {
+					let scaled = self.scaled(CURRENT, factor);
+					let value = self.constant(value.get());
+
+					if self.is_current(offset) {
+						self.w
+							.line(format_args!("{CURRENT} = {scaled}.wrapping_add({value});"))?;
+					} else {
+						let cell = self.cell(offset);
+
+						self.w
+							.line(format_args!("{cell} = {cell}.wrapping_add({scaled});"))?;
+						self.w.line(format_args!("{CURRENT} = {value};"))?;
+					}
+				}
+				_ => return Err(CodegenError::Unsupported(instr.into())),
+			},
+			SuperInstruction::FindAndSetZero { offset, value } => {
+				self.find_zero(offset)?;
+
+				let value = self.constant(value.get());
+				self.w.line(format_args!("{CURRENT} = {value};"))?;
+			}
+			SuperInstruction::SetUntilZero { value, offset } => {
+				let value = self.constant(value.get_or_zero());
+
+				self.while_nz(|this| {
+					this.w.line(format_args!("{CURRENT} = {value};"))?;
+					this.move_ptr(offset)
+				})?;
+			}
+			SuperInstruction::FindCellByZero { jump_by, offset } => {
+				self.find_zero(jump_by)?;
+				self.move_ptr(offset)?;
+			}
+			SuperInstruction::ShiftVals { jump_by, offset } => {
+				self.while_nz(|this| {
+					this.scale_and_move(offset, 1)?;
+					this.move_ptr(jump_by)
+				})?;
+			}
+			i => return Err(CodegenError::Unsupported(i.into())),
+		}
+
+		Ok(())
+	}
+
+	fn scale_and_move(&mut self, offset: Offset, factor: u32) -> Result<(), CodegenError> {
+		let scaled = self.scaled(CURRENT, factor);
+
+		if self.is_current(offset) {
+			if scaled != CURRENT {
+				self.w.line(format_args!("{CURRENT} = {scaled};"))?;
+			}
+		} else {
+			let cell = self.cell(offset);
+
+			self.w
+				.line(format_args!("{cell} = {cell}.wrapping_add({scaled});"))?;
+			self.w.line(format_args!("{CURRENT} = 0;"))?;
+		}
+
+		Ok(())
+	}
+
+	fn scale_and_fetch(&mut self, offset: Offset, factor: u32) -> Result<(), CodegenError> {
+		if self.is_current(offset) {
+			return self.scale_and_move(offset, factor);
+		}
+
+		let cell = self.cell(offset);
+		let scaled = self.scaled(&cell, factor);
+
+		self.w.line(format_args!(
+			"{CURRENT} = {CURRENT}.wrapping_add({scaled});"
+		))?;
+		self.w.line(format_args!("{cell} = 0;"))?;
+
+		Ok(())
+	}
+
+	fn scale_and_take(&mut self, offset: Offset, factor: u32) -> Result<(), CodegenError> {
+		self.scale_and_move(offset, factor)?;
+		self.move_ptr(offset)
+	}
+
+	fn find_zero(&mut self, jump_by: Offset) -> Result<(), CodegenError> {
+		self.while_nz(|this| this.move_ptr(jump_by))
+	}
+
+	fn move_ptr(&mut self, offset: Offset) -> Result<(), CodegenError> {
+		let offset = self.options.wrap_offset(offset);
+
+		if !matches!(offset, 0) {
+			self.uses_at = true;
+			self.moves_ptr = true;
+			self.w.line(format_args!("ptr = at(ptr, {offset});"))?;
+		}
+
+		Ok(())
+	}
+
+	fn while_nz(
+		&mut self,
+		body: impl FnOnce(&mut Self) -> Result<(), CodegenError>,
+	) -> Result<(), CodegenError> {
+		self.w.line(format_args!("while {CURRENT} != 0 {{"))?;
+		self.w.indent();
+
+		body(self)?;
+
+		self.w.dedent();
+		self.w.line(format_args!("}}"))?;
+
+		Ok(())
+	}
+
+	const fn is_current(&self, offset: Offset) -> bool {
+		matches!(self.options.wrap_offset(offset), 0)
+	}
+
+	fn cell(&mut self, offset: Offset) -> String {
+		match self.options.wrap_offset(offset) {
+			0 => CURRENT.to_owned(),
+			offset => {
+				self.uses_at = true;
+				format!("tape[at(ptr, {offset})]")
+			}
+		}
+	}
+
+	const fn constant(&self, value: u32) -> u32 {
+		self.options.cell_width.wrap_unsigned(value)
+	}
+
+	fn scaled(&self, value: &str, factor: u32) -> String {
+		match self.options.cell_width.wrap_unsigned(factor) {
+			1 => value.to_owned(),
+			factor => format!("{value}.wrapping_mul({factor})"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use vmm_ir::{CellWidth, EofPolicy, Instruction};
+
+	use super::{RustCodegen, RustEntry};
+	use crate::{Codegen as _, CodegenOptions};
+
+	#[test]
+	fn library_entry() {
+		let options = CodegenOptions::new()
+			.and_with_cell_width(CellWidth::U32)
+			.and_with_eof_policy(EofPolicy::Max);
+
+		let code = RustCodegen::new(options)
+			.and_with_entry(RustEntry::Library)
+			.generate(&[Instruction::Read, Instruction::write_once()])
+			.unwrap();
+
+		assert!(code.contains("type Cell = u32;\n"));
+		assert!(code.contains(
+			"pub fn run(input: &mut impl Read, output: &mut impl Write) -> io::Result<()> {\n"
+		));
+		assert!(code.contains("=> *cell = Cell::MAX,\n"));
+		assert!(code.contains("output.write_all(&[tape[ptr] as u8])?;\n"));
+		assert!(!code.contains("fn main"));
+		assert!(!code.contains("fn at"));
+	}
+
+	#[test]
+	fn wraps_arithmetic() {
+		let code = RustCodegen::new(CodegenOptions::new().and_with_tape_len(10))
+			.generate(&[
+				Instruction::inc_val_at(-3, -1),
+				Instruction::dynamic_loop([Instruction::inc_val(-1), Instruction::move_ptr(4)]),
+			])
+			.unwrap();
+
+		assert!(code.contains("fn main() -> io::Result<()> {\n"));
+		assert!(code.contains("fn run(_input: &mut impl Read"));
+		assert!(code.contains(concat!(
+			"\ttape[at(ptr, 9)] = tape[at(ptr, 9)].wrapping_sub(3);\n",
+			"\twhile tape[ptr] != 0 {\n",
+			"\t\ttape[ptr] = tape[ptr].wrapping_sub(1);\n",
+			"\t\tptr = at(ptr, 4);\n",
+			"\t}\n",
+		)));
+	}
+}
@@ -0,0 +1,516 @@
+use std::fmt::Write;
+
+use vmm_ir::{
+	BlockInstruction, CellWidth, EofPolicy, HasIo as _, Instruction, Offset, ScaleAnd,
+	SuperInstruction,
+};
+use vmm_utils::GetOrZero as _;
+
+use super::{Codegen, CodegenError, CodegenOptions, writer::CodeWriter};
+
+const PTR: &str = "(local.get $ptr)";
+
+const PAGE_SIZE: usize = 64 * 1024;
+
+/// Emits a program as a WebAssembly text module.
+///
+/// The tape lives at the start of the exported `memory`, and the exported `run` function executes
+/// the program. Input and output go through two imported functions, `env.read: () -> i32`, which
+/// returns the next byte or a negative value at the end of input, and `env.write: (i32) -> ()`,
+/// which receives a byte.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WasmCodegen {
+	options: CodegenOptions,
+}
+
+impl WasmCodegen {
+	#[must_use]
+	pub const fn new(options: CodegenOptions) -> Self {
+		Self { options }
+	}
+
+	const fn tape_bytes(self) -> usize {
+		self.options.tape_len * cell_bytes(self.options.cell_width)
+	}
+}
+
+impl Codegen for WasmCodegen {
+	fn options(&self) -> CodegenOptions {
+		self.options
+	}
+
+	fn emit(&self, program: &[Instruction], out: &mut dyn Write) -> Result<(), CodegenError> {
+		if matches!(self.options.tape_len, 0) {
+			return Err(CodegenError::EmptyTape);
+		}
+
+		let tape_bytes = self.tape_bytes();
+
+		if tape_bytes > i32::MAX as usize {
+			return Err(CodegenError::TapeTooLarge(self.options.tape_len));
+		}
+
+		let mut body = String::new();
+
+		let mut emitter = Emitter {
+			w: CodeWriter::new(&mut body, "  "),
+			options: self.options,
+			uses_at: false,
+			next_label: 0,
+		};
+
+		emitter.w.indent();
+		emitter.w.indent();
+		emitter.block(program)?;
+
+		let uses_at = emitter.uses_at;
+
+		let (_, store) = memory_ops(self.options.cell_width);
+
+		let mut w = CodeWriter::new(out, "  ");
+
+		w.line(format_args!("(module"))?;
+		w.indent();
+		w.line(format_args!(
+			"(import \"env\" \"read\" (func $read (result i32)))"
+		))?;
+		w.line(format_args!(
+			"(import \"env\" \"write\" (func $write (param i32)))"
+		))?;
+		w.blank()?;
+		w.line(format_args!(
+			"(memory (export \"memory\") {})",
+			tape_bytes.div_ceil(PAGE_SIZE)
+		))?;
+		w.blank()?;
+		w.line(format_args!("(func (export \"run\")"))?;
+		w.indent();
+		w.line(format_args!("(local $ptr i32)"))?;
+		w.raw(&body)?;
+		w.dedent();
+		w.line(format_args!(")"))?;
+
+		if uses_at {
+			w.blank()?;
+			w.line(format_args!(
+				";; The address of the cell `offset` bytes after `ptr`, wrapping around the tape."
+			))?;
+			w.line(format_args!(
+				"(func $at (param $ptr i32) (param $offset i32) (result i32)"
+			))?;
+			w.indent();
+			w.line(format_args!("(local $addr i32)"))?;
+			w.line(format_args!(
+				"(local.set $addr (i32.add (local.get $ptr) (local.get $offset)))"
+			))?;
+			w.line(format_args!("(select"))?;
+			w.indent();
+			w.line(format_args!(
+				"(i32.sub (local.get $addr) (i32.const {tape_bytes}))"
+			))?;
+			w.line(format_args!("(local.get $addr)"))?;
+			w.line(format_args!(
+				"(i32.ge_u (local.get $addr) (i32.const {tape_bytes}))))"
+			))?;
+			w.dedent();
+			w.dedent();
+		}
+
+		if program.has_read() {
+			w.blank()?;
+			w.line(format_args!("(func $read_cell (param $addr i32)"))?;
+			w.indent();
+			w.line(format_args!("(local $byte i32)"))?;
+			w.line(format_args!("(local.set $byte (call $read))"))?;
+			w.line(format_args!(
+				"(if (i32.ge_s (local.get $byte) (i32.const 0))"
+			))?;
+			w.indent();
+
+			let on_eof = match self.options.eof {
+				EofPolicy::Zero => Some("(i32.const 0)"),
+				EofPolicy::Max => Some("(i32.const -1)"),
+				EofPolicy::Unchanged => None,
+			};
+
+			if let Some(value) = on_eof {
+				w.line(format_args!(
+					"(then ({store} (local.get $addr) (local.get $byte)))"
+				))?;
+				w.line(format_args!("(else ({store} (local.get $addr) {value}))))"))?;
+			} else {
+				w.line(format_args!(
+					"(then ({store} (local.get $addr) (local.get $byte)))))"
+				))?;
+			}
+
+			w.dedent();
+			w.dedent();
+		}
+
+		w.dedent();
+		w.line(format_args!(")"))?;
+
+		Ok(())
+	}
+}
+
+struct Emitter<'a> {
+	w: CodeWriter<'a>,
+	options: CodegenOptions,
+	uses_at: bool,
+	next_label: usize,
+}
+
+impl Emitter<'_> {
+	fn block(&mut self, instructions: &[Instruction]) -> Result<(), CodegenError> {
+		instructions
+			.iter()
+			.try_for_each(|instr| self.instruction(instr))
+	}
+
+	fn instruction(&mut self, instr: &Instruction) -> Result<(), CodegenError> {
+		match instr {
+			Instruction::Boundary => {}
+			Instruction::IncVal { value, offset } => {
+				let value = self.options.cell_width.wrap_signed(*value);
+
+				if !matches!(value, 0) {
+					let addr = self.addr(*offset);
+					let op = if value > 0 { "i32.add" } else { "i32.sub" };
+					let cell = self.load(&addr);
+
+					self.store(
+						&addr,
+						&format!("({op} {cell} (i32.const {}))", value.unsigned_abs()),
+					)?;
+				}
+			}
+			Instruction::SetVal { value, offset } => {
+				let addr = self.addr(*offset);
+				let value = self.constant(value.get_or_zero());
+
+				self.store(&addr, &format!("(i32.const {value})"))?;
+			}
+			Instruction::SubCell { offset } => {
+				let current = self.load(PTR);
+
+				if self.is_current(*offset) {
+					self.store(PTR, &format!("(i32.sub (i32.const 0) {current})"))?;
+				} else {
+					let addr = self.addr(*offset);
+					let cell = self.load(&addr);
+
+					self.store(&addr, &format!("(i32.sub {cell} {current})"))?;
+					self.clear_current()?;
+				}
+			}
+			Instruction::ScaleVal { factor } => {
+				let current = self.load(PTR);
+				let scaled = self.scaled(&current, *factor);
+
+				if scaled != current {
+					self.store(PTR, &scaled)?;
+				}
+			}
+			Instruction::MoveVal(offset) => self.scale_and_move(*offset, 1)?,
+			Instruction::FetchVal(offset) => self.scale_and_fetch(*offset, 1)?,
+			Instruction::TakeVal(offset) => self.scale_and_take(*offset, 1)?,
+			Instruction::ReplaceVal(offset) => {
+				if !self.is_current(*offset) {
+					let addr = self.addr(*offset);
+					let cell = self.load(&addr);
+
+					self.store(PTR, &cell)?;
+					self.store(&addr, "(i32.const 0)")?;
+				}
+			}
+			Instruction::MovePtr(offset) => self.move_ptr(*offset)?,
+			Instruction::FindZero(jump_by) => self.find_zero(*jump_by)?,
+			Instruction::Read => self.w.line(format_args!("(call $read_cell {PTR})"))?,
+			Instruction::Write { offset } => {
+				let addr = self.addr(*offset);
+				let cell = self.load(&addr);
+
+				if matches!(self.options.cell_width, CellWidth::U8) {
+					self.w.line(format_args!("(call $write {cell})"))?;
+				} else {
+					self.w.line(format_args!(
+						"(call $write (i32.and {cell} (i32.const 255)))"
+					))?;
+				}
+			}
+			Instruction::Block(BlockInstruction::DynamicLoop(body)) => {
+				self.while_nz(|this| this.block(body))?;
+			}
+			Instruction::Block(BlockInstruction::IfNz(body)) => {
+				let current = self.load(PTR);
+
+				self.w.line(format_args!("(if {current}"))?;
+				self.w.indent();
+				self.w.line(format_args!("(then"))?;
+				self.w.indent();
+
+				self.block(body)?;
+				self.clear_current()?;
+
+				self.w.dedent();
+				self.w.line(format_args!("))"))?;
+				self.w.dedent();
+			}
+			Instruction::Super(s) => self.super_instruction(*s)?,
+			i => return Err(CodegenError::Unsupported(i.clone())),
+		}
+
+		Ok(())
+	}
+
+	fn super_instruction(&mut self, instr: SuperInstruction) -> Result<(), CodegenError> {
+		match instr {
+			SuperInstruction::ScaleAnd {
+				action,
+				offset,
+				factor,
+			} => match action {
+				ScaleAnd::Move => self.scale_and_move(offset, factor)?,
+				ScaleAnd::Fetch => self.scale_and_fetch(offset, factor)?,
+				ScaleAnd::Take => self.scale_and_take(offset, factor)?,
+				ScaleAnd::Set(value) => {
+					let current = self.load(PTR);
+					let scaled = self.scaled(&current, factor);
+					let value = self.constant(value.get());
+
+					if self.is_current(offset) {
+						self.store(PTR, &format!("(i32.add {scaled} (i32.const {value}))"))?;
+					} else {
+						let addr = self.addr(offset);
+						let cell = self.load(&addr);
+
+						self.store(&addr, &format!("(i32.add {cell} {scaled})"))?;
+						self.store(PTR, &format!("(i32.const {value})"))?;
+					}
+				}
+				_ => return Err(CodegenError::Unsupported(instr.into())),
+			},
+			SuperInstruction::FindAndSetZero { offset, value } => {
+				self.find_zero(offset)?;
+
+				let value = self.constant(value.get());
+				self.store(PTR, &format!("(i32.const {value})"))?;
+			}
+			SuperInstruction::SetUntilZero { value, offset } => {
+				let value = self.constant(value.get_or_zero());
+
+				self.while_nz(|this| {
+					this.store(PTR, &format!("(i32.const {value})"))?;
+					this.move_ptr(offset)
+				})?;
+			}
+			SuperInstruction::FindCellByZero { jump_by, offset } => {
+				self.find_zero(jump_by)?;
+				self.move_ptr(offset)?;
+			}
+			SuperInstruction::ShiftVals { jump_by, offset } => {
+				self.while_nz(|this| {
+					this.scale_and_move(offset, 1)?;
+					this.move_ptr(jump_by)
+				})?;
+			}
+			i => return Err(CodegenError::Unsupported(i.into())),
+		}
+
+		Ok(())
+	}
+
+	fn scale_and_move(&mut self, offset: Offset, factor: u32) -> Result<(), CodegenError> {
+		let current = self.load(PTR);
+		let scaled = self.scaled(&current, factor);
+
+		if self.is_current(offset) {
+			if scaled != current {
+				self.store(PTR, &scaled)?;
+			}
+		} else {
+			let addr = self.addr(offset);
+			let cell = self.load(&addr);
+
+			self.store(&addr, &format!("(i32.add {cell} {scaled})"))?;
+			self.clear_current()?;
+		}
+
+		Ok(())
+	}
+
+	fn scale_and_fetch(&mut self, offset: Offset, factor: u32) -> Result<(), CodegenError> {
+		if self.is_current(offset) {
+			return self.scale_and_move(offset, factor);
+		}
+
+		let current = self.load(PTR);
+		let addr = self.addr(offset);
+		let cell = self.load(&addr);
+		let scaled = self.scaled(&cell, factor);
+
+		self.store(PTR, &format!("(i32.add {current} {scaled})"))?;
+		self.store(&addr, "(i32.const 0)")?;
+
+		Ok(())
+	}
+
+	fn scale_and_take(&mut self, offset: Offset, factor: u32) -> Result<(), CodegenError> {
+		self.scale_and_move(offset, factor)?;
+		self.move_ptr(offset)
+	}
+
+	fn find_zero(&mut self, jump_by: Offset) -> Result<(), CodegenError> {
+		self.while_nz(|this| this.move_ptr(jump_by))
+	}
+
+	fn move_ptr(&mut self, offset: Offset) -> Result<(), CodegenError> {
+		if !self.is_current(offset) {
+			let addr = self.addr(offset);
+
+			self.w.line(format_args!("(local.set $ptr {addr})"))?;
+		}
+
+		Ok(())
+	}
+
+	fn while_nz(
+		&mut self,
+		body: impl FnOnce(&mut Self) -> Result<(), CodegenError>,
+	) -> Result<(), CodegenError> {
+		let label = self.next_label;
+		self.next_label += 1;
+
+		let current = self.load(PTR);
+
+		self.w.line(format_args!("(block $done{label}"))?;
+		self.w.indent();
+		self.w.line(format_args!("(loop $loop{label}"))?;
+		self.w.indent();
+		self.w
+			.line(format_args!("(br_if $done{label} (i32.eqz {current}))"))?;
+
+		body(self)?;
+
+		self.w.line(format_args!("(br $loop{label})))"))?;
+		self.w.dedent();
+		self.w.dedent();
+
+		Ok(())
+	}
+
+	fn clear_current(&mut self) -> Result<(), CodegenError> {
+		self.store(PTR, "(i32.const 0)")
+	}
+
+	fn store(&mut self, addr: &str, value: &str) -> Result<(), CodegenError> {
+		let (_, store) = memory_ops(self.options.cell_width);
+
+		self.w.line(format_args!("({store} {addr} {value})"))?;
+
+		Ok(())
+	}
+
+	fn load(&self, addr: &str) -> String {
+		let (load, _) = memory_ops(self.options.cell_width);
+
+		format!("({load} {addr})")
+	}
+
+	const fn is_current(&self, offset: Offset) -> bool {
+		matches!(self.options.wrap_offset(offset), 0)
+	}
+
+	fn addr(&mut self, offset: Offset) -> String {
+		match self.options.wrap_offset(offset) * cell_bytes(self.options.cell_width) {
+			0 => PTR.to_owned(),
+			offset => {
+				self.uses_at = true;
+				format!("(call $at {PTR} (i32.const {offset}))")
+			}
+		}
+	}
+
+	const fn constant(&self, value: u32) -> u32 {
+		self.options.cell_width.wrap_unsigned(value)
+	}
+
+	fn scaled(&self, value: &str, factor: u32) -> String {
+		match self.options.cell_width.wrap_unsigned(factor) {
+			1 => value.to_owned(),
+			factor => format!("(i32.mul {value} (i32.const {factor}))"),
+		}
+	}
+}
+
+const fn cell_bytes(cell_width: CellWidth) -> usize {
+	cell_width.bits() as usize / 8
+}
+
+const fn memory_ops(cell_width: CellWidth) -> (&'static str, &'static str) {
+	match cell_width {
+		CellWidth::U8 => ("i32.load8_u", "i32.store8"),
+		CellWidth::U16 => ("i32.load16_u", "i32.store16"),
+		CellWidth::U32 => ("i32.load", "i32.store"),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use vmm_ir::{CellWidth, EofPolicy, Instruction};
+
+	use super::WasmCodegen;
+	use crate::{Codegen as _, CodegenError, CodegenOptions};
+
+	#[test]
+	fn honours_options() {
+		let options = CodegenOptions::new()
+			.and_with_tape_len(100_000)
+			.and_with_cell_width(CellWidth::U16)
+			.and_with_eof_policy(EofPolicy::Unchanged);
+
+		let code = WasmCodegen::new(options)
+			.generate(&[Instruction::Read, Instruction::write_once()])
+			.unwrap();
+
+		assert!(code.contains("(memory (export \"memory\") 4)\n"));
+		assert!(code.contains("(call $read_cell (local.get $ptr))\n"));
+		assert!(
+			code.contains(
+				"(call $write (i32.and (i32.load16_u (local.get $ptr)) (i32.const 255)))\n"
+			)
+		);
+		assert!(!code.contains("(else"));
+		assert!(!code.contains("func $at"));
+	}
+
+	#[test]
+	fn wraps_offsets() {
+		let code = WasmCodegen::new(CodegenOptions::new().and_with_tape_len(10))
+			.generate(&[Instruction::dynamic_loop([
+				Instruction::inc_val_at(-3, -1),
+				Instruction::move_ptr(4),
+			])])
+			.unwrap();
+
+		assert!(code.contains(concat!(
+			"    (block $done0\n",
+			"      (loop $loop0\n",
+			"        (br_if $done0 (i32.eqz (i32.load8_u (local.get $ptr))))\n",
+			"        (i32.store8 (call $at (local.get $ptr) (i32.const 9)) (i32.sub (i32.load8_u (call $at (local.get $ptr) (i32.const 9))) (i32.const 3)))\n",
+			"        (local.set $ptr (call $at (local.get $ptr) (i32.const 4)))\n",
+			"        (br $loop0)))\n",
+		)));
+		assert!(code.contains("(i32.ge_u (local.get $addr) (i32.const 10))))\n"));
+	}
+
+	#[test]
+	fn rejects_empty_tape() {
+		assert_eq!(
+			WasmCodegen::new(CodegenOptions::new().and_with_tape_len(0)).generate(&[]),
+			Err(CodegenError::EmptyTape)
+		);
+	}
+}
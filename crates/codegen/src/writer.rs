@@ -0,0 +1,43 @@
+use core::fmt::{Arguments, Result as FmtResult, Write};
+
+/// Writes lines of source code, tracking the indentation of nested blocks.
+pub struct CodeWriter<'a> {
+	out: &'a mut dyn Write,
+	indent: &'static str,
+	depth: usize,
+}
+
+impl<'a> CodeWriter<'a> {
+	pub fn new(out: &'a mut dyn Write, indent: &'static str) -> Self {
+		Self {
+			out,
+			indent,
+			depth: 0,
+		}
+	}
+
+	pub fn line(&mut self, args: Arguments<'_>) -> FmtResult {
+		for _ in 0..self.depth {
+			self.out.write_str(self.indent)?;
+		}
+
+		self.out.write_fmt(args)?;
+		self.out.write_char('\n')
+	}
+
+	pub fn blank(&mut self) -> FmtResult {
+		self.out.write_char('\n')
+	}
+
+	pub fn raw(&mut self, s: &str) -> FmtResult {
+		self.out.write_str(s)
+	}
+
+	pub const fn indent(&mut self) {
+		self.depth += 1;
+	}
+
+	pub const fn dedent(&mut self) {
+		self.depth -= 1;
+	}
+}
@@ -0,0 +1,78 @@
+use std::{
+	error::Error as StdError,
+	fmt::{Display, Formatter, Result as FmtResult},
+	str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// What the interpreter does when the pointer, or a cell offset from it, leaves the tape.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BoundsPolicy {
+	/// Leave it to the tape, fixed-size tapes wrap around and growable tapes grow.
+	#[default]
+	Wrap,
+	/// Stop with [`RuntimeError::PointerOutOfBounds`](crate::RuntimeError::PointerOutOfBounds).
+	Error,
+	/// Clamp to the first or last cell of the tape.
+	Saturate,
+}
+
+impl BoundsPolicy {
+	pub(crate) const fn resolve(self, ptr: usize, offset: isize, len: usize) -> Option<usize> {
+		match ptr.checked_add_signed(offset) {
+			Some(idx) if idx < len => Some(idx),
+			_ if matches!(self, Self::Saturate) => Some(if offset < 0 { 0 } else { len - 1 }),
+			_ => None,
+		}
+	}
+}
+
+impl Display for BoundsPolicy {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		f.write_str(match self {
+			Self::Wrap => "wrap",
+			Self::Error => "error",
+			Self::Saturate => "saturate",
+		})
+	}
+}
+
+impl FromStr for BoundsPolicy {
+	type Err = ParseBoundsPolicyError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"wrap" => Ok(Self::Wrap),
+			"error" => Ok(Self::Error),
+			"saturate" | "clamp" => Ok(Self::Saturate),
+			s => Err(ParseBoundsPolicyError(s.to_owned())),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBoundsPolicyError(String);
+
+impl Display for ParseBoundsPolicyError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		f.write_str("invalid bounds policy: ")?;
+		f.write_str(&self.0)
+	}
+}
+
+impl StdError for ParseBoundsPolicyError {}
+
+#[cfg(test)]
+mod tests {
+	use super::BoundsPolicy;
+
+	#[test]
+	fn resolve() {
+		assert_eq!(BoundsPolicy::Error.resolve(2, 3, 10), Some(5));
+		assert_eq!(BoundsPolicy::Error.resolve(2, -3, 10), None);
+		assert_eq!(BoundsPolicy::Error.resolve(2, 8, 10), None);
+		assert_eq!(BoundsPolicy::Saturate.resolve(2, -3, 10), Some(0));
+		assert_eq!(BoundsPolicy::Saturate.resolve(2, 8, 10), Some(9));
+	}
+}
@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+/// Limits on how much work the interpreter does before giving up, all unlimited by default.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutionBudget {
+	/// The total number of instructions and loop iterations that may be executed.
+	pub fuel: Option<u64>,
+	/// The number of iterations a single loop or scan may run for each time it is entered.
+	pub loop_limit: Option<usize>,
+	/// How long the program may run for, measured from the first instruction.
+	pub time_limit: Option<Duration>,
+}
+
+impl ExecutionBudget {
+	#[must_use]
+	pub const fn unlimited() -> Self {
+		Self {
+			fuel: None,
+			loop_limit: None,
+			time_limit: None,
+		}
+	}
+
+	#[must_use]
+	pub const fn and_with_fuel(mut self, fuel: u64) -> Self {
+		self.fuel = Some(fuel);
+		self
+	}
+
+	#[must_use]
+	pub const fn and_with_loop_limit(mut self, loop_limit: usize) -> Self {
+		self.loop_limit = Some(loop_limit);
+		self
+	}
+
+	#[must_use]
+	pub const fn and_with_time_limit(mut self, time_limit: Duration) -> Self {
+		self.time_limit = Some(time_limit);
+		self
+	}
+
+	#[must_use]
+	pub const fn is_unlimited(self) -> bool {
+		self.fuel.is_none() && self.loop_limit.is_none() && self.time_limit.is_none()
+	}
+}
@@ -0,0 +1,325 @@
+use std::{io::prelude::*, mem, num::NonZeroU32};
+
+use vmm_ir::{BlockInstruction, Instruction, Offset, SuperInstruction};
+use vmm_program::{SourceMap, SourceMapEntry, SourceSpan};
+use vmm_tape::Tape;
+
+use super::{Interpreter, RuntimeError};
+
+/// A single operation of [`Bytecode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+	IncVal {
+		value: i32,
+		offset: Offset,
+	},
+	SetVal {
+		value: Option<NonZeroU32>,
+		offset: Offset,
+	},
+	MovePtr(Offset),
+	Read,
+	Write(Offset),
+	FindZero(Offset),
+	SubCell(Offset),
+	ScaleVal(u32),
+	FetchVal(Offset),
+	MoveVal(Offset),
+	TakeVal(Offset),
+	ReplaceVal(Offset),
+	Super(SuperInstruction),
+	/// Skip past the matching [`Op::LoopEnd`] if the current cell is zero, which is the given
+	/// number of ops away.
+	LoopStart(usize),
+	/// Jump back to the first op after the matching [`Op::LoopStart`] if the current cell isn't
+	/// zero.
+	LoopEnd(usize),
+	/// Skip past the matching [`Op::IfEnd`] if the current cell is zero.
+	IfStart(usize),
+	/// Clear the current cell, which ends an [`BlockInstruction::IfNz`].
+	IfEnd,
+}
+
+/// A program flattened into a single list of [`Op`]s, with blocks lowered to relative jumps.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Bytecode {
+	ops: Box<[Op]>,
+	spans: Box<[Option<SourceSpan>]>,
+}
+
+impl Bytecode {
+	pub fn compile(program: &[Instruction]) -> Result<Self, RuntimeError> {
+		Self::compile_inner(program, None)
+	}
+
+	/// Compile the program, remembering the span of source each op came from.
+	pub fn compile_with_source_map(
+		program: &[Instruction],
+		source_map: &SourceMap,
+	) -> Result<Self, RuntimeError> {
+		Self::compile_inner(program, Some(source_map))
+	}
+
+	fn compile_inner(
+		program: &[Instruction],
+		source_map: Option<&SourceMap>,
+	) -> Result<Self, RuntimeError> {
+		let mut lowering = Lowering {
+			ops: Vec::with_capacity(program.len()),
+			spans: Vec::with_capacity(program.len()),
+		};
+
+		lowering.lower(program, source_map)?;
+
+		Ok(Self {
+			ops: lowering.ops.into_boxed_slice(),
+			spans: lowering.spans.into_boxed_slice(),
+		})
+	}
+
+	#[must_use]
+	pub const fn ops(&self) -> &[Op] {
+		&self.ops
+	}
+
+	/// The span of source the op at the index came from, if compiled with a source map.
+	#[must_use]
+	pub fn span(&self, index: usize) -> Option<SourceSpan> {
+		self.spans.get(index).copied().flatten()
+	}
+}
+
+struct Lowering {
+	ops: Vec<Op>,
+	spans: Vec<Option<SourceSpan>>,
+}
+
+impl Lowering {
+	fn push(&mut self, op: Op, span: Option<SourceSpan>) {
+		self.ops.push(op);
+		self.spans.push(span);
+	}
+
+	fn lower(
+		&mut self,
+		instructions: &[Instruction],
+		source_map: Option<&SourceMap>,
+	) -> Result<(), RuntimeError> {
+		for (i, instr) in instructions.iter().enumerate() {
+			let entry = source_map.and_then(|source_map| source_map.get(i));
+			let span = entry.map(SourceMapEntry::span);
+			let block_source_map = entry.map(SourceMapEntry::block);
+
+			let op = match instr {
+				Instruction::Boundary => continue,
+				Instruction::IncVal { value, offset } => Op::IncVal {
+					value: *value,
+					offset: *offset,
+				},
+				Instruction::SetVal { value, offset } => Op::SetVal {
+					value: *value,
+					offset: *offset,
+				},
+				Instruction::MovePtr(offset) => Op::MovePtr(*offset),
+				Instruction::Read => Op::Read,
+				Instruction::Write { offset } => Op::Write(*offset),
+				Instruction::FindZero(offset) => Op::FindZero(*offset),
+				Instruction::SubCell { offset } => Op::SubCell(*offset),
+				Instruction::ScaleVal { factor } => Op::ScaleVal(*factor),
+				Instruction::FetchVal(offset) => Op::FetchVal(*offset),
+				Instruction::MoveVal(offset) => Op::MoveVal(*offset),
+				Instruction::TakeVal(offset) => Op::TakeVal(*offset),
+				Instruction::ReplaceVal(offset) => Op::ReplaceVal(*offset),
+				Instruction::Super(s) => Op::Super(*s),
+				Instruction::Block(BlockInstruction::DynamicLoop(body)) => {
+					let distance = self.lower_block(body, block_source_map, span, Op::LoopStart)?;
+
+					Op::LoopEnd(distance)
+				}
+				Instruction::Block(BlockInstruction::IfNz(body)) => {
+					self.lower_block(body, block_source_map, span, Op::IfStart)?;
+
+					Op::IfEnd
+				}
+				i => return Err(RuntimeError::Unimplemented(i.clone())),
+			};
+
+			self.push(op, span);
+		}
+
+		Ok(())
+	}
+
+	// Lowers the body of a block after its start op, returning the distance from the start op to
+	// the end op that the caller pushes next.
+	fn lower_block(
+		&mut self,
+		body: &[Instruction],
+		source_map: Option<&SourceMap>,
+		span: Option<SourceSpan>,
+		start_op: fn(usize) -> Op,
+	) -> Result<usize, RuntimeError> {
+		let start = self.ops.len();
+
+		self.push(start_op(0), span);
+
+		self.lower(body, source_map)?;
+
+		let distance = self.ops.len() - start;
+
+		self.ops[start] = start_op(distance);
+
+		Ok(distance)
+	}
+}
+
+impl<T: Tape, R, W> Interpreter<T, R, W>
+where
+	R: Read + 'static,
+	W: Write + 'static,
+{
+	/// Compile the program to [`Bytecode`] and run it, which skips the profiler.
+	pub fn run_bytecode(&mut self) -> Result<(), RuntimeError> {
+		let program = mem::take(self.program_mut());
+
+		let bytecode = match &self.source_map {
+			Some(source_map) => Bytecode::compile_with_source_map(&program, source_map)?,
+			None => Bytecode::compile(&program)?,
+		};
+
+		self.execute_bytecode(&bytecode)
+	}
+
+	pub fn execute_bytecode(&mut self, bytecode: &Bytecode) -> Result<(), RuntimeError> {
+		let mut pc = 0;
+
+		self.error_span = None;
+
+		// The op that failed is the one before the program counter.
+		self.execute_ops(bytecode.ops(), &mut pc)
+			.inspect_err(|_| self.error_span = bytecode.span(pc - 1))
+	}
+
+	fn execute_ops(&mut self, ops: &[Op], pc: &mut usize) -> Result<(), RuntimeError> {
+		let checked = !self.budget.is_unlimited();
+
+		let mut iterations = Vec::new();
+
+		while let Some(&op) = ops.get(*pc) {
+			*pc += 1;
+
+			if checked && !matches!(op, Op::LoopEnd(..) | Op::IfEnd) {
+				self.consume_fuel()?;
+			}
+
+			match op {
+				Op::IncVal { value, offset } => self.inc_val(value, offset)?,
+				Op::SetVal { value, offset } => self.set_val(value, offset)?,
+				Op::MovePtr(offset) => self.move_ptr(offset)?,
+				Op::Read => self.read_char()?,
+				Op::Write(offset) => self.write(offset)?,
+				Op::FindZero(offset) => self.find_zero(offset)?,
+				Op::SubCell(offset) => self.sub_cell(offset)?,
+				Op::ScaleVal(factor) => self.scale_val(factor)?,
+				Op::FetchVal(offset) => self.fetch_val(offset)?,
+				Op::MoveVal(offset) => self.move_val(offset)?,
+				Op::TakeVal(offset) => self.take_val(offset)?,
+				Op::ReplaceVal(offset) => self.replace_val(offset)?,
+				Op::Super(s) => self.execute_super_instruction(s)?,
+				Op::LoopStart(distance) => {
+					if self.current_cell().is_zero() {
+						*pc += distance;
+					} else if checked {
+						iterations.push(0);
+						self.next_iteration(iterations.last_mut().unwrap())?;
+					}
+				}
+				Op::LoopEnd(distance) => {
+					if self.current_cell().is_zero() {
+						iterations.pop();
+					} else {
+						if checked {
+							self.next_iteration(iterations.last_mut().unwrap())?;
+						}
+
+						*pc -= distance;
+					}
+				}
+				Op::IfStart(distance) => {
+					if self.current_cell().is_zero() {
+						*pc += distance;
+					}
+				}
+				Op::IfEnd => _ = mem::take(self.cell_mut().as_mut_value()),
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use vmm_ir::Instruction;
+	use vmm_program::{SourceMap, SourceMapEntry, SourceSpan};
+
+	use super::{Bytecode, Op};
+
+	#[test]
+	fn jumps_are_resolved() {
+		let program = [
+			Instruction::inc_val(2),
+			Instruction::dynamic_loop([
+				Instruction::inc_val(-1),
+				Instruction::if_nz([Instruction::move_ptr(1)]),
+			]),
+			Instruction::write_once(),
+		];
+
+		let bytecode = Bytecode::compile(&program).unwrap();
+
+		assert_eq!(
+			bytecode.ops(),
+			[
+				Op::IncVal {
+					value: 2,
+					offset: 0.into()
+				},
+				Op::LoopStart(5),
+				Op::IncVal {
+					value: -1,
+					offset: 0.into()
+				},
+				Op::IfStart(2),
+				Op::MovePtr(1.into()),
+				Op::IfEnd,
+				Op::LoopEnd(5),
+				Op::Write(0.into()),
+			]
+		);
+	}
+
+	#[test]
+	fn spans_stay_with_their_ops() {
+		let program = [
+			Instruction::dynamic_loop([Instruction::inc_val(-1)]),
+			Instruction::write_once(),
+		];
+
+		// The loop's body has no entries, so its op mustn't take the spans of the ops after it.
+		let mut source_map = SourceMap::new();
+
+		source_map.push(SourceMapEntry::with_block(
+			SourceSpan::new(0, 3),
+			SourceMap::new(),
+		));
+		source_map.push(SourceMapEntry::new(SourceSpan::new(3, 4)));
+
+		let bytecode = Bytecode::compile_with_source_map(&program, &source_map).unwrap();
+
+		assert_eq!(bytecode.span(0), Some(SourceSpan::new(0, 3)));
+		assert_eq!(bytecode.span(1), None);
+		assert_eq!(bytecode.span(2), Some(SourceSpan::new(0, 3)));
+		assert_eq!(bytecode.span(3), Some(SourceSpan::new(3, 4)));
+	}
+}
@@ -0,0 +1,661 @@
+use std::{
+	collections::BTreeMap,
+	error::Error as StdError,
+	fmt::{Display, Formatter, Result as FmtResult, Write as _},
+	io::prelude::*,
+	mem,
+	num::ParseIntError,
+	str::FromStr,
+};
+
+use vmm_ir::{BlockInstruction, Instruction};
+use vmm_program::{Program, SourceMapEntry, SourceSpan};
+use vmm_tape::{Cell, CellValue, Tape};
+
+use super::{Interpreter, RuntimeError};
+
+#[derive(Debug, Clone)]
+pub struct Debugger<T, R, W> {
+	interpreter: Interpreter<T, R, W>,
+	program: Program,
+	position: InstructionPath,
+	iterations: Vec<usize>,
+	breakpoints: BTreeMap<usize, WatchedBreakpoint>,
+	next_breakpoint: usize,
+}
+
+impl<T: Tape, R, W> Debugger<T, R, W>
+where
+	R: Read + 'static,
+	W: Write + 'static,
+{
+	pub fn new(mut interpreter: Interpreter<T, R, W>) -> Self {
+		let program = mem::take(interpreter.program_mut());
+
+		let mut this = Self {
+			interpreter,
+			program,
+			position: InstructionPath::root(),
+			iterations: Vec::new(),
+			breakpoints: BTreeMap::new(),
+			next_breakpoint: 0,
+		};
+
+		this.settle();
+
+		this
+	}
+
+	pub const fn interpreter(&self) -> &Interpreter<T, R, W> {
+		&self.interpreter
+	}
+
+	pub const fn interpreter_mut(&mut self) -> &mut Interpreter<T, R, W> {
+		&mut self.interpreter
+	}
+
+	pub const fn program(&self) -> &Program {
+		&self.program
+	}
+
+	pub const fn position(&self) -> &InstructionPath {
+		&self.position
+	}
+
+	/// The breakpoints with their IDs, which stay the same when others are removed.
+	pub fn breakpoints(&self) -> impl Iterator<Item = (usize, &Breakpoint)> {
+		self.breakpoints
+			.iter()
+			.map(|(id, watched)| (*id, &watched.breakpoint))
+	}
+
+	pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> usize {
+		let id = self.next_breakpoint;
+		let held = breakpoint.holds(&self.interpreter, &self.position);
+
+		self.breakpoints
+			.insert(id, WatchedBreakpoint { breakpoint, held });
+		self.next_breakpoint += 1;
+
+		id
+	}
+
+	pub fn remove_breakpoint(&mut self, id: usize) -> Option<Breakpoint> {
+		self.breakpoints
+			.remove(&id)
+			.map(|watched| watched.breakpoint)
+	}
+
+	pub fn clear_breakpoints(&mut self) {
+		self.breakpoints.clear();
+	}
+
+	/// The instruction that will be executed by the next step, if the program hasn't finished.
+	pub fn current_instruction(&self) -> Option<&Instruction> {
+		resolve(&self.program, &self.position)
+	}
+
+	/// The span of source the next instruction came from, if the interpreter has a source map.
+	pub fn current_span(&self) -> Option<SourceSpan> {
+		self.interpreter
+			.source_map()?
+			.span(self.position.segments())
+	}
+
+	pub fn is_finished(&self) -> bool {
+		self.current_instruction().is_none()
+	}
+
+	/// Execute a single instruction, entering blocks instead of running them as a whole.
+	pub fn step(&mut self) -> Result<DebugEvent, RuntimeError> {
+		let Some(instr) = resolve(&self.program, &self.position) else {
+			return Ok(DebugEvent::Finished);
+		};
+
+		let span = self.current_span();
+
+		self.interpreter.error_span = None;
+		self.interpreter.profile_span(span);
+
+		match instr {
+			Instruction::Block(block) => {
+				self.interpreter
+					.consume_fuel()
+					.inspect_err(|_| self.interpreter.blame_span(span))?;
+
+				if let Some(profiler) = &mut self.interpreter.profiler {
+					profiler.handle(instr);
+				}
+
+				if self.interpreter.current_cell().is_zero() {
+					self.position.advance();
+				} else {
+					self.iterations.push(0);
+
+					if matches!(block, BlockInstruction::DynamicLoop(_)) {
+						self.interpreter
+							.next_iteration(self.iterations.last_mut().unwrap())
+							.inspect_err(|_| self.interpreter.blame_span(span))?;
+					}
+
+					self.position.enter();
+				}
+			}
+			instr => {
+				self.interpreter
+					.execute_instruction(instr, None)
+					.inspect_err(|_| self.interpreter.blame_span(span))?;
+				self.position.advance();
+			}
+		}
+
+		self.settle_checked()?;
+
+		Ok(self.event_after_step())
+	}
+
+	/// Execute a single instruction, running blocks to completion.
+	pub fn step_over(&mut self) -> Result<DebugEvent, RuntimeError> {
+		let Some(instr) = resolve(&self.program, &self.position) else {
+			return Ok(DebugEvent::Finished);
+		};
+
+		if !matches!(instr, Instruction::Block(..)) {
+			return self.step();
+		}
+
+		let span = self.current_span();
+
+		self.interpreter.error_span = None;
+		self.interpreter.profile_span(span);
+
+		// The block's own source map is needed to blame the instruction inside it that fails.
+		let source_map = self.interpreter.source_map.take();
+
+		let result = self.interpreter.execute_instruction(
+			instr,
+			source_map
+				.as_ref()
+				.and_then(|source_map| source_map.entry(self.position.segments()))
+				.map(SourceMapEntry::block),
+		);
+
+		self.interpreter.source_map = source_map;
+
+		result.inspect_err(|_| self.interpreter.blame_span(span))?;
+		self.position.advance();
+
+		self.settle_checked()?;
+
+		Ok(self.event_after_step())
+	}
+
+	/// Step until a breakpoint is hit or the program finishes.
+	pub fn resume(&mut self) -> Result<DebugEvent, RuntimeError> {
+		loop {
+			match self.step()? {
+				DebugEvent::Stepped => {}
+				event => return Ok(event),
+			}
+		}
+	}
+
+	pub fn into_interpreter(self) -> Interpreter<T, R, W> {
+		let mut interpreter = self.interpreter;
+
+		*interpreter.program_mut() = self.program;
+
+		interpreter
+	}
+
+	fn event_after_step(&mut self) -> DebugEvent {
+		if self.is_finished() {
+			return DebugEvent::Finished;
+		}
+
+		let mut hit = None;
+
+		// Every breakpoint has to see the new state, even once an earlier one has been hit.
+		for (id, watched) in &mut self.breakpoints {
+			let holds = watched.breakpoint.holds(&self.interpreter, &self.position);
+
+			// Cell conditions only fire when they start to hold, not on every step while they do.
+			let fires = holds
+				&& (matches!(watched.breakpoint, Breakpoint::Instruction(_)) || !watched.held);
+
+			watched.held = holds;
+
+			if fires {
+				hit.get_or_insert(*id);
+			}
+		}
+
+		hit.map_or(DebugEvent::Stepped, DebugEvent::Breakpoint)
+	}
+
+	fn settle(&mut self) {
+		_ = self.settle_checked();
+	}
+
+	// Resolve block ends until the position points at an instruction or the end of the program.
+	fn settle_checked(&mut self) -> Result<(), RuntimeError> {
+		while resolve(&self.program, &self.position).is_none() && self.position.depth() > 1 {
+			let parent = self.position.parent();
+
+			let Some(Instruction::Block(block)) = resolve(&self.program, &parent) else {
+				unreachable!("parent of a nested position is always a block");
+			};
+
+			match block {
+				BlockInstruction::DynamicLoop(_) if !self.interpreter.current_cell().is_zero() => {
+					self.interpreter
+						.next_iteration(self.iterations.last_mut().unwrap())?;
+
+					self.position.restart();
+				}
+				BlockInstruction::IfNz(_) => {
+					mem::take(self.interpreter.cell_mut().as_mut_value());
+
+					self.leave();
+				}
+				_ => self.leave(),
+			}
+		}
+
+		Ok(())
+	}
+
+	fn leave(&mut self) {
+		self.iterations.pop();
+		self.position.leave();
+		self.position.advance();
+	}
+}
+
+impl<T: Tape, R, W> Interpreter<T, R, W>
+where
+	R: Read + 'static,
+	W: Write + 'static,
+{
+	#[inline]
+	pub fn into_debugger(self) -> Debugger<T, R, W> {
+		Debugger::new(self)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugEvent {
+	Stepped,
+	Breakpoint(usize),
+	Finished,
+}
+
+#[derive(Debug, Clone)]
+struct WatchedBreakpoint {
+	breakpoint: Breakpoint,
+	held: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Breakpoint {
+	/// Stop before executing the instruction at the path.
+	Instruction(InstructionPath),
+	/// Stop when the cell at the index comes to hold the value.
+	Cell { index: usize, value: u32 },
+	/// Stop when the cell under the pointer comes to hold the value.
+	CurrentCell(u32),
+}
+
+impl Breakpoint {
+	fn holds<T: Tape, R, W>(
+		&self,
+		interpreter: &Interpreter<T, R, W>,
+		position: &InstructionPath,
+	) -> bool {
+		match self {
+			Self::Instruction(path) => path == position,
+			Self::Cell { index, value } => interpreter
+				.tape()
+				.as_slice()
+				.get(*index)
+				.is_some_and(|cell| cell.value().to_u32() == *value),
+			Self::CurrentCell(value) => interpreter.current_cell().value().to_u32() == *value,
+		}
+	}
+}
+
+impl Display for Breakpoint {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::Instruction(path) => {
+				f.write_str("at ")?;
+				Display::fmt(&path, f)
+			}
+			Self::Cell { index, value } => write!(f, "when cell #{index} == {value}"),
+			Self::CurrentCell(value) => write!(f, "when current cell == {value}"),
+		}
+	}
+}
+
+/// The position of an instruction in a (possibly nested) program.
+///
+/// Each segment is an index into the block selected by the previous segments, so `2.0` is the
+/// first instruction inside the block at index 2.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct InstructionPath(Vec<usize>);
+
+impl InstructionPath {
+	#[must_use]
+	pub fn new(segments: impl IntoIterator<Item = usize>) -> Self {
+		Self(segments.into_iter().collect())
+	}
+
+	#[must_use]
+	pub fn root() -> Self {
+		Self(vec![0])
+	}
+
+	#[must_use]
+	pub fn segments(&self) -> &[usize] {
+		&self.0
+	}
+
+	#[must_use]
+	pub const fn depth(&self) -> usize {
+		self.0.len()
+	}
+
+	#[must_use]
+	pub fn parent(&self) -> Self {
+		Self(self.0[..self.0.len() - 1].to_vec())
+	}
+
+	fn enter(&mut self) {
+		self.0.push(0);
+	}
+
+	fn leave(&mut self) {
+		self.0.pop();
+	}
+
+	fn advance(&mut self) {
+		if let Some(last) = self.0.last_mut() {
+			*last += 1;
+		}
+	}
+
+	fn restart(&mut self) {
+		if let Some(last) = self.0.last_mut() {
+			*last = 0;
+		}
+	}
+}
+
+impl Display for InstructionPath {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		let mut first = true;
+
+		for segment in &self.0 {
+			if !first {
+				f.write_char('.')?;
+			}
+
+			Display::fmt(&segment, f)?;
+			first = false;
+		}
+
+		Ok(())
+	}
+}
+
+impl FromStr for InstructionPath {
+	type Err = ParseIntError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		s.split('.')
+			.map(str::parse)
+			.collect::<Result<Vec<_>, _>>()
+			.map(Self)
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TapeWindow<'a, V: CellValue = u8> {
+	start: usize,
+	cells: &'a [Cell<V>],
+	ptr: usize,
+}
+
+impl<'a, V: CellValue> TapeWindow<'a, V> {
+	#[must_use]
+	pub fn new(cells: &'a [Cell<V>], ptr: usize, radius: usize) -> Self {
+		let start = ptr.saturating_sub(radius);
+		let end = ptr.saturating_add(radius + 1).min(cells.len());
+
+		Self {
+			start,
+			cells: &cells[start..end],
+			ptr,
+		}
+	}
+
+	#[must_use]
+	pub const fn start(self) -> usize {
+		self.start
+	}
+
+	#[must_use]
+	pub const fn cells(self) -> &'a [Cell<V>] {
+		self.cells
+	}
+
+	#[must_use]
+	pub const fn ptr(self) -> usize {
+		self.ptr
+	}
+}
+
+impl<V: CellValue> Display for TapeWindow<'_, V> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		for (i, cell) in self.cells.iter().enumerate() {
+			let index = self.start + i;
+
+			if index == self.ptr {
+				write!(f, "[#{index}: {cell}]")?;
+			} else {
+				write!(f, " #{index}: {cell} ")?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl<T: Tape, R, W> Interpreter<T, R, W> {
+	#[must_use]
+	pub fn tape_window(&self, radius: usize) -> TapeWindow<'_, T::Value> {
+		TapeWindow::new(self.tape().as_slice(), self.ptr().value(), radius)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBreakpointError(String);
+
+impl Display for ParseBreakpointError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		f.write_str("invalid breakpoint: ")?;
+		f.write_str(&self.0)
+	}
+}
+
+impl StdError for ParseBreakpointError {}
+
+impl FromStr for Breakpoint {
+	type Err = ParseBreakpointError;
+
+	/// Parses `<path>`, `#<index>=<value>` or `*=<value>`.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let err = |_| ParseBreakpointError(s.to_owned());
+
+		if let Some((cell, value)) = s.split_once('=') {
+			let value = value.trim().parse().map_err(err)?;
+
+			match cell.trim() {
+				"*" => Ok(Self::CurrentCell(value)),
+				cell => {
+					let index = cell
+						.strip_prefix('#')
+						.ok_or_else(|| ParseBreakpointError(s.to_owned()))?
+						.parse()
+						.map_err(err)?;
+
+					Ok(Self::Cell { index, value })
+				}
+			}
+		} else {
+			s.trim().parse().map(Self::Instruction).map_err(err)
+		}
+	}
+}
+
+fn resolve<'a>(program: &'a [Instruction], path: &InstructionPath) -> Option<&'a Instruction> {
+	let (first, rest) = path.segments().split_first()?;
+
+	let mut instr = program.get(*first)?;
+
+	for segment in rest {
+		let Instruction::Block(block) = instr else {
+			return None;
+		};
+
+		instr = block.get(*segment)?;
+	}
+
+	Some(instr)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io;
+
+	use vmm_ir::Instruction;
+	use vmm_program::Program;
+	use vmm_tape::BoxTape;
+
+	use super::{Breakpoint, DebugEvent, Debugger, InstructionPath};
+	use crate::Interpreter;
+
+	fn debugger(
+		program: impl IntoIterator<Item = Instruction>,
+	) -> Debugger<BoxTape, io::Empty, Vec<u8>> {
+		Interpreter::new(
+			program.into_iter().collect::<Program>(),
+			io::empty(),
+			Vec::new(),
+		)
+		.into_debugger()
+	}
+
+	#[test]
+	fn step_enters_loops() {
+		let mut debugger = debugger([
+			Instruction::inc_val(2),
+			Instruction::dynamic_loop([Instruction::inc_val(-1), Instruction::inc_val_at(1, 1)]),
+		]);
+
+		assert_eq!(debugger.step().unwrap(), DebugEvent::Stepped);
+		assert_eq!(debugger.position(), &InstructionPath::new([1]));
+
+		assert_eq!(debugger.step().unwrap(), DebugEvent::Stepped);
+		assert_eq!(debugger.position(), &InstructionPath::new([1, 0]));
+
+		debugger.step().unwrap();
+		debugger.step().unwrap();
+
+		assert_eq!(debugger.position(), &InstructionPath::new([1, 0]));
+
+		debugger.step().unwrap();
+
+		assert_eq!(debugger.step().unwrap(), DebugEvent::Finished);
+		assert_eq!(debugger.interpreter().get_cell(1).value(), 2);
+	}
+
+	#[test]
+	fn step_over_runs_blocks() {
+		let mut debugger = debugger([
+			Instruction::inc_val(3),
+			Instruction::dynamic_loop([Instruction::inc_val(-1), Instruction::inc_val_at(2, 1)]),
+			Instruction::write_once(),
+		]);
+
+		debugger.step_over().unwrap();
+		debugger.step_over().unwrap();
+
+		assert_eq!(debugger.position(), &InstructionPath::new([2]));
+		assert_eq!(debugger.interpreter().get_cell(1).value(), 6);
+	}
+
+	#[test]
+	fn breakpoints() {
+		let mut debugger = debugger([
+			Instruction::inc_val(5),
+			Instruction::dynamic_loop([Instruction::inc_val(-1), Instruction::inc_val_at(1, 1)]),
+			Instruction::move_ptr(1),
+		]);
+
+		let cell = debugger.add_breakpoint("#1=3".parse().unwrap());
+		let instr = debugger.add_breakpoint(Breakpoint::Instruction(InstructionPath::new([2])));
+
+		assert_eq!(debugger.resume().unwrap(), DebugEvent::Breakpoint(cell));
+		assert_eq!(debugger.interpreter().current_cell().value(), 2);
+
+		assert_eq!(
+			debugger.remove_breakpoint(cell),
+			Some("#1=3".parse().unwrap())
+		);
+		assert_eq!(debugger.remove_breakpoint(cell), None);
+
+		assert_eq!(debugger.resume().unwrap(), DebugEvent::Breakpoint(instr));
+		assert_eq!(
+			debugger.breakpoints().collect::<Vec<_>>(),
+			[(instr, &Breakpoint::Instruction(InstructionPath::new([2])))]
+		);
+		assert_eq!(debugger.resume().unwrap(), DebugEvent::Finished);
+	}
+
+	#[test]
+	fn cell_breakpoints_fire_when_the_condition_starts_to_hold() {
+		let mut debugger = debugger([
+			Instruction::inc_val(1),
+			Instruction::move_ptr(1),
+			Instruction::move_ptr(1),
+			Instruction::move_ptr(-2),
+			Instruction::inc_val(1),
+		]);
+
+		let cell = debugger.add_breakpoint("#0=1".parse().unwrap());
+		let current = debugger.add_breakpoint(Breakpoint::CurrentCell(0));
+
+		assert_eq!(debugger.resume().unwrap(), DebugEvent::Breakpoint(cell));
+		assert_eq!(debugger.position(), &InstructionPath::new([1]));
+
+		assert_eq!(debugger.resume().unwrap(), DebugEvent::Breakpoint(current));
+		assert_eq!(debugger.position(), &InstructionPath::new([2]));
+
+		assert_eq!(debugger.resume().unwrap(), DebugEvent::Finished);
+	}
+
+	#[test]
+	fn tape_window() {
+		let mut debugger = debugger([Instruction::inc_val(7), Instruction::move_ptr(1)]);
+
+		debugger.resume().unwrap();
+
+		let window = debugger.interpreter().tape_window(1);
+
+		assert_eq!(window.start(), 0);
+		assert_eq!(window.cells().len(), 3);
+		assert_eq!(window.to_string(), " #0: 7 [#1: 0] #2: 0 ");
+	}
+}
@@ -1,6 +1,10 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg, doc_cfg))]
 #![cfg_attr(feature = "nightly", feature(portable_simd))]
 
+mod bounds;
+mod budget;
+mod bytecode;
+mod debugger;
 mod profiler;
 
 use std::{
@@ -8,44 +12,80 @@ use std::{
 	fmt::{Debug, Display, Formatter, Result as FmtResult},
 	io::{Error as IoError, ErrorKind as IoErrorKind, Stdin, Stdout, prelude::*, stdin, stdout},
 	mem,
-	num::NonZeroU8,
+	num::NonZeroU32,
+	time::{Duration, Instant},
 };
 
 use tap::prelude::*;
-use vmm_ir::{BlockInstruction, Instruction, Offset, ScaleAnd, SuperInstruction};
-use vmm_num::ops::{WrappingAddAssign, WrappingMul, WrappingMulAssign, WrappingSubAssign};
-use vmm_program::Program;
-use vmm_tape::{Cell, Tape, TapePointer};
+use vmm_ir::{BlockInstruction, EofPolicy, Instruction, Offset, ScaleAnd, SuperInstruction};
+use vmm_num::ops::{
+	WrappingAddAssign, WrappingFrom, WrappingMul, WrappingMulAssign, WrappingSubAssign,
+};
+use vmm_program::{Program, SourceMap, SourceMapEntry, SourceSpan};
+use vmm_tape::{Cell, CellValue as _, Tape, TapePointer};
 use vmm_utils::GetOrZero as _;
 
-pub use self::profiler::*;
+pub use self::{bounds::*, budget::*, bytecode::*, debugger::*, profiler::*};
 
-pub const ITERATION_LIMIT: usize = 100_000;
+// Reading the clock on every instruction is too slow, so the time limit is only checked this often.
+const TIME_CHECK_INTERVAL: u64 = 1 << 12;
 
 #[derive(Debug, Clone)]
 pub struct Interpreter<T, R = Stdin, W = Stdout> {
 	program: Program,
+	source_map: Option<SourceMap>,
+	error_span: Option<SourceSpan>,
 	input: R,
 	output: W,
 	profiler: Option<Profiler>,
+	source_profile: Option<SourceProfile>,
+	bounds: BoundsPolicy,
+	eof: EofPolicy,
+	budget: ExecutionBudget,
+	fuel_used: u64,
+	started: Option<Instant>,
 	tape: T,
 }
 
 impl<T: Tape, R, W> Interpreter<T, R, W> {
 	#[inline]
 	pub fn new(program: Program, input: R, output: W) -> Self {
+		Self::with_tape(program, input, output, {
+			let mut t = T::default();
+
+			t.init();
+
+			t
+		})
+	}
+
+	#[inline]
+	pub fn with_tape_len(program: Program, input: R, output: W, len: usize) -> Self {
+		Self::with_tape(program, input, output, {
+			let mut t = T::with_len(len);
+
+			t.init();
+
+			t
+		})
+	}
+
+	#[inline]
+	pub const fn with_tape(program: Program, input: R, output: W, tape: T) -> Self {
 		Self {
 			program,
+			source_map: None,
+			error_span: None,
 			input,
 			output,
 			profiler: None,
-			tape: {
-				let mut t = T::default();
-
-				t.init();
-
-				t
-			},
+			source_profile: None,
+			bounds: BoundsPolicy::Wrap,
+			eof: EofPolicy::Zero,
+			budget: ExecutionBudget::unlimited(),
+			fuel_used: 0,
+			started: None,
+			tape,
 		}
 	}
 
@@ -56,16 +96,96 @@ impl<T: Tape, R, W> Interpreter<T, R, W> {
 
 	#[inline]
 	#[must_use]
-	pub const fn and_with_profiler(mut self) -> Self {
+	pub fn and_with_profiler(mut self) -> Self {
 		self.profiler = Some(Profiler::new());
+		self.source_profile = Some(SourceProfile::new());
+		self
+	}
+
+	/// Track where in the source each instruction came from, so errors and the profiler can point
+	/// back to it.
+	///
+	/// # Panics
+	///
+	/// If the source map doesn't have an entry for every instruction of the program.
+	#[inline]
+	#[must_use]
+	pub fn and_with_source_map(mut self, source_map: SourceMap) -> Self {
+		assert!(
+			source_map.matches(&self.program),
+			"source map must match the program"
+		);
+
+		self.source_map = Some(source_map);
+		self
+	}
+
+	#[inline]
+	pub const fn source_map(&self) -> Option<&SourceMap> {
+		self.source_map.as_ref()
+	}
+
+	/// The span of the innermost instruction that failed during the last run.
+	#[inline]
+	pub const fn error_span(&self) -> Option<SourceSpan> {
+		self.error_span
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn and_with_bounds_policy(mut self, bounds: BoundsPolicy) -> Self {
+		self.bounds = bounds;
+		self
+	}
+
+	#[inline]
+	pub const fn bounds_policy(&self) -> BoundsPolicy {
+		self.bounds
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn and_with_eof_policy(mut self, eof: EofPolicy) -> Self {
+		self.eof = eof;
 		self
 	}
 
+	#[inline]
+	pub const fn eof_policy(&self) -> EofPolicy {
+		self.eof
+	}
+
+	#[inline]
+	#[must_use]
+	pub const fn and_with_budget(mut self, budget: ExecutionBudget) -> Self {
+		self.budget = budget;
+		self
+	}
+
+	#[inline]
+	pub const fn budget(&self) -> ExecutionBudget {
+		self.budget
+	}
+
+	/// The number of instructions and loop iterations executed so far, only counted while a budget
+	/// is set.
+	#[inline]
+	pub const fn fuel_used(&self) -> u64 {
+		self.fuel_used
+	}
+
 	#[inline]
 	pub fn profiler(&self) -> Profiler {
 		self.profiler.unwrap_or_default()
 	}
 
+	/// How often the instructions from each span of source ran, only filled in when both a
+	/// profiler and a source map are set.
+	#[inline]
+	pub const fn source_profile(&self) -> Option<&SourceProfile> {
+		self.source_profile.as_ref()
+	}
+
 	#[inline]
 	pub const fn program(&self) -> &Program {
 		&self.program
@@ -87,20 +207,20 @@ impl<T: Tape, R, W> Interpreter<T, R, W> {
 	}
 
 	#[inline]
-	pub fn current_cell(&self) -> &Cell {
+	pub fn current_cell(&self) -> &Cell<T::Value> {
 		unsafe { self.tape().current_cell_unchecked() }
 	}
 
 	#[inline]
-	pub fn cell_mut(&mut self) -> &mut Cell {
+	pub fn cell_mut(&mut self) -> &mut Cell<T::Value> {
 		unsafe { self.tape_mut().current_cell_unchecked_mut() }
 	}
 
-	pub fn get_cell(&self, idx: usize) -> &Cell {
+	pub fn get_cell(&self, idx: usize) -> &Cell<T::Value> {
 		unsafe { self.tape().get_unchecked(idx) }
 	}
 
-	pub fn get_mut_cell(&mut self, idx: usize) -> &mut Cell {
+	pub fn get_mut_cell(&mut self, idx: usize) -> &mut Cell<T::Value> {
 		unsafe { self.tape_mut().get_unchecked_mut(idx) }
 	}
 
@@ -131,52 +251,120 @@ where
 {
 	#[inline]
 	pub fn into_dyn(self) -> Interpreter<T, Box<dyn Read>, Box<dyn Write>> {
-		Interpreter::new(self.program, Box::new(self.input), Box::new(self.output))
+		self.map_io(|input, output| -> (Box<dyn Read>, Box<dyn Write>) {
+			(Box::new(input), Box::new(output))
+		})
 	}
 
 	#[inline]
 	pub fn with_input<RR: Read>(self, input: RR) -> Interpreter<T, RR, W> {
-		Interpreter::new(self.program, input, self.output)
+		self.map_io(|_, output| (input, output))
 	}
 
 	#[inline]
 	pub fn with_output<WW: Write>(self, output: WW) -> Interpreter<T, R, WW> {
-		Interpreter::new(self.program, self.input, output)
+		self.map_io(|input, _| (input, output))
 	}
 
 	#[inline]
 	pub fn with_io<RR: Read, WW: Write>(self, input: RR, output: WW) -> Interpreter<T, RR, WW> {
-		Interpreter::new(self.program, input, output)
+		self.map_io(|_, _| (input, output))
+	}
+
+	fn map_io<RR, WW>(self, f: impl FnOnce(R, W) -> (RR, WW)) -> Interpreter<T, RR, WW> {
+		let (input, output) = f(self.input, self.output);
+
+		Interpreter {
+			program: self.program,
+			source_map: self.source_map,
+			error_span: self.error_span,
+			input,
+			output,
+			profiler: self.profiler,
+			source_profile: self.source_profile,
+			bounds: self.bounds,
+			eof: self.eof,
+			budget: self.budget,
+			fuel_used: self.fuel_used,
+			started: self.started,
+			tape: self.tape,
+		}
 	}
 
 	#[inline]
 	pub fn run(&mut self) -> Result<(), RuntimeError> {
 		let program = mem::take(self.program_mut());
+		let source_map = self.source_map.take();
+
+		self.error_span = None;
+
+		let result = self.execute_block(&program, source_map.as_ref());
+
+		self.source_map = source_map;
+
+		result
+	}
+
+	// Run the instructions of a single block, along with the source map for them.
+	#[inline]
+	fn execute_block(
+		&mut self,
+		instrs: &[Instruction],
+		source_map: Option<&SourceMap>,
+	) -> Result<(), RuntimeError> {
+		for (i, instr) in instrs.iter().enumerate() {
+			let entry = source_map.and_then(|source_map| source_map.get(i));
+			let span = entry.map(SourceMapEntry::span);
+
+			self.profile_span(span);
+
+			self.execute_instruction(instr, entry.map(SourceMapEntry::block))
+				.inspect_err(|_| self.blame_span(span))?;
+		}
+
+		Ok(())
+	}
+
+	#[inline]
+	fn profile_span(&mut self, span: Option<SourceSpan>) {
+		if let (Some(profile), Some(span)) = (&mut self.source_profile, span) {
+			profile.handle(span);
+		}
+	}
 
-		program
-			.into_iter()
-			.try_for_each(|instr| self.execute_instruction(instr))
+	// Errors are blamed on the innermost instruction, which is the first to see them.
+	const fn blame_span(&mut self, span: Option<SourceSpan>) {
+		if self.error_span.is_none() {
+			self.error_span = span;
+		}
 	}
 
 	#[inline]
 	fn read_char(&mut self) -> Result<(), RuntimeError> {
-		loop {
+		let value = loop {
 			let mut buf = [0];
 			let err = self.input.read_exact(&mut buf);
-			match err.as_ref().map_err(IoError::kind) {
-				Err(IoErrorKind::UnexpectedEof) => {
-					mem::take(&mut buf);
+			if matches!(
+				err.as_ref().map_err(IoError::kind),
+				Err(IoErrorKind::UnexpectedEof)
+			) {
+				match self.eof {
+					EofPolicy::Zero => break T::Value::ZERO,
+					EofPolicy::Max => break T::Value::wrapping_from(u32::MAX),
+					EofPolicy::Unchanged => return Ok(()),
 				}
-				_ => err?,
 			}
 
+			err?;
+
 			if cfg!(target_os = "windows") && matches!(buf[0], b'\r') {
 				continue;
 			}
 
-			self.cell_mut().set_value(buf[0]);
-			break;
-		}
+			break T::Value::wrapping_from(buf[0]);
+		};
+
+		self.cell_mut().set_value(value);
 
 		Ok(())
 	}
@@ -187,33 +375,46 @@ where
 	}
 
 	#[inline]
-	fn inc_val(&mut self, value: i8, offset: Offset) -> Result<(), RuntimeError> {
-		let idx = self.calculate_index(offset);
+	fn inc_val(&mut self, value: i32, offset: Offset) -> Result<(), RuntimeError> {
+		let idx = self.calculate_index(offset)?;
 
-		WrappingAddAssign::wrapping_add_assign(self.get_mut_cell(idx), value);
+		WrappingAddAssign::wrapping_add_assign(
+			self.get_mut_cell(idx),
+			T::Value::wrapping_from(value),
+		);
 
 		Ok(())
 	}
 
 	#[inline]
-	fn set_val(&mut self, value: Option<NonZeroU8>, offset: Offset) -> Result<(), RuntimeError> {
-		let idx = self.calculate_index(offset);
+	fn set_val(&mut self, value: Option<NonZeroU32>, offset: Offset) -> Result<(), RuntimeError> {
+		let idx = self.calculate_index(offset)?;
 
-		self.get_mut_cell(idx).set_value(value.get_or_zero());
+		self.get_mut_cell(idx)
+			.set_value(WrappingFrom::wrapping_from(value.get_or_zero()));
 
 		Ok(())
 	}
 
 	#[inline]
 	fn move_ptr(&mut self, offset: Offset) -> Result<(), RuntimeError> {
-		*self.ptr_mut() += offset.value();
+		if matches!(self.bounds, BoundsPolicy::Wrap) {
+			self.tape_mut().move_ptr(offset.value());
+		} else {
+			let idx = self.resolve_offset(offset.value())?;
+
+			unsafe { self.ptr_mut().set_unchecked(idx) };
+		}
 
 		Ok(())
 	}
 
 	#[inline]
 	fn find_zero(&mut self, offset: Offset) -> Result<(), RuntimeError> {
+		let mut iterations = 0;
+
 		while !self.current_cell().is_zero() {
+			self.next_iteration(&mut iterations)?;
 			self.move_ptr(offset)?;
 		}
 
@@ -221,19 +422,17 @@ where
 	}
 
 	#[inline]
-	fn dyn_loop(&mut self, instructions: &[Instruction]) -> Result<(), RuntimeError> {
-		let mut iterations = 0usize;
+	fn dyn_loop(
+		&mut self,
+		instructions: &[Instruction],
+		source_map: Option<&SourceMap>,
+	) -> Result<(), RuntimeError> {
+		let mut iterations = 0;
 
 		while !self.current_cell().is_zero() {
-			iterations += 1;
-
-			if matches!(iterations, ITERATION_LIMIT) {
-				return Err(RuntimeError::TooManyIterations(self.ptr().value()));
-			}
+			self.next_iteration(&mut iterations)?;
 
-			instructions
-				.iter()
-				.try_for_each(|instr| self.execute_instruction(instr))?;
+			self.execute_block(instructions, source_map)?;
 		}
 
 		Ok(())
@@ -241,9 +440,9 @@ where
 
 	#[inline]
 	fn move_val(&mut self, offset: Offset) -> Result<(), RuntimeError> {
-		let src_value = mem::take(self.cell_mut().as_mut_u8());
+		let src_value = mem::take(self.cell_mut().as_mut_value());
 
-		let dst_offset = self.calculate_index(offset);
+		let dst_offset = self.calculate_index(offset)?;
 
 		WrappingAddAssign::wrapping_add_assign(self.get_mut_cell(dst_offset), src_value);
 
@@ -252,9 +451,9 @@ where
 
 	#[inline]
 	fn fetch_val(&mut self, offset: Offset) -> Result<(), RuntimeError> {
-		let src_offset = self.calculate_index(offset);
+		let src_offset = self.calculate_index(offset)?;
 
-		let value = mem::take(self.get_mut_cell(src_offset).as_mut_u8());
+		let value = mem::take(self.get_mut_cell(src_offset).as_mut_value());
 
 		WrappingAddAssign::wrapping_add_assign(self.cell_mut(), value);
 
@@ -262,29 +461,29 @@ where
 	}
 
 	#[inline]
-	fn scale_and_move_val(&mut self, factor: u8, offset: Offset) -> Result<(), RuntimeError> {
+	fn scale_and_move_val(&mut self, factor: u32, offset: Offset) -> Result<(), RuntimeError> {
+		let dst_offset = self.calculate_index(offset)?;
 		let src_offset = self.ptr().value();
-		let dst_offset = self.calculate_index(offset);
 
-		let src_val = mem::take(self.get_mut_cell(src_offset).as_mut_u8());
+		let src_val = mem::take(self.get_mut_cell(src_offset).as_mut_value());
 
 		WrappingAddAssign::wrapping_add_assign(
 			self.get_mut_cell(dst_offset),
-			WrappingMul::wrapping_mul(src_val, factor),
+			WrappingMul::wrapping_mul(src_val, T::Value::wrapping_from(factor)),
 		);
 
 		Ok(())
 	}
 
 	#[inline]
-	fn fetch_and_scale_val(&mut self, factor: u8, offset: Offset) -> Result<(), RuntimeError> {
-		let src_offset = self.calculate_index(offset);
+	fn fetch_and_scale_val(&mut self, factor: u32, offset: Offset) -> Result<(), RuntimeError> {
+		let src_offset = self.calculate_index(offset)?;
 
-		let value = mem::take(self.get_mut_cell(src_offset).as_mut_u8());
+		let value = mem::take(self.get_mut_cell(src_offset).as_mut_value());
 
 		WrappingAddAssign::wrapping_add_assign(
 			self.cell_mut(),
-			WrappingMul::wrapping_mul(value, factor),
+			WrappingMul::wrapping_mul(value, T::Value::wrapping_from(factor)),
 		);
 
 		Ok(())
@@ -293,18 +492,21 @@ where
 	#[inline]
 	fn scale_and_set_val(
 		&mut self,
-		factor: u8,
+		factor: u32,
 		offset: Offset,
-		value: NonZeroU8,
+		value: NonZeroU32,
 	) -> Result<(), RuntimeError> {
+		let dst_offset = self.calculate_index(offset)?;
 		let src_offset = self.ptr().value();
-		let dst_offset = self.calculate_index(offset);
 
-		let src_val = mem::replace(self.get_mut_cell(src_offset).as_mut_u8(), value.get());
+		let src_val = mem::replace(
+			self.get_mut_cell(src_offset).as_mut_value(),
+			WrappingFrom::wrapping_from(value.get()),
+		);
 
 		WrappingAddAssign::wrapping_add_assign(
 			self.get_mut_cell(dst_offset),
-			WrappingMul::wrapping_mul(src_val, factor),
+			WrappingMul::wrapping_mul(src_val, T::Value::wrapping_from(factor)),
 		);
 
 		Ok(())
@@ -313,29 +515,33 @@ where
 	#[inline]
 	fn set_until_zero(
 		&mut self,
-		value: Option<NonZeroU8>,
-		Offset(offset): Offset,
+		value: Option<NonZeroU32>,
+		offset: Offset,
 	) -> Result<(), RuntimeError> {
+		let mut iterations = 0;
+
 		while !self.current_cell().is_zero() {
-			self.cell_mut().set_value(value.get_or_zero());
-			*self.ptr_mut() += offset;
+			self.next_iteration(&mut iterations)?;
+			self.cell_mut()
+				.set_value(WrappingFrom::wrapping_from(value.get_or_zero()));
+			self.move_ptr(offset)?;
 		}
 
 		Ok(())
 	}
 
 	#[inline]
-	fn scale_val(&mut self, factor: u8) -> Result<(), RuntimeError> {
-		WrappingMulAssign::wrapping_mul_assign(self.cell_mut(), factor);
+	fn scale_val(&mut self, factor: u32) -> Result<(), RuntimeError> {
+		WrappingMulAssign::wrapping_mul_assign(self.cell_mut(), T::Value::wrapping_from(factor));
 
 		Ok(())
 	}
 
 	#[inline]
 	fn sub_cell(&mut self, offset: Offset) -> Result<(), RuntimeError> {
-		let idx = self.calculate_index(offset);
+		let idx = self.calculate_index(offset)?;
 
-		let current_value = mem::take(self.cell_mut().as_mut_u8());
+		let current_value = mem::take(self.cell_mut().as_mut_value());
 
 		WrappingSubAssign::wrapping_sub_assign(self.get_mut_cell(idx), current_value);
 
@@ -344,7 +550,7 @@ where
 
 	#[inline]
 	fn take_val(&mut self, offset: Offset) -> Result<(), RuntimeError> {
-		let current_value = mem::take(self.cell_mut().as_mut_u8());
+		let current_value = mem::take(self.cell_mut().as_mut_value());
 
 		self.move_ptr(offset)?;
 
@@ -354,21 +560,21 @@ where
 	}
 
 	#[inline]
-	fn scale_and_take_val(&mut self, factor: u8, offset: Offset) -> Result<(), RuntimeError> {
-		let current_value = mem::take(self.cell_mut().as_mut_u8());
+	fn scale_and_take_val(&mut self, factor: u32, offset: Offset) -> Result<(), RuntimeError> {
+		let current_value = mem::take(self.cell_mut().as_mut_value());
 
 		self.move_ptr(offset)?;
 
 		WrappingAddAssign::wrapping_add_assign(
 			self.cell_mut(),
-			WrappingMul::wrapping_mul(current_value, factor),
+			WrappingMul::wrapping_mul(current_value, T::Value::wrapping_from(factor)),
 		);
 
 		Ok(())
 	}
 
 	#[inline]
-	fn find_and_set_zero(&mut self, offset: Offset, value: NonZeroU8) -> Result<(), RuntimeError> {
+	fn find_and_set_zero(&mut self, offset: Offset, value: NonZeroU32) -> Result<(), RuntimeError> {
 		self.find_zero(offset)?;
 
 		self.set_val(Some(value), Offset(0))?;
@@ -387,18 +593,21 @@ where
 
 	#[inline]
 	fn replace_val(&mut self, offset: Offset) -> Result<(), RuntimeError> {
-		let src_offset = self.calculate_index(offset);
+		let src_offset = self.calculate_index(offset)?;
 
-		let value = mem::take(self.get_mut_cell(src_offset).as_mut_u8());
+		let value = mem::take(self.get_mut_cell(src_offset).as_mut_value());
 
-		_ = mem::replace(self.cell_mut().as_mut_u8(), value);
+		_ = mem::replace(self.cell_mut().as_mut_value(), value);
 
 		Ok(())
 	}
 
 	#[inline]
 	fn shift_vals(&mut self, jump_by: Offset, offset: Offset) -> Result<(), RuntimeError> {
+		let mut iterations = 0;
+
 		while !self.current_cell().is_zero() {
+			self.next_iteration(&mut iterations)?;
 			self.move_val(offset)?;
 			self.move_ptr(jump_by)?;
 		}
@@ -407,55 +616,64 @@ where
 	}
 
 	fn write(&mut self, offset: Offset) -> Result<(), RuntimeError> {
-		let idx = self.calculate_index(offset);
+		let idx = self.calculate_index(offset)?;
 
-		let byte = self.get_cell(idx).value();
+		let byte = self.get_cell(idx).value().to_u8();
 
 		self.write_to_output(byte)
 	}
 
 	#[inline]
-	fn execute_instruction(&mut self, instr: &Instruction) -> Result<(), RuntimeError> {
+	fn execute_instruction(
+		&mut self,
+		instr: &Instruction,
+		block_source_map: Option<&SourceMap>,
+	) -> Result<(), RuntimeError> {
+		if !matches!(instr, Instruction::Boundary) {
+			self.consume_fuel()?;
+		}
+
 		if let Some(profiler) = &mut self.profiler {
 			profiler.handle(instr);
 		}
 
 		match instr {
-			Instruction::Boundary => self.boundary()?,
-			Instruction::IncVal { value, offset } => self.inc_val(*value, *offset)?,
-			Instruction::SetVal { value, offset } => self.set_val(*value, *offset)?,
-			Instruction::MovePtr(offset) => self.move_ptr(*offset)?,
-			Instruction::Read => self.read_char()?,
-			Instruction::FindZero(i) => self.find_zero(*i)?,
-			Instruction::SubCell { offset } => self.sub_cell(*offset)?,
-			Instruction::Block(l) => self.execute_loop_instruction(l)?,
-			Instruction::ScaleVal { factor } => self.scale_val(*factor)?,
-			Instruction::Super(s) => self.execute_super_instruction(*s)?,
-			Instruction::FetchVal(offset) => self.fetch_val(*offset)?,
-			Instruction::MoveVal(offset) => self.move_val(*offset)?,
-			Instruction::TakeVal(offset) => self.take_val(*offset)?,
-			Instruction::ReplaceVal(offset) => self.replace_val(*offset)?,
-			Instruction::Write { offset } => self.write(*offset)?,
-			i => return Err(RuntimeError::Unimplemented(i.clone())),
+			Instruction::Boundary => self.boundary(),
+			Instruction::IncVal { value, offset } => self.inc_val(*value, *offset),
+			Instruction::SetVal { value, offset } => self.set_val(*value, *offset),
+			Instruction::MovePtr(offset) => self.move_ptr(*offset),
+			Instruction::Read => self.read_char(),
+			Instruction::FindZero(i) => self.find_zero(*i),
+			Instruction::SubCell { offset } => self.sub_cell(*offset),
+			Instruction::Block(l) => self.execute_loop_instruction(l, block_source_map),
+			Instruction::ScaleVal { factor } => self.scale_val(*factor),
+			Instruction::Super(s) => self.execute_super_instruction(*s),
+			Instruction::FetchVal(offset) => self.fetch_val(*offset),
+			Instruction::MoveVal(offset) => self.move_val(*offset),
+			Instruction::TakeVal(offset) => self.take_val(*offset),
+			Instruction::ReplaceVal(offset) => self.replace_val(*offset),
+			Instruction::Write { offset } => self.write(*offset),
+			i => Err(RuntimeError::Unimplemented(i.clone())),
 		}
-
-		Ok(())
+		.map_err(|e| e.in_instruction(instr))
 	}
 
 	#[inline]
-	fn execute_loop_instruction(&mut self, instr: &BlockInstruction) -> Result<(), RuntimeError> {
+	fn execute_loop_instruction(
+		&mut self,
+		instr: &BlockInstruction,
+		source_map: Option<&SourceMap>,
+	) -> Result<(), RuntimeError> {
 		match instr {
-			BlockInstruction::DynamicLoop(instrs) => self.dyn_loop(instrs)?,
+			BlockInstruction::DynamicLoop(instrs) => self.dyn_loop(instrs, source_map)?,
 			BlockInstruction::IfNz(instrs) => {
 				if self.current_cell().is_zero() {
 					return Ok(());
 				}
 
-				instrs
-					.iter()
-					.try_for_each(|i| self.execute_instruction(i))?;
+				self.execute_block(instrs, source_map)?;
 
-				mem::take(self.cell_mut().as_mut_u8());
+				mem::take(self.cell_mut().as_mut_value());
 			}
 			i => return Err(RuntimeError::Unimplemented(i.clone().convert())),
 		}
@@ -503,13 +721,67 @@ where
 	}
 
 	#[inline]
-	#[allow(clippy::missing_const_for_fn)]
-	fn calculate_index(&self, offset: Offset) -> usize {
-		if matches!(offset, Offset(0)) {
-			self.ptr().value()
-		} else {
-			(self.ptr() + offset.0).value()
+	fn consume_fuel(&mut self) -> Result<(), RuntimeError> {
+		if self.budget.is_unlimited() {
+			return Ok(());
+		}
+
+		self.fuel_used += 1;
+
+		if let Some(fuel) = self.budget.fuel
+			&& self.fuel_used > fuel
+		{
+			return Err(RuntimeError::OutOfFuel(fuel));
+		}
+
+		if let Some(time_limit) = self.budget.time_limit {
+			let started = *self.started.get_or_insert_with(Instant::now);
+
+			if self.fuel_used.is_multiple_of(TIME_CHECK_INTERVAL) && started.elapsed() > time_limit
+			{
+				return Err(RuntimeError::OutOfTime(time_limit));
+			}
 		}
+
+		Ok(())
+	}
+
+	// Count an iteration of a loop or scan, which uses fuel like an instruction does.
+	#[inline]
+	fn next_iteration(&mut self, iterations: &mut usize) -> Result<(), RuntimeError> {
+		*iterations += 1;
+
+		if let Some(limit) = self.budget.loop_limit
+			&& *iterations > limit
+		{
+			return Err(RuntimeError::TooManyIterations {
+				ptr: self.ptr().value(),
+				limit,
+			});
+		}
+
+		self.consume_fuel()
+	}
+
+	#[inline]
+	fn calculate_index(&mut self, offset: Offset) -> Result<usize, RuntimeError> {
+		match (offset, self.bounds) {
+			(Offset(0), _) => Ok(self.ptr().value()),
+			(Offset(offset), BoundsPolicy::Wrap) => Ok(self.tape_mut().index_of(offset)),
+			(Offset(offset), _) => self.resolve_offset(offset),
+		}
+	}
+
+	fn resolve_offset(&self, offset: isize) -> Result<usize, RuntimeError> {
+		let ptr = self.ptr().value();
+
+		self.bounds.resolve(ptr, offset, self.tape().len()).ok_or(
+			RuntimeError::PointerOutOfBounds {
+				ptr,
+				offset,
+				instruction: None,
+			},
+		)
 	}
 
 	fn write_to_output(&mut self, byte: u8) -> Result<(), RuntimeError> {
@@ -535,10 +807,37 @@ impl<T: Tape> Interpreter<T, Stdin, Stdout> {
 pub enum RuntimeError {
 	Io(IoError),
 	Unimplemented(Instruction),
-	TooManyIterations(usize),
+	TooManyIterations {
+		ptr: usize,
+		limit: usize,
+	},
+	OutOfFuel(u64),
+	OutOfTime(Duration),
+	PointerOutOfBounds {
+		ptr: usize,
+		offset: isize,
+		instruction: Option<Instruction>,
+	},
 	NoBytes,
 }
 
+impl RuntimeError {
+	fn in_instruction(self, instr: &Instruction) -> Self {
+		match self {
+			Self::PointerOutOfBounds {
+				ptr,
+				offset,
+				instruction: None,
+			} => Self::PointerOutOfBounds {
+				ptr,
+				offset,
+				instruction: Some(instr.clone()),
+			},
+			e => e,
+		}
+	}
+}
+
 impl Display for RuntimeError {
 	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
 		match self {
@@ -548,11 +847,37 @@ impl Display for RuntimeError {
 				Debug::fmt(&instr, f)?;
 				f.write_str(" is unimplmented")
 			}
-			Self::TooManyIterations(i) => {
+			Self::TooManyIterations { ptr, limit } => {
 				f.write_str("loop exceeded ")?;
-				Display::fmt(&ITERATION_LIMIT, f)?;
+				Display::fmt(&limit, f)?;
 				f.write_str(" iterations at cell ")?;
-				Display::fmt(&i, f)
+				Display::fmt(&ptr, f)
+			}
+			Self::OutOfFuel(fuel) => {
+				f.write_str("ran out of fuel after ")?;
+				Display::fmt(&fuel, f)?;
+				f.write_str(" steps")
+			}
+			Self::OutOfTime(limit) => {
+				f.write_str("exceeded the time limit of ")?;
+				Debug::fmt(&limit, f)
+			}
+			Self::PointerOutOfBounds {
+				ptr,
+				offset,
+				instruction,
+			} => {
+				f.write_str("pointer out of bounds: cell ")?;
+				Display::fmt(&ptr, f)?;
+				f.write_str(" offset by ")?;
+				Display::fmt(&offset, f)?;
+
+				if let Some(instr) = instruction {
+					f.write_str(" in ")?;
+					Display::fmt(&instr, f)?;
+				}
+
+				Ok(())
 			}
 			Self::NoBytes => f.write_str("called write with no bytes"),
 		}
@@ -563,7 +888,12 @@ impl StdError for RuntimeError {
 	fn source(&self) -> Option<&(dyn StdError + 'static)> {
 		match self {
 			Self::Io(e) => Some(e),
-			Self::Unimplemented(_) | Self::TooManyIterations(_) | Self::NoBytes => None,
+			Self::Unimplemented(_)
+			| Self::TooManyIterations { .. }
+			| Self::OutOfFuel(_)
+			| Self::OutOfTime(_)
+			| Self::PointerOutOfBounds { .. }
+			| Self::NoBytes => None,
 		}
 	}
 }
@@ -1,5 +1,8 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use vmm_ir::{BlockInstruction, Instruction};
+use vmm_program::SourceSpan;
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Profiler {
@@ -46,3 +49,39 @@ impl Profiler {
 		}
 	}
 }
+
+/// How many times the instructions from each span of source were executed.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(transparent)]
+pub struct SourceProfile(BTreeMap<SourceSpan, u64>);
+
+impl SourceProfile {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self(BTreeMap::new())
+	}
+
+	pub fn handle(&mut self, span: SourceSpan) {
+		*self.0.entry(span).or_default() += 1;
+	}
+
+	#[must_use]
+	pub fn get(&self, span: SourceSpan) -> u64 {
+		self.0.get(&span).copied().unwrap_or_default()
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (SourceSpan, u64)> + '_ {
+		self.0.iter().map(|(span, count)| (*span, *count))
+	}
+
+	/// The spans that ran most often, in descending order.
+	#[must_use]
+	pub fn hottest(&self, limit: usize) -> Vec<(SourceSpan, u64)> {
+		let mut spans = self.iter().collect::<Vec<_>>();
+
+		spans.sort_by(|(a_span, a), (b_span, b)| b.cmp(a).then(a_span.cmp(b_span)));
+		spans.truncate(limit);
+
+		spans
+	}
+}
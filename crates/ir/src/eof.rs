@@ -0,0 +1,76 @@
+use alloc::{borrow::ToOwned as _, string::String};
+use core::{
+	error::Error as CoreError,
+	fmt::{Display, Formatter, Result as FmtResult},
+	str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// What a [`Read`](crate::Instruction::Read) does to the current cell once the input is exhausted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EofPolicy {
+	/// Set the cell to 0.
+	#[default]
+	Zero,
+	/// Set the cell to its maximum value (-1), 255 for 8-bit cells.
+	Max,
+	/// Leave the cell as it was.
+	Unchanged,
+}
+
+impl EofPolicy {
+	/// Whether a read always replaces the value of the current cell.
+	#[must_use]
+	pub const fn overwrites_cell(self) -> bool {
+		!matches!(self, Self::Unchanged)
+	}
+}
+
+impl Display for EofPolicy {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		f.write_str(match self {
+			Self::Zero => "zero",
+			Self::Max => "max",
+			Self::Unchanged => "unchanged",
+		})
+	}
+}
+
+impl FromStr for EofPolicy {
+	type Err = ParseEofPolicyError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"zero" | "0" => Ok(Self::Zero),
+			"max" | "255" | "-1" => Ok(Self::Max),
+			"unchanged" => Ok(Self::Unchanged),
+			s => Err(ParseEofPolicyError(s.to_owned())),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseEofPolicyError(String);
+
+impl Display for ParseEofPolicyError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		f.write_str("invalid eof policy: ")?;
+		f.write_str(&self.0)
+	}
+}
+
+impl CoreError for ParseEofPolicyError {}
+
+#[cfg(test)]
+mod tests {
+	use super::EofPolicy;
+
+	#[test]
+	fn parse() {
+		assert_eq!("0".parse(), Ok(EofPolicy::Zero));
+		assert_eq!("-1".parse(), Ok(EofPolicy::Max));
+		assert_eq!("unchanged".parse(), Ok(EofPolicy::Unchanged));
+		assert!("1".parse::<EofPolicy>().is_err());
+	}
+}
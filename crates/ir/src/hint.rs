@@ -7,21 +7,21 @@ use super::Offset;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum CompilerHint {
 	KnownValue {
-		value: Option<NonZero<u8>>,
+		value: Option<NonZero<u32>>,
 		offset: Offset,
 	},
 }
 
 impl CompilerHint {
 	#[must_use]
-	pub const fn known_value(value: u8) -> Self {
+	pub const fn known_value(value: u32) -> Self {
 		Self::KnownValue {
 			value: NonZero::new(value),
 			offset: Offset(0),
 		}
 	}
 
-	pub fn known_value_at(value: u8, offset: impl Into<Offset>) -> Self {
+	pub fn known_value_at(value: u32, offset: impl Into<Offset>) -> Self {
 		Self::KnownValue {
 			value: NonZero::new(value),
 			offset: offset.into(),
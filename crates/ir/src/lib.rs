@@ -4,22 +4,24 @@
 extern crate alloc;
 
 mod block_instr;
+mod eof;
 mod hint;
 mod offset;
 mod super_instr;
 mod utils;
+mod width;
 
 use alloc::string::ToString;
 use core::{
 	fmt::{Debug, Display, Formatter, Result as FmtResult},
-	num::NonZeroU8,
+	num::NonZeroU32,
 };
 
 use serde::{Deserialize, Serialize};
 use tap::prelude::*;
 use vmm_utils::GetOrZero as _;
 
-pub use self::{block_instr::*, hint::*, offset::*, super_instr::*, utils::*};
+pub use self::{block_instr::*, eof::*, hint::*, offset::*, super_instr::*, utils::*, width::*};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[non_exhaustive]
@@ -29,7 +31,7 @@ pub enum Instruction {
 	Boundary,
 	/// Increment the value at the current cell (offset = None) or at an offset
 	IncVal {
-		value: i8,
+		value: i32,
 		offset: Offset,
 	},
 	SubCell {
@@ -37,12 +39,12 @@ pub enum Instruction {
 	},
 	/// Set the value at the current cell (offset = None) or at an offset
 	SetVal {
-		value: Option<NonZeroU8>,
+		value: Option<NonZeroU32>,
 		offset: Offset,
 	},
 	/// Multiply self by factor
 	ScaleVal {
-		factor: u8,
+		factor: u32,
 	},
 	MoveVal(Offset),
 	FetchVal(Offset),
@@ -66,7 +68,7 @@ pub enum Instruction {
 
 impl Instruction {
 	#[must_use]
-	pub fn inc_val(v: i8) -> Self {
+	pub fn inc_val(v: i32) -> Self {
 		Self::inc_val_at(v, 0)
 	}
 
@@ -92,12 +94,12 @@ impl Instruction {
 	}
 
 	#[must_use]
-	pub fn scale_and_take_val(factor: u8, offset: impl Into<Offset>) -> Self {
+	pub fn scale_and_take_val(factor: u32, offset: impl Into<Offset>) -> Self {
 		SuperInstruction::scale_and_take_val(factor, offset).convert()
 	}
 
 	#[must_use]
-	pub fn inc_val_at(v: i8, offset: impl Into<Offset>) -> Self {
+	pub fn inc_val_at(v: i32, offset: impl Into<Offset>) -> Self {
 		Self::IncVal {
 			value: v.convert(),
 			offset: offset.convert(),
@@ -105,19 +107,19 @@ impl Instruction {
 	}
 
 	#[must_use]
-	pub fn find_and_set_zero(v: NonZeroU8, offset: impl Into<Offset>) -> Self {
+	pub fn find_and_set_zero(v: NonZeroU32, offset: impl Into<Offset>) -> Self {
 		Self::Super(SuperInstruction::find_and_set_zero(v, offset))
 	}
 
 	#[must_use]
-	pub fn set_val(v: u8) -> Self {
+	pub fn set_val(v: u32) -> Self {
 		Self::set_val_at(v, 0)
 	}
 
 	#[must_use]
-	pub fn set_val_at(v: u8, offset: impl Into<Offset>) -> Self {
+	pub fn set_val_at(v: u32, offset: impl Into<Offset>) -> Self {
 		Self::SetVal {
-			value: NonZeroU8::new(v),
+			value: NonZeroU32::new(v),
 			offset: offset.convert(),
 		}
 	}
@@ -156,17 +158,17 @@ impl Instruction {
 	}
 
 	#[must_use]
-	pub fn scale_and_move_val(factor: u8, offset: impl Into<Offset>) -> Self {
+	pub fn scale_and_move_val(factor: u32, offset: impl Into<Offset>) -> Self {
 		SuperInstruction::scale_and_move_val(factor, offset).convert()
 	}
 
 	#[must_use]
-	pub fn fetch_and_scale_val(factor: u8, offset: impl Into<Offset>) -> Self {
+	pub fn fetch_and_scale_val(factor: u32, offset: impl Into<Offset>) -> Self {
 		SuperInstruction::fetch_and_scale_val(factor, offset).convert()
 	}
 
 	#[must_use]
-	pub fn scale_and_set_val(factor: u8, offset: impl Into<Offset>, value: NonZeroU8) -> Self {
+	pub fn scale_and_set_val(factor: u32, offset: impl Into<Offset>, value: NonZeroU32) -> Self {
 		SuperInstruction::scale_and_set_val(factor, offset, value).convert()
 	}
 
@@ -206,7 +208,7 @@ impl Instruction {
 	}
 
 	#[must_use]
-	pub const fn scale_val(factor: u8) -> Self {
+	pub const fn scale_val(factor: u32) -> Self {
 		Self::ScaleVal { factor }
 	}
 
@@ -221,7 +223,7 @@ impl Instruction {
 	}
 
 	#[must_use]
-	pub fn set_until_zero(value: u8, offset: impl Into<Offset>) -> Self {
+	pub fn set_until_zero(value: u32, offset: impl Into<Offset>) -> Self {
 		Self::Super(SuperInstruction::set_until_zero(value, offset))
 	}
 
@@ -236,17 +238,20 @@ impl Instruction {
 	}
 
 	#[must_use]
-	pub const fn is_overwriting_current_cell(&self) -> bool {
+	pub const fn is_overwriting_current_cell(&self, eof: EofPolicy) -> bool {
+		if matches!(self, Self::Read) {
+			return eof.overwrites_cell();
+		}
+
 		matches!(
 			self,
 			Self::SetVal {
 				offset: Offset(0),
 				..
-			} | Self::Read
-				| Self::Super(SuperInstruction::ScaleAnd {
-					action: ScaleAnd::Move,
-					..
-				}) | Self::Block(BlockInstruction::IfNz(..))
+			} | Self::Super(SuperInstruction::ScaleAnd {
+				action: ScaleAnd::Move,
+				..
+			}) | Self::Block(BlockInstruction::IfNz(..))
 				| Self::MoveVal(..)
 		)
 	}
@@ -2,7 +2,7 @@ mod scale;
 
 use core::{
 	fmt::{Debug, Display, Formatter, Result as FmtResult},
-	num::NonZeroU8,
+	num::NonZeroU32,
 };
 
 use serde::{Deserialize, Serialize};
@@ -18,14 +18,14 @@ pub enum SuperInstruction {
 	ScaleAnd {
 		action: ScaleAnd,
 		offset: Offset,
-		factor: u8,
+		factor: u32,
 	},
 	FindAndSetZero {
 		offset: Offset,
-		value: NonZeroU8,
+		value: NonZeroU32,
 	},
 	SetUntilZero {
-		value: Option<NonZeroU8>,
+		value: Option<NonZeroU32>,
 		offset: Offset,
 	},
 	FindCellByZero {
@@ -40,7 +40,7 @@ pub enum SuperInstruction {
 
 impl SuperInstruction {
 	#[must_use]
-	pub fn scale_and(factor: u8, offset: impl Into<Offset>, action: ScaleAnd) -> Self {
+	pub fn scale_and(factor: u32, offset: impl Into<Offset>, action: ScaleAnd) -> Self {
 		Self::ScaleAnd {
 			action,
 			offset: offset.into(),
@@ -49,27 +49,27 @@ impl SuperInstruction {
 	}
 
 	#[must_use]
-	pub fn scale_and_move_val(factor: u8, offset: impl Into<Offset>) -> Self {
+	pub fn scale_and_move_val(factor: u32, offset: impl Into<Offset>) -> Self {
 		Self::scale_and(factor, offset, ScaleAnd::Move)
 	}
 
 	#[must_use]
-	pub fn scale_and_take_val(factor: u8, offset: impl Into<Offset>) -> Self {
+	pub fn scale_and_take_val(factor: u32, offset: impl Into<Offset>) -> Self {
 		Self::scale_and(factor, offset, ScaleAnd::Take)
 	}
 
 	#[must_use]
-	pub fn fetch_and_scale_val(factor: u8, offset: impl Into<Offset>) -> Self {
+	pub fn fetch_and_scale_val(factor: u32, offset: impl Into<Offset>) -> Self {
 		Self::scale_and(factor, offset, ScaleAnd::Fetch)
 	}
 
 	#[must_use]
-	pub fn scale_and_set_val(factor: u8, offset: impl Into<Offset>, value: NonZeroU8) -> Self {
+	pub fn scale_and_set_val(factor: u32, offset: impl Into<Offset>, value: NonZeroU32) -> Self {
 		Self::scale_and(factor, offset, ScaleAnd::Set(value))
 	}
 
 	#[must_use]
-	pub fn find_and_set_zero(value: NonZeroU8, offset: impl Into<Offset>) -> Self {
+	pub fn find_and_set_zero(value: NonZeroU32, offset: impl Into<Offset>) -> Self {
 		Self::FindAndSetZero {
 			offset: offset.convert::<Offset>(),
 			value,
@@ -77,9 +77,9 @@ impl SuperInstruction {
 	}
 
 	#[must_use]
-	pub fn set_until_zero(value: u8, offset: impl Into<Offset>) -> Self {
+	pub fn set_until_zero(value: u32, offset: impl Into<Offset>) -> Self {
 		Self::SetUntilZero {
-			value: NonZeroU8::new(value),
+			value: NonZeroU32::new(value),
 			offset: offset.convert(),
 		}
 	}
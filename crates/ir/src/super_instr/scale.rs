@@ -1,6 +1,6 @@
 use core::{
 	fmt::{Debug, Display, Formatter, Result as FmtResult},
-	num::NonZeroU8,
+	num::NonZeroU32,
 };
 
 use serde::{Deserialize, Serialize};
@@ -11,7 +11,7 @@ pub enum ScaleAnd {
 	Move,
 	Fetch,
 	Take,
-	Set(NonZeroU8),
+	Set(NonZeroU32),
 }
 
 impl Display for ScaleAnd {
@@ -0,0 +1,79 @@
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+use serde::{Deserialize, Serialize};
+
+/// The number of bits in a tape cell.
+///
+/// Instruction immediates are stored as 32-bit values, and are taken modulo the cell width when
+/// executed.
+#[derive(
+	Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum CellWidth {
+	#[default]
+	U8,
+	U16,
+	U32,
+}
+
+impl CellWidth {
+	#[must_use]
+	pub const fn from_bits(bits: u32) -> Option<Self> {
+		match bits {
+			8 => Some(Self::U8),
+			16 => Some(Self::U16),
+			32 => Some(Self::U32),
+			_ => None,
+		}
+	}
+
+	#[must_use]
+	pub const fn bits(self) -> u32 {
+		match self {
+			Self::U8 => u8::BITS,
+			Self::U16 => u16::BITS,
+			Self::U32 => u32::BITS,
+		}
+	}
+
+	#[must_use]
+	pub const fn max_value(self) -> u32 {
+		u32::MAX >> (u32::BITS - self.bits())
+	}
+
+	/// Truncate an unsigned immediate (a set value or a factor) to the cell width.
+	#[must_use]
+	pub const fn wrap_unsigned(self, value: u32) -> u32 {
+		value & self.max_value()
+	}
+
+	/// Truncate a signed immediate (an increment) to the cell width, keeping its sign.
+	#[must_use]
+	pub const fn wrap_signed(self, value: i32) -> i32 {
+		match self {
+			Self::U8 => value as i8 as i32,
+			Self::U16 => value as i16 as i32,
+			Self::U32 => value,
+		}
+	}
+}
+
+impl Display for CellWidth {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		Display::fmt(&self.bits(), f)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::CellWidth;
+
+	#[test]
+	fn wrap() {
+		assert_eq!(CellWidth::U8.wrap_unsigned(300), 44);
+		assert_eq!(CellWidth::U16.wrap_unsigned(300), 300);
+		assert_eq!(CellWidth::U8.wrap_signed(200), -56);
+		assert_eq!(CellWidth::U16.wrap_signed(-70_000), -4464);
+		assert_eq!(CellWidth::U32.wrap_unsigned(u32::MAX), u32::MAX);
+	}
+}
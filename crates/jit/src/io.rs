@@ -0,0 +1,64 @@
+use std::io::{Error as IoError, ErrorKind as IoErrorKind, prelude::*};
+
+use vmm_ir::EofPolicy;
+use vmm_tape::CellValue;
+
+pub const READ_SYMBOL: &str = "vmm_jit_read";
+pub const WRITE_SYMBOL: &str = "vmm_jit_write";
+
+/// The I/O state handed to compiled code, which passes it back to the callbacks.
+pub struct JitIo<'a> {
+	pub input: &'a mut dyn Read,
+	pub output: &'a mut dyn Write,
+	pub eof: EofPolicy,
+	pub error: Option<IoError>,
+}
+
+// Both callbacks return 0 on success, anything else makes the compiled code return early.
+
+pub extern "C" fn read<V: CellValue>(io: *mut JitIo<'_>, cell: *mut V) -> u32 {
+	let io = unsafe { &mut *io };
+
+	let value = loop {
+		let mut buf = [0];
+
+		match io.input.read_exact(&mut buf) {
+			Ok(()) if cfg!(target_os = "windows") && matches!(buf[0], b'\r') => {}
+			Ok(()) => break V::wrapping_from(buf[0]),
+			Err(e) if matches!(e.kind(), IoErrorKind::UnexpectedEof) => match io.eof {
+				EofPolicy::Zero => break V::ZERO,
+				EofPolicy::Max => break V::wrapping_from(u32::MAX),
+				EofPolicy::Unchanged => return 0,
+			},
+			Err(e) => {
+				io.error = Some(e);
+				return 1;
+			}
+		}
+	};
+
+	unsafe { cell.write(value) };
+
+	0
+}
+
+pub extern "C" fn write(io: *mut JitIo<'_>, value: u32) -> u32 {
+	let io = unsafe { &mut *io };
+
+	let byte = value as u8;
+
+	let result = if !cfg!(target_os = "windows") || byte < 128 {
+		io.output.write_all(&[byte])
+	} else {
+		Ok(())
+	}
+	.and_then(|()| io.output.flush());
+
+	match result {
+		Ok(()) => 0,
+		Err(e) => {
+			io.error = Some(e);
+			1
+		}
+	}
+}
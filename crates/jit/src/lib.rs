@@ -0,0 +1,358 @@
+#![cfg_attr(docsrs, feature(doc_auto_cfg, doc_cfg))]
+
+mod io;
+mod translate;
+
+use std::{
+	error::Error as StdError,
+	ffi::c_void,
+	fmt::{Debug, Display, Formatter, Result as FmtResult},
+	io::{Error as IoError, prelude::*},
+	marker::PhantomData,
+	mem,
+};
+
+use cranelift::{
+	codegen::ir::{Function, UserFuncName},
+	jit::{JITBuilder, JITModule},
+	module::{FuncId, Linkage, Module, ModuleError, default_libcall_names},
+	prelude::*,
+};
+use vmm_ir::{EofPolicy, Instruction};
+use vmm_tape::{CellValue, Tape};
+
+use self::{
+	io::{JitIo, READ_SYMBOL, WRITE_SYMBOL},
+	translate::Translator,
+};
+
+type EntryFn<V> = unsafe extern "C" fn(*mut V, usize, *mut c_void) -> usize;
+
+/// A program compiled to native code for tapes of a fixed length, holding cells of type `V`.
+///
+/// The compiled code always wraps the pointer around the tape, and doesn't enforce an execution
+/// budget.
+pub struct JitProgram<V: CellValue = u8> {
+	module: Option<JITModule>,
+	entry: *const u8,
+	tape_len: usize,
+	marker: PhantomData<V>,
+}
+
+impl<V: CellValue> JitProgram<V> {
+	pub fn compile(program: &[Instruction], tape_len: usize) -> Result<Self, JitError> {
+		assert!(tape_len > 0, "tape length must be greater than 0");
+
+		let mut flag_builder = settings::builder();
+		flag_builder.set("opt_level", "speed")?;
+		flag_builder.set("use_colocated_libcalls", "false")?;
+		flag_builder.set("is_pic", "false")?;
+
+		let isa = cranelift::native::builder()
+			.map_err(|e| JitError::Isa(e.to_owned()))?
+			.finish(settings::Flags::new(flag_builder))
+			.map_err(|e| JitError::Isa(e.to_string()))?;
+
+		let mut jit_builder = JITBuilder::with_isa(isa, default_libcall_names());
+		jit_builder.symbol(READ_SYMBOL, io::read::<V> as *const u8);
+		jit_builder.symbol(WRITE_SYMBOL, io::write as *const u8);
+
+		let mut module = JITModule::new(jit_builder);
+
+		let id = define_entry::<V>(&mut module, program, tape_len)?;
+
+		module.finalize_definitions()?;
+
+		let entry = module.get_finalized_function(id);
+
+		Ok(Self {
+			module: Some(module),
+			entry,
+			tape_len,
+			marker: PhantomData,
+		})
+	}
+
+	#[must_use]
+	pub const fn tape_len(&self) -> usize {
+		self.tape_len
+	}
+
+	/// Run the program on `tape`, starting from and updating its pointer.
+	pub fn run<T, R, W>(
+		&self,
+		tape: &mut T,
+		mut input: R,
+		mut output: W,
+		eof: EofPolicy,
+	) -> Result<(), JitError>
+	where
+		T: Tape<Value = V>,
+		R: Read,
+		W: Write,
+	{
+		if tape.len() != self.tape_len {
+			return Err(JitError::TapeLength {
+				expected: self.tape_len,
+				actual: tape.len(),
+			});
+		}
+
+		let mut cells = tape
+			.as_slice()
+			.iter()
+			.map(|cell| cell.value())
+			.collect::<Vec<_>>();
+
+		let mut io = JitIo {
+			input: &mut input,
+			output: &mut output,
+			eof,
+			error: None,
+		};
+
+		let ptr = unsafe {
+			let entry = mem::transmute::<*const u8, EntryFn<V>>(self.entry);
+
+			entry(cells.as_mut_ptr(), tape.ptr().value(), (&raw mut io).cast())
+		};
+
+		for (cell, value) in tape.as_mut_slice().iter_mut().zip(cells) {
+			cell.set_value(value);
+		}
+
+		tape.ptr_mut().set(ptr);
+
+		io.error.map_or(Ok(()), |e| Err(e.into()))
+	}
+}
+
+impl<V: CellValue> Debug for JitProgram<V> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		f.debug_struct("JitProgram")
+			.field("entry", &self.entry)
+			.field("tape_len", &self.tape_len)
+			.finish_non_exhaustive()
+	}
+}
+
+impl<V: CellValue> Drop for JitProgram<V> {
+	fn drop(&mut self) {
+		if let Some(module) = self.module.take() {
+			unsafe { module.free_memory() };
+		}
+	}
+}
+
+fn define_entry<V: CellValue>(
+	module: &mut JITModule,
+	program: &[Instruction],
+	tape_len: usize,
+) -> Result<FuncId, JitError> {
+	let ptr_type = module.target_config().pointer_type();
+	let call_conv = module.target_config().default_call_conv;
+
+	let cell_type = match V::BITS {
+		8 => types::I8,
+		16 => types::I16,
+		_ => types::I32,
+	};
+
+	let mut read_sig = Signature::new(call_conv);
+	read_sig
+		.params
+		.extend([AbiParam::new(ptr_type), AbiParam::new(ptr_type)]);
+	read_sig.returns.push(AbiParam::new(types::I32));
+
+	let mut write_sig = Signature::new(call_conv);
+	write_sig
+		.params
+		.extend([AbiParam::new(ptr_type), AbiParam::new(types::I32)]);
+	write_sig.returns.push(AbiParam::new(types::I32));
+
+	let mut entry_sig = Signature::new(call_conv);
+	entry_sig.params.extend([
+		AbiParam::new(ptr_type),
+		AbiParam::new(ptr_type),
+		AbiParam::new(ptr_type),
+	]);
+	entry_sig.returns.push(AbiParam::new(ptr_type));
+
+	let read_id = module.declare_function(READ_SYMBOL, Linkage::Import, &read_sig)?;
+	let write_id = module.declare_function(WRITE_SYMBOL, Linkage::Import, &write_sig)?;
+	let entry_id = module.declare_function("vmm_jit_entry", Linkage::Export, &entry_sig)?;
+
+	let mut ctx = module.make_context();
+	ctx.func = Function::with_name_signature(UserFuncName::user(0, entry_id.as_u32()), entry_sig);
+
+	let mut builder_ctx = FunctionBuilderContext::new();
+	let mut builder = FunctionBuilder::new(&mut ctx.func, &mut builder_ctx);
+
+	let read = module.declare_func_in_func(read_id, builder.func);
+	let write = module.declare_func_in_func(write_id, builder.func);
+
+	let entry = builder.create_block();
+	let exit = builder.create_block();
+
+	builder.append_block_params_for_function_params(entry);
+	builder.append_block_param(exit, ptr_type);
+
+	builder.switch_to_block(entry);
+	builder.seal_block(entry);
+
+	let [cells, ptr, io] = builder.block_params(entry) else {
+		unreachable!("entry block has three parameters");
+	};
+
+	let (cells, ptr, io) = (*cells, *ptr, *io);
+
+	let mut translator = Translator {
+		builder,
+		cells,
+		io,
+		ptr,
+		ptr_type,
+		cell_type,
+		tape_len,
+		read,
+		write,
+		exit,
+	};
+
+	translator.translate(program)?;
+
+	let Translator {
+		mut builder, ptr, ..
+	} = translator;
+
+	builder.ins().jump(exit, &[ptr.into()]);
+
+	builder.switch_to_block(exit);
+	builder.seal_block(exit);
+
+	let ptr = builder.block_params(exit)[0];
+	builder.ins().return_(&[ptr]);
+
+	builder.finalize();
+
+	module.define_function(entry_id, &mut ctx)?;
+	module.clear_context(&mut ctx);
+
+	Ok(entry_id)
+}
+
+#[derive(Debug)]
+pub enum JitError {
+	Unsupported(Instruction),
+	Isa(String),
+	Module(Box<ModuleError>),
+	TapeLength { expected: usize, actual: usize },
+	Io(IoError),
+}
+
+impl Display for JitError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::Unsupported(instr) => {
+				f.write_str("instruction ")?;
+				Debug::fmt(&instr, f)?;
+				f.write_str(" is not supported by the jit")
+			}
+			Self::Isa(e) => {
+				f.write_str("unable to target the host: ")?;
+				f.write_str(e)
+			}
+			Self::Module(e) => Display::fmt(&e, f),
+			Self::TapeLength { expected, actual } => {
+				f.write_str("program was compiled for a tape of ")?;
+				Display::fmt(&expected, f)?;
+				f.write_str(" cells, but the tape has ")?;
+				Display::fmt(&actual, f)?;
+				f.write_str(" cells")
+			}
+			Self::Io(e) => Display::fmt(&e, f),
+		}
+	}
+}
+
+impl StdError for JitError {
+	fn source(&self) -> Option<&(dyn StdError + 'static)> {
+		match self {
+			Self::Module(e) => Some(e),
+			Self::Io(e) => Some(e),
+			Self::Unsupported(_) | Self::Isa(_) | Self::TapeLength { .. } => None,
+		}
+	}
+}
+
+impl From<ModuleError> for JitError {
+	fn from(value: ModuleError) -> Self {
+		Self::Module(Box::new(value))
+	}
+}
+
+impl From<settings::SetError> for JitError {
+	fn from(value: settings::SetError) -> Self {
+		Self::Isa(value.to_string())
+	}
+}
+
+impl From<IoError> for JitError {
+	fn from(value: IoError) -> Self {
+		Self::Io(value)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io;
+
+	use vmm_ir::{EofPolicy, Instruction};
+	use vmm_tape::{Tape as _, VecTape};
+
+	use super::JitProgram;
+
+	#[test]
+	fn runs_loops() {
+		let program = [
+			Instruction::inc_val(3),
+			Instruction::dynamic_loop([Instruction::inc_val(-1), Instruction::inc_val_at(2, 1)]),
+			Instruction::move_ptr(1),
+			Instruction::write_once(),
+		];
+
+		let jit = JitProgram::<u8>::compile(&program, 8).unwrap();
+
+		let mut tape = VecTape::with_len(8);
+		tape.init();
+
+		let mut output = Vec::new();
+
+		jit.run(&mut tape, io::empty(), &mut output, EofPolicy::Zero)
+			.unwrap();
+
+		assert_eq!(output, [6]);
+		assert_eq!(tape.ptr().value(), 1);
+		assert_eq!(tape.as_slice()[1].value(), 6);
+	}
+
+	#[test]
+	fn pointer_wraps() {
+		let program = [
+			Instruction::move_ptr(-1),
+			Instruction::inc_val(1),
+			Instruction::move_ptr(3),
+		];
+
+		let jit = JitProgram::<u16>::compile(&program, 4).unwrap();
+
+		let mut tape = VecTape::<u16>::with_len(4);
+		tape.init();
+
+		jit.run(&mut tape, io::empty(), io::sink(), EofPolicy::Zero)
+			.unwrap();
+
+		assert_eq!(tape.as_slice()[3].value(), 1);
+		assert_eq!(tape.ptr().value(), 2);
+	}
+}
@@ -0,0 +1,336 @@
+use cranelift::{
+	codegen::ir::FuncRef,
+	prelude::{types::I32, *},
+};
+use vmm_ir::{BlockInstruction, Instruction, Offset, ScaleAnd, SuperInstruction};
+use vmm_utils::GetOrZero as _;
+
+use super::JitError;
+
+/// Lowers instructions into the body of a function, tracking the tape pointer as an SSA value.
+pub struct Translator<'a> {
+	pub builder: FunctionBuilder<'a>,
+	pub cells: Value,
+	pub io: Value,
+	pub ptr: Value,
+	pub ptr_type: Type,
+	pub cell_type: Type,
+	pub tape_len: usize,
+	pub read: FuncRef,
+	pub write: FuncRef,
+	pub exit: Block,
+}
+
+impl Translator<'_> {
+	pub fn translate(&mut self, instructions: &[Instruction]) -> Result<(), JitError> {
+		instructions
+			.iter()
+			.try_for_each(|instr| self.translate_instruction(instr))
+	}
+
+	fn translate_instruction(&mut self, instr: &Instruction) -> Result<(), JitError> {
+		match instr {
+			Instruction::Boundary => {}
+			Instruction::IncVal { value, offset } => {
+				let current = self.load(*offset);
+				let value = self.cell_const(*value as u32);
+				let value = self.builder.ins().iadd(current, value);
+				self.store(*offset, value);
+			}
+			Instruction::SetVal { value, offset } => {
+				let value = self.cell_const(value.get_or_zero());
+				self.store(*offset, value);
+			}
+			Instruction::MovePtr(offset) => self.move_ptr(*offset),
+			Instruction::Read => {
+				let cell = self.cell_addr(Offset(0));
+				let call = self.builder.ins().call(self.read, &[self.io, cell]);
+				let status = self.builder.inst_results(call)[0];
+				self.exit_on_error(status);
+			}
+			Instruction::Write { offset } => {
+				let value = self.load(*offset);
+				let value = self.extend_to_i32(value);
+				let call = self.builder.ins().call(self.write, &[self.io, value]);
+				let status = self.builder.inst_results(call)[0];
+				self.exit_on_error(status);
+			}
+			Instruction::FindZero(jump_by) => self.find_zero(*jump_by)?,
+			Instruction::SubCell { offset } => {
+				let current = self.take(Offset(0));
+				let value = self.load(*offset);
+				let value = self.builder.ins().isub(value, current);
+				self.store(*offset, value);
+			}
+			Instruction::ScaleVal { factor } => {
+				let current = self.load(Offset(0));
+				let factor = self.cell_const(*factor);
+				let value = self.builder.ins().imul(current, factor);
+				self.store(Offset(0), value);
+			}
+			Instruction::FetchVal(offset) => self.scale_and_fetch(*offset, None),
+			Instruction::MoveVal(offset) => self.scale_and_move(*offset, None),
+			Instruction::TakeVal(offset) => self.scale_and_take(*offset, None),
+			Instruction::ReplaceVal(offset) => {
+				let value = self.take(*offset);
+				self.store(Offset(0), value);
+			}
+			Instruction::Super(s) => self.translate_super_instruction(*s)?,
+			Instruction::Block(BlockInstruction::DynamicLoop(body)) => {
+				self.translate_loop(|this| this.translate(body))?;
+			}
+			Instruction::Block(BlockInstruction::IfNz(body)) => self.translate_if_nz(body)?,
+			i => return Err(JitError::Unsupported(i.clone())),
+		}
+
+		Ok(())
+	}
+
+	fn translate_super_instruction(&mut self, instr: SuperInstruction) -> Result<(), JitError> {
+		match instr {
+			SuperInstruction::ScaleAnd {
+				action: ScaleAnd::Move,
+				offset,
+				factor,
+			} => self.scale_and_move(offset, Some(factor)),
+			SuperInstruction::ScaleAnd {
+				action: ScaleAnd::Fetch,
+				offset,
+				factor,
+			} => self.scale_and_fetch(offset, Some(factor)),
+			SuperInstruction::ScaleAnd {
+				action: ScaleAnd::Take,
+				offset,
+				factor,
+			} => self.scale_and_take(offset, Some(factor)),
+			SuperInstruction::ScaleAnd {
+				action: ScaleAnd::Set(value),
+				offset,
+				factor,
+			} => {
+				let current = self.load(Offset(0));
+				let value = self.cell_const(value.get());
+				self.store(Offset(0), value);
+				self.add_scaled(offset, current, Some(factor));
+			}
+			SuperInstruction::FindAndSetZero { offset, value } => {
+				self.find_zero(offset)?;
+				let value = self.cell_const(value.get());
+				self.store(Offset(0), value);
+			}
+			SuperInstruction::SetUntilZero { value, offset } => {
+				self.translate_loop(|this| {
+					let value = this.cell_const(value.get_or_zero());
+					this.store(Offset(0), value);
+					this.move_ptr(offset);
+
+					Ok(())
+				})?;
+			}
+			SuperInstruction::FindCellByZero { jump_by, offset } => {
+				self.find_zero(jump_by)?;
+				self.move_ptr(offset);
+			}
+			SuperInstruction::ShiftVals { jump_by, offset } => {
+				self.translate_loop(|this| {
+					this.scale_and_move(offset, None);
+					this.move_ptr(jump_by);
+
+					Ok(())
+				})?;
+			}
+			i => return Err(JitError::Unsupported(i.into())),
+		}
+
+		Ok(())
+	}
+
+	fn find_zero(&mut self, jump_by: Offset) -> Result<(), JitError> {
+		self.translate_loop(|this| {
+			this.move_ptr(jump_by);
+
+			Ok(())
+		})
+	}
+
+	fn scale_and_move(&mut self, offset: Offset, factor: Option<u32>) {
+		let current = self.take(Offset(0));
+		self.add_scaled(offset, current, factor);
+	}
+
+	fn scale_and_fetch(&mut self, offset: Offset, factor: Option<u32>) {
+		let value = self.take(offset);
+		self.add_scaled(Offset(0), value, factor);
+	}
+
+	fn scale_and_take(&mut self, offset: Offset, factor: Option<u32>) {
+		let current = self.take(Offset(0));
+		self.move_ptr(offset);
+		self.add_scaled(Offset(0), current, factor);
+	}
+
+	fn add_scaled(&mut self, offset: Offset, value: Value, factor: Option<u32>) {
+		let value = match factor {
+			Some(factor) => {
+				let factor = self.cell_const(factor);
+				self.builder.ins().imul(value, factor)
+			}
+			None => value,
+		};
+
+		let current = self.load(offset);
+		let value = self.builder.ins().iadd(current, value);
+		self.store(offset, value);
+	}
+
+	// Runs `body` while the current cell isn't zero.
+	fn translate_loop(
+		&mut self,
+		body: impl FnOnce(&mut Self) -> Result<(), JitError>,
+	) -> Result<(), JitError> {
+		let header = self.builder.create_block();
+		let body_block = self.builder.create_block();
+		let after = self.builder.create_block();
+
+		self.builder.append_block_param(header, self.ptr_type);
+		self.builder.append_block_param(after, self.ptr_type);
+
+		self.builder.ins().jump(header, &[self.ptr.into()]);
+
+		self.builder.switch_to_block(header);
+		self.ptr = self.builder.block_params(header)[0];
+
+		let current = self.load(Offset(0));
+		self.builder
+			.ins()
+			.brif(current, body_block, &[], after, &[self.ptr.into()]);
+
+		self.builder.switch_to_block(body_block);
+		self.builder.seal_block(body_block);
+
+		body(self)?;
+
+		self.builder.ins().jump(header, &[self.ptr.into()]);
+		self.builder.seal_block(header);
+
+		self.builder.switch_to_block(after);
+		self.builder.seal_block(after);
+		self.ptr = self.builder.block_params(after)[0];
+
+		Ok(())
+	}
+
+	fn translate_if_nz(&mut self, body: &[Instruction]) -> Result<(), JitError> {
+		let body_block = self.builder.create_block();
+		let after = self.builder.create_block();
+
+		self.builder.append_block_param(after, self.ptr_type);
+
+		let current = self.load(Offset(0));
+		self.builder
+			.ins()
+			.brif(current, body_block, &[], after, &[self.ptr.into()]);
+
+		self.builder.switch_to_block(body_block);
+		self.builder.seal_block(body_block);
+
+		self.translate(body)?;
+
+		let zero = self.cell_const(0);
+		self.store(Offset(0), zero);
+
+		self.builder.ins().jump(after, &[self.ptr.into()]);
+
+		self.builder.switch_to_block(after);
+		self.builder.seal_block(after);
+		self.ptr = self.builder.block_params(after)[0];
+
+		Ok(())
+	}
+
+	fn exit_on_error(&mut self, status: Value) {
+		let next = self.builder.create_block();
+
+		self.builder
+			.ins()
+			.brif(status, self.exit, &[self.ptr.into()], next, &[]);
+
+		self.builder.switch_to_block(next);
+		self.builder.seal_block(next);
+	}
+
+	fn move_ptr(&mut self, offset: Offset) {
+		self.ptr = self.wrap(offset);
+	}
+
+	// The index of the cell `offset` away from the pointer, wrapping around the tape.
+	fn wrap(&mut self, offset: Offset) -> Value {
+		let len = self.tape_len as i64;
+		let offset = (offset.value() as i64).rem_euclid(len);
+
+		if matches!(offset, 0) {
+			return self.ptr;
+		}
+
+		let moved = self.builder.ins().iadd_imm(self.ptr, offset);
+		let past_end = self
+			.builder
+			.ins()
+			.icmp_imm(IntCC::UnsignedGreaterThanOrEqual, moved, len);
+		let wrapped = self.builder.ins().iadd_imm(moved, -len);
+
+		self.builder.ins().select(past_end, wrapped, moved)
+	}
+
+	fn cell_addr(&mut self, offset: Offset) -> Value {
+		let index = self.wrap(offset);
+		let byte_offset = self
+			.builder
+			.ins()
+			.imul_imm(index, i64::from(self.cell_type.bytes()));
+
+		self.builder.ins().iadd(self.cells, byte_offset)
+	}
+
+	fn load(&mut self, offset: Offset) -> Value {
+		let addr = self.cell_addr(offset);
+
+		self.builder
+			.ins()
+			.load(self.cell_type, MemFlags::trusted(), addr, 0)
+	}
+
+	fn store(&mut self, offset: Offset, value: Value) {
+		let addr = self.cell_addr(offset);
+
+		self.builder
+			.ins()
+			.store(MemFlags::trusted(), value, addr, 0);
+	}
+
+	// Load a cell and clear it.
+	fn take(&mut self, offset: Offset) -> Value {
+		let value = self.load(offset);
+		let zero = self.cell_const(0);
+		self.store(offset, zero);
+
+		value
+	}
+
+	// A constant of the cell type, truncated to the cell width.
+	fn cell_const(&mut self, value: u32) -> Value {
+		let mask = u32::MAX >> (u32::BITS - self.cell_type.bits());
+
+		self.builder
+			.ins()
+			.iconst(self.cell_type, i64::from(value & mask))
+	}
+
+	fn extend_to_i32(&mut self, value: Value) -> Value {
+		if self.cell_type == I32 {
+			value
+		} else {
+			self.builder.ins().uextend(I32, value)
+		}
+	}
+}
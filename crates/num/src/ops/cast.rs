@@ -0,0 +1,39 @@
+pub trait WrappingFrom<T> {
+	fn wrapping_from(value: T) -> Self;
+}
+
+macro_rules! impl_wrapping_from {
+	($($ty:ty)*) => {
+		impl_wrapping_from!(@each [$($ty)*] $($ty)*);
+	};
+	(@each $all:tt $($ty:ty)*) => {
+		$(impl_wrapping_from!(@from $ty, $all);)*
+	};
+	(@from $from:ty, [$($to:ty)*]) => {
+		$(
+			impl $crate::ops::WrappingFrom<$from> for $to {
+				#[inline]
+				#[allow(clippy::cast_lossless)]
+				fn wrapping_from(value: $from) -> Self {
+					value as Self
+				}
+			}
+		)*
+	};
+}
+
+impl_wrapping_from!(i8 u8 i16 u16 i32 u32 i64 u64 i128 u128 isize usize);
+
+#[cfg(test)]
+mod tests {
+	use super::WrappingFrom;
+
+	#[test]
+	fn wrapping_from() {
+		assert_eq!(u8::wrapping_from(300i32), 44);
+		assert_eq!(u8::wrapping_from(-1i32), u8::MAX);
+		assert_eq!(u16::wrapping_from(-1i32), u16::MAX);
+		assert_eq!(i32::wrapping_from(u32::MAX), -1);
+		assert_eq!(u32::wrapping_from(200u8), 200);
+	}
+}
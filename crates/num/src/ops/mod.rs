@@ -1,4 +1,5 @@
 mod arith;
 mod bit;
+mod cast;
 
-pub use self::{arith::*, bit::*};
+pub use self::{arith::*, bit::*, cast::*};
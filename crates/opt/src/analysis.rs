@@ -0,0 +1,484 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use vmm_ir::{
+	BlockInstruction, CellWidth, Instruction, IsZeroingCell as _, Offset, PtrMovement as _,
+	ScaleAnd, SuperInstruction,
+};
+use vmm_tape::TAPE_SIZE;
+use vmm_utils::GetOrZero as _;
+
+/// What is known about the values of the cells around the pointer at some point in a program.
+///
+/// Found by running forwards through the instructions, keeping exact values through straight-line
+/// code. A loop only ends once the current cell is zero, which is known after every loop even when
+/// nothing else about it is.
+///
+/// The tape wraps around, so once the cells looked at span the whole tape two of them could be the
+/// same cell, and everything is forgotten.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellValues {
+	cell_width: CellWidth,
+	tape_len: usize,
+	ptr: isize,
+	// Keyed by cell relative to where the analysis started, so moving the pointer is cheap.
+	cells: BTreeMap<isize, Option<u32>>,
+	rest: Option<u32>,
+	// The lowest and highest cells written to since everything was last forgotten.
+	low: isize,
+	high: isize,
+}
+
+impl CellValues {
+	/// Nothing is known about any cell.
+	#[must_use]
+	pub const fn unknown(cell_width: CellWidth) -> Self {
+		Self {
+			cell_width,
+			tape_len: TAPE_SIZE,
+			ptr: 0,
+			cells: BTreeMap::new(),
+			rest: None,
+			low: 0,
+			high: 0,
+		}
+	}
+
+	/// Every cell is zero, as it is at the start of a program.
+	#[must_use]
+	pub const fn zeroed(cell_width: CellWidth) -> Self {
+		Self {
+			cell_width,
+			tape_len: TAPE_SIZE,
+			ptr: 0,
+			cells: BTreeMap::new(),
+			rest: Some(0),
+			low: 0,
+			high: 0,
+		}
+	}
+
+	#[must_use]
+	pub const fn cell_width(&self) -> CellWidth {
+		self.cell_width
+	}
+
+	/// How many cells the tape has before it wraps around, defaulting to [`TAPE_SIZE`].
+	#[must_use]
+	pub const fn and_with_tape_len(mut self, tape_len: usize) -> Self {
+		self.tape_len = tape_len;
+		self
+	}
+
+	#[must_use]
+	pub const fn tape_len(&self) -> usize {
+		self.tape_len
+	}
+
+	/// The value of the cell at `offset` from the pointer, if it is known.
+	#[must_use]
+	pub fn get(&self, offset: impl Into<Offset>) -> Option<u32> {
+		let cell = self.ptr + offset.into().0;
+
+		if self.wraps_with(cell) {
+			return None;
+		}
+
+		self.cells.get(&cell).copied().unwrap_or(self.rest)
+	}
+
+	#[must_use]
+	pub fn is_zero(&self, offset: impl Into<Offset>) -> bool {
+		matches!(self.get(offset), Some(0))
+	}
+
+	/// The values after running `instrs`.
+	#[must_use]
+	pub fn analyze(&self, instrs: &[Instruction]) -> Self {
+		let mut values = self.clone();
+
+		instrs.iter().for_each(|instr| values.apply(instr));
+
+		values
+	}
+
+	/// The values after running `instr`.
+	#[must_use]
+	pub fn after(&self, instr: &Instruction) -> Self {
+		let mut values = self.clone();
+
+		values.apply(instr);
+
+		values
+	}
+
+	/// What is known at the start of every iteration of a loop with the given body, which starts
+	/// with these values.
+	#[must_use]
+	pub fn loop_body_entry(&self, body: &[Instruction]) -> Self {
+		let mut values = self.clone();
+
+		if values.forget_writes(body, 0) != Some(0) {
+			values.forget_all();
+		}
+
+		values.set(0, None);
+
+		values
+	}
+
+	/// What is known at the start of an if-non-zero block, which starts with these values.
+	#[must_use]
+	pub fn if_body_entry(&self) -> Self {
+		let mut values = self.clone();
+
+		values.set(0, None);
+
+		values
+	}
+
+	pub fn apply(&mut self, instr: &Instruction) {
+		match instr {
+			Instruction::Boundary | Instruction::Write { .. } => {}
+			Instruction::IncVal { value, offset } => {
+				let new = self.get(*offset).map(|old| old.wrapping_add_signed(*value));
+
+				self.set(offset.0, new);
+			}
+			Instruction::SetVal { value, offset } => self.set(offset.0, Some(value.get_or_zero())),
+			Instruction::SubCell { offset } => {
+				let current = self.get(0);
+
+				self.set(0, Some(0));
+
+				let new = self
+					.get(*offset)
+					.zip(current)
+					.map(|(old, current)| old.wrapping_sub(current));
+
+				self.set(offset.0, new);
+			}
+			Instruction::ScaleVal { factor } => {
+				let new = self.get(0).map(|old| old.wrapping_mul(*factor));
+
+				self.set(0, new);
+			}
+			Instruction::MoveVal(offset) => self.move_val(0, offset.0, 1),
+			Instruction::FetchVal(offset) => self.move_val(offset.0, 0, 1),
+			Instruction::TakeVal(offset) => {
+				self.move_val(0, offset.0, 1);
+				self.ptr += offset.0;
+			}
+			Instruction::ReplaceVal(offset) => {
+				let value = self.get(*offset);
+
+				self.set(offset.0, Some(0));
+				self.set(0, value);
+			}
+			Instruction::MovePtr(offset) => self.ptr += offset.0,
+			Instruction::Read => self.set(0, None),
+			Instruction::FindZero(..) => {
+				self.forget_all();
+				self.set(0, Some(0));
+			}
+			Instruction::Super(instr) => self.apply_super(*instr),
+			Instruction::Block(BlockInstruction::DynamicLoop(body)) => self.apply_loop(body),
+			Instruction::Block(BlockInstruction::IfNz(body)) => self.apply_if_nz(body),
+			instr => self.forget_around(instr),
+		}
+	}
+
+	fn apply_super(&mut self, instr: SuperInstruction) {
+		match instr {
+			SuperInstruction::ScaleAnd {
+				action: ScaleAnd::Move,
+				offset,
+				factor,
+			} => self.move_val(0, offset.0, factor),
+			SuperInstruction::ScaleAnd {
+				action: ScaleAnd::Fetch,
+				offset,
+				factor,
+			} => self.move_val(offset.0, 0, factor),
+			SuperInstruction::ScaleAnd {
+				action: ScaleAnd::Take,
+				offset,
+				factor,
+			} => {
+				self.move_val(0, offset.0, factor);
+				self.ptr += offset.0;
+			}
+			SuperInstruction::ScaleAnd {
+				action: ScaleAnd::Set(value),
+				offset,
+				factor,
+			} => {
+				let current = self.get(0);
+
+				self.set(0, Some(value.get()));
+
+				let new = self
+					.get(offset)
+					.zip(current)
+					.map(|(old, current)| old.wrapping_add(current.wrapping_mul(factor)));
+
+				self.set(offset.0, new);
+			}
+			SuperInstruction::FindAndSetZero { value, .. } => {
+				self.forget_all();
+				self.set(0, Some(value.get()));
+			}
+			SuperInstruction::SetUntilZero { .. } | SuperInstruction::ShiftVals { .. } => {
+				self.forget_all();
+				self.set(0, Some(0));
+			}
+			SuperInstruction::FindCellByZero { offset, .. } => {
+				self.forget_all();
+				self.set(-offset.0, Some(0));
+			}
+			_ => self.forget_around(&Instruction::Super(instr)),
+		}
+	}
+
+	fn apply_loop(&mut self, body: &[Instruction]) {
+		if self.is_zero(0) {
+			return;
+		}
+
+		if self.forget_writes(body, 0) != Some(0) {
+			self.forget_all();
+		}
+
+		self.set(0, Some(0));
+	}
+
+	fn apply_if_nz(&mut self, body: &[Instruction]) {
+		if self.is_zero(0) {
+			return;
+		}
+
+		let taken = self.if_body_entry().analyze(body);
+
+		if !matches!(body.ptr_movement(), Some(Offset(0))) {
+			self.forget_all();
+		} else if matches!(self.get(0), Some(..)) {
+			*self = taken;
+		} else {
+			self.join(&taken);
+		}
+
+		self.set(0, Some(0));
+	}
+
+	fn move_val(&mut self, from: isize, to: isize, factor: u32) {
+		let value = self.get(from);
+
+		self.set(from, Some(0));
+
+		let new = self
+			.get(to)
+			.zip(value)
+			.map(|(old, value)| old.wrapping_add(value.wrapping_mul(factor)));
+
+		self.set(to, new);
+	}
+
+	/// Forgets every cell `instrs` could write to, returning where they leave the pointer if that
+	/// is known.
+	fn forget_writes(&mut self, instrs: &[Instruction], mut pos: isize) -> Option<isize> {
+		for instr in instrs {
+			match instr {
+				Instruction::Boundary | Instruction::Write { .. } | Instruction::MovePtr(..) => {}
+				Instruction::IncVal { offset, .. } | Instruction::SetVal { offset, .. } => {
+					self.set(pos + offset.0, None);
+				}
+				Instruction::Read | Instruction::ScaleVal { .. } => self.set(pos, None),
+				Instruction::SubCell { offset }
+				| Instruction::MoveVal(offset)
+				| Instruction::FetchVal(offset)
+				| Instruction::TakeVal(offset)
+				| Instruction::ReplaceVal(offset)
+				| Instruction::Super(SuperInstruction::ScaleAnd { offset, .. }) => {
+					self.set(pos, None);
+					self.set(pos + offset.0, None);
+				}
+				Instruction::Block(block) => {
+					if self.forget_writes(block, pos)? != pos {
+						return None;
+					}
+
+					self.set(pos, None);
+
+					continue;
+				}
+				_ => return None,
+			}
+
+			pos += instr.ptr_movement()?.0;
+		}
+
+		Some(pos)
+	}
+
+	fn join(&mut self, other: &Self) {
+		let offsets = self
+			.cells
+			.keys()
+			.map(|cell| cell - self.ptr)
+			.chain(other.cells.keys().map(|cell| cell - other.ptr))
+			.collect::<BTreeSet<_>>();
+
+		let mut joined = Self {
+			rest: self.rest.filter(|_| self.rest == other.rest),
+			..Self::unknown(self.cell_width).and_with_tape_len(self.tape_len)
+		};
+
+		for offset in offsets {
+			let value = self
+				.get(offset)
+				.filter(|_| self.get(offset) == other.get(offset));
+
+			joined.set(offset, value);
+		}
+
+		*self = joined;
+	}
+
+	// For instructions the analysis doesn't know about.
+	fn forget_around(&mut self, instr: &Instruction) {
+		self.forget_all();
+
+		if instr.is_zeroing_cell() {
+			self.set(0, Some(0));
+		}
+	}
+
+	fn forget_all(&mut self) {
+		self.ptr = 0;
+		self.cells.clear();
+		self.rest = None;
+		self.low = 0;
+		self.high = 0;
+	}
+
+	// Whether `cell` could be the same as one already written to.
+	fn wraps_with(&self, cell: isize) -> bool {
+		self.high.max(cell).abs_diff(self.low.min(cell)) >= self.tape_len
+	}
+
+	fn set(&mut self, offset: isize, value: Option<u32>) {
+		let value = value.map(|value| self.cell_width.wrap_unsigned(value));
+
+		if self.wraps_with(self.ptr + offset) {
+			self.forget_all();
+		}
+
+		let cell = self.ptr + offset;
+
+		self.low = self.low.min(cell);
+		self.high = self.high.max(cell);
+
+		if value == self.rest {
+			self.cells.remove(&cell);
+		} else {
+			self.cells.insert(cell, value);
+		}
+	}
+}
+
+impl Default for CellValues {
+	fn default() -> Self {
+		Self::unknown(CellWidth::default())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use vmm_ir::{CellWidth, Instruction};
+
+	use super::CellValues;
+
+	fn zeroed() -> CellValues {
+		CellValues::zeroed(CellWidth::U8)
+	}
+
+	#[test]
+	fn follows_straight_line_code() {
+		let values = zeroed().analyze(&[
+			Instruction::inc_val(3),
+			Instruction::move_ptr(2),
+			Instruction::inc_val(-1),
+			Instruction::fetch_val(-2),
+		]);
+
+		assert_eq!(values.get(0), Some(2));
+		assert_eq!(values.get(-2), Some(0));
+		assert_eq!(values.get(5), Some(0));
+	}
+
+	#[test]
+	fn wraps_to_cell_width() {
+		assert_eq!(zeroed().after(&Instruction::inc_val(-1)).get(0), Some(255));
+		assert!(zeroed().after(&Instruction::set_val(256)).is_zero(0));
+	}
+
+	#[test]
+	fn loops_end_on_zero() {
+		let values = CellValues::unknown(CellWidth::U8).analyze(&[
+			Instruction::set_val_at(4, 2),
+			Instruction::dynamic_loop([Instruction::inc_val(-1), Instruction::inc_val_at(1, 1)]),
+		]);
+
+		assert!(values.is_zero(0));
+		assert_eq!(values.get(1), None);
+		assert_eq!(values.get(2), Some(4));
+
+		let values = values.after(&Instruction::dynamic_loop([
+			Instruction::move_ptr(1),
+			Instruction::inc_val(1),
+		]));
+
+		assert_eq!(values.get(2), Some(4), "skipped loops change nothing");
+	}
+
+	#[test]
+	fn loops_that_move_forget_everything() {
+		let values = zeroed().analyze(&[
+			Instruction::inc_val_at(1, 3),
+			Instruction::inc_val(1),
+			Instruction::dynamic_loop([Instruction::move_ptr(1)]),
+		]);
+
+		assert!(values.is_zero(0));
+		assert_eq!(values.get(3), None);
+		assert!(
+			zeroed()
+				.loop_body_entry(&[Instruction::move_ptr(1)])
+				.get(1)
+				.is_none()
+		);
+	}
+
+	#[test]
+	fn joins_if_branches() {
+		let values = zeroed().analyze(&[
+			Instruction::read(),
+			Instruction::set_val_at(1, 1),
+			Instruction::if_nz([Instruction::set_val_at(1, 1), Instruction::set_val_at(2, 2)]),
+		]);
+
+		assert!(values.is_zero(0));
+		assert_eq!(values.get(1), Some(1));
+		assert_eq!(values.get(2), None);
+	}
+
+	#[test]
+	fn forgets_cells_the_tape_wraps_onto() {
+		let values = zeroed()
+			.and_with_tape_len(4)
+			.analyze(&[Instruction::inc_val(1), Instruction::move_ptr(3)]);
+
+		assert!(values.is_zero(0));
+		assert_eq!(values.get(1), None, "the first cell again");
+		assert_eq!(values.after(&Instruction::set_val_at(2, 1)).get(-3), None);
+	}
+}
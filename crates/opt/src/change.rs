@@ -1,6 +1,9 @@
+use std::slice;
+
 use tracing::{Level, trace};
 use vmm_ir::Instruction;
 use vmm_num::Wrapping;
+use vmm_program::{SourceMap, SourceMapEntry, SourceSpan};
 use vmm_utils::InsertOrPush as _;
 use vmm_vec::SmallVec;
 
@@ -40,9 +43,21 @@ impl Change {
 		Self::Replace(i)
 	}
 
+	/// Applies the change to the `size` instructions starting at `i`, keeping the source map in
+	/// step when there is one.
 	#[inline]
-	#[tracing::instrument(skip(self, ops, size), level = Level::TRACE)]
-	pub fn apply(self, ops: &mut Vec<Instruction>, i: usize, size: usize) -> (bool, usize) {
+	#[tracing::instrument(skip(self, ops, source_map, size), level = Level::TRACE)]
+	pub fn apply(
+		self,
+		ops: &mut Vec<Instruction>,
+		source_map: Option<&mut SourceMap>,
+		i: usize,
+		size: usize,
+	) -> (bool, usize) {
+		if let Some(source_map) = source_map {
+			self.apply_to_source_map(ops, source_map.entries_mut(), i, size);
+		}
+
 		match self {
 			Self::Remove => {
 				let removed = ops.drain(i..(i + size)).collect::<SmallVec<_, 2>>();
@@ -86,4 +101,109 @@ impl Change {
 			}
 		}
 	}
+
+	/// Instructions that are only moved keep their spans, anything new gets the span of everything
+	/// it replaced.
+	fn apply_to_source_map(
+		&self,
+		ops: &[Instruction],
+		entries: &mut Vec<SourceMapEntry>,
+		i: usize,
+		size: usize,
+	) {
+		let new_instrs = match self {
+			Self::Remove => {
+				entries.drain(i..(i + size));
+				return;
+			}
+			Self::RemoveOffset(offset) => {
+				entries.remove(Wrapping::add(i, *offset));
+				return;
+			}
+			Self::Swap(instrs) => &instrs[..],
+			Self::Replace(instr) => slice::from_ref(instr),
+		};
+
+		let mut replaced = ops[i..(i + size)]
+			.iter()
+			.zip(entries.drain(i..(i + size)))
+			.map(|(instr, entry)| (instr, Some(entry)))
+			.collect::<SmallVec<_, 4>>();
+
+		let span = replaced
+			.iter()
+			.filter_map(|(_, entry)| entry.as_ref().map(SourceMapEntry::span))
+			.reduce(SourceSpan::merge)
+			.unwrap_or_default();
+
+		for (offset, instr) in new_instrs.iter().enumerate() {
+			let entry = replaced
+				.iter_mut()
+				.find(|(old, entry)| *old == instr && entry.is_some())
+				.and_then(|(_, entry)| entry.take())
+				.unwrap_or_else(|| SourceMapEntry::covering(instr, span));
+
+			entries.insert_or_push(i + offset, entry);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use vmm_ir::Instruction;
+	use vmm_program::{SourceMap, SourceMapEntry, SourceSpan};
+
+	use super::Change;
+
+	fn source_map(spans: impl IntoIterator<Item = (usize, usize)>) -> SourceMap {
+		spans
+			.into_iter()
+			.map(|(start, end)| SourceMapEntry::new(SourceSpan::new(start, end)))
+			.collect()
+	}
+
+	#[test]
+	fn swaps_keep_moved_spans() {
+		let mut ops = vec![
+			Instruction::inc_val(1),
+			Instruction::move_ptr(1),
+			Instruction::write_once(),
+		];
+		let mut map = source_map([(0, 1), (1, 2), (2, 3)]);
+
+		Change::swap([Instruction::move_ptr(1), Instruction::inc_val_at(1, -1)]).apply(
+			&mut ops,
+			Some(&mut map),
+			0,
+			2,
+		);
+
+		assert!(map.matches(&ops));
+		assert_eq!(map.span(&[0]), Some(SourceSpan::new(1, 2)));
+		assert_eq!(map.span(&[1]), Some(SourceSpan::new(0, 2)));
+		assert_eq!(map.span(&[2]), Some(SourceSpan::new(2, 3)));
+	}
+
+	#[test]
+	fn replacements_cover_what_they_replace() {
+		let mut ops = vec![
+			Instruction::write_once(),
+			Instruction::dynamic_loop([Instruction::inc_val(-1)]),
+		];
+		let mut map = source_map([(0, 1), (1, 4)]);
+
+		Change::replace(Instruction::if_nz([Instruction::write_once()])).apply(
+			&mut ops,
+			Some(&mut map),
+			0,
+			2,
+		);
+
+		assert!(map.matches(&ops));
+		assert_eq!(map.span(&[0, 0]), Some(SourceSpan::new(0, 4)));
+
+		Change::remove().apply(&mut ops, Some(&mut map), 0, 1);
+
+		assert!(map.is_empty());
+	}
 }
@@ -0,0 +1,194 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use vmm_ir::Instruction;
+use vmm_program::{SourceMap, SourceMapEntry, SourceSpan};
+
+use crate::Change;
+
+/// A limit on how many [`Change`]s passes may apply, after which they leave the program alone.
+///
+/// Optimizing with increasing amounts of fuel replays the optimizer one change at a time, which is
+/// enough to find the exact change that broke a program.
+#[derive(Debug, Default, Clone)]
+pub struct Fuel {
+	remaining: Option<u64>,
+	consumed: u64,
+	last_change: Option<AppliedChange>,
+}
+
+impl Fuel {
+	#[must_use]
+	pub const fn unlimited() -> Self {
+		Self {
+			remaining: None,
+			consumed: 0,
+			last_change: None,
+		}
+	}
+
+	#[must_use]
+	pub const fn new(limit: u64) -> Self {
+		Self {
+			remaining: Some(limit),
+			consumed: 0,
+			last_change: None,
+		}
+	}
+
+	#[must_use]
+	pub const fn remaining(&self) -> Option<u64> {
+		self.remaining
+	}
+
+	/// How many changes have been applied.
+	#[must_use]
+	pub const fn consumed(&self) -> u64 {
+		self.consumed
+	}
+
+	#[must_use]
+	pub const fn is_exhausted(&self) -> bool {
+		matches!(self.remaining, Some(0))
+	}
+
+	/// The last change applied, only kept when the fuel is limited.
+	#[must_use]
+	pub const fn last_change(&self) -> Option<&AppliedChange> {
+		self.last_change.as_ref()
+	}
+
+	/// Whether there is enough fuel left to apply another change.
+	pub(crate) fn consume(&mut self, change: impl FnOnce() -> AppliedChange) -> bool {
+		match &mut self.remaining {
+			None => {}
+			Some(0) => return false,
+			Some(remaining) => {
+				*remaining -= 1;
+				self.last_change = Some(change());
+			}
+		}
+
+		self.consumed += 1;
+		true
+	}
+}
+
+/// A [`Change`] made by a pass, along with the instructions it changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedChange {
+	pass: String,
+	span: Option<SourceSpan>,
+	before: Vec<Instruction>,
+	after: Vec<Instruction>,
+}
+
+impl AppliedChange {
+	pub(crate) fn new(
+		pass: &str,
+		window: &[Instruction],
+		change: &Change,
+		source_map: Option<&SourceMap>,
+		i: usize,
+	) -> Self {
+		let mut after = window.to_vec();
+
+		change.clone().apply(&mut after, None, 0, window.len());
+
+		let span = source_map.and_then(|source_map| {
+			(i..(i + window.len()))
+				.filter_map(|index| source_map.get(index).map(SourceMapEntry::span))
+				.reduce(SourceSpan::merge)
+		});
+
+		Self {
+			pass: pass.to_owned(),
+			span,
+			before: window.to_vec(),
+			after,
+		}
+	}
+
+	/// The pass that made the change.
+	#[must_use]
+	pub fn pass(&self) -> &str {
+		&self.pass
+	}
+
+	/// Where the changed instructions came from, when a source map was kept.
+	#[must_use]
+	pub const fn span(&self) -> Option<SourceSpan> {
+		self.span
+	}
+
+	#[must_use]
+	pub fn before(&self) -> &[Instruction] {
+		&self.before
+	}
+
+	#[must_use]
+	pub fn after(&self) -> &[Instruction] {
+		&self.after
+	}
+}
+
+impl Display for AppliedChange {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		// Blocks take up several lines, each of which is marked.
+		for (marker, instrs) in [('-', &self.before), ('+', &self.after)] {
+			for line in instrs.iter().flat_map(|instr| {
+				instr
+					.to_string()
+					.lines()
+					.map(str::to_owned)
+					.collect::<Vec<_>>()
+			}) {
+				writeln!(f, "{marker} {line}")?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use vmm_ir::Instruction;
+
+	use super::{AppliedChange, Fuel};
+	use crate::Change;
+
+	#[test]
+	fn runs_out() {
+		let window = [Instruction::inc_val(1), Instruction::inc_val(1)];
+		let change = || {
+			AppliedChange::new(
+				"collapse_stacked_instr",
+				&window,
+				&Change::replace(Instruction::inc_val(2)),
+				None,
+				0,
+			)
+		};
+
+		let mut fuel = Fuel::new(1);
+
+		assert!(fuel.consume(change));
+		assert!(fuel.is_exhausted());
+		assert!(!fuel.consume(change));
+		assert_eq!(fuel.consumed(), 1);
+
+		let last = fuel.last_change().unwrap();
+
+		assert_eq!(last.before(), window);
+		assert_eq!(last.after(), [Instruction::inc_val(2)]);
+	}
+
+	#[test]
+	fn unlimited_fuel_keeps_nothing() {
+		let mut fuel = Fuel::unlimited();
+
+		assert!(fuel.consume(|| unreachable!()));
+		assert!(fuel.last_change().is_none());
+		assert_eq!(fuel.consumed(), 1);
+	}
+}
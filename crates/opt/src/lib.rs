@@ -1,33 +1,177 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg, doc_cfg))]
 
+mod analysis;
 mod change;
+mod fuel;
 mod metadata;
 mod pass;
 pub mod passes;
+mod pipeline;
+mod stats;
+mod validate;
 
 use std::{
 	error::Error as StdError,
 	fmt::{Debug, Display, Formatter, Result as FmtResult},
 	mem,
+	time::Instant,
 };
 
 use tap::prelude::*;
 use tracing::{debug, info, warn};
-use vmm_ir::{BlockInstruction, Instruction};
-use vmm_program::Program;
+use vmm_ir::{BlockInstruction, CellWidth, EofPolicy, Instruction};
+use vmm_program::{Program, SourceMap, SourceMapEntry};
+use vmm_tape::TAPE_SIZE;
 
 #[allow(clippy::wildcard_imports)]
 use self::passes::*;
-pub use self::{change::*, metadata::*, pass::*};
+pub use self::{
+	analysis::*, change::*, fuel::*, metadata::*, pass::*, pipeline::*, stats::*, validate::*,
+};
 
 pub struct Optimizer<S: MetadataStore = HashMetadataStore> {
 	program: Program,
+	source_map: Option<SourceMap>,
+	pipeline: PassPipeline,
+	fuel: Fuel,
+	stats: OptimizerStats,
+	validation: Option<Validation>,
 	store: S,
+	cell_width: CellWidth,
+	tape_len: usize,
+	eof: EofPolicy,
+	// Writes RemoveUnusedBoundaryInstr dropped from the end of the program, in program order.
+	dropped_writes: Vec<Instruction>,
 }
 
 impl<S: MetadataStore> Optimizer<S> {
-	pub const fn new(program: Program, store: S) -> Self {
-		Self { program, store }
+	pub fn new(program: Program, store: S) -> Self {
+		Self {
+			program,
+			source_map: None,
+			pipeline: PassPipeline::default(),
+			fuel: Fuel::unlimited(),
+			stats: OptimizerStats::new(),
+			validation: None,
+			store,
+			cell_width: CellWidth::U8,
+			tape_len: TAPE_SIZE,
+			eof: EofPolicy::Zero,
+			dropped_writes: Vec::new(),
+		}
+	}
+
+	/// Optimize for tape cells of the given width, defaulting to 8-bit cells.
+	#[must_use]
+	pub const fn and_with_cell_width(mut self, cell_width: CellWidth) -> Self {
+		self.cell_width = cell_width;
+		self
+	}
+
+	#[must_use]
+	pub const fn cell_width(&self) -> CellWidth {
+		self.cell_width
+	}
+
+	/// Optimize for a tape that wraps around after the given number of cells, defaulting to
+	/// [`TAPE_SIZE`].
+	#[must_use]
+	pub const fn and_with_tape_len(mut self, tape_len: usize) -> Self {
+		self.tape_len = tape_len;
+		self
+	}
+
+	#[must_use]
+	pub const fn tape_len(&self) -> usize {
+		self.tape_len
+	}
+
+	/// Optimize for the given behaviour of reads at the end of input, defaulting to writing 0.
+	#[must_use]
+	pub const fn and_with_eof_policy(mut self, eof: EofPolicy) -> Self {
+		self.eof = eof;
+		self
+	}
+
+	#[must_use]
+	pub const fn eof_policy(&self) -> EofPolicy {
+		self.eof
+	}
+
+	/// Keep a source map for the program up to date as it is optimized.
+	///
+	/// # Panics
+	///
+	/// If the source map doesn't have an entry for every instruction of the program.
+	#[must_use]
+	pub fn and_with_source_map(mut self, source_map: SourceMap) -> Self {
+		assert!(
+			source_map.matches(&self.program),
+			"source map must match the program"
+		);
+
+		self.source_map = Some(source_map);
+		self
+	}
+
+	#[must_use]
+	pub const fn source_map(&self) -> Option<&SourceMap> {
+		self.source_map.as_ref()
+	}
+
+	/// Run the given passes on each iteration, defaulting to [`OptLevel::O2`].
+	#[must_use]
+	pub fn and_with_pipeline(mut self, pipeline: PassPipeline) -> Self {
+		self.pipeline = pipeline;
+		self
+	}
+
+	#[must_use]
+	pub const fn pipeline(&self) -> &PassPipeline {
+		&self.pipeline
+	}
+
+	/// Apply at most `limit` changes, however many passes would like to make.
+	#[must_use]
+	pub fn and_with_fuel(mut self, limit: u64) -> Self {
+		self.fuel = Fuel::new(limit);
+		self
+	}
+
+	#[must_use]
+	pub const fn fuel(&self) -> &Fuel {
+		&self.fuel
+	}
+
+	/// Where snapshots and the statistics report are kept.
+	#[must_use]
+	pub const fn store(&self) -> &S {
+		&self.store
+	}
+
+	/// What each pass did, filled in as the program is optimized.
+	#[must_use]
+	pub const fn stats(&self) -> &OptimizerStats {
+		&self.stats
+	}
+
+	/// Check the optimized program against the original once optimizing is done, on a tape as
+	/// long as the one being optimized for.
+	#[must_use]
+	pub fn and_with_validation(mut self, validation: Validation) -> Self {
+		self.validation = Some(validation);
+		self
+	}
+
+	#[must_use]
+	pub fn and_without_validation(mut self) -> Self {
+		self.validation = None;
+		self
+	}
+
+	#[must_use]
+	pub const fn validation(&self) -> Option<&Validation> {
+		self.validation.as_ref()
 	}
 
 	#[tracing::instrument("optimize program", skip(self))]
@@ -42,6 +186,10 @@ impl<S: MetadataStore> Optimizer<S> {
 			));
 		}
 
+		let original = self.validation.is_some().then(|| self.program.clone());
+
+		self.normalize_values(&mut false);
+
 		let mut iteration = 1;
 
 		let mut progress = self.optimization_pass(iteration)?;
@@ -51,24 +199,18 @@ impl<S: MetadataStore> Optimizer<S> {
 			progress = self.optimization_pass(iteration)?;
 		}
 
-		if !matches!(iteration, 1) {
-			let (first_program, last_program) = (
-				self.store.get_program_snapshot(1)?,
-				self.store.get_program_snapshot(iteration)?,
-			);
-
-			if let Some((first_program, last_program)) = Option::zip(first_program, last_program)
-				&& first_program.into_iter().collect::<RawProgram>()
-					!= last_program.into_iter().collect::<RawProgram>()
-			{
-				warn!("program instructions do not match, semantics may be different");
-			}
+		if let Some(original) = original {
+			self.validate(&original)?;
 		}
 
 		if let Some(program) = self.store.get_program_snapshot(iteration)? {
 			self.store.insert_program_snapshot(0, &program)?;
 		}
 
+		info!("pass statistics:\n{}", self.stats);
+
+		self.store.insert(0, &self.stats)?;
+
 		Ok(Program::Finalized(
 			mem::take(&mut self.program)
 				.iter()
@@ -78,6 +220,29 @@ impl<S: MetadataStore> Optimizer<S> {
 		))
 	}
 
+	fn validate(&self, original: &Program) -> Result<(), OptimizerError> {
+		let Some(validation) = &self.validation else {
+			return Ok(());
+		};
+
+		// Dropping writes just before the program ends changes the tape it leaves behind, but not
+		// anything the program does, so they're made again to compare the rest of the tape.
+		let mut optimized = self.program.clone();
+		let instrs = optimized.as_raw();
+		let end = instrs.len() - usize::from(matches!(instrs.last(), Some(Instruction::Boundary)));
+
+		instrs.splice(end..end, self.dropped_writes.iter().cloned());
+
+		validation
+			.clone()
+			.and_with_tape_len(self.tape_len)
+			.validate(original, &optimized, self.cell_width, self.eof)?;
+
+		debug!("optimized program matches the original");
+
+		Ok(())
+	}
+
 	#[tracing::instrument("run pass", skip(self))]
 	fn optimization_pass(&mut self, iteration: usize) -> Result<bool, OptimizerError> {
 		let starting_instruction_count = self.program.rough_estimate();
@@ -99,118 +264,280 @@ impl<S: MetadataStore> Optimizer<S> {
 		Ok(progress)
 	}
 
-	fn run_pass<P>(&mut self, pass: &mut P, progress: &mut bool)
+	fn run_pass<P>(&mut self, name: PassName, pass: &mut P, progress: &mut bool)
 	where
 		P: Debug + Pass,
 	{
+		if self.fuel.is_exhausted() {
+			return;
+		}
+
+		let mut pass_progress = false;
+
+		let stats = self.stats.pass_mut(name.as_str());
+		let before = self.program.rough_estimate();
+		let start = Instant::now();
+
 		pass.tap(|pass| debug!("running pass {pass:?}"))
-			.pipe(|pass| run_pass(pass, self.program.as_raw(), progress));
+			.pipe(|pass| {
+				run_pass(
+					pass,
+					self.program.as_raw(),
+					self.source_map.as_mut(),
+					&CellValues::zeroed(self.cell_width).and_with_tape_len(self.tape_len),
+					&mut self.fuel,
+					stats,
+					&mut pass_progress,
+				);
+			});
+
+		stats.record_run(before, self.program.rough_estimate(), start.elapsed());
+
+		if pass_progress {
+			self.normalize_values(&mut false);
+		}
+
+		*progress |= pass_progress;
 	}
 
-	fn run_default_peephole_pass<P>(&mut self, progress: &mut bool)
+	fn normalize_values(&mut self, progress: &mut bool) {
+		run_pass(
+			&mut NormalizeValuesPass::new(self.cell_width),
+			self.program.as_raw(),
+			self.source_map.as_mut(),
+			&CellValues::unknown(self.cell_width),
+			&mut Fuel::unlimited(),
+			&mut PassStats::default(),
+			progress,
+		);
+	}
+
+	fn run_default_peephole_pass<P>(&mut self, name: PassName, progress: &mut bool)
 	where
 		P: Debug + Default + PeepholePass,
 	{
-		let mut pass = PeepholeRunner::<P>::default();
+		self.run_peephole_pass(name, P::default(), progress);
+	}
 
-		self.run_pass(&mut pass, progress);
+	fn run_peephole_pass<P>(&mut self, name: PassName, pass: P, progress: &mut bool)
+	where
+		P: Debug + PeepholePass,
+	{
+		self.run_pass(name, &mut PeepholeRunner(pass), progress);
 	}
 
 	#[allow(unused)]
-	fn run_default_range_peephole_pass<P>(&mut self, progress: &mut bool)
+	fn run_default_range_peephole_pass<P>(&mut self, name: PassName, progress: &mut bool)
 	where
 		P: Debug + Default + RangePeepholePass,
 	{
 		let mut pass = RangePeepholeRunner::<P>::default();
 
-		self.run_pass(&mut pass, progress);
+		self.run_pass(name, &mut pass, progress);
 	}
 
-	fn run_default_block_pass<P>(&mut self, progress: &mut bool)
+	fn run_default_block_pass<P>(&mut self, name: PassName, progress: &mut bool)
 	where
 		P: Debug + Default + LoopPass,
 	{
-		self.run_default_dynamic_loop_pass::<P>(progress);
-		self.run_default_if_nz_pass::<P>(progress);
+		self.run_default_dynamic_loop_pass::<P>(name, progress);
+		self.run_default_if_nz_pass::<P>(name, progress);
 	}
 
-	fn run_default_dynamic_loop_pass<P>(&mut self, progress: &mut bool)
+	fn run_default_dynamic_loop_pass<P>(&mut self, name: PassName, progress: &mut bool)
 	where
 		P: Debug + Default + LoopPass,
 	{
-		self.run_default_peephole_pass::<DynamicLoopRunner<P>>(progress);
+		self.run_default_peephole_pass::<DynamicLoopRunner<P>>(name, progress);
 	}
 
-	fn run_default_if_nz_pass<P>(&mut self, progress: &mut bool)
+	fn run_default_if_nz_pass<P>(&mut self, name: PassName, progress: &mut bool)
 	where
 		P: Debug + Default + LoopPass,
 	{
-		self.run_default_peephole_pass::<IfNzRunner<P>>(progress);
+		self.run_default_peephole_pass::<IfNzRunner<P>>(name, progress);
 	}
 
 	fn run_all_passes(&mut self, progress: &mut bool) {
-		self.run_default_peephole_pass::<CollapseStackedInstrPass>(progress);
-		self.run_default_peephole_pass::<CollapseRelativeInstrPass>(progress);
-
-		self.run_default_block_pass::<OptimizeClearCellPass>(progress);
-		self.run_default_dynamic_loop_pass::<OptimizeClearLoopPass>(progress);
-		self.run_default_block_pass::<OptimizeFindZeroPass>(progress);
-		self.run_default_peephole_pass::<OptimizeSetZeroPass>(progress);
-		self.run_default_block_pass::<OptimizeScaleAndMoveValPass>(progress);
-		self.run_default_peephole_pass::<OptimizeFetchAndScaleValPass>(progress);
-		self.run_default_peephole_pass::<OptimizeScaleValPass>(progress);
-		self.run_default_peephole_pass::<OptimizeZeroedCellIncValPass>(progress);
-		self.run_default_peephole_pass::<OptimizeScaleAndTakeValPass>(progress);
-		self.run_default_peephole_pass::<OptimizeScaleAndSetValPass>(progress);
-		self.run_default_peephole_pass::<OptimizeSetScaleValPass>(progress);
-		self.run_default_peephole_pass::<OptimizeTakeValPass>(progress);
-		self.run_default_peephole_pass::<OptimizeTakeFetchValPass>(progress);
-		self.run_default_block_pass::<OptimizeIfNzPass>(progress);
-		self.run_default_dynamic_loop_pass::<OptimizeSubCellPass>(progress);
-		self.run_default_peephole_pass::<OptimizeConstantSubPass>(progress);
-		self.run_default_peephole_pass::<OptimizeFetchValPass>(progress);
-		self.run_default_dynamic_loop_pass::<OptimizeSetUntilZeroPass>(progress);
-		self.run_default_peephole_pass::<OptimizeReplaceValPass>(progress);
-		self.run_default_peephole_pass::<OptimizeFindCellByZeroPass>(progress);
-		self.run_default_dynamic_loop_pass::<OptimizeShiftValsPass>(progress);
-		self.run_default_peephole_pass::<OptimizeSuperInstrPass>(progress);
-		self.run_default_peephole_pass::<OptimizeSetWriteIncPass>(progress);
-		self.run_default_peephole_pass::<OptimizeConstantShiftPass>(progress);
-		self.run_default_peephole_pass::<OptimizeMoveValPass>(progress);
-
-		self.run_default_peephole_pass::<ReorderMoveChangePass>(progress);
-		self.run_default_peephole_pass::<ReorderOffsetBetweenMovesPass>(progress);
-		self.run_default_peephole_pass::<CombineMoveChangePass>(progress);
-		self.run_default_peephole_pass::<SortIncInstrPass>(progress);
-		self.run_default_peephole_pass::<SortSetInstrPass>(progress);
-		self.run_default_peephole_pass::<ReorderSetIncPass>(progress);
-
-		self.run_default_peephole_pass::<RemoveRedundantChangeValBasicPass>(progress);
-		self.run_default_peephole_pass::<RemoveRedundantChangeValOffsetPass>(progress);
-		self.run_default_peephole_pass::<RemovePointlessInstrPass>(progress);
-		self.run_default_peephole_pass::<RemoveRedundantScaleValInstrBasicPass>(progress);
-		self.run_default_peephole_pass::<RemoveRedundantShiftsPass>(progress);
-
-		self.run_default_dynamic_loop_pass::<RemoveEmptyLoopsPass>(progress);
-		self.run_default_peephole_pass::<RemoveUnreachableLoopsPass>(progress);
-		self.run_default_peephole_pass::<RemoveUnusedBoundaryInstrPass>(progress);
-		self.run_default_dynamic_loop_pass::<RemoveInfiniteLoopsPass>(progress);
-
-		self.run_default_peephole_pass::<UnrollConstantLoopsPass>(progress);
-		self.run_default_peephole_pass::<UnrollIncrementLoopsPass>(progress);
-		self.run_default_peephole_pass::<UnrollScaleAndPass>(progress);
+		for pass in self.pipeline.passes().to_vec() {
+			self.run_named_pass(pass, progress);
+		}
+	}
+
+	fn run_named_pass(&mut self, pass: PassName, progress: &mut bool) {
+		match pass {
+			PassName::CollapseStackedInstr => {
+				self.run_default_peephole_pass::<CollapseStackedInstrPass>(pass, progress);
+			}
+			PassName::CollapseRelativeInstr => {
+				self.run_default_peephole_pass::<CollapseRelativeInstrPass>(pass, progress);
+			}
+			PassName::OptimizeClearCell => {
+				self.run_default_block_pass::<OptimizeClearCellPass>(pass, progress);
+			}
+			PassName::OptimizeClearLoop => {
+				self.run_default_dynamic_loop_pass::<OptimizeClearLoopPass>(pass, progress);
+			}
+			PassName::OptimizeFindZero => {
+				self.run_default_block_pass::<OptimizeFindZeroPass>(pass, progress);
+			}
+			PassName::OptimizeSetZero => {
+				self.run_default_peephole_pass::<OptimizeSetZeroPass>(pass, progress);
+			}
+			PassName::OptimizeScaleAndMoveVal => {
+				self.run_default_block_pass::<OptimizeScaleAndMoveValPass>(pass, progress);
+			}
+			PassName::OptimizeFetchAndScaleVal => {
+				self.run_default_peephole_pass::<OptimizeFetchAndScaleValPass>(pass, progress);
+			}
+			PassName::OptimizeScaleVal => {
+				self.run_default_peephole_pass::<OptimizeScaleValPass>(pass, progress);
+			}
+			PassName::OptimizeZeroedCellIncVal => {
+				self.run_default_peephole_pass::<OptimizeZeroedCellIncValPass>(pass, progress);
+			}
+			PassName::OptimizeScaleAndTakeVal => {
+				self.run_default_peephole_pass::<OptimizeScaleAndTakeValPass>(pass, progress);
+			}
+			PassName::OptimizeScaleAndSetVal => {
+				self.run_default_peephole_pass::<OptimizeScaleAndSetValPass>(pass, progress);
+			}
+			PassName::OptimizeSetScaleVal => {
+				self.run_default_peephole_pass::<OptimizeSetScaleValPass>(pass, progress);
+			}
+			PassName::OptimizeTakeVal => {
+				self.run_default_peephole_pass::<OptimizeTakeValPass>(pass, progress);
+			}
+			PassName::OptimizeTakeFetchVal => {
+				self.run_default_peephole_pass::<OptimizeTakeFetchValPass>(pass, progress);
+			}
+			PassName::OptimizeIfNz => {
+				self.run_default_block_pass::<OptimizeIfNzPass>(pass, progress);
+			}
+			PassName::OptimizeSubCell => {
+				self.run_default_dynamic_loop_pass::<OptimizeSubCellPass>(pass, progress);
+			}
+			PassName::OptimizeConstantSub => {
+				self.run_default_peephole_pass::<OptimizeConstantSubPass>(pass, progress);
+			}
+			PassName::OptimizeFetchVal => {
+				self.run_default_peephole_pass::<OptimizeFetchValPass>(pass, progress);
+			}
+			PassName::OptimizeSetUntilZero => {
+				self.run_default_dynamic_loop_pass::<OptimizeSetUntilZeroPass>(pass, progress);
+			}
+			PassName::OptimizeReplaceVal => {
+				self.run_default_peephole_pass::<OptimizeReplaceValPass>(pass, progress);
+			}
+			PassName::OptimizeFindCellByZero => {
+				self.run_default_peephole_pass::<OptimizeFindCellByZeroPass>(pass, progress);
+			}
+			PassName::OptimizeShiftVals => {
+				self.run_default_dynamic_loop_pass::<OptimizeShiftValsPass>(pass, progress);
+			}
+			PassName::OptimizeSuperInstr => {
+				self.run_default_peephole_pass::<OptimizeSuperInstrPass>(pass, progress);
+			}
+			PassName::OptimizeSetWriteInc => {
+				self.run_default_peephole_pass::<OptimizeSetWriteIncPass>(pass, progress);
+			}
+			PassName::OptimizeConstantShift => {
+				self.run_default_peephole_pass::<OptimizeConstantShiftPass>(pass, progress);
+			}
+			PassName::OptimizeKnownValues => {
+				self.run_default_peephole_pass::<OptimizeKnownValuesPass>(pass, progress);
+			}
+			PassName::OptimizeMoveVal => {
+				self.run_default_peephole_pass::<OptimizeMoveValPass>(pass, progress);
+			}
+			PassName::ReorderMoveChange => {
+				self.run_default_peephole_pass::<ReorderMoveChangePass>(pass, progress);
+			}
+			PassName::ReorderOffsetBetweenMoves => {
+				self.run_default_peephole_pass::<ReorderOffsetBetweenMovesPass>(pass, progress);
+			}
+			PassName::CombineMoveChange => {
+				self.run_default_peephole_pass::<CombineMoveChangePass>(pass, progress);
+			}
+			PassName::SortIncInstr => {
+				self.run_default_peephole_pass::<SortIncInstrPass>(pass, progress);
+			}
+			PassName::SortSetInstr => {
+				self.run_default_peephole_pass::<SortSetInstrPass>(pass, progress);
+			}
+			PassName::ReorderSetInc => {
+				self.run_default_peephole_pass::<ReorderSetIncPass>(pass, progress);
+			}
+			PassName::RemoveRedundantChangeValBasic => {
+				self.run_peephole_pass(
+					pass,
+					RemoveRedundantChangeValBasicPass::new(self.eof),
+					progress,
+				);
+			}
+			PassName::RemoveRedundantChangeValOffset => {
+				self.run_default_peephole_pass::<RemoveRedundantChangeValOffsetPass>(
+					pass, progress,
+				);
+			}
+			PassName::RemovePointlessInstr => {
+				self.run_default_peephole_pass::<RemovePointlessInstrPass>(pass, progress);
+			}
+			PassName::RemoveRedundantScaleValInstrBasic => {
+				self.run_default_peephole_pass::<RemoveRedundantScaleValInstrBasicPass>(
+					pass, progress,
+				);
+			}
+			PassName::RemoveRedundantShifts => {
+				self.run_default_peephole_pass::<RemoveRedundantShiftsPass>(pass, progress);
+			}
+			PassName::RemoveEmptyLoops => {
+				self.run_default_dynamic_loop_pass::<RemoveEmptyLoopsPass>(pass, progress);
+			}
+			PassName::RemoveUnreachableLoops => {
+				self.run_default_peephole_pass::<RemoveUnreachableLoopsPass>(pass, progress);
+			}
+			PassName::RemoveUnusedBoundaryInstr => {
+				let mut runner = PeepholeRunner(RemoveUnusedBoundaryInstrPass::default());
+				let consumed = self.fuel.consumed();
+
+				self.run_pass(pass, &mut runner, progress);
+
+				let applied =
+					usize::try_from(self.fuel.consumed() - consumed).unwrap_or(usize::MAX);
+
+				// Each write was just before the end when it was dropped.
+				for write in runner.0.into_dropped_writes(applied) {
+					self.dropped_writes.insert(0, write);
+				}
+			}
+			PassName::RemoveInfiniteLoops => {
+				self.run_default_dynamic_loop_pass::<RemoveInfiniteLoopsPass>(pass, progress);
+			}
+			PassName::UnrollConstantLoops => {
+				self.run_default_peephole_pass::<UnrollConstantLoopsPass>(pass, progress);
+			}
+			PassName::UnrollIncrementLoops => {
+				self.run_default_peephole_pass::<UnrollIncrementLoopsPass>(pass, progress);
+			}
+			PassName::UnrollScaleAnd => {
+				self.run_default_peephole_pass::<UnrollScaleAndPass>(pass, progress);
+			}
+		}
 	}
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum OptimizerError {
 	MetadataStore(MetadataStoreError),
+	Validation(ValidationError),
 }
 
 impl Display for OptimizerError {
 	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
 		match self {
 			Self::MetadataStore(_) => f.write_str("issue storing metadata")?,
+			Self::Validation(_) => f.write_str("optimized program doesn't match the original")?,
 		}
 
 		Ok(())
@@ -221,6 +548,7 @@ impl StdError for OptimizerError {
 	fn source(&self) -> Option<&(dyn StdError + 'static)> {
 		match self {
 			Self::MetadataStore(e) => Some(e),
+			Self::Validation(e) => Some(e),
 		}
 	}
 }
@@ -231,34 +559,225 @@ impl From<MetadataStoreError> for OptimizerError {
 	}
 }
 
+impl From<ValidationError> for OptimizerError {
+	fn from(value: ValidationError) -> Self {
+		Self::Validation(value)
+	}
+}
+
 #[allow(clippy::needless_for_each)]
-fn run_pass<P>(pass: &mut P, v: &mut Vec<Instruction>, progress: &mut bool)
-where
+fn run_pass<P>(
+	pass: &mut P,
+	v: &mut Vec<Instruction>,
+	mut source_map: Option<&mut SourceMap>,
+	values: &CellValues,
+	fuel: &mut Fuel,
+	stats: &mut PassStats,
+	progress: &mut bool,
+) where
 	P: Pass,
 {
-	*progress |= pass.run_pass(v);
-
-	if pass.should_run_on_dyn_loop() {
-		for i in v.iter_mut() {
-			if let Instruction::Block(BlockInstruction::DynamicLoop(i)) = i {
-				let mut v = i.to_vec();
+	*progress |= pass.run_pass(v, source_map.as_deref_mut(), values, fuel, stats);
+
+	let tracks_values = pass.uses_cell_values();
+	let mut values = if tracks_values {
+		values.clone()
+	} else {
+		CellValues::unknown(values.cell_width())
+	};
+
+	for (index, i) in v.iter_mut().enumerate() {
+		let (body, entry) = match i {
+			Instruction::Block(BlockInstruction::DynamicLoop(body))
+				if pass.should_run_on_dyn_loop() =>
+			{
+				let entry = if tracks_values {
+					values.loop_body_entry(body)
+				} else {
+					values.clone()
+				};
 
-				run_pass(pass, &mut v, progress);
+				(body, entry)
+			}
+			Instruction::Block(BlockInstruction::IfNz(body)) if pass.should_run_on_if() => {
+				(body, values.if_body_entry())
+			}
+			_ => {
+				if tracks_values {
+					values.apply(i);
+				}
 
-				*i = v.into_iter().collect();
+				continue;
 			}
+		};
+
+		let mut body_instrs = body.to_vec();
+
+		run_pass(
+			pass,
+			&mut body_instrs,
+			block_source_map(source_map.as_deref_mut(), index),
+			&entry,
+			fuel,
+			stats,
+			progress,
+		);
+
+		*body = body_instrs.into_iter().collect();
+
+		if tracks_values {
+			values.apply(i);
 		}
 	}
+}
 
-	if pass.should_run_on_if() {
-		for i in v {
-			if let Instruction::Block(BlockInstruction::IfNz(i)) = i {
-				let mut v = i.to_vec();
+fn block_source_map(source_map: Option<&mut SourceMap>, index: usize) -> Option<&mut SourceMap> {
+	source_map
+		.and_then(|source_map| source_map.get_mut(index))
+		.map(SourceMapEntry::block_mut)
+}
 
-				run_pass(pass, &mut v, progress);
+#[cfg(test)]
+mod tests {
+	use vmm_ir::{Instruction, Offset};
+	use vmm_program::Program;
 
-				*i = v.into_iter().collect();
-			}
+	use crate::{
+		Change, NoopStore, Optimizer, OptimizerError, PassName, PeepholePass, Validation,
+		ValidationError,
+	};
+
+	// Drops every write, which no pass should ever get away with.
+	#[derive(Debug)]
+	struct DropWritesPass;
+
+	impl PeepholePass for DropWritesPass {
+		const SIZE: usize = 1;
+
+		fn run_pass(&mut self, window: &[Instruction]) -> Option<Change> {
+			self.should_run(window).then(Change::remove)
 		}
+
+		fn should_run(&self, window: &[Instruction]) -> bool {
+			matches!(window, [Instruction::Write { .. }])
+		}
+	}
+
+	// Drops moves by four cells, which only changes nothing on a tape of four cells.
+	#[derive(Debug)]
+	struct DropWrappingMovesPass;
+
+	impl PeepholePass for DropWrappingMovesPass {
+		const SIZE: usize = 1;
+
+		fn run_pass(&mut self, window: &[Instruction]) -> Option<Change> {
+			self.should_run(window).then(Change::remove)
+		}
+
+		fn should_run(&self, window: &[Instruction]) -> bool {
+			matches!(window, [Instruction::MovePtr(Offset(4))])
+		}
+	}
+
+	// Drops increments two cells along, which nothing prints.
+	#[derive(Debug)]
+	struct DropUnprintedIncsPass;
+
+	impl PeepholePass for DropUnprintedIncsPass {
+		const SIZE: usize = 1;
+
+		fn run_pass(&mut self, window: &[Instruction]) -> Option<Change> {
+			self.should_run(window).then(Change::remove)
+		}
+
+		fn should_run(&self, window: &[Instruction]) -> bool {
+			matches!(
+				window,
+				[Instruction::IncVal {
+					offset: Offset(2),
+					..
+				}]
+			)
+		}
+	}
+
+	#[test]
+	fn reports_miscompiles() {
+		let original = [Instruction::inc_val(1), Instruction::write_once()]
+			.into_iter()
+			.collect::<Program>();
+
+		let mut optimizer = Optimizer::new(original.clone(), NoopStore::new())
+			.and_with_validation(Validation::new());
+
+		optimizer.run_peephole_pass(PassName::RemovePointlessInstr, DropWritesPass, &mut false);
+
+		assert!(matches!(
+			optimizer.validate(&original),
+			Err(OptimizerError::Validation(ValidationError::Output { .. }))
+		));
+	}
+
+	#[test]
+	fn validates_on_the_tape_being_optimized_for() {
+		let original = [
+			Instruction::inc_val(1),
+			Instruction::move_ptr(4),
+			Instruction::write_once(),
+		]
+		.into_iter()
+		.collect::<Program>();
+
+		let mut optimizer = Optimizer::new(original.clone(), NoopStore::new())
+			.and_with_tape_len(4)
+			.and_with_validation(Validation::new());
+
+		optimizer.run_peephole_pass(
+			PassName::RemoveRedundantShifts,
+			DropWrappingMovesPass,
+			&mut false,
+		);
+
+		assert!(optimizer.validate(&original).is_ok());
+	}
+	#[test]
+	fn compares_the_tape_around_dropped_writes() {
+		let original = [
+			Instruction::Boundary,
+			Instruction::inc_val(1),
+			Instruction::inc_val_at(3, 2),
+			Instruction::write_once(),
+			Instruction::set_val_at(2, 1),
+			Instruction::move_ptr(1),
+			Instruction::Boundary,
+		]
+		.into_iter()
+		.collect::<Program>();
+
+		let mut optimizer = Optimizer::new(original.clone(), NoopStore::new())
+			.and_with_validation(Validation::new());
+
+		let mut progress = true;
+
+		while progress {
+			progress = false;
+			optimizer.run_named_pass(PassName::RemoveUnusedBoundaryInstr, &mut progress);
+		}
+
+		assert_eq!(optimizer.program.len(), 5, "the set and move are dropped");
+		assert!(optimizer.validate(&original).is_ok());
+
+		optimizer.run_peephole_pass(
+			PassName::RemovePointlessInstr,
+			DropUnprintedIncsPass,
+			&mut false,
+		);
+
+		assert!(matches!(
+			optimizer.validate(&original),
+			Err(OptimizerError::Validation(ValidationError::Tape {
+				cell: 2
+			}))
+		));
 	}
 }
@@ -171,32 +171,3 @@ pub trait MetadataStore {
 #[repr(transparent)]
 #[serde(transparent)]
 struct Program(Vec<Instruction>);
-
-#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
-#[repr(transparent)]
-#[serde(transparent)]
-pub(super) struct RawProgram(String);
-
-impl Debug for RawProgram {
-	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-		Display::fmt(&self, f)
-	}
-}
-
-impl Display for RawProgram {
-	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-		f.write_str(&self.0)
-	}
-}
-
-impl FromIterator<Instruction> for RawProgram {
-	fn from_iter<T: IntoIterator<Item = Instruction>>(iter: T) -> Self {
-		Self(iter.into_iter().map(|i| i.to_string()).collect())
-	}
-}
-
-impl<'a> FromIterator<&'a Instruction> for RawProgram {
-	fn from_iter<T: IntoIterator<Item = &'a Instruction>>(iter: T) -> Self {
-		Self(iter.into_iter().map(ToString::to_string).collect())
-	}
-}
@@ -3,12 +3,28 @@ mod runners;
 use std::{fmt::Debug, ops::RangeInclusive};
 
 use vmm_ir::Instruction;
+use vmm_program::SourceMap;
 
 pub use self::runners::*;
-use super::Change;
+use super::{CellValues, Change, Fuel, PassStats};
 
 pub trait Pass: Debug {
-	fn run_pass(&mut self, program: &mut Vec<Instruction>) -> bool;
+	/// Runs over a single block, which starts with the given cell values, keeping the source map
+	/// for it in step when there is one and applying no more changes than the fuel allows and
+	/// recording what it did in `stats`.
+	fn run_pass(
+		&mut self,
+		program: &mut Vec<Instruction>,
+		source_map: Option<&mut SourceMap>,
+		values: &CellValues,
+		fuel: &mut Fuel,
+		stats: &mut PassStats,
+	) -> bool;
+
+	/// Whether the pass looks at [`CellValues`], which are only worked out for passes that do.
+	fn uses_cell_values(&self) -> bool {
+		false
+	}
 
 	fn should_run_on_dyn_loop(&self) -> bool {
 		true
@@ -24,11 +40,31 @@ pub trait PeepholePass {
 
 	fn run_pass(&mut self, window: &[Instruction]) -> Option<Change>;
 
+	/// Like [`Self::run_pass`], knowing what the cells hold before the window.
+	#[allow(unused)]
+	fn run_pass_with_values(
+		&mut self,
+		window: &[Instruction],
+		values: &CellValues,
+	) -> Option<Change> {
+		self.run_pass(window)
+	}
+
 	#[allow(unused)]
 	fn should_run(&self, window: &[Instruction]) -> bool {
 		true
 	}
 
+	#[allow(unused)]
+	fn should_run_with_values(&self, window: &[Instruction], values: &CellValues) -> bool {
+		self.should_run(window)
+	}
+
+	/// Whether the pass looks at [`CellValues`], which are only worked out for passes that do.
+	fn uses_cell_values(&self) -> bool {
+		false
+	}
+
 	fn should_run_on_dyn_loop(&self) -> bool {
 		true
 	}
@@ -43,11 +79,31 @@ pub trait RangePeepholePass {
 
 	fn run_pass(&mut self, window: &[Instruction]) -> Option<Change>;
 
+	/// Like [`Self::run_pass`], knowing what the cells hold before the window.
+	#[allow(unused)]
+	fn run_pass_with_values(
+		&mut self,
+		window: &[Instruction],
+		values: &CellValues,
+	) -> Option<Change> {
+		self.run_pass(window)
+	}
+
 	#[allow(unused)]
 	fn should_run(&self, window: &[Instruction]) -> bool {
 		true
 	}
 
+	#[allow(unused)]
+	fn should_run_with_values(&self, window: &[Instruction], values: &CellValues) -> bool {
+		self.should_run(window)
+	}
+
+	/// Whether the pass looks at [`CellValues`], which are only worked out for passes that do.
+	fn uses_cell_values(&self) -> bool {
+		false
+	}
+
 	fn should_run_on_dyn_loop(&self) -> bool {
 		true
 	}
@@ -4,9 +4,10 @@ use std::fmt::{Debug, Formatter, Result as FmtResult};
 
 use tracing::warn;
 use vmm_ir::Instruction;
+use vmm_program::SourceMap;
 
 pub use self::range::*;
-use crate::{Pass, PeepholePass};
+use crate::{AppliedChange, CellValues, Fuel, Pass, PassStats, PeepholePass};
 
 #[repr(transparent)]
 pub struct PeepholeRunner<P>(pub P);
@@ -28,24 +29,58 @@ where
 	P: Debug + PeepholePass,
 {
 	#[inline]
-	fn run_pass(&mut self, program: &mut Vec<Instruction>) -> bool {
+	fn run_pass(
+		&mut self,
+		program: &mut Vec<Instruction>,
+		mut source_map: Option<&mut SourceMap>,
+		values: &CellValues,
+		fuel: &mut Fuel,
+		stats: &mut PassStats,
+	) -> bool {
 		let mut i = 0;
 		let mut progress = false;
 
+		let tracks_values = self.0.uses_cell_values();
+		// What the cells hold before the window, kept up to date as the window moves.
+		let mut values = if tracks_values {
+			values.clone()
+		} else {
+			CellValues::unknown(values.cell_width())
+		};
+
 		while program.len() >= P::SIZE && i < program.len() - (P::SIZE - 1) {
 			let window = program[i..(P::SIZE + i)].to_vec();
 
 			assert_eq!(window.len(), P::SIZE);
 
-			if !self.0.should_run(&window) {
+			let should_run = self.0.should_run_with_values(&window, &values);
+
+			stats.record_window(should_run);
+
+			if !should_run {
+				if tracks_values {
+					values.apply(&program[i]);
+				}
+
 				i += 1;
 				continue;
 			}
 
-			let change = self.0.run_pass(&window);
+			let change = self
+				.0
+				.run_pass_with_values(&window, &values)
+				.filter(|change| {
+					fuel.consume(|| {
+						AppliedChange::new(stats.pass(), &window, change, source_map.as_deref(), i)
+					})
+				});
+
+			if let Some(change) = &change {
+				stats.record_change(change);
+			}
 
 			let (changed, removed) = change
-				.map(|c| c.apply(program, i, P::SIZE))
+				.map(|c| c.apply(program, source_map.as_deref_mut(), i, P::SIZE))
 				.unwrap_or_default();
 
 			i -= removed;
@@ -53,21 +88,29 @@ where
 			if changed {
 				progress = true;
 			} else {
-				i += 1;
-
-				if self.0.should_run(&window) {
+				if !fuel.is_exhausted() && self.0.should_run_with_values(&window, &values) {
 					warn!(
 						"pass {:?}::should_run was true but didn't make changes",
 						self.0
 					);
 					tracing::debug!("{window:?}");
 				}
+
+				if tracks_values {
+					values.apply(&program[i]);
+				}
+
+				i += 1;
 			}
 		}
 
 		progress
 	}
 
+	fn uses_cell_values(&self) -> bool {
+		self.0.uses_cell_values()
+	}
+
 	fn should_run_on_dyn_loop(&self) -> bool {
 		self.0.should_run_on_dyn_loop()
 	}
@@ -2,8 +2,9 @@ use std::fmt::{Debug, Formatter, Result as FmtResult};
 
 use tracing::warn;
 use vmm_ir::Instruction;
+use vmm_program::SourceMap;
 
-use crate::{Pass, RangePeepholePass};
+use crate::{AppliedChange, CellValues, Fuel, Pass, PassStats, RangePeepholePass};
 
 #[repr(transparent)]
 pub struct RangePeepholeRunner<P>(pub P);
@@ -24,31 +25,67 @@ impl<P> Pass for RangePeepholeRunner<P>
 where
 	P: Debug + RangePeepholePass,
 {
-	fn run_pass(&mut self, program: &mut Vec<Instruction>) -> bool {
+	fn run_pass(
+		&mut self,
+		program: &mut Vec<Instruction>,
+		mut source_map: Option<&mut SourceMap>,
+		values: &CellValues,
+		fuel: &mut Fuel,
+		stats: &mut PassStats,
+	) -> bool {
 		let mut i = 0;
 		let mut progress = false;
 
+		let tracks_values = self.0.uses_cell_values();
+		// What the cells hold before the window, kept up to date as the window moves.
+		let mut values = if tracks_values {
+			values.clone()
+		} else {
+			CellValues::unknown(values.cell_width())
+		};
+
 		while program.len() >= *P::RANGE.end() && i < program.len() - (P::RANGE.end() - 1) {
 			for limit in P::RANGE {
 				let window = program[i..(limit + i)].to_vec();
 
 				assert!(P::RANGE.contains(&window.len()));
 
-				if !self.0.should_run(&window) {
+				let should_run = self.0.should_run_with_values(&window, &values);
+
+				stats.record_window(should_run);
+
+				if !should_run {
 					continue;
 				}
 
-				let change = self.0.run_pass(&window);
+				let change = self
+					.0
+					.run_pass_with_values(&window, &values)
+					.filter(|change| {
+						fuel.consume(|| {
+							AppliedChange::new(
+								stats.pass(),
+								&window,
+								change,
+								source_map.as_deref(),
+								i,
+							)
+						})
+					});
+
+				if let Some(change) = &change {
+					stats.record_change(change);
+				}
 
 				let (changed, removed) = change
-					.map(|c| c.apply(program, i, limit))
+					.map(|c| c.apply(program, source_map.as_deref_mut(), i, limit))
 					.unwrap_or_default();
 
 				i -= removed;
 
 				if changed {
 					progress = true;
-				} else if self.0.should_run(&window) {
+				} else if !fuel.is_exhausted() && self.0.should_run_with_values(&window, &values) {
 					warn!(
 						"pass {:?}::should_run was true but didn't make changes",
 						self.0
@@ -57,9 +94,17 @@ where
 				}
 			}
 
+			if let Some(instr) = program.get(i).filter(|_| tracks_values) {
+				values.apply(instr);
+			}
+
 			i += 1;
 		}
 
 		progress
 	}
+
+	fn uses_cell_values(&self) -> bool {
+		self.0.uses_cell_values()
+	}
 }
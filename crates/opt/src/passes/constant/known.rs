@@ -0,0 +1,94 @@
+use vmm_ir::{Instruction, ScaleAnd, SuperInstruction};
+
+use crate::{CellValues, Change, PeepholePass};
+
+/// Folds instructions that read a cell with a known value, as long as that doesn't grow the program.
+#[derive(Debug, Default)]
+pub struct OptimizeKnownValuesPass;
+
+impl PeepholePass for OptimizeKnownValuesPass {
+	const SIZE: usize = 1;
+
+	#[inline]
+	fn run_pass(&mut self, window: &[Instruction]) -> Option<Change> {
+		self.run_pass_with_values(window, &CellValues::default())
+	}
+
+	fn run_pass_with_values(
+		&mut self,
+		window: &[Instruction],
+		values: &CellValues,
+	) -> Option<Change> {
+		match window {
+			[Instruction::ScaleVal { factor }] => Some(Change::replace(Instruction::set_val(
+				values
+					.cell_width()
+					.wrap_unsigned(values.get(0)?.wrapping_mul(*factor)),
+			))),
+			[
+				Instruction::MoveVal(..)
+				| Instruction::SubCell { .. }
+				| Instruction::Super(SuperInstruction::ScaleAnd {
+					action: ScaleAnd::Move,
+					..
+				}),
+			] if values.is_zero(0) => Some(Change::remove()),
+			[
+				Instruction::TakeVal(offset)
+				| Instruction::Super(SuperInstruction::ScaleAnd {
+					action: ScaleAnd::Take,
+					offset,
+					..
+				}),
+			] if values.is_zero(0) => Some(Change::replace(Instruction::move_ptr(*offset))),
+			[
+				Instruction::Super(SuperInstruction::ScaleAnd {
+					action: ScaleAnd::Set(value),
+					..
+				}),
+			] if values.is_zero(0) => Some(Change::replace(Instruction::set_val(value.get()))),
+			[
+				Instruction::FetchVal(offset)
+				| Instruction::Super(SuperInstruction::ScaleAnd {
+					action: ScaleAnd::Fetch,
+					offset,
+					..
+				}),
+			] if values.is_zero(*offset) => Some(Change::remove()),
+			[Instruction::ReplaceVal(offset)] if values.is_zero(*offset) => {
+				Some(Change::replace(Instruction::clear_val()))
+			}
+			_ => None,
+		}
+	}
+
+	#[inline]
+	fn should_run_with_values(&self, window: &[Instruction], values: &CellValues) -> bool {
+		match window {
+			[Instruction::ScaleVal { .. }] => values.get(0).is_some(),
+			[
+				Instruction::MoveVal(..)
+				| Instruction::SubCell { .. }
+				| Instruction::TakeVal(..)
+				| Instruction::Super(SuperInstruction::ScaleAnd {
+					action: ScaleAnd::Move | ScaleAnd::Take | ScaleAnd::Set(..),
+					..
+				}),
+			] => values.is_zero(0),
+			[
+				Instruction::FetchVal(offset)
+				| Instruction::ReplaceVal(offset)
+				| Instruction::Super(SuperInstruction::ScaleAnd {
+					action: ScaleAnd::Fetch,
+					offset,
+					..
+				}),
+			] => values.is_zero(*offset),
+			_ => false,
+		}
+	}
+
+	fn uses_cell_values(&self) -> bool {
+		true
+	}
+}
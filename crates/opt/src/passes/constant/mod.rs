@@ -1,4 +1,5 @@
+mod known;
 mod shift;
 mod sub;
 
-pub use self::{shift::*, sub::*};
+pub use self::{known::*, shift::*, sub::*};
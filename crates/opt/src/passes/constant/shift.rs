@@ -1,4 +1,4 @@
-use vmm_ir::{Instruction, Offset};
+use vmm_ir::{Instruction, Offset, ScaleAnd, SuperInstruction};
 use vmm_utils::GetOrZero as _;
 
 use crate::{Change, PeepholePass};
@@ -20,7 +20,7 @@ impl PeepholePass for OptimizeConstantShiftPass {
 			] => Some(Change::swap([
 				Instruction::clear_val(),
 				Instruction::move_ptr(offset),
-				Instruction::inc_val(value.get_or_zero() as i8),
+				Instruction::inc_val(value.get_or_zero() as i32),
 			])),
 			[
 				Instruction::SetVal {
@@ -30,8 +30,33 @@ impl PeepholePass for OptimizeConstantShiftPass {
 				Instruction::MoveVal(offset),
 			] => Some(Change::swap([
 				Instruction::clear_val(),
-				Instruction::inc_val_at(value.get_or_zero() as i8, offset),
+				Instruction::inc_val_at(value.get_or_zero() as i32, offset),
 			])),
+			[
+				Instruction::SetVal {
+					value,
+					offset: Offset(0),
+				},
+				Instruction::Super(SuperInstruction::ScaleAnd {
+					action,
+					offset,
+					factor,
+				}),
+			] => {
+				let value = value.get_or_zero().wrapping_mul(*factor) as i32;
+
+				Some(Change::swap(match action {
+					ScaleAnd::Move => [
+						Instruction::clear_val(),
+						Instruction::inc_val_at(value, offset),
+					],
+					ScaleAnd::Set(set) => [
+						Instruction::set_val(set.get()),
+						Instruction::inc_val_at(value, offset),
+					],
+					_ => return None,
+				}))
+			}
 			_ => None,
 		}
 	}
@@ -44,8 +69,54 @@ impl PeepholePass for OptimizeConstantShiftPass {
 					offset: Offset(0),
 					..
 				},
-				Instruction::MoveVal(..) | Instruction::TakeVal(..)
+				Instruction::MoveVal(..)
+					| Instruction::TakeVal(..)
+					| Instruction::Super(SuperInstruction::ScaleAnd {
+						action: ScaleAnd::Move | ScaleAnd::Set(..),
+						..
+					})
 			]
 		)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use vmm_ir::{Instruction, SuperInstruction};
+
+	use super::OptimizeConstantShiftPass;
+	use crate::{Change, PeepholePass as _};
+
+	#[test]
+	fn folds_scaled_moves_of_constants() {
+		let mut pass = OptimizeConstantShiftPass;
+
+		let Some(Change::Swap(instrs)) = pass.run_pass(&[
+			Instruction::set_val(3),
+			Instruction::Super(SuperInstruction::scale_and_move_val(2, 1)),
+		]) else {
+			panic!("move should be folded");
+		};
+
+		assert_eq!(
+			instrs,
+			[Instruction::clear_val(), Instruction::inc_val_at(6, 1)]
+		);
+
+		let Some(Change::Swap(instrs)) = pass.run_pass(&[
+			Instruction::set_val(3),
+			Instruction::Super(SuperInstruction::scale_and_set_val(
+				2,
+				1,
+				4.try_into().unwrap(),
+			)),
+		]) else {
+			panic!("set should be folded");
+		};
+
+		assert_eq!(
+			instrs,
+			[Instruction::set_val(4), Instruction::inc_val_at(6, 1)]
+		);
+	}
+}
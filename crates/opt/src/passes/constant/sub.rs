@@ -16,8 +16,8 @@ impl PeepholePass for OptimizeConstantSubPass {
 					offset: Offset(0),
 				},
 				Instruction::SubCell { offset },
-			] if i8::try_from(value.get()).is_ok() => {
-				let value = i8::try_from(value.get()).ok()?;
+			] if i32::try_from(value.get()).is_ok() => {
+				let value = i32::try_from(value.get()).ok()?;
 				Some(Change::swap([
 					Instruction::clear_val(),
 					Instruction::inc_val_at(-value, offset),
@@ -37,7 +37,7 @@ impl PeepholePass for OptimizeConstantSubPass {
 				},
 				Instruction::SubCell { .. }
 			]
-			if i8::try_from(i.get()).is_ok()
+			if i32::try_from(i.get()).is_ok()
 		)
 	}
 }
@@ -10,6 +10,7 @@ mod find_cell_by_zero;
 mod find_zero;
 mod if_nz;
 mod move_val;
+mod normalize_values;
 mod remove_dead_code;
 mod reorder_instr;
 mod replace_val;
@@ -34,9 +35,10 @@ mod zeroed_cell_inc;
 pub use self::{
 	clear_cell::*, clear_loop::*, collapse_relative_instr::*, collapse_stacked_instr::*,
 	combine_move_change::*, constant::*, fetch_and_scale_val::*, fetch_val::*,
-	find_cell_by_zero::*, find_zero::*, if_nz::*, move_val::*, remove_dead_code::*,
-	reorder_instr::*, replace_val::*, scale_and_move_val::*, scale_and_set_val::*,
-	scale_and_take_val::*, scale_val::*, set_scale::*, set_until_zero::*, set_write_change::*,
-	set_zero::*, shift_vals::*, sub_cell::*, sup::*, take_to_fetch::*, take_val::*,
-	unroll_constant_loops::*, unroll_increment_loops::*, unroll_super_scale::*, zeroed_cell_inc::*,
+	find_cell_by_zero::*, find_zero::*, if_nz::*, move_val::*, normalize_values::*,
+	remove_dead_code::*, reorder_instr::*, replace_val::*, scale_and_move_val::*,
+	scale_and_set_val::*, scale_and_take_val::*, scale_val::*, set_scale::*, set_until_zero::*,
+	set_write_change::*, set_zero::*, shift_vals::*, sub_cell::*, sup::*, take_to_fetch::*,
+	take_val::*, unroll_constant_loops::*, unroll_increment_loops::*, unroll_super_scale::*,
+	zeroed_cell_inc::*,
 };
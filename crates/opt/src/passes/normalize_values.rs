@@ -0,0 +1,148 @@
+use std::num::NonZeroU32;
+
+use vmm_ir::{CellWidth, Instruction, ScaleAnd, SuperInstruction};
+use vmm_program::SourceMap;
+use vmm_utils::GetOrZero as _;
+
+use crate::{CellValues, Fuel, Pass, PassStats};
+
+/// Truncates every immediate to the width of the cells it will be applied to, so that passes
+/// comparing immediates see the value that will actually be stored.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NormalizeValuesPass {
+	width: CellWidth,
+}
+
+impl NormalizeValuesPass {
+	#[must_use]
+	pub const fn new(width: CellWidth) -> Self {
+		Self { width }
+	}
+
+	fn normalize(self, instr: &Instruction) -> Option<Instruction> {
+		let width = self.width;
+
+		let normalized = match *instr {
+			Instruction::IncVal { value, offset } => Instruction::IncVal {
+				value: width.wrap_signed(value),
+				offset,
+			},
+			Instruction::SetVal { value, offset } => Instruction::SetVal {
+				value: NonZeroU32::new(width.wrap_unsigned(value.get_or_zero())),
+				offset,
+			},
+			Instruction::ScaleVal { factor } => Instruction::ScaleVal {
+				factor: width.wrap_unsigned(factor),
+			},
+			Instruction::Super(SuperInstruction::ScaleAnd {
+				action,
+				offset,
+				factor,
+			}) => {
+				let action = match action {
+					ScaleAnd::Set(value) => NonZeroU32::new(width.wrap_unsigned(value.get()))
+						.map_or(ScaleAnd::Move, ScaleAnd::Set),
+					action => action,
+				};
+
+				SuperInstruction::scale_and(width.wrap_unsigned(factor), offset, action).into()
+			}
+			Instruction::Super(SuperInstruction::FindAndSetZero { offset, value }) => {
+				match NonZeroU32::new(width.wrap_unsigned(value.get())) {
+					Some(value) => SuperInstruction::find_and_set_zero(value, offset).into(),
+					None => Instruction::FindZero(offset),
+				}
+			}
+			Instruction::Super(SuperInstruction::SetUntilZero { value, offset }) => {
+				SuperInstruction::set_until_zero(width.wrap_unsigned(value.get_or_zero()), offset)
+					.into()
+			}
+			_ => return None,
+		};
+
+		(normalized != *instr).then_some(normalized)
+	}
+}
+
+impl Pass for NormalizeValuesPass {
+	// Instructions are only ever rewritten in place, so the source map is left as is. Nothing here
+	// is a `Change` either, truncating values never alters what a program does so it costs no fuel.
+	fn run_pass(
+		&mut self,
+		program: &mut Vec<Instruction>,
+		_: Option<&mut SourceMap>,
+		_: &CellValues,
+		_: &mut Fuel,
+		_: &mut PassStats,
+	) -> bool {
+		let mut progress = false;
+
+		for instr in program {
+			if let Some(normalized) = self.normalize(instr) {
+				*instr = normalized;
+				progress = true;
+			}
+		}
+
+		progress
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use vmm_ir::{CellWidth, Instruction, SuperInstruction};
+
+	use super::NormalizeValuesPass;
+	use crate::{CellValues, Fuel, Pass as _, PassStats};
+
+	#[test]
+	fn truncates_to_cell_width() {
+		let mut program = vec![
+			Instruction::inc_val(300),
+			Instruction::set_val(256),
+			Instruction::Super(SuperInstruction::scale_and_set_val(
+				3,
+				1,
+				512.try_into().unwrap(),
+			)),
+		];
+
+		assert!(NormalizeValuesPass::new(CellWidth::U8).run_pass(
+			&mut program,
+			None,
+			&CellValues::default(),
+			&mut Fuel::unlimited(),
+			&mut PassStats::default()
+		));
+
+		assert_eq!(
+			program,
+			[
+				Instruction::inc_val(44),
+				Instruction::clear_val(),
+				Instruction::Super(SuperInstruction::scale_and_move_val(3, 1)),
+			]
+		);
+
+		assert!(!NormalizeValuesPass::new(CellWidth::U8).run_pass(
+			&mut program,
+			None,
+			&CellValues::default(),
+			&mut Fuel::unlimited(),
+			&mut PassStats::default()
+		));
+	}
+
+	#[test]
+	fn wider_cells_keep_values() {
+		let mut program = vec![Instruction::inc_val(300), Instruction::set_val(256)];
+
+		assert!(!NormalizeValuesPass::new(CellWidth::U16).run_pass(
+			&mut program,
+			None,
+			&CellValues::default(),
+			&mut Fuel::unlimited(),
+			&mut PassStats::default()
+		));
+	}
+}
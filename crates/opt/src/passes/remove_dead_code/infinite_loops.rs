@@ -17,16 +17,34 @@ impl LoopPass for RemoveInfiniteLoopsPass {
 
 	#[inline]
 	fn should_run(&self, loop_values: &[Instruction]) -> bool {
+		// A loop ending with a read stops at the end of input, only a cell set to something other
+		// than zero keeps it going forever.
 		matches!(
 			loop_values,
 			[
 				..,
-				Instruction::Read
-					| Instruction::SetVal {
-						value: Some(..),
-						offset: Offset(0)
-					}
-			] | [Instruction::Read, ..]
+				Instruction::SetVal {
+					value: Some(..),
+					offset: Offset(0)
+				}
+			]
 		) && !loop_values.might_move_ptr()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use vmm_ir::Instruction;
+
+	use super::RemoveInfiniteLoopsPass;
+	use crate::LoopPass as _;
+
+	#[test]
+	fn loops_that_read_end_with_input() {
+		let pass = RemoveInfiniteLoopsPass;
+
+		assert!(!pass.should_run(&[Instruction::write_once(), Instruction::read()]));
+		assert!(!pass.should_run(&[Instruction::read(), Instruction::write_once()]));
+		assert!(pass.should_run(&[Instruction::write_once(), Instruction::set_val(1)]));
+	}
+}
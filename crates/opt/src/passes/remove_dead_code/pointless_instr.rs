@@ -1,6 +1,7 @@
 use vmm_ir::{Instruction, Offset};
+use vmm_utils::GetOrZero as _;
 
-use crate::{Change, PeepholePass};
+use crate::{CellValues, Change, PeepholePass};
 
 #[derive(Debug, Default)]
 pub struct RemovePointlessInstrPass;
@@ -23,4 +24,20 @@ impl PeepholePass for RemovePointlessInstrPass {
 				| Instruction::MoveVal(Offset(0))]
 		)
 	}
+
+	// Sets of cells that already hold the value are dead too.
+	#[inline]
+	fn should_run_with_values(&self, window: &[Instruction], values: &CellValues) -> bool {
+		self.should_run(window)
+			|| matches!(
+				window,
+				[Instruction::SetVal { value, offset }]
+					if values.get(*offset)
+						== Some(values.cell_width().wrap_unsigned(value.get_or_zero()))
+			)
+	}
+
+	fn uses_cell_values(&self) -> bool {
+		true
+	}
 }
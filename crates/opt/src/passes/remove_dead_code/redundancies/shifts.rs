@@ -1,7 +1,9 @@
 use vmm_ir::Instruction;
 
-use crate::{Change, PeepholePass};
+use crate::{CellValues, Change, PeepholePass};
 
+/// Moves a value straight to where a take and a move would leave it, which is only the same when
+/// the take lands on an empty cell.
 #[derive(Debug, Default)]
 pub struct RemoveRedundantShiftsPass;
 
@@ -9,22 +11,58 @@ impl PeepholePass for RemoveRedundantShiftsPass {
 	const SIZE: usize = 2;
 
 	fn run_pass(&mut self, window: &[Instruction]) -> Option<Change> {
+		self.run_pass_with_values(window, &CellValues::default())
+	}
+
+	fn run_pass_with_values(
+		&mut self,
+		window: &[Instruction],
+		values: &CellValues,
+	) -> Option<Change> {
 		match window {
-			[Instruction::TakeVal(x), Instruction::MoveVal(y)] => Some(Change::swap([
-				Instruction::move_val(x + y),
-				Instruction::move_ptr(x),
-			])),
+			[Instruction::TakeVal(x), Instruction::MoveVal(y)] if values.is_zero(*x) => {
+				Some(Change::swap([
+					Instruction::move_val(x + y),
+					Instruction::move_ptr(x),
+				]))
+			}
 			_ => None,
 		}
 	}
 
-	fn should_run(&self, window: &[Instruction]) -> bool {
+	fn should_run_with_values(&self, window: &[Instruction], values: &CellValues) -> bool {
 		matches!(
 			window,
-			[
-				Instruction::TakeVal(..) | Instruction::MoveVal(..),
-				Instruction::MoveVal(..)
-			]
+			[Instruction::TakeVal(x), Instruction::MoveVal(..)] if values.is_zero(*x)
 		)
 	}
+
+	fn uses_cell_values(&self) -> bool {
+		true
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use vmm_ir::{CellWidth, Instruction};
+
+	use super::RemoveRedundantShiftsPass;
+	use crate::{CellValues, Change, PeepholePass as _};
+
+	#[test]
+	fn only_shifts_onto_empty_cells() {
+		let window = [Instruction::take_val(1), Instruction::move_val(-1)];
+		let mut pass = RemoveRedundantShiftsPass;
+
+		assert!(pass.run_pass(&window).is_none());
+		assert!(!pass.should_run_with_values(&window, &CellValues::unknown(CellWidth::U8)));
+
+		let Some(Change::Swap(instrs)) =
+			pass.run_pass_with_values(&window, &CellValues::zeroed(CellWidth::U8))
+		else {
+			panic!("a take onto an empty cell should be shifted");
+		};
+
+		assert_eq!(instrs, [Instruction::move_val(0), Instruction::move_ptr(1)]);
+	}
 }
@@ -1,17 +1,35 @@
-use vmm_ir::{BlockInstruction, Instruction, Offset, ScaleAnd, SuperInstruction};
+use vmm_ir::{BlockInstruction, EofPolicy, Instruction, Offset, ScaleAnd, SuperInstruction};
 use vmm_num::ops::WrappingAdd;
 use vmm_utils::GetOrZero as _;
 
 use crate::{Change, PeepholePass};
 
 #[derive(Debug, Default)]
-pub struct RemoveRedundantChangeValBasicPass;
+pub struct RemoveRedundantChangeValBasicPass {
+	eof: EofPolicy,
+}
+
+impl RemoveRedundantChangeValBasicPass {
+	#[must_use]
+	pub const fn new(eof: EofPolicy) -> Self {
+		Self { eof }
+	}
+
+	// A read only makes earlier changes redundant if it always writes to the cell.
+	const fn keeps_read_input(&self, window: &[Instruction]) -> bool {
+		matches!(window, [_, Instruction::Read]) && !self.eof.overwrites_cell()
+	}
+}
 
 impl PeepholePass for RemoveRedundantChangeValBasicPass {
 	const SIZE: usize = 2;
 
 	#[inline]
 	fn run_pass(&mut self, window: &[Instruction]) -> Option<Change> {
+		if self.keeps_read_input(window) {
+			return None;
+		}
+
 		match window {
 			[
 				Instruction::SetVal {
@@ -88,59 +106,61 @@ impl PeepholePass for RemoveRedundantChangeValBasicPass {
 
 	#[inline]
 	fn should_run(&self, window: &[Instruction]) -> bool {
-		matches!(
-			window,
-			[
-				Instruction::SetVal {
-					offset: Offset(0),
-					..
-				},
-				Instruction::IncVal {
-					offset: Offset(0),
-					..
-				} | Instruction::Read
-			] | [
-				Instruction::Block(BlockInstruction::DynamicLoop(..) | BlockInstruction::IfNz(..))
-					| Instruction::Super(
+		!self.keeps_read_input(window)
+			&& matches!(
+				window,
+				[
+					Instruction::SetVal {
+						offset: Offset(0),
+						..
+					},
+					Instruction::IncVal {
+						offset: Offset(0),
+						..
+					} | Instruction::Read
+				] | [
+					Instruction::Block(
+						BlockInstruction::DynamicLoop(..) | BlockInstruction::IfNz(..)
+					) | Instruction::Super(
 						SuperInstruction::ScaleAnd {
 							action: ScaleAnd::Move,
 							..
 						} | SuperInstruction::SetUntilZero { .. }
 					) | Instruction::SubCell { .. }
-					| Instruction::MoveVal(..),
-				Instruction::SetVal {
-					offset: Offset(0),
-					value: None
-				}
-			] | [
-				Instruction::Super(SuperInstruction::ScaleAnd {
-					action: ScaleAnd::Fetch,
-					..
-				}) | Instruction::IncVal {
-					offset: Offset(0),
-					..
-				},
-				Instruction::Read
-			] | [
-				Instruction::IncVal {
-					offset: Offset(0),
-					..
-				} | Instruction::TakeVal(..)
-					| Instruction::Super(SuperInstruction::ScaleAnd {
-						action: ScaleAnd::Take,
+						| Instruction::MoveVal(..),
+					Instruction::SetVal {
+						offset: Offset(0),
+						value: None
+					}
+				] | [
+					Instruction::Super(SuperInstruction::ScaleAnd {
+						action: ScaleAnd::Fetch,
 						..
-					}) | Instruction::ScaleVal { .. },
-				Instruction::SetVal {
-					offset: Offset(0),
-					..
-				}
-			] | [
-				Instruction::SetVal {
-					value: None,
-					offset: Offset(0)
-				},
-				Instruction::SubCell { .. } | Instruction::ScaleVal { .. }
-			]
-		)
+					}) | Instruction::IncVal {
+						offset: Offset(0),
+						..
+					},
+					Instruction::Read
+				] | [
+					Instruction::IncVal {
+						offset: Offset(0),
+						..
+					} | Instruction::TakeVal(..)
+						| Instruction::Super(SuperInstruction::ScaleAnd {
+							action: ScaleAnd::Take,
+							..
+						}) | Instruction::ScaleVal { .. },
+					Instruction::SetVal {
+						offset: Offset(0),
+						..
+					}
+				] | [
+					Instruction::SetVal {
+						value: None,
+						offset: Offset(0)
+					},
+					Instruction::SubCell { .. } | Instruction::ScaleVal { .. }
+				]
+			)
 	}
 }
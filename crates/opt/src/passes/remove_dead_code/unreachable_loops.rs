@@ -1,23 +1,38 @@
-use vmm_ir::{Instruction, IsZeroingCell as _, SuperInstruction};
+use vmm_ir::{Instruction, SuperInstruction};
 
-use crate::{Change, PeepholePass};
+use crate::{CellValues, Change, PeepholePass};
 
+/// Removes loops that are never entered because the current cell is known to be zero.
 #[derive(Debug, Default)]
 pub struct RemoveUnreachableLoopsPass;
 
 impl PeepholePass for RemoveUnreachableLoopsPass {
-	const SIZE: usize = 2;
+	const SIZE: usize = 1;
 
 	#[inline]
 	fn run_pass(&mut self, window: &[Instruction]) -> Option<Change> {
-		match window {
-			[i, Instruction::Block(..)] if i.is_zeroing_cell() => Some(Change::remove_offset(1)),
-			_ => None,
-		}
+		self.run_pass_with_values(window, &CellValues::default())
 	}
 
 	#[inline]
-	fn should_run(&self, window: &[Instruction]) -> bool {
-		matches!(window, [i, Instruction::Block(..) | Instruction::Super(SuperInstruction::ShiftVals { .. })] if i.is_zeroing_cell())
+	fn run_pass_with_values(
+		&mut self,
+		window: &[Instruction],
+		values: &CellValues,
+	) -> Option<Change> {
+		self.should_run_with_values(window, values)
+			.then(Change::remove)
+	}
+
+	#[inline]
+	fn should_run_with_values(&self, window: &[Instruction], values: &CellValues) -> bool {
+		matches!(
+			window,
+			[Instruction::Block(..) | Instruction::Super(SuperInstruction::ShiftVals { .. })]
+		) && values.is_zero(0)
+	}
+
+	fn uses_cell_values(&self) -> bool {
+		true
 	}
 }
@@ -2,15 +2,29 @@ use vmm_ir::{BlockInstruction, Instruction, Offset};
 
 use crate::{Change, PeepholePass};
 
+/// Drops instructions at either end of the program that can't change what it does.
+///
+/// Writes just before the end are dropped too, which does change the tape the program leaves
+/// behind, so the pass keeps the ones it dropped.
 #[derive(Debug, Default)]
-pub struct RemoveUnusedBoundaryInstrPass;
+pub struct RemoveUnusedBoundaryInstrPass {
+	changes: Vec<Option<Instruction>>,
+}
+
+impl RemoveUnusedBoundaryInstrPass {
+	/// The writes dropped by the first `applied` changes, which are the ones that weren't turned
+	/// down for lack of fuel, latest first.
+	pub fn into_dropped_writes(self, applied: usize) -> impl Iterator<Item = Instruction> {
+		self.changes.into_iter().take(applied).flatten()
+	}
+}
 
 impl PeepholePass for RemoveUnusedBoundaryInstrPass {
 	const SIZE: usize = 2;
 
 	#[inline]
 	fn run_pass(&mut self, window: &[Instruction]) -> Option<Change> {
-		match window {
+		let change = match window {
 			[
 				Instruction::Boundary,
 				Instruction::Block(BlockInstruction::DynamicLoop(..))
@@ -25,14 +39,21 @@ impl PeepholePass for RemoveUnusedBoundaryInstrPass {
 				},
 			] => Some(Change::swap([
 				Instruction::Boundary,
-				Instruction::set_val_at(*value as u8, offset),
+				Instruction::set_val_at(*value as u32, offset),
 			])),
 			[
 				Instruction::MovePtr(..) | Instruction::SetVal { .. },
 				Instruction::Boundary,
 			] => Some(Change::remove_offset(0)),
 			_ => None,
-		}
+		}?;
+
+		self.changes.push(match window {
+			[write, Instruction::Boundary] => Some(write.clone()),
+			_ => None,
+		});
+
+		Some(change)
 	}
 
 	#[inline]
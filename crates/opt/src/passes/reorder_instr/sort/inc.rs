@@ -23,11 +23,11 @@ impl PeepholePass for SortIncInstrPass {
 	}
 }
 
-fn sorter_key(instr: &Instruction) -> (Offset, Option<i8>) {
+fn sorter_key(instr: &Instruction) -> (Offset, Option<i32>) {
 	(instr.offset().get_or_zero(), get_inc_value(instr))
 }
 
-const fn get_inc_value(i: &Instruction) -> Option<i8> {
+const fn get_inc_value(i: &Instruction) -> Option<i32> {
 	match i {
 		Instruction::IncVal { value, .. } => Some(*value),
 		_ => None,
@@ -1,5 +1,3 @@
-use std::num::NonZeroU8;
-
 use vmm_ir::{Instruction, Offset};
 use vmm_iter::IteratorExt as _;
 use vmm_utils::GetOrZero as _;
@@ -25,13 +23,33 @@ impl PeepholePass for SortSetInstrPass {
 	}
 }
 
-fn sorter_key(a: &Instruction) -> (Offset, Option<NonZeroU8>) {
-	(a.offset().get_or_zero(), get_set_value(a))
+// Only the offset, as the last of two sets to the same cell has to stay last.
+fn sorter_key(a: &Instruction) -> Offset {
+	a.offset().get_or_zero()
 }
 
-const fn get_set_value(i: &Instruction) -> Option<NonZeroU8> {
-	match i {
-		Instruction::SetVal { value, .. } => *value,
-		_ => None,
+#[cfg(test)]
+mod tests {
+	use vmm_ir::Instruction;
+
+	use super::SortSetInstrPass;
+	use crate::{Change, PeepholePass as _};
+
+	#[test]
+	fn keeps_sets_to_the_same_cell_in_order() {
+		let mut pass = SortSetInstrPass;
+
+		assert!(!pass.should_run(&[Instruction::set_val(5), Instruction::set_val(3)]));
+
+		let Some(Change::Swap(instrs)) =
+			pass.run_pass(&[Instruction::set_val_at(5, 1), Instruction::set_val(3)])
+		else {
+			panic!("sets should be sorted");
+		};
+
+		assert_eq!(
+			instrs,
+			[Instruction::set_val(3), Instruction::set_val_at(5, 1)]
+		);
 	}
 }
@@ -16,14 +16,14 @@ impl LoopPass for OptimizeScaleAndMoveValPass {
 				},
 				Instruction::MovePtr(x),
 				Instruction::IncVal {
-					value: j @ 0..=i8::MAX,
+					value: j @ 0..=i32::MAX,
 					offset: Offset(0),
 				},
 				Instruction::MovePtr(y),
 			]
 			| [
 				Instruction::IncVal {
-					value: j @ 0..=i8::MAX,
+					value: j @ 0..=i32::MAX,
 					offset: Offset(0),
 				},
 				Instruction::MovePtr(y),
@@ -32,36 +32,27 @@ impl LoopPass for OptimizeScaleAndMoveValPass {
 					offset: Offset(0),
 				},
 				Instruction::MovePtr(x),
-			] if *x == -y => {
-				let x = *x;
-
-				Some(Change::replace(Instruction::scale_and_move_val(
-					*j as u8, x,
-				)))
-			}
+			] if *x == -y => Some(Change::replace(scale_and_move_val(*j as u32, *x))),
 			[
 				Instruction::IncVal {
 					value: -1,
 					offset: Offset(0),
 				},
 				Instruction::IncVal {
-					value: value @ 0..=i8::MAX,
+					value: value @ 0..=i32::MAX,
 					offset: x,
 				},
 			]
 			| [
 				Instruction::IncVal {
-					value: value @ 0..=i8::MAX,
+					value: value @ 0..=i32::MAX,
 					offset: x,
 				},
 				Instruction::IncVal {
 					value: -1,
 					offset: Offset(0),
 				},
-			] => Some(Change::replace(Instruction::scale_and_move_val(
-				*value as u8,
-				*x,
-			))),
+			] => Some(Change::replace(scale_and_move_val(*value as u32, *x))),
 			_ => None,
 		}
 	}
@@ -100,12 +91,12 @@ impl LoopPass for OptimizeScaleAndMoveValPass {
 					offset: Offset(0)
 				},
 				Instruction::IncVal {
-					value: 0..=i8::MAX,
+					value: 0..=i32::MAX,
 					..
 				}
 			] | [
 				Instruction::IncVal {
-					value: 0..=i8::MAX,
+					value: 0..=i32::MAX,
 					..
 				},
 				Instruction::IncVal {
@@ -116,3 +107,34 @@ impl LoopPass for OptimizeScaleAndMoveValPass {
 		)
 	}
 }
+
+/// A factor of one is a plain move, which later passes know more about.
+fn scale_and_move_val(factor: u32, offset: Offset) -> Instruction {
+	if factor == 1 {
+		Instruction::move_val(offset)
+	} else {
+		Instruction::scale_and_move_val(factor, offset)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use vmm_ir::{Instruction, Offset};
+
+	use super::OptimizeScaleAndMoveValPass;
+	use crate::{Change, LoopPass as _};
+
+	#[test]
+	fn moves_by_a_factor_of_one_are_plain_moves() {
+		let mut pass = OptimizeScaleAndMoveValPass;
+
+		assert!(matches!(
+			pass.run_pass(&[Instruction::inc_val(-1), Instruction::inc_val_at(1, 1)]),
+			Some(Change::Replace(Instruction::MoveVal(Offset(1))))
+		));
+		assert!(matches!(
+			pass.run_pass(&[Instruction::inc_val(-1), Instruction::inc_val_at(2, 1)]),
+			Some(Change::Replace(Instruction::Super(..)))
+		));
+	}
+}
@@ -34,7 +34,7 @@ impl PeepholePass for OptimizeSetScaleValPass {
 			] => Some(Change::swap([
 				Instruction::clear_val(),
 				Instruction::move_ptr(*offset),
-				Instruction::inc_val(WrappingMul::wrapping_mul(value.get(), factor) as i8),
+				Instruction::inc_val(WrappingMul::wrapping_mul(value.get(), factor) as i32),
 			])),
 			[
 				Instruction::SetVal {
@@ -49,7 +49,7 @@ impl PeepholePass for OptimizeSetScaleValPass {
 			] => Some(Change::swap([
 				Instruction::clear_val(),
 				Instruction::inc_val_at(
-					WrappingMul::wrapping_mul(value.get(), factor) as i8,
+					WrappingMul::wrapping_mul(value.get(), factor) as i32,
 					*offset,
 				),
 			])),
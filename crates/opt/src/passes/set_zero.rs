@@ -1,4 +1,4 @@
-use std::num::NonZeroU8;
+use std::num::NonZeroU32;
 
 use vmm_ir::{Instruction, Offset};
 
@@ -20,7 +20,7 @@ impl PeepholePass for OptimizeSetZeroPass {
 					offset: Offset(0),
 				},
 			] => Some(Change::replace(Instruction::find_and_set_zero(
-				NonZeroU8::new(*value as u8)?,
+				NonZeroU32::new(*value as u32)?,
 				*offset,
 			))),
 			[
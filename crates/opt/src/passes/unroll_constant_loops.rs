@@ -41,14 +41,20 @@ impl PeepholePass for UnrollConstantLoopsPass {
 							value,
 							offset: Offset(0),
 						},
-					] if *value < 0 && matches!(i.get() % value.unsigned_abs(), 0) => {
-						let mut output = Vec::with_capacity((i.get() as usize) * rest.len());
+					] if *value < 0
+						&& matches!(i.get() % value.unsigned_abs(), 0)
+						&& is_unrollable(rest) =>
+					{
+						let mut output = Vec::with_capacity((i.get() as usize) * rest.len() + 1);
 
 						Span::from(0..i.get())
 							.into_iter()
 							.step_by(value.unsigned_abs() as usize)
 							.for_each(|_| output.extend_from_slice(rest));
 
+						// The loop only ends once the counter is zero.
+						output.push(Instruction::clear_val());
+
 						Some(Change::swap(output))
 					}
 					_ => None,
@@ -86,15 +92,76 @@ impl PeepholePass for UnrollConstantLoopsPass {
 					offset: Offset(0),
 					value
 				},
-				..
+				rest @ ..
 			] | [
-				..,
+				rest @ ..,
 				Instruction::IncVal {
 					offset: Offset(0),
 					value
 				}
 			]
-			if *value < 0 && matches!(i.get() % value.unsigned_abs(), 0)
+			if *value < 0 && matches!(i.get() % value.unsigned_abs(), 0) && is_unrollable(rest)
 		)
 	}
 }
+
+// Anything that moves, loops or touches the counter would see it at a different value once unrolled.
+fn is_unrollable(body: &[Instruction]) -> bool {
+	body.iter().all(|instr| {
+		matches!(
+			instr,
+			Instruction::IncVal { offset, .. } | Instruction::SetVal { offset, .. }
+				if !matches!(offset, Offset(0))
+		)
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use vmm_ir::Instruction;
+
+	use super::UnrollConstantLoopsPass;
+	use crate::{Change, PeepholePass as _};
+
+	#[test]
+	fn clears_the_counter() {
+		let Some(Change::Swap(instrs)) = UnrollConstantLoopsPass.run_pass(&[
+			Instruction::set_val(2),
+			Instruction::dynamic_loop([Instruction::inc_val(-1), Instruction::inc_val_at(3, 1)]),
+		]) else {
+			panic!("loop should be unrolled");
+		};
+
+		assert_eq!(
+			instrs,
+			[
+				Instruction::inc_val_at(3, 1),
+				Instruction::inc_val_at(3, 1),
+				Instruction::clear_val(),
+			]
+		);
+	}
+
+	#[test]
+	fn keeps_loops_that_depend_on_the_counter() {
+		let pass = UnrollConstantLoopsPass;
+
+		assert!(!pass.should_run(&[
+			Instruction::set_val(4),
+			Instruction::dynamic_loop([
+				Instruction::inc_val(-2),
+				Instruction::inc_val_at(1, 1),
+				Instruction::inc_val(1),
+			]),
+		]));
+		assert!(!pass.should_run(&[
+			Instruction::set_val(4),
+			Instruction::dynamic_loop([
+				Instruction::inc_val(-1),
+				Instruction::move_ptr(1),
+				Instruction::inc_val(1),
+				Instruction::move_ptr(-1),
+			]),
+		]));
+	}
+}
@@ -5,6 +5,8 @@ use crate::{Change, PeepholePass};
 
 const MAX_LOOP_UNROLLING: usize = 5;
 
+const MAX_UNROLLED_ITERATIONS: i32 = i8::MAX as i32;
+
 #[derive(Debug, Default)]
 pub struct UnrollIncrementLoopsPass;
 
@@ -20,7 +22,7 @@ impl PeepholePass for UnrollIncrementLoopsPass {
 					offset: Offset(0),
 				},
 				raw_loop @ Instruction::Block(BlockInstruction::DynamicLoop(inner)),
-			] if *i > 0
+			] if matches!(*i, 1..=MAX_UNROLLED_ITERATIONS)
 				&& !raw_loop.might_move_ptr()
 				&& (raw_loop.nested_loops() < MAX_LOOP_UNROLLING) =>
 			{
@@ -40,9 +42,9 @@ impl PeepholePass for UnrollIncrementLoopsPass {
 						},
 					] => {
 						let mut output =
-							Vec::with_capacity((*i as u8 as usize) * rest.len() + inner.len());
+							Vec::with_capacity((*i as usize) * rest.len() + inner.len());
 
-						Span::from(0..(*i as u8))
+						Span::from(0..*i)
 							.into_iter()
 							.for_each(|_| output.extend_from_slice(rest));
 
@@ -72,7 +74,7 @@ impl PeepholePass for UnrollIncrementLoopsPass {
 			return false;
 		};
 
-		if *i <= 0 {
+		if !matches!(*i, 1..=MAX_UNROLLED_ITERATIONS) {
 			return false;
 		}
 
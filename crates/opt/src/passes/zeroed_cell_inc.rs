@@ -1,32 +1,46 @@
-use vmm_ir::{Instruction, IsZeroingCell as _, Offset};
+use vmm_ir::Instruction;
 
-use crate::{Change, PeepholePass};
+use crate::{CellValues, Change, PeepholePass};
 
+/// Turns increments of cells with known values, such as ones just cleared, into sets.
 #[derive(Debug, Default)]
 pub struct OptimizeZeroedCellIncValPass;
 
 impl PeepholePass for OptimizeZeroedCellIncValPass {
-	const SIZE: usize = 2;
+	const SIZE: usize = 1;
 
 	#[inline]
 	fn run_pass(&mut self, window: &[Instruction]) -> Option<Change> {
+		self.run_pass_with_values(window, &CellValues::default())
+	}
+
+	#[inline]
+	fn run_pass_with_values(
+		&mut self,
+		window: &[Instruction],
+		values: &CellValues,
+	) -> Option<Change> {
 		match window {
-			[
-				i,
-				Instruction::IncVal {
-					value,
-					offset: Offset(0),
-				},
-			] if i.is_zeroing_cell() => Some(Change::swap([
-				i.clone(),
-				Instruction::set_val(*value as u8),
-			])),
+			[Instruction::IncVal { value, offset }] => {
+				let known = values.get(*offset)?;
+
+				Some(Change::replace(Instruction::set_val_at(
+					values
+						.cell_width()
+						.wrap_unsigned(known.wrapping_add_signed(*value)),
+					offset,
+				)))
+			}
 			_ => None,
 		}
 	}
 
 	#[inline]
-	fn should_run(&self, window: &[Instruction]) -> bool {
-		matches!(window, [i, Instruction::IncVal { offset: Offset(0), .. }] if i.is_zeroing_cell())
+	fn should_run_with_values(&self, window: &[Instruction], values: &CellValues) -> bool {
+		matches!(window, [Instruction::IncVal { offset, .. }] if values.get(*offset).is_some())
+	}
+
+	fn uses_cell_values(&self) -> bool {
+		true
 	}
 }
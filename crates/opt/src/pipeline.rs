@@ -0,0 +1,395 @@
+use std::{
+	error::Error as StdError,
+	fmt::{Display, Formatter, Result as FmtResult},
+	str::FromStr,
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+macro_rules! pass_names {
+	($($(#[$meta:meta])* $variant:ident => $name:literal,)*) => {
+		/// A pass the optimizer can run, named in snake case after its type without the `Pass`
+		/// suffix.
+		#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+		pub enum PassName {
+			$($(#[$meta])* $variant,)*
+		}
+
+		impl PassName {
+			/// Every pass, in the order they run at [`OptLevel::O2`].
+			pub const ALL: &[Self] = &[$(Self::$variant,)*];
+
+			#[must_use]
+			pub const fn as_str(self) -> &'static str {
+				match self {
+					$(Self::$variant => $name,)*
+				}
+			}
+		}
+
+		impl FromStr for PassName {
+			type Err = UnknownPassError;
+
+			fn from_str(s: &str) -> Result<Self, Self::Err> {
+				match s {
+					$($name => Ok(Self::$variant),)*
+					_ => Err(UnknownPassError(s.to_owned())),
+				}
+			}
+		}
+	};
+}
+
+pass_names! {
+	CollapseStackedInstr => "collapse_stacked_instr",
+	CollapseRelativeInstr => "collapse_relative_instr",
+	OptimizeClearCell => "optimize_clear_cell",
+	OptimizeClearLoop => "optimize_clear_loop",
+	OptimizeFindZero => "optimize_find_zero",
+	OptimizeSetZero => "optimize_set_zero",
+	OptimizeScaleAndMoveVal => "optimize_scale_and_move_val",
+	OptimizeFetchAndScaleVal => "optimize_fetch_and_scale_val",
+	OptimizeScaleVal => "optimize_scale_val",
+	OptimizeZeroedCellIncVal => "optimize_zeroed_cell_inc_val",
+	OptimizeScaleAndTakeVal => "optimize_scale_and_take_val",
+	OptimizeScaleAndSetVal => "optimize_scale_and_set_val",
+	OptimizeSetScaleVal => "optimize_set_scale_val",
+	OptimizeTakeVal => "optimize_take_val",
+	OptimizeTakeFetchVal => "optimize_take_fetch_val",
+	OptimizeIfNz => "optimize_if_nz",
+	OptimizeSubCell => "optimize_sub_cell",
+	OptimizeConstantSub => "optimize_constant_sub",
+	OptimizeFetchVal => "optimize_fetch_val",
+	OptimizeSetUntilZero => "optimize_set_until_zero",
+	OptimizeReplaceVal => "optimize_replace_val",
+	OptimizeFindCellByZero => "optimize_find_cell_by_zero",
+	OptimizeShiftVals => "optimize_shift_vals",
+	OptimizeSuperInstr => "optimize_super_instr",
+	OptimizeSetWriteInc => "optimize_set_write_inc",
+	OptimizeConstantShift => "optimize_constant_shift",
+	OptimizeKnownValues => "optimize_known_values",
+	OptimizeMoveVal => "optimize_move_val",
+	ReorderMoveChange => "reorder_move_change",
+	ReorderOffsetBetweenMoves => "reorder_offset_between_moves",
+	CombineMoveChange => "combine_move_change",
+	SortIncInstr => "sort_inc_instr",
+	SortSetInstr => "sort_set_instr",
+	ReorderSetInc => "reorder_set_inc",
+	RemoveRedundantChangeValBasic => "remove_redundant_change_val_basic",
+	RemoveRedundantChangeValOffset => "remove_redundant_change_val_offset",
+	RemovePointlessInstr => "remove_pointless_instr",
+	RemoveRedundantScaleValInstrBasic => "remove_redundant_scale_val_instr_basic",
+	RemoveRedundantShifts => "remove_redundant_shifts",
+	RemoveEmptyLoops => "remove_empty_loops",
+	RemoveUnreachableLoops => "remove_unreachable_loops",
+	RemoveUnusedBoundaryInstr => "remove_unused_boundary_instr",
+	RemoveInfiniteLoops => "remove_infinite_loops",
+	UnrollConstantLoops => "unroll_constant_loops",
+	UnrollIncrementLoops => "unroll_increment_loops",
+	UnrollScaleAnd => "unroll_scale_and",
+}
+
+impl PassName {
+	/// Whether the pass can make the program larger, trading size for fewer executed
+	/// instructions.
+	#[must_use]
+	pub const fn grows_program(self) -> bool {
+		matches!(
+			self,
+			Self::UnrollConstantLoops | Self::UnrollIncrementLoops | Self::UnrollScaleAnd
+		)
+	}
+}
+
+impl Display for PassName {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		f.write_str(self.as_str())
+	}
+}
+
+impl Serialize for PassName {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(self.as_str())
+	}
+}
+
+impl<'de> Deserialize<'de> for PassName {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		String::deserialize(deserializer)?
+			.parse()
+			.map_err(serde::de::Error::custom)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownPassError(String);
+
+impl Display for UnknownPassError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		f.write_str("unknown pass ")?;
+		f.write_str(&self.0)
+	}
+}
+
+impl StdError for UnknownPassError {}
+
+/// A preset [`PassPipeline`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OptLevel {
+	/// No passes, only values are normalized to the cell width.
+	O0,
+	/// Collapse runs of instructions and turn clear loops into sets.
+	O1,
+	/// Every pass.
+	#[default]
+	O2,
+	/// Every pass that doesn't grow the program, keeping
+	/// [`rough_estimate`](vmm_program::Program::rough_estimate) as low as possible.
+	Os,
+}
+
+impl Display for OptLevel {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		f.write_str(match self {
+			Self::O0 => "0",
+			Self::O1 => "1",
+			Self::O2 => "2",
+			Self::Os => "s",
+		})
+	}
+}
+
+impl FromStr for OptLevel {
+	type Err = ParseOptLevelError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.strip_prefix('O').unwrap_or(s) {
+			"0" => Ok(Self::O0),
+			"1" => Ok(Self::O1),
+			"2" => Ok(Self::O2),
+			"s" => Ok(Self::Os),
+			_ => Err(ParseOptLevelError(s.to_owned())),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptLevelError(String);
+
+impl Display for ParseOptLevelError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		f.write_str("unknown optimization level ")?;
+		f.write_str(&self.0)
+	}
+}
+
+impl StdError for ParseOptLevelError {}
+
+/// The passes the optimizer runs on every iteration, in order.
+///
+/// When read from a file, a pipeline starts from a `level` (O2 if not given) or an explicit list
+/// of `passes`, then appends anything in `enable` and removes anything in `disable`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "PipelineConfig", into = "PipelineConfig")]
+pub struct PassPipeline {
+	passes: Vec<PassName>,
+}
+
+impl PassPipeline {
+	/// A pipeline without any passes.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self { passes: Vec::new() }
+	}
+
+	#[must_use]
+	pub fn level(level: OptLevel) -> Self {
+		let passes = match level {
+			OptLevel::O0 => Vec::new(),
+			OptLevel::O1 => vec![
+				PassName::CollapseStackedInstr,
+				PassName::CollapseRelativeInstr,
+				PassName::OptimizeClearCell,
+				PassName::OptimizeClearLoop,
+			],
+			OptLevel::O2 => PassName::ALL.to_vec(),
+			OptLevel::Os => PassName::ALL
+				.iter()
+				.copied()
+				.filter(|pass| !pass.grows_program())
+				.collect(),
+		};
+
+		Self { passes }
+	}
+
+	/// Run the pass last.
+	#[must_use]
+	pub fn and_with_pass(mut self, pass: PassName) -> Self {
+		self.passes.push(pass);
+		self
+	}
+
+	/// Run the pass just before the first run of another, or last if that isn't in the pipeline.
+	#[must_use]
+	pub fn and_with_pass_before(mut self, pass: PassName, before: PassName) -> Self {
+		let index = self
+			.passes
+			.iter()
+			.position(|p| *p == before)
+			.unwrap_or(self.passes.len());
+
+		self.passes.insert(index, pass);
+		self
+	}
+
+	/// Stop running the pass, wherever it is in the pipeline.
+	#[must_use]
+	pub fn and_without_pass(mut self, pass: PassName) -> Self {
+		self.passes.retain(|p| *p != pass);
+		self
+	}
+
+	#[must_use]
+	pub fn passes(&self) -> &[PassName] {
+		&self.passes
+	}
+
+	#[must_use]
+	pub fn contains(&self, pass: PassName) -> bool {
+		self.passes.contains(&pass)
+	}
+
+	#[must_use]
+	pub const fn is_empty(&self) -> bool {
+		self.passes.is_empty()
+	}
+}
+
+impl Default for PassPipeline {
+	fn default() -> Self {
+		Self::level(OptLevel::default())
+	}
+}
+
+impl From<OptLevel> for PassPipeline {
+	fn from(value: OptLevel) -> Self {
+		Self::level(value)
+	}
+}
+
+impl FromIterator<PassName> for PassPipeline {
+	fn from_iter<T>(iter: T) -> Self
+	where
+		T: IntoIterator<Item = PassName>,
+	{
+		Self {
+			passes: iter.into_iter().collect(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct PipelineConfig {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	level: Option<OptLevel>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	passes: Option<Vec<PassName>>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	enable: Vec<PassName>,
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	disable: Vec<PassName>,
+}
+
+impl From<PipelineConfig> for PassPipeline {
+	fn from(value: PipelineConfig) -> Self {
+		let pipeline = match value.passes {
+			Some(passes) => passes.into_iter().collect(),
+			None => Self::level(value.level.unwrap_or_default()),
+		};
+
+		let pipeline = value.enable.into_iter().fold(pipeline, Self::and_with_pass);
+
+		value
+			.disable
+			.into_iter()
+			.fold(pipeline, Self::and_without_pass)
+	}
+}
+
+impl From<PassPipeline> for PipelineConfig {
+	fn from(value: PassPipeline) -> Self {
+		Self {
+			passes: Some(value.passes),
+			..Self::default()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{OptLevel, PassName, PassPipeline};
+
+	#[test]
+	fn names_round_trip() {
+		for pass in PassName::ALL {
+			assert_eq!(pass.as_str().parse::<PassName>(), Ok(*pass));
+		}
+
+		assert!("optimize_everything".parse::<PassName>().is_err());
+	}
+
+	#[test]
+	fn levels() {
+		assert!(PassPipeline::level(OptLevel::O0).is_empty());
+		assert_eq!(PassPipeline::default().passes(), PassName::ALL);
+		assert!(!PassPipeline::level(OptLevel::Os).contains(PassName::UnrollConstantLoops));
+		assert!(PassPipeline::level(OptLevel::Os).contains(PassName::OptimizeShiftVals));
+		assert_eq!("Os".parse(), Ok(OptLevel::Os));
+		assert_eq!("1".parse(), Ok(OptLevel::O1));
+	}
+
+	#[test]
+	fn builder_reorders_passes() {
+		let pipeline = PassPipeline::level(OptLevel::O1)
+			.and_without_pass(PassName::OptimizeClearLoop)
+			.and_with_pass_before(PassName::OptimizeClearLoop, PassName::CollapseStackedInstr)
+			.and_with_pass(PassName::OptimizeFindZero);
+
+		assert_eq!(
+			pipeline.passes(),
+			[
+				PassName::OptimizeClearLoop,
+				PassName::CollapseStackedInstr,
+				PassName::CollapseRelativeInstr,
+				PassName::OptimizeClearCell,
+				PassName::OptimizeFindZero,
+			]
+		);
+	}
+
+	#[test]
+	fn reads_ron() {
+		let pipeline = ron::from_str::<PassPipeline>(
+			r#"(level: Some(Os), enable: ["unroll_scale_and"], disable: ["sort_inc_instr"])"#,
+		)
+		.unwrap();
+
+		assert_eq!(
+			pipeline,
+			PassPipeline::level(OptLevel::Os)
+				.and_with_pass(PassName::UnrollScaleAnd)
+				.and_without_pass(PassName::SortIncInstr)
+		);
+
+		let explicit =
+			ron::from_str::<PassPipeline>(r#"(passes: Some(["optimize_clear_loop"]))"#).unwrap();
+
+		assert_eq!(explicit.passes(), [PassName::OptimizeClearLoop]);
+		assert_eq!(
+			ron::from_str::<PassPipeline>(&ron::to_string(&pipeline).unwrap()),
+			Ok(pipeline)
+		);
+	}
+}
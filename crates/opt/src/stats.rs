@@ -0,0 +1,241 @@
+use std::{
+	fmt::{Display, Formatter, Result as FmtResult},
+	time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Change;
+
+/// What a single pass did over the whole optimization.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PassStats {
+	pass: String,
+	runs: u64,
+	windows: u64,
+	should_run_hits: u64,
+	removes: u64,
+	remove_offsets: u64,
+	swaps: u64,
+	replaces: u64,
+	instruction_delta: i64,
+	time: Duration,
+}
+
+impl PassStats {
+	#[must_use]
+	pub fn new(pass: impl Into<String>) -> Self {
+		Self {
+			pass: pass.into(),
+			..Self::default()
+		}
+	}
+
+	#[must_use]
+	pub fn pass(&self) -> &str {
+		&self.pass
+	}
+
+	/// How many times the optimizer ran the pass.
+	#[must_use]
+	pub const fn runs(&self) -> u64 {
+		self.runs
+	}
+
+	/// How many windows the pass looked at.
+	#[must_use]
+	pub const fn windows(&self) -> u64 {
+		self.windows
+	}
+
+	/// How many windows the pass said it would change.
+	#[must_use]
+	pub const fn should_run_hits(&self) -> u64 {
+		self.should_run_hits
+	}
+
+	#[must_use]
+	pub const fn removes(&self) -> u64 {
+		self.removes
+	}
+
+	#[must_use]
+	pub const fn remove_offsets(&self) -> u64 {
+		self.remove_offsets
+	}
+
+	#[must_use]
+	pub const fn swaps(&self) -> u64 {
+		self.swaps
+	}
+
+	#[must_use]
+	pub const fn replaces(&self) -> u64 {
+		self.replaces
+	}
+
+	/// How many changes of any kind were applied.
+	#[must_use]
+	pub const fn changes(&self) -> u64 {
+		self.removes + self.remove_offsets + self.swaps + self.replaces
+	}
+
+	/// How much the rough estimate of the program grew, negative when the pass shrank it.
+	#[must_use]
+	pub const fn instruction_delta(&self) -> i64 {
+		self.instruction_delta
+	}
+
+	#[must_use]
+	pub const fn time(&self) -> Duration {
+		self.time
+	}
+
+	pub(crate) const fn record_window(&mut self, should_run: bool) {
+		self.windows += 1;
+
+		if should_run {
+			self.should_run_hits += 1;
+		}
+	}
+
+	pub(crate) const fn record_change(&mut self, change: &Change) {
+		match change {
+			Change::Remove => self.removes += 1,
+			Change::RemoveOffset(..) => self.remove_offsets += 1,
+			Change::Swap(..) => self.swaps += 1,
+			Change::Replace(..) => self.replaces += 1,
+		}
+	}
+
+	#[allow(clippy::cast_possible_wrap)]
+	pub(crate) fn record_run(&mut self, before: usize, after: usize, time: Duration) {
+		self.runs += 1;
+		self.instruction_delta += after as i64 - before as i64;
+		self.time += time;
+	}
+}
+
+/// Statistics for every pass the optimizer ran, in the order they first ran.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(transparent)]
+#[serde(transparent)]
+pub struct OptimizerStats {
+	passes: Vec<PassStats>,
+}
+
+impl OptimizerStats {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self { passes: Vec::new() }
+	}
+
+	#[must_use]
+	pub fn get(&self, pass: &str) -> Option<&PassStats> {
+		self.passes.iter().find(|stats| stats.pass == pass)
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = &PassStats> {
+		self.passes.iter()
+	}
+
+	#[must_use]
+	pub const fn is_empty(&self) -> bool {
+		self.passes.is_empty()
+	}
+
+	pub(crate) fn pass_mut(&mut self, pass: &str) -> &mut PassStats {
+		let index = if let Some(index) = self.passes.iter().position(|stats| stats.pass == pass) {
+			index
+		} else {
+			self.passes.push(PassStats::new(pass));
+			self.passes.len() - 1
+		};
+
+		&mut self.passes[index]
+	}
+}
+
+impl Display for OptimizerStats {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		let width = self
+			.passes
+			.iter()
+			.map(|stats| stats.pass.len())
+			.chain(Some("pass".len()))
+			.max()
+			.unwrap_or_default();
+
+		writeln!(
+			f,
+			"{:width$} {:>5} {:>8} {:>6} {:>7} {:>7} {:>7} {:>7} {:>7} {:>10}",
+			"pass",
+			"runs",
+			"windows",
+			"hits",
+			"remove",
+			"offset",
+			"swap",
+			"replace",
+			"delta",
+			"time"
+		)?;
+
+		for stats in &self.passes {
+			writeln!(
+				f,
+				"{:width$} {:>5} {:>8} {:>6} {:>7} {:>7} {:>7} {:>7} {:>7} {:>10.2?}",
+				stats.pass,
+				stats.runs,
+				stats.windows,
+				stats.should_run_hits,
+				stats.removes,
+				stats.remove_offsets,
+				stats.swaps,
+				stats.replaces,
+				stats.instruction_delta,
+				stats.time
+			)?;
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use vmm_ir::Instruction;
+
+	use super::{OptimizerStats, PassStats};
+	use crate::Change;
+
+	#[test]
+	fn keeps_passes_in_order() {
+		let mut stats = OptimizerStats::new();
+
+		stats.pass_mut("B").record_window(true);
+		stats.pass_mut("A").record_window(false);
+		stats
+			.pass_mut("B")
+			.record_change(&Change::replace(Instruction::clear_val()));
+		stats
+			.pass_mut("B")
+			.record_run(4, 1, Duration::from_millis(1));
+
+		assert_eq!(
+			stats.iter().map(PassStats::pass).collect::<Vec<_>>(),
+			["B", "A"]
+		);
+
+		let b = stats.get("B").unwrap();
+
+		assert_eq!(b.windows(), 1);
+		assert_eq!(b.should_run_hits(), 1);
+		assert_eq!(b.replaces(), 1);
+		assert_eq!(b.changes(), 1);
+		assert_eq!(b.instruction_delta(), -3);
+		assert_eq!(stats.get("A").unwrap().should_run_hits(), 0);
+	}
+}
@@ -0,0 +1,277 @@
+use std::{
+	error::Error as StdError,
+	fmt::{Display, Formatter, Result as FmtResult},
+	io::Cursor,
+	mem,
+};
+
+use vmm_interpret::{ExecutionBudget, Interpreter, RuntimeError};
+use vmm_ir::{CellWidth, EofPolicy};
+use vmm_program::Program;
+use vmm_tape::{CellValue, TAPE_SIZE, Tape, VecTape};
+
+/// Checks an optimized program does the same as the original by running both side by side.
+///
+/// Both programs read the same input, and have to produce the same output and leave the tape in
+/// the same state. When either runs out of fuel, the one that wrote less has to be one that ran out,
+/// and only the output it got to is compared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Validation {
+	input: Vec<u8>,
+	fuel: u64,
+	tape_len: usize,
+	compare_tape: bool,
+}
+
+impl Validation {
+	pub const DEFAULT_FUEL: u64 = 1_000_000;
+
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			input: Vec::new(),
+			fuel: Self::DEFAULT_FUEL,
+			tape_len: TAPE_SIZE,
+			compare_tape: true,
+		}
+	}
+
+	/// What both programs read, defaulting to nothing.
+	#[must_use]
+	pub fn and_with_input(mut self, input: impl Into<Vec<u8>>) -> Self {
+		self.input = input.into();
+		self
+	}
+
+	#[must_use]
+	pub fn input(&self) -> &[u8] {
+		&self.input
+	}
+
+	/// How many instructions and loop iterations each program may run.
+	#[must_use]
+	pub const fn and_with_fuel(mut self, fuel: u64) -> Self {
+		self.fuel = fuel;
+		self
+	}
+
+	#[must_use]
+	pub const fn fuel(&self) -> u64 {
+		self.fuel
+	}
+
+	/// How many cells the tape both programs run on has, defaulting to [`TAPE_SIZE`].
+	#[must_use]
+	pub const fn and_with_tape_len(mut self, tape_len: usize) -> Self {
+		self.tape_len = tape_len;
+		self
+	}
+
+	#[must_use]
+	pub const fn tape_len(&self) -> usize {
+		self.tape_len
+	}
+
+	/// Only compare output, for when the optimized program is allowed to skip writes nothing reads.
+	#[must_use]
+	pub const fn and_without_tape(mut self) -> Self {
+		self.compare_tape = false;
+		self
+	}
+
+	#[must_use]
+	pub const fn compares_tape(&self) -> bool {
+		self.compare_tape
+	}
+
+	/// Runs both programs, returning how they differ if they do.
+	///
+	/// # Errors
+	///
+	/// If the programs don't do the same thing.
+	pub fn validate(
+		&self,
+		original: &Program,
+		optimized: &Program,
+		cell_width: CellWidth,
+		eof: EofPolicy,
+	) -> Result<(), ValidationError> {
+		match cell_width {
+			CellWidth::U8 => self.validate_with::<u8>(original, optimized, eof),
+			CellWidth::U16 => self.validate_with::<u16>(original, optimized, eof),
+			CellWidth::U32 => self.validate_with::<u32>(original, optimized, eof),
+		}
+	}
+
+	fn validate_with<V: CellValue>(
+		&self,
+		original: &Program,
+		optimized: &Program,
+		eof: EofPolicy,
+	) -> Result<(), ValidationError> {
+		let original = self.run::<V>(original, eof);
+		let optimized = self.run::<V>(optimized, eof);
+
+		if original.out_of_fuel || optimized.out_of_fuel {
+			let matches = if original.output.len() <= optimized.output.len() {
+				original.is_prefix_of(&optimized)
+			} else {
+				optimized.is_prefix_of(&original)
+			};
+
+			return if matches {
+				Ok(())
+			} else {
+				Err(ValidationError::output(&original, &optimized))
+			};
+		}
+
+		if original.output != optimized.output {
+			return Err(ValidationError::output(&original, &optimized));
+		}
+
+		if original.error.as_ref().map(mem::discriminant)
+			!= optimized.error.as_ref().map(mem::discriminant)
+		{
+			return Err(ValidationError::Outcome {
+				original: original.error.map(|e| e.to_string()),
+				optimized: optimized.error.map(|e| e.to_string()),
+			});
+		}
+
+		if !self.compare_tape {
+			return Ok(());
+		}
+
+		if let Some(cell) = original
+			.tape
+			.iter()
+			.zip(&optimized.tape)
+			.position(|(original, optimized)| original != optimized)
+		{
+			return Err(ValidationError::Tape { cell });
+		}
+
+		if original.ptr != optimized.ptr {
+			return Err(ValidationError::Pointer {
+				original: original.ptr,
+				optimized: optimized.ptr,
+			});
+		}
+
+		Ok(())
+	}
+
+	fn run<V: CellValue>(&self, program: &Program, eof: EofPolicy) -> Run<V> {
+		let mut interpreter = Interpreter::<VecTape<V>, _, _>::with_tape_len(
+			program.clone(),
+			Cursor::new(self.input.clone()),
+			Vec::new(),
+			self.tape_len,
+		)
+		.and_with_eof_policy(eof)
+		.and_with_budget(ExecutionBudget::unlimited().and_with_fuel(self.fuel));
+
+		let error = interpreter.run().err();
+
+		Run {
+			out_of_fuel: matches!(error, Some(RuntimeError::OutOfFuel(_))),
+			tape: interpreter
+				.tape()
+				.as_slice()
+				.iter()
+				.map(|cell| cell.value())
+				.collect(),
+			ptr: interpreter.tape().ptr().value(),
+			output: interpreter.output().clone(),
+			error,
+		}
+	}
+}
+
+impl Default for Validation {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+struct Run<V> {
+	output: Vec<u8>,
+	tape: Vec<V>,
+	ptr: usize,
+	error: Option<RuntimeError>,
+	out_of_fuel: bool,
+}
+
+impl<V> Run<V> {
+	// Writing less is only fine when the run was cut short.
+	fn is_prefix_of(&self, other: &Self) -> bool {
+		other.output.starts_with(&self.output)
+			&& (self.out_of_fuel || self.output.len() == other.output.len())
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+	Output {
+		original: Vec<u8>,
+		optimized: Vec<u8>,
+	},
+	/// One program stopped with an error the other didn't.
+	Outcome {
+		original: Option<String>,
+		optimized: Option<String>,
+	},
+	/// The first cell left with a different value.
+	Tape {
+		cell: usize,
+	},
+	Pointer {
+		original: usize,
+		optimized: usize,
+	},
+}
+
+impl ValidationError {
+	fn output<V>(original: &Run<V>, optimized: &Run<V>) -> Self {
+		Self::Output {
+			original: original.output.clone(),
+			optimized: optimized.output.clone(),
+		}
+	}
+}
+
+impl Display for ValidationError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::Output {
+				original,
+				optimized,
+			} => write!(
+				f,
+				"optimized program wrote {:?} instead of {:?}",
+				String::from_utf8_lossy(optimized),
+				String::from_utf8_lossy(original)
+			),
+			Self::Outcome {
+				original,
+				optimized,
+			} => write!(
+				f,
+				"optimized program ended with {} instead of {}",
+				optimized.as_deref().unwrap_or("no error"),
+				original.as_deref().unwrap_or("no error")
+			),
+			Self::Tape { cell } => write!(f, "optimized program left cell {cell} different"),
+			Self::Pointer {
+				original,
+				optimized,
+			} => write!(
+				f,
+				"optimized program left the pointer at {optimized} instead of {original}"
+			),
+		}
+	}
+}
+
+impl StdError for ValidationError {}
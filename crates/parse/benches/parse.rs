@@ -0,0 +1,49 @@
+mod recursive;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use vmm_parse::Parser;
+
+const AWIB: &str = include_str!("../../../programs/awib.bf");
+
+fn nested(depth: usize) -> String {
+	"+[".repeat(depth) + &"-]".repeat(depth)
+}
+
+fn awib(c: &mut Criterion) {
+	let mut group = c.benchmark_group("awib");
+
+	group.bench_function("stack", |b| b.iter(|| Parser::new(AWIB).scan().unwrap()));
+	group.bench_function("recursive", |b| {
+		b.iter(|| recursive::scan(AWIB).unwrap());
+	});
+
+	group.finish();
+}
+
+fn deep_nesting(c: &mut Criterion) {
+	let mut group = c.benchmark_group("deep_nesting");
+
+	for depth in [100, 1_000, 5_000, 50_000] {
+		let source = nested(depth);
+
+		group.bench_with_input(BenchmarkId::new("stack", depth), &source, |b, source| {
+			b.iter(|| Parser::new(source).scan().unwrap());
+		});
+
+		// The recursive parser overflows the stack long before this depth.
+		if depth <= 5_000 {
+			group.bench_with_input(
+				BenchmarkId::new("recursive", depth),
+				&source,
+				|b, source| {
+					b.iter(|| recursive::scan(source).unwrap());
+				},
+			);
+		}
+	}
+
+	group.finish();
+}
+
+criterion_group!(benches, awib, deep_nesting);
+criterion_main!(benches);
@@ -0,0 +1,89 @@
+//! The recursive parser that [`Parser`](vmm_parse::Parser) replaced, kept to benchmark against.
+
+use logos::Logos;
+use vmm_ir::Instruction;
+use vmm_parse::{OpCode, ParseErrorKind};
+use vmm_program::{SourceMap, SourceMapEntry, SourceSpan};
+
+pub fn scan(source: &str) -> Result<Vec<Instruction>, (ParseErrorKind, usize)> {
+	let opcodes = OpCode::lexer(source)
+		.spanned()
+		.filter_map(|(op, span)| op.ok().map(|op| (op, span.start)));
+
+	let (mut parsed, mut source_map) = parse(opcodes)?;
+
+	parsed.insert(0, Instruction::Boundary);
+	source_map
+		.entries_mut()
+		.insert(0, SourceMapEntry::new(SourceSpan::new(0, 0)));
+
+	parsed.push(Instruction::Boundary);
+	source_map.push(SourceMapEntry::new(SourceSpan::new(
+		source.len(),
+		source.len(),
+	)));
+
+	Ok(parsed)
+}
+
+fn parse(
+	opcodes: impl Iterator<Item = (OpCode, usize)>,
+) -> Result<(Vec<Instruction>, SourceMap), (ParseErrorKind, usize)> {
+	let opcodes = opcodes.collect::<Vec<_>>();
+	let mut program = Vec::new();
+	let mut source_map = SourceMap::new();
+	let mut loop_stack = 0;
+	let mut loop_start = 0;
+
+	opcodes
+		.iter()
+		.copied()
+		.enumerate()
+		.try_for_each(|(i, (op, offset))| {
+			if matches!(loop_stack, 0) {
+				if let Some(instr) = match op {
+					OpCode::Increment => Some(Instruction::inc_val(1)),
+					OpCode::Decrement => Some(Instruction::inc_val(-1)),
+					OpCode::Output => Some(Instruction::write_once()),
+					OpCode::MoveRight => Some(Instruction::move_ptr(1)),
+					OpCode::MoveLeft => Some(Instruction::move_ptr(-1)),
+					OpCode::Input => Some(Instruction::read()),
+					OpCode::JumpRight => {
+						loop_start = i;
+						loop_stack += 1;
+						None
+					}
+					OpCode::JumpLeft => return Err((ParseErrorKind::UnmatchedClose, offset)),
+				} {
+					program.push(instr);
+					source_map.push(SourceMapEntry::new(SourceSpan::new(offset, offset + 1)));
+				}
+			} else {
+				match op {
+					OpCode::JumpRight => loop_stack += 1,
+					OpCode::JumpLeft => {
+						loop_stack -= 1;
+						if matches!(loop_stack, 0) {
+							let (body, body_map) =
+								parse(opcodes[loop_start + 1..i].iter().copied())?;
+
+							program.push(Instruction::dynamic_loop(body));
+							source_map.push(SourceMapEntry::with_block(
+								SourceSpan::new(opcodes[loop_start].1, offset + 1),
+								body_map,
+							));
+						}
+					}
+					_ => {}
+				}
+			}
+
+			Ok(())
+		})?;
+
+	if !matches!(loop_stack, 0) {
+		return Err((ParseErrorKind::UnclosedOpen, opcodes[loop_start].1));
+	}
+
+	Ok((program, source_map))
+}
@@ -0,0 +1,145 @@
+use alloc::string::{String, ToString as _};
+use core::{
+	error::Error as StdError,
+	fmt::{Display, Formatter, Result as FmtResult, Write as _},
+};
+
+/// A position in the original source, before anything was filtered out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourceLocation {
+	offset: usize,
+	line: usize,
+	column: usize,
+}
+
+impl SourceLocation {
+	/// Finds the line and column of a byte offset into `source`.
+	#[must_use]
+	pub fn new(source: &str, offset: usize) -> Self {
+		let before = &source[..offset];
+		let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+		Self {
+			offset,
+			line: before.matches('\n').count() + 1,
+			column: before[line_start..].chars().count() + 1,
+		}
+	}
+
+	#[must_use]
+	pub const fn offset(self) -> usize {
+		self.offset
+	}
+
+	/// The line, starting at 1.
+	#[must_use]
+	pub const fn line(self) -> usize {
+		self.line
+	}
+
+	/// The column in characters, starting at 1.
+	#[must_use]
+	pub const fn column(self) -> usize {
+		self.column
+	}
+}
+
+impl Display for SourceLocation {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		Display::fmt(&self.line, f)?;
+		f.write_char(':')?;
+		Display::fmt(&self.column, f)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParseErrorKind {
+	/// A `]` with no `[` before it.
+	UnmatchedClose,
+	/// A `[` that is never closed.
+	UnclosedOpen,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+	kind: ParseErrorKind,
+	location: SourceLocation,
+	source_line: String,
+}
+
+impl ParseError {
+	pub(crate) fn new(kind: ParseErrorKind, source: &str, offset: usize) -> Self {
+		let location = SourceLocation::new(source, offset);
+
+		let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+		let line_end = source[offset..]
+			.find('\n')
+			.map_or(source.len(), |i| offset + i);
+
+		Self {
+			kind,
+			location,
+			source_line: source[line_start..line_end].trim_end_matches('\r').into(),
+		}
+	}
+
+	#[must_use]
+	pub const fn kind(&self) -> ParseErrorKind {
+		self.kind
+	}
+
+	#[must_use]
+	pub const fn location(&self) -> SourceLocation {
+		self.location
+	}
+
+	/// The line of source the error is on.
+	#[must_use]
+	pub fn source_line(&self) -> &str {
+		&self.source_line
+	}
+
+	/// Shows the error along with the line it is on, pointing at the bracket.
+	#[must_use]
+	pub const fn snippet(&self) -> Snippet<'_> {
+		Snippet(self)
+	}
+}
+
+impl Display for ParseError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		f.write_str(match self.kind {
+			ParseErrorKind::UnmatchedClose => "unmatched ']'",
+			ParseErrorKind::UnclosedOpen => "unclosed '['",
+		})?;
+		f.write_str(" at ")?;
+		Display::fmt(&self.location, f)
+	}
+}
+
+impl StdError for ParseError {}
+
+pub struct Snippet<'a>(&'a ParseError);
+
+impl Display for Snippet<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		let error = self.0;
+		let line = error.location.line.to_string();
+		let gutter = " ".repeat(line.len());
+
+		writeln!(f, "{error}")?;
+		writeln!(f, "{gutter} |")?;
+		writeln!(f, "{line} | {}", error.source_line)?;
+		write!(f, "{gutter} | ")?;
+
+		// Tabs are kept so the caret lines up however they are shown.
+		for c in error.source_line.chars().take(error.location.column - 1) {
+			f.write_char(if c == '\t' { '\t' } else { ' ' })?;
+		}
+
+		f.write_str(match error.kind {
+			ParseErrorKind::UnmatchedClose => "^ this loop has no beginning",
+			ParseErrorKind::UnclosedOpen => "^ this loop is never closed",
+		})
+	}
+}
@@ -3,19 +3,18 @@
 
 extern crate alloc;
 
+mod error;
 mod opcode;
 
 use alloc::vec::Vec;
-use core::{
-	error::Error as StdError,
-	fmt::{Debug, Display, Formatter, Result as FmtResult},
-};
+use core::mem;
 
 use logos::{Lexer, Logos};
-use tracing::{debug, info, trace, trace_span};
+use tracing::{debug, info, trace};
 use vmm_ir::Instruction;
+use vmm_program::{SourceMap, SourceMapEntry, SourceSpan};
 
-pub use self::opcode::*;
+pub use self::{error::*, opcode::*};
 
 #[derive(Debug, Clone)]
 pub struct Parser<'source> {
@@ -33,96 +32,209 @@ impl<'source> Parser<'source> {
 	}
 
 	#[inline]
-	#[tracing::instrument(skip(self))]
 	pub fn scan(self) -> Result<Vec<Instruction>, ParseError> {
+		self.scan_with_source_map().map(|(program, _)| program)
+	}
+
+	/// Scans the program along with the span of source each instruction was parsed from.
+	#[inline]
+	#[tracing::instrument(skip(self))]
+	pub fn scan_with_source_map(self) -> Result<(Vec<Instruction>, SourceMap), ParseError> {
 		info!("scanning {} chars", self.inner.source().len());
 
-		let mut parsed = parse(self.inner.filter_map(Result::ok), 0)?;
+		let source = self.inner.source();
+		let opcodes = self
+			.inner
+			.spanned()
+			.filter_map(|(op, span)| op.ok().map(|op| (op, span.start)));
+
+		let (mut parsed, mut source_map) =
+			parse(opcodes).map_err(|(kind, offset)| ParseError::new(kind, source, offset))?;
 
 		parsed.insert(0, Instruction::Boundary);
+		source_map
+			.entries_mut()
+			.insert(0, SourceMapEntry::new(SourceSpan::new(0, 0)));
 
 		parsed.push(Instruction::Boundary);
+		source_map.push(SourceMapEntry::new(SourceSpan::new(
+			source.len(),
+			source.len(),
+		)));
 
-		Ok(parsed)
+		Ok((parsed, source_map))
 	}
 }
 
-#[derive(Debug)]
-pub enum ParseError {
-	UnmatchedBracket(usize),
+/// A loop that has been opened but not yet closed, holding everything parsed before it.
+struct OpenLoop {
+	offset: usize,
+	program: Vec<Instruction>,
+	source_map: SourceMap,
 }
 
-impl Display for ParseError {
-	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-		match self {
-			Self::UnmatchedBracket(index) => {
-				f.write_str("loop ending at #")?;
-				Display::fmt(&index, f)?;
-				f.write_str(" has no beginning")
+/// Parses opcodes tagged with their byte offsets in a single pass, failing with the offset of the
+/// unbalanced bracket.
+///
+/// Every opcode is a single byte, so an instruction's span ends just after its offset.
+#[inline]
+fn parse(
+	opcodes: impl Iterator<Item = (OpCode, usize)>,
+) -> Result<(Vec<Instruction>, SourceMap), (ParseErrorKind, usize)> {
+	let mut program = Vec::new();
+	let mut source_map = SourceMap::new();
+	let mut open_loops = Vec::<OpenLoop>::new();
+
+	for (op, offset) in opcodes {
+		let instr = match op {
+			OpCode::Increment => Instruction::inc_val(1),
+			OpCode::Decrement => Instruction::inc_val(-1),
+			OpCode::Output => Instruction::write_once(),
+			OpCode::MoveRight => Instruction::move_ptr(1),
+			OpCode::MoveLeft => Instruction::move_ptr(-1),
+			OpCode::Input => Instruction::read(),
+			OpCode::JumpRight => {
+				open_loops.push(OpenLoop {
+					offset,
+					program: mem::take(&mut program),
+					source_map: mem::take(&mut source_map),
+				});
+
+				continue;
 			}
-		}
+			OpCode::JumpLeft => {
+				let open = open_loops
+					.pop()
+					.ok_or((ParseErrorKind::UnmatchedClose, offset))?;
+
+				let body = mem::replace(&mut program, open.program);
+				let body_map = mem::replace(&mut source_map, open.source_map);
+
+				trace!(
+					depth = open_loops.len(),
+					"got loop of {} instructions",
+					body.len()
+				);
+
+				program.push(Instruction::dynamic_loop(body));
+				source_map.push(SourceMapEntry::with_block(
+					SourceSpan::new(open.offset, offset + 1),
+					body_map,
+				));
+
+				continue;
+			}
+		};
+
+		trace!(depth = open_loops.len(), "got instruction {op}");
+
+		program.push(instr);
+		source_map.push(SourceMapEntry::new(SourceSpan::new(offset, offset + 1)));
+	}
+
+	// The outermost loop is the one left open, any inside it were closed by its brackets.
+	if let Some(open) = open_loops.first() {
+		return Err((ParseErrorKind::UnclosedOpen, open.offset));
 	}
+
+	Ok((program, source_map))
 }
 
-impl StdError for ParseError {}
+#[cfg(test)]
+mod tests {
+	use alloc::string::ToString as _;
 
-#[inline]
-fn parse(
-	opcodes: impl Iterator<Item = OpCode>,
-	depth: usize,
-) -> Result<Vec<Instruction>, ParseError> {
-	let span = trace_span!("parse", depth);
+	use vmm_ir::Instruction;
+	use vmm_program::SourceSpan;
 
-	let guard = span.enter();
+	use super::{ParseError, ParseErrorKind, Parser};
 
-	let opcodes = opcodes.into_iter().collect::<Vec<_>>();
-	let mut program = Vec::new();
-	let mut loop_stack = 0;
-	let mut loop_start = 0;
-
-	opcodes
-		.iter()
-		.copied()
-		.enumerate()
-		.try_for_each(|(i, op)| {
-			if matches!(loop_stack, 0) {
-				if let Some(instr) = match op {
-					OpCode::Increment => Some(Instruction::inc_val(1)),
-					OpCode::Decrement => Some(Instruction::inc_val(-1)),
-					OpCode::Output => Some(Instruction::write_once()),
-					OpCode::MoveRight => Some(Instruction::move_ptr(1)),
-					OpCode::MoveLeft => Some(Instruction::move_ptr(-1)),
-					OpCode::Input => Some(Instruction::read()),
-					OpCode::JumpRight => {
-						loop_start = i;
-						loop_stack += 1;
-						None
-					}
-					OpCode::JumpLeft => return Err(ParseError::UnmatchedBracket(i)),
-				} {
-					trace!(parent: &span, "got instruction {op}");
-					program.push(instr);
-				}
-			} else {
-				match op {
-					OpCode::JumpRight => loop_stack += 1,
-					OpCode::JumpLeft => {
-						loop_stack -= 1;
-						if matches!(loop_stack, 0) {
-							program.push(Instruction::dynamic_loop(parse(
-								opcodes[loop_start + 1..i].iter().copied(),
-								depth + 1,
-							)?));
-						}
-					}
-					_ => {}
-				}
-			}
+	fn error(source: &str) -> ParseError {
+		Parser::new(source).scan().unwrap_err()
+	}
+
+	#[test]
+	fn skips_comments() {
+		assert_eq!(
+			Parser::new("add one: +\nloop [-]").scan(),
+			Ok(alloc::vec![
+				Instruction::Boundary,
+				Instruction::inc_val(1),
+				Instruction::dynamic_loop([Instruction::inc_val(-1)]),
+				Instruction::Boundary,
+			])
+		);
+	}
+
+	#[test]
+	fn maps_instructions_to_source() {
+		let (program, source_map) = Parser::new("a +\n[->]").scan_with_source_map().unwrap();
+
+		assert!(source_map.matches(&program));
+		assert_eq!(source_map.span(&[0]), Some(SourceSpan::new(0, 0)));
+		assert_eq!(source_map.span(&[1]), Some(SourceSpan::new(2, 3)));
+		assert_eq!(source_map.span(&[2]), Some(SourceSpan::new(4, 8)));
+		assert_eq!(source_map.span(&[2, 1]), Some(SourceSpan::new(6, 7)));
+		assert_eq!(source_map.span(&[3]), Some(SourceSpan::new(8, 8)));
+	}
+
+	#[test]
+	fn parses_deep_nesting() {
+		const DEPTH: usize = 10_000;
+
+		let source = "[>".repeat(DEPTH) + &"]".repeat(DEPTH);
+		let (program, source_map) = Parser::new(&source).scan_with_source_map().unwrap();
 
-			Ok(())
-		})?;
+		let mut instrs = &program[1..2];
+		let mut depth = 0;
+
+		while let [Instruction::Block(block)] = instrs {
+			instrs = &block[1..];
+			depth += 1;
+		}
 
-	drop(guard);
+		assert_eq!(depth, DEPTH);
+		assert!(instrs.is_empty());
 
-	Ok(program)
+		// Each loop comes second, after the leading boundary or the `>` of its parent.
+		assert_eq!(
+			source_map.span(&[1; DEPTH]),
+			Some(SourceSpan::new(2 * DEPTH - 2, 2 * DEPTH + 1))
+		);
+	}
+
+	#[test]
+	fn reports_unmatched_close() {
+		let error = error("+[-]\n  é] +");
+
+		assert_eq!(error.kind(), ParseErrorKind::UnmatchedClose);
+		assert_eq!(error.location().offset(), 9);
+		assert_eq!(error.location().line(), 2);
+		assert_eq!(error.location().column(), 4);
+		assert_eq!(error.to_string(), "unmatched ']' at 2:4");
+	}
+
+	#[test]
+	fn reports_unclosed_open() {
+		let error = error("+\n>[ move [-] <");
+
+		assert_eq!(error.kind(), ParseErrorKind::UnclosedOpen);
+		assert_eq!(error.location().offset(), 3);
+		assert_eq!(error.source_line(), ">[ move [-] <");
+		assert_eq!(error.to_string(), "unclosed '[' at 2:2");
+	}
+
+	#[test]
+	fn renders_snippets() {
+		assert_eq!(
+			error("\n\n\n\n\n\n\n\n\n+\r\n\t[\r\n")
+				.snippet()
+				.to_string(),
+			"unclosed '[' at 11:2\n   |\n11 | \t[\n   | \t^ this loop is never closed"
+		);
+		assert_eq!(
+			error("+]").snippet().to_string(),
+			"unmatched ']' at 1:2\n  |\n1 | +]\n  |  ^ this loop has no beginning"
+		);
+	}
 }
@@ -3,6 +3,8 @@
 
 extern crate alloc;
 
+mod source;
+
 use alloc::{boxed::Box, vec::Vec};
 use core::{
 	fmt::{Debug, Formatter, Result as FmtResult},
@@ -15,6 +17,8 @@ use serde::{Deserialize, Serialize};
 use vmm_ir::{HasIo, Instruction};
 use vmm_utils::HeapSize;
 
+pub use self::source::*;
+
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Program {
 	Raw(Vec<Instruction>),
@@ -0,0 +1,249 @@
+use alloc::vec::Vec;
+use core::{
+	fmt::{Display, Formatter, Result as FmtResult},
+	ops::Range,
+	slice,
+};
+
+use serde::{Deserialize, Serialize};
+use vmm_ir::Instruction;
+
+/// A range of bytes in the source a program was parsed from.
+#[derive(
+	Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct SourceSpan {
+	start: usize,
+	end: usize,
+}
+
+impl SourceSpan {
+	#[must_use]
+	pub const fn new(start: usize, end: usize) -> Self {
+		assert!(start <= end, "span must not end before it starts");
+
+		Self { start, end }
+	}
+
+	#[must_use]
+	pub const fn start(self) -> usize {
+		self.start
+	}
+
+	#[must_use]
+	pub const fn end(self) -> usize {
+		self.end
+	}
+
+	#[must_use]
+	pub const fn len(self) -> usize {
+		self.end - self.start
+	}
+
+	#[must_use]
+	pub const fn is_empty(self) -> bool {
+		self.start == self.end
+	}
+
+	/// The smallest span covering both spans.
+	#[must_use]
+	pub const fn merge(self, other: Self) -> Self {
+		Self {
+			start: if self.start < other.start {
+				self.start
+			} else {
+				other.start
+			},
+			end: if self.end > other.end {
+				self.end
+			} else {
+				other.end
+			},
+		}
+	}
+
+	#[must_use]
+	pub const fn as_range(self) -> Range<usize> {
+		self.start..self.end
+	}
+}
+
+impl Display for SourceSpan {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		Display::fmt(&self.start, f)?;
+		f.write_str("..")?;
+		Display::fmt(&self.end, f)
+	}
+}
+
+impl From<Range<usize>> for SourceSpan {
+	fn from(value: Range<usize>) -> Self {
+		Self::new(value.start, value.end)
+	}
+}
+
+/// Where each instruction of a program came from, nested the same way as its blocks.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(transparent)]
+pub struct SourceMap(Vec<SourceMapEntry>);
+
+impl SourceMap {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self(Vec::new())
+	}
+
+	#[must_use]
+	pub const fn len(&self) -> usize {
+		self.0.len()
+	}
+
+	#[must_use]
+	pub const fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
+	#[must_use]
+	pub fn get(&self, index: usize) -> Option<&SourceMapEntry> {
+		self.0.get(index)
+	}
+
+	pub fn get_mut(&mut self, index: usize) -> Option<&mut SourceMapEntry> {
+		self.0.get_mut(index)
+	}
+
+	/// Finds the entry for a nested instruction, where each index selects an instruction in the
+	/// block selected by the previous ones.
+	#[must_use]
+	pub fn entry(&self, path: &[usize]) -> Option<&SourceMapEntry> {
+		let (first, rest) = path.split_first()?;
+
+		rest.iter()
+			.try_fold(self.get(*first)?, |entry, index| entry.block.get(*index))
+	}
+
+	#[must_use]
+	pub fn span(&self, path: &[usize]) -> Option<SourceSpan> {
+		self.entry(path).map(SourceMapEntry::span)
+	}
+
+	pub fn push(&mut self, entry: SourceMapEntry) {
+		self.0.push(entry);
+	}
+
+	pub fn iter(&self) -> slice::Iter<'_, SourceMapEntry> {
+		self.0.iter()
+	}
+
+	/// The entries, to be kept in step with the instructions they describe.
+	pub const fn entries_mut(&mut self) -> &mut Vec<SourceMapEntry> {
+		&mut self.0
+	}
+
+	/// Whether every instruction in the program, including nested ones, has exactly one entry.
+	#[must_use]
+	pub fn matches(&self, program: &[Instruction]) -> bool {
+		self.len() == program.len()
+			&& self.iter().zip(program).all(|(entry, instr)| match instr {
+				Instruction::Block(block) => entry.block.matches(block),
+				_ => entry.block.is_empty(),
+			})
+	}
+}
+
+impl FromIterator<SourceMapEntry> for SourceMap {
+	fn from_iter<T>(iter: T) -> Self
+	where
+		T: IntoIterator<Item = SourceMapEntry>,
+	{
+		Self(iter.into_iter().collect())
+	}
+}
+
+impl<'a> IntoIterator for &'a SourceMap {
+	type IntoIter = slice::Iter<'a, SourceMapEntry>;
+	type Item = &'a SourceMapEntry;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceMapEntry {
+	span: SourceSpan,
+	block: SourceMap,
+}
+
+impl SourceMapEntry {
+	#[must_use]
+	pub const fn new(span: SourceSpan) -> Self {
+		Self::with_block(span, SourceMap::new())
+	}
+
+	#[must_use]
+	pub const fn with_block(span: SourceSpan, block: SourceMap) -> Self {
+		Self { span, block }
+	}
+
+	/// Gives the instruction, and everything nested in it, the same span.
+	#[must_use]
+	pub fn covering(instr: &Instruction, span: SourceSpan) -> Self {
+		let block = match instr {
+			Instruction::Block(block) => block.iter().map(|i| Self::covering(i, span)).collect(),
+			_ => SourceMap::new(),
+		};
+
+		Self::with_block(span, block)
+	}
+
+	#[must_use]
+	pub const fn span(&self) -> SourceSpan {
+		self.span
+	}
+
+	/// The entries for the instructions inside a block, empty for any other instruction.
+	#[must_use]
+	pub const fn block(&self) -> &SourceMap {
+		&self.block
+	}
+
+	pub const fn block_mut(&mut self) -> &mut SourceMap {
+		&mut self.block
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use vmm_ir::Instruction;
+
+	use super::{SourceMap, SourceMapEntry, SourceSpan};
+
+	#[test]
+	fn merges_spans() {
+		assert_eq!(
+			SourceSpan::new(4, 6).merge(SourceSpan::new(1, 2)),
+			SourceSpan::new(1, 6)
+		);
+	}
+
+	#[test]
+	fn finds_nested_entries() {
+		let program = [
+			Instruction::inc_val(1),
+			Instruction::dynamic_loop([Instruction::inc_val(-1), Instruction::move_ptr(1)]),
+		];
+
+		let map = [
+			SourceMapEntry::new(SourceSpan::new(0, 1)),
+			SourceMapEntry::covering(&program[1], SourceSpan::new(1, 5)),
+		]
+		.into_iter()
+		.collect::<SourceMap>();
+
+		assert!(map.matches(&program));
+		assert!(!map.matches(&program[..1]));
+		assert_eq!(map.span(&[1, 1]), Some(SourceSpan::new(1, 5)));
+		assert_eq!(map.span(&[0, 0]), None);
+	}
+}
@@ -0,0 +1,781 @@
+use alloc::{rc::Rc, vec::Vec};
+use core::{
+	error::Error as CoreError,
+	fmt::{Debug, Display, Formatter, Result as FmtResult},
+};
+
+use hashbrown::HashMap;
+
+use crate::ir::{
+	BasicBlockId, BinaryOp, FunctionData, FunctionId, Program, Type, TypeKind, ValueData, ValueId,
+	ValueType,
+};
+
+/// A value computed while evaluating a program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+	Int(i32),
+	/// The index of a slot in the simulated memory.
+	Ptr(usize),
+	Undef,
+}
+
+/// Executes a [`Program`] directly, as a reference for anything that lowers into it.
+///
+/// Memory is a list of slots, one for each integer or pointer. Declared functions named
+/// `@getchar` and `@putchar` read from the input and write to the output, with `@getchar`
+/// returning `-1` once the input runs out.
+#[derive(Debug, Clone)]
+pub struct Evaluator<'a> {
+	program: &'a Program,
+	memory: Vec<Value>,
+	globals: HashMap<ValueId, Value>,
+	compiled: HashMap<FunctionId, Rc<Compiled>>,
+	input: Vec<u8>,
+	read: usize,
+	output: Vec<u8>,
+}
+
+impl<'a> Evaluator<'a> {
+	#[must_use]
+	pub fn new(program: &'a Program) -> Self {
+		let mut memory = Vec::new();
+		let mut globals = HashMap::new();
+
+		for value in program.inst_layout() {
+			let data = program.borrow_value(*value);
+
+			let ValueType::GlobalAlloc(alloc) = data.value_ty() else {
+				continue;
+			};
+
+			let values = program.borrow_values();
+			globals.insert(*value, Value::Ptr(memory.len()));
+			flatten(&values, &values[&alloc.init()], &mut memory);
+		}
+
+		Self {
+			program,
+			memory,
+			globals,
+			compiled: HashMap::new(),
+			input: Vec::new(),
+			read: 0,
+			output: Vec::new(),
+		}
+	}
+
+	#[must_use]
+	pub fn and_with_input(mut self, input: impl Into<Vec<u8>>) -> Self {
+		self.input = input.into();
+		self
+	}
+
+	/// Calls the function named `@main` with no arguments.
+	pub fn run(&mut self) -> Result<Option<Value>, EvalError> {
+		let main = self
+			.program
+			.func_layout()
+			.iter()
+			.copied()
+			.find(|func| self.program.func(*func).name() == "@main")
+			.ok_or(EvalError::NoMain)?;
+
+		self.call(main, &[])
+	}
+
+	pub fn call(&mut self, func: FunctionId, args: &[Value]) -> Result<Option<Value>, EvalError> {
+		let data = self.program.func(func);
+
+		if data.is_decl() {
+			return self.intrinsic(func, args);
+		}
+
+		let compiled = Rc::clone(
+			self.compiled
+				.entry(func)
+				.or_insert_with(|| Rc::new(Compiled::new(data, &self.globals))),
+		);
+
+		let frame_start = self.memory.len();
+		let result = compiled.run(self, args);
+		self.memory.truncate(frame_start);
+
+		result
+	}
+
+	#[must_use]
+	pub fn output(&self) -> &[u8] {
+		&self.output
+	}
+
+	/// The contents of a global allocation.
+	#[must_use]
+	pub fn global(&self, value: ValueId) -> Option<&[Value]> {
+		let Value::Ptr(start) = *self.globals.get(&value)? else {
+			return None;
+		};
+
+		let len = match &**self.program.borrow_value(value).ty() {
+			TypeKind::Ptr(ty) => slots(ty),
+			_ => return None,
+		};
+
+		self.memory.get(start..start + len)
+	}
+
+	fn intrinsic(&mut self, func: FunctionId, args: &[Value]) -> Result<Option<Value>, EvalError> {
+		match (self.program.func(func).name(), args) {
+			("@getchar", []) => {
+				let c = self.input.get(self.read).map_or(-1, |c| i32::from(*c));
+				self.read += 1;
+
+				Ok(Some(Value::Int(c)))
+			}
+			("@putchar", [Value::Int(c)]) => {
+				self.output.push(*c as u8);
+
+				Ok(None)
+			}
+			_ => Err(EvalError::UnknownFunction(func)),
+		}
+	}
+
+	const fn slot(&self, ptr: Value, inst: ValueId) -> Result<usize, EvalError> {
+		match ptr {
+			Value::Ptr(slot) if slot < self.memory.len() => Ok(slot),
+			Value::Ptr(..) => Err(EvalError::OutOfBounds(inst)),
+			Value::Int(..) => Err(EvalError::TypeMismatch(inst)),
+			Value::Undef => Err(EvalError::UseOfUndef(inst)),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+	NoMain,
+	UnknownFunction(FunctionId),
+	UndefinedValue(ValueId),
+	UseOfUndef(ValueId),
+	TypeMismatch(ValueId),
+	OutOfBounds(ValueId),
+	DivisionByZero(ValueId),
+	MissingTerminator(BasicBlockId),
+	Unsupported(ValueId),
+}
+
+impl Display for EvalError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::NoMain => f.write_str("program has no @main function"),
+			Self::UnknownFunction(func) => {
+				f.write_str("function ")?;
+				Debug::fmt(&func, f)?;
+				f.write_str(" has no body and is not an intrinsic")
+			}
+			Self::UndefinedValue(value) => {
+				Debug::fmt(&value, f)?;
+				f.write_str(" was used before it was evaluated")
+			}
+			Self::UseOfUndef(value) => {
+				Debug::fmt(&value, f)?;
+				f.write_str(" depends on an undefined value")
+			}
+			Self::TypeMismatch(value) => {
+				Debug::fmt(&value, f)?;
+				f.write_str(" has operands of the wrong type")
+			}
+			Self::OutOfBounds(value) => {
+				Debug::fmt(&value, f)?;
+				f.write_str(" accesses memory out of bounds")
+			}
+			Self::DivisionByZero(value) => {
+				Debug::fmt(&value, f)?;
+				f.write_str(" divides by zero")
+			}
+			Self::MissingTerminator(bb) => {
+				f.write_str("basic block ")?;
+				Debug::fmt(&bb, f)?;
+				f.write_str(" does not end in a terminator")
+			}
+			Self::Unsupported(value) => {
+				Debug::fmt(&value, f)?;
+				f.write_str(" cannot be evaluated")
+			}
+		}
+	}
+}
+
+impl CoreError for EvalError {}
+
+/// A function translated into dense registers and block indices, so evaluating it does not have
+/// to look anything up by id.
+#[derive(Debug)]
+struct Compiled {
+	registers: usize,
+	params: Vec<usize>,
+	blocks: Vec<CompiledBlock>,
+}
+
+#[derive(Debug)]
+struct CompiledBlock {
+	id: BasicBlockId,
+	params: Vec<usize>,
+	ops: Vec<Op>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Operand {
+	Register(usize, ValueId),
+	Constant(Value),
+}
+
+#[derive(Debug)]
+struct Target {
+	block: usize,
+	args: Vec<Operand>,
+}
+
+#[derive(Debug)]
+enum Op {
+	Alloc {
+		dest: usize,
+		slots: usize,
+	},
+	Load {
+		dest: usize,
+		src: Operand,
+		inst: ValueId,
+	},
+	Store {
+		value: Operand,
+		dest: Operand,
+		inst: ValueId,
+	},
+	StoreAggregate {
+		values: Vec<Value>,
+		dest: Operand,
+		inst: ValueId,
+	},
+	Offset {
+		dest: usize,
+		src: Operand,
+		index: Operand,
+		size: usize,
+		len: Option<usize>,
+		inst: ValueId,
+	},
+	Binary {
+		dest: usize,
+		op: BinaryOp,
+		lhs: Operand,
+		rhs: Operand,
+		inst: ValueId,
+	},
+	Branch {
+		cond: Operand,
+		then: Target,
+		otherwise: Target,
+		inst: ValueId,
+	},
+	Jump(Target),
+	Call {
+		dest: Option<usize>,
+		func: FunctionId,
+		args: Vec<Operand>,
+	},
+	Return(Option<Operand>),
+	Unsupported(ValueId),
+}
+
+impl Compiled {
+	fn new(func: &FunctionData, globals: &HashMap<ValueId, Value>) -> Self {
+		let dfg = func.dfg();
+		let layout = func.layout();
+
+		let mut registers = HashMap::new();
+		let mut register = |value: ValueId| {
+			let next = registers.len();
+			*registers.entry(value).or_insert(next)
+		};
+
+		let params = func.params().iter().map(|param| register(*param)).collect();
+
+		let blocks = layout
+			.basic_blocks()
+			.iter()
+			.enumerate()
+			.map(|(i, bb)| (*bb, i))
+			.collect::<HashMap<_, _>>();
+
+		let mut compiled = layout
+			.basic_blocks()
+			.iter()
+			.map(|bb| CompiledBlock {
+				id: *bb,
+				params: dfg
+					.basic_block(*bb)
+					.params()
+					.iter()
+					.map(|param| register(*param))
+					.collect(),
+				ops: Vec::new(),
+			})
+			.collect::<Vec<_>>();
+
+		// Every instruction gets a register up front, so operands can refer to results defined
+		// later in the layout.
+		for bb in layout.basic_blocks() {
+			for inst in layout.insts(*bb) {
+				register(*inst);
+			}
+		}
+
+		let operand = |value: ValueId| {
+			if value.is_global() {
+				return Operand::Constant(globals.get(&value).copied().unwrap_or(Value::Undef));
+			}
+
+			if let Some(register) = registers.get(&value) {
+				return Operand::Register(*register, value);
+			}
+
+			Operand::Constant(match dfg.value(value).value_ty() {
+				ValueType::Integer(i) => Value::Int(i.value()),
+				ValueType::ZeroInit(..) => Value::Int(0),
+				_ => Value::Undef,
+			})
+		};
+
+		let target = |bb: BasicBlockId, args: &[ValueId]| Target {
+			block: blocks[&bb],
+			args: args.iter().map(|arg| operand(*arg)).collect(),
+		};
+
+		for (bb, block) in layout.basic_blocks().iter().zip(&mut compiled) {
+			for inst in layout.insts(*bb) {
+				let data = dfg.value(*inst);
+				let dest = registers[inst];
+				let inst = *inst;
+
+				let op = match data.value_ty() {
+					ValueType::Alloc(..) => match &**data.ty() {
+						TypeKind::Ptr(ty) => Op::Alloc {
+							dest,
+							slots: slots(ty),
+						},
+						_ => Op::Unsupported(inst),
+					},
+					ValueType::Load(load) if is_scalar(data.ty()) => Op::Load {
+						dest,
+						src: operand(load.src()),
+						inst,
+					},
+					ValueType::Store(store) => {
+						let value = store.value();
+
+						if !value.is_global() && !is_scalar(dfg.value(value).ty()) {
+							let mut values = Vec::new();
+							flatten(dfg.values(), dfg.value(value), &mut values);
+
+							Op::StoreAggregate {
+								values,
+								dest: operand(store.dest()),
+								inst,
+							}
+						} else {
+							Op::Store {
+								value: operand(value),
+								dest: operand(store.dest()),
+								inst,
+							}
+						}
+					}
+					ValueType::GetPtr(get) => match &**data.ty() {
+						TypeKind::Ptr(ty) => Op::Offset {
+							dest,
+							src: operand(get.src()),
+							index: operand(get.index()),
+							size: slots(ty),
+							len: None,
+							inst,
+						},
+						_ => Op::Unsupported(inst),
+					},
+					ValueType::GetElementPtr(get) => match &*dfg.value_type(get.src()) {
+						TypeKind::Ptr(array) => match &**array {
+							TypeKind::Array(elem, len) => Op::Offset {
+								dest,
+								src: operand(get.src()),
+								index: operand(get.index()),
+								size: slots(elem),
+								len: Some(*len),
+								inst,
+							},
+							_ => Op::Unsupported(inst),
+						},
+						_ => Op::Unsupported(inst),
+					},
+					ValueType::Binary(binary) => Op::Binary {
+						dest,
+						op: binary.op(),
+						lhs: operand(binary.lhs()),
+						rhs: operand(binary.rhs()),
+						inst,
+					},
+					ValueType::Branch(branch) => Op::Branch {
+						cond: operand(branch.cond()),
+						then: target(branch.true_basic_block(), branch.true_args()),
+						otherwise: target(branch.false_basic_block(), branch.false_args()),
+						inst,
+					},
+					ValueType::Jump(jump) => Op::Jump(target(jump.target(), jump.args())),
+					ValueType::Call(call) => Op::Call {
+						dest: (!data.ty().is_unit()).then_some(dest),
+						func: call.callee(),
+						args: call.args().iter().map(|arg| operand(*arg)).collect(),
+					},
+					ValueType::Return(ret) => Op::Return(ret.value().map(operand)),
+					_ => Op::Unsupported(inst),
+				};
+
+				block.ops.push(op);
+			}
+		}
+
+		Self {
+			registers: registers.len(),
+			params,
+			blocks: compiled,
+		}
+	}
+
+	fn run(&self, eval: &mut Evaluator<'_>, args: &[Value]) -> Result<Option<Value>, EvalError> {
+		let mut frame = Frame {
+			registers: alloc::vec![None; self.registers],
+		};
+
+		for (param, arg) in self.params.iter().zip(args) {
+			frame.registers[*param] = Some(*arg);
+		}
+
+		let mut block = 0;
+
+		loop {
+			let Some(compiled) = self.blocks.get(block) else {
+				return Ok(None);
+			};
+
+			let mut next = None;
+
+			for op in &compiled.ops {
+				match op {
+					Op::Alloc { dest, slots } => {
+						frame.set(*dest, Value::Ptr(eval.memory.len()));
+						eval.memory
+							.extend(core::iter::repeat_n(Value::Undef, *slots));
+					}
+					Op::Load { dest, src, inst } => {
+						let slot = eval.slot(frame.get(*src)?, *inst)?;
+						frame.set(*dest, eval.memory[slot]);
+					}
+					Op::Store { value, dest, inst } => {
+						let slot = eval.slot(frame.get(*dest)?, *inst)?;
+						eval.memory[slot] = frame.get(*value)?;
+					}
+					Op::StoreAggregate { values, dest, inst } => {
+						let slot = eval.slot(frame.get(*dest)?, *inst)?;
+
+						let Some(dest) = eval.memory.get_mut(slot..slot + values.len()) else {
+							return Err(EvalError::OutOfBounds(*inst));
+						};
+
+						dest.copy_from_slice(values);
+					}
+					Op::Offset {
+						dest,
+						src,
+						index,
+						size,
+						len,
+						inst,
+					} => {
+						let index = frame.int(*index, *inst)?;
+
+						if let Some(len) = len
+							&& !usize::try_from(index).is_ok_and(|index| index < *len)
+						{
+							return Err(EvalError::OutOfBounds(*inst));
+						}
+
+						frame.set(*dest, offset(frame.get(*src)?, index, *size, *inst)?);
+					}
+					Op::Binary {
+						dest,
+						op,
+						lhs,
+						rhs,
+						inst,
+					} => {
+						let lhs = frame.int(*lhs, *inst)?;
+						let rhs = frame.int(*rhs, *inst)?;
+
+						let value = op.eval(lhs, rhs).ok_or(EvalError::DivisionByZero(*inst))?;
+						frame.set(*dest, Value::Int(value));
+					}
+					Op::Branch {
+						cond,
+						then,
+						otherwise,
+						inst,
+					} => {
+						next = Some(if matches!(frame.int(*cond, *inst)?, 0) {
+							otherwise
+						} else {
+							then
+						});
+
+						break;
+					}
+					Op::Jump(target) => {
+						next = Some(target);
+						break;
+					}
+					Op::Call { dest, func, args } => {
+						let args = args
+							.iter()
+							.map(|arg| frame.get(*arg))
+							.collect::<Result<Vec<_>, _>>()?;
+
+						let value = eval.call(*func, &args)?;
+
+						if let (Some(dest), Some(value)) = (dest, value) {
+							frame.set(*dest, value);
+						}
+					}
+					Op::Return(value) => return value.map(|value| frame.get(value)).transpose(),
+					Op::Unsupported(inst) => return Err(EvalError::Unsupported(*inst)),
+				}
+			}
+
+			let Some(target) = next else {
+				return Err(EvalError::MissingTerminator(compiled.id));
+			};
+
+			let args = target
+				.args
+				.iter()
+				.map(|arg| frame.get(*arg))
+				.collect::<Result<Vec<_>, _>>()?;
+
+			for (param, arg) in self.blocks[target.block].params.iter().zip(args) {
+				frame.set(*param, arg);
+			}
+
+			block = target.block;
+		}
+	}
+}
+
+struct Frame {
+	registers: Vec<Option<Value>>,
+}
+
+impl Frame {
+	fn get(&self, operand: Operand) -> Result<Value, EvalError> {
+		match operand {
+			Operand::Register(register, value) => {
+				self.registers[register].ok_or(EvalError::UndefinedValue(value))
+			}
+			Operand::Constant(value) => Ok(value),
+		}
+	}
+
+	fn set(&mut self, register: usize, value: Value) {
+		self.registers[register] = Some(value);
+	}
+
+	fn int(&self, operand: Operand, user: ValueId) -> Result<i32, EvalError> {
+		match self.get(operand)? {
+			Value::Int(i) => Ok(i),
+			Value::Ptr(..) => Err(EvalError::TypeMismatch(user)),
+			Value::Undef => Err(EvalError::UseOfUndef(user)),
+		}
+	}
+}
+
+fn offset(ptr: Value, index: i32, size: usize, inst: ValueId) -> Result<Value, EvalError> {
+	let Value::Ptr(base) = ptr else {
+		return Err(EvalError::TypeMismatch(inst));
+	};
+
+	isize::try_from(size)
+		.ok()
+		.and_then(|size| (index as isize).checked_mul(size))
+		.and_then(|offset| base.checked_add_signed(offset))
+		.map(Value::Ptr)
+		.ok_or(EvalError::OutOfBounds(inst))
+}
+
+/// The number of memory slots a value of the type takes up.
+fn slots(ty: &Type) -> usize {
+	match &**ty {
+		TypeKind::Int(..) | TypeKind::Ptr(..) => 1,
+		TypeKind::Array(base, len) => slots(base) * len,
+		TypeKind::Unit | TypeKind::Function(..) => 0,
+	}
+}
+
+fn is_scalar(ty: &Type) -> bool {
+	matches!(**ty, TypeKind::Int(..) | TypeKind::Ptr(..))
+}
+
+/// Writes the slots of a constant into `out`.
+fn flatten(values: &HashMap<ValueId, ValueData>, data: &ValueData, out: &mut Vec<Value>) {
+	match data.value_ty() {
+		ValueType::Integer(i) => out.push(Value::Int(i.value())),
+		ValueType::ZeroInit(..) => {
+			out.extend(core::iter::repeat_n(Value::Int(0), slots(data.ty())));
+		}
+		ValueType::Aggregate(aggregate) => {
+			for element in aggregate.values() {
+				flatten(values, &values[element], out);
+			}
+		}
+		_ => out.extend(core::iter::repeat_n(Value::Undef, slots(data.ty()))),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use vmm_ir::Instruction;
+
+	use super::{EvalError, Evaluator, Value};
+	use crate::{Lowering, ir::Program};
+
+	fn parse(text: &str) -> Program {
+		text.parse().unwrap()
+	}
+
+	#[test]
+	fn passes_block_args() -> Result<(), EvalError> {
+		let program = parse(
+			"global @values = alloc [i32, 4], {1, 2, 3, 4}
+
+decl @putchar(i32)
+
+fun @sum(%n: i32): i32 {
+%entry:
+  jump %cond(0, %n)
+
+%cond(%acc: i32, %i: i32):
+  br %i, %body, %end
+
+%body:
+  %next = sub %i, 1
+  %elem = getelemptr @values, %next
+  %x = load %elem
+  %total = add %acc, %x
+  jump %cond(%total, %next)
+
+%end:
+  ret %acc
+}
+
+fun @main(): i32 {
+%entry:
+  %s = call @sum(4)
+  %c = add %s, 55
+  call @putchar(%c)
+  ret %s
+}",
+		);
+
+		let mut evaluator = Evaluator::new(&program);
+
+		assert_eq!(evaluator.run()?, Some(Value::Int(10)));
+		assert_eq!(evaluator.output(), b"A");
+
+		Ok(())
+	}
+
+	#[test]
+	fn runs_lowered_programs() -> Result<(), EvalError> {
+		let program = Lowering::new()
+			.and_with_tape_len(4)
+			.lower(&[
+				Instruction::read(),
+				Instruction::dynamic_loop([
+					Instruction::inc_val(-1),
+					Instruction::move_ptr(1),
+					Instruction::inc_val(2),
+					Instruction::move_ptr(-1),
+				]),
+				Instruction::move_ptr(1),
+				Instruction::write_once(),
+			])
+			.unwrap();
+
+		let mut evaluator = Evaluator::new(&program).and_with_input(*b"!");
+		evaluator.run()?;
+
+		assert_eq!(evaluator.output(), b"B");
+		assert_eq!(
+			evaluator.global(program.inst_layout()[0]),
+			Some(&[Value::Int(0), Value::Int(66), Value::Int(0), Value::Int(0)][..])
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn reports_errors() {
+		let program = parse(
+			"fun @main(): i32 {
+%entry:
+  %x = div 1, 0
+  ret %x
+}",
+		);
+
+		assert!(matches!(
+			Evaluator::new(&program).run(),
+			Err(EvalError::DivisionByZero(..))
+		));
+
+		let program = parse(
+			"fun @main(): i32 {
+%entry:
+  %a = alloc [i32, 2]
+  %p = getelemptr %a, 2
+  %x = load %p
+  ret %x
+}",
+		);
+
+		assert!(matches!(
+			Evaluator::new(&program).run(),
+			Err(EvalError::OutOfBounds(..))
+		));
+
+		let program = parse(
+			"fun @main(): i32 {
+%entry:
+  %a = alloc i32
+  %x = load %a
+  %y = add %x, 1
+  ret %y
+}",
+		);
+
+		assert!(matches!(
+			Evaluator::new(&program).run(),
+			Err(EvalError::UseOfUndef(..))
+		));
+
+		assert_eq!(
+			Evaluator::new(&Program::new()).run(),
+			Err(EvalError::NoMain)
+		);
+	}
+}
@@ -0,0 +1,272 @@
+use alloc::{string::String, vec::Vec};
+
+use super::{
+	Aggregate, Alloc, BasicBlockData, BasicBlockId, Binary, BinaryOp, BlockArgRef, Branch, Call,
+	DataFlowGraph, FunctionId, GetElementPtr, GetPtr, GlobalAlloc, Integer, Jump, Load, Program,
+	Return, Store, Type, TypeKind, Undef, ValueData, ValueId, ValueType, ZeroInit,
+};
+
+/// Creates values, inferring their types from their operands.
+pub trait ValueBuilder: Sized {
+	fn raw(self, data: ValueData) -> ValueId;
+
+	fn value_type(&self, value: ValueId) -> Type;
+
+	fn integer(self, value: i32) -> ValueId {
+		self.raw(ValueData::new(
+			Type::i32(),
+			ValueType::Integer(Integer::I32(value)),
+		))
+	}
+
+	fn zero_init(self, ty: Type) -> ValueId {
+		self.raw(ValueData::new(ty, ValueType::ZeroInit(ZeroInit)))
+	}
+
+	fn undef(self, ty: Type) -> ValueId {
+		self.raw(ValueData::new(ty, ValueType::Undef(Undef)))
+	}
+
+	fn aggregate(self, values: Vec<ValueId>) -> ValueId {
+		let first = values.first().expect("aggregate must not be empty");
+		let ty = self.value_type(*first).into_array(values.len());
+
+		self.raw(ValueData::new(
+			ty,
+			ValueType::Aggregate(Aggregate::new(values)),
+		))
+	}
+}
+
+/// Creates the instructions of a function.
+pub trait LocalInstBuilder: ValueBuilder {
+	fn function_type(&self, func: FunctionId) -> Type;
+
+	fn alloc(self, ty: Type) -> ValueId {
+		self.raw(ValueData::new(ty.into_ptr(), ValueType::Alloc(Alloc)))
+	}
+
+	fn load(self, src: ValueId) -> ValueId {
+		let TypeKind::Ptr(ty) = TypeKind::from(self.value_type(src)) else {
+			panic!("load source must be a pointer");
+		};
+
+		self.raw(ValueData::new(ty, ValueType::Load(Load::new(src))))
+	}
+
+	fn store(self, value: ValueId, dest: ValueId) -> ValueId {
+		self.raw(ValueData::new(
+			Type::unit(),
+			ValueType::Store(Store::new(value, dest)),
+		))
+	}
+
+	fn get_ptr(self, src: ValueId, index: ValueId) -> ValueId {
+		let ty = self.value_type(src);
+		assert!(matches!(*ty, TypeKind::Ptr(..)), "source must be a pointer");
+
+		self.raw(ValueData::new(
+			ty,
+			ValueType::GetPtr(GetPtr::new(src, index)),
+		))
+	}
+
+	fn get_element_ptr(self, src: ValueId, index: ValueId) -> ValueId {
+		let ty = match TypeKind::from(self.value_type(src)) {
+			TypeKind::Ptr(base) => match TypeKind::from(base) {
+				TypeKind::Array(elem, _) => elem.into_ptr(),
+				_ => panic!("source must be a pointer to an array"),
+			},
+			_ => panic!("source must be a pointer to an array"),
+		};
+
+		self.raw(ValueData::new(
+			ty,
+			ValueType::GetElementPtr(GetElementPtr::new(src, index)),
+		))
+	}
+
+	fn binary(self, op: BinaryOp, lhs: ValueId, rhs: ValueId) -> ValueId {
+		self.raw(ValueData::new(
+			Type::i32(),
+			ValueType::Binary(Binary::new(op, lhs, rhs)),
+		))
+	}
+
+	fn branch(
+		self,
+		cond: ValueId,
+		true_basic_block: BasicBlockId,
+		false_basic_block: BasicBlockId,
+	) -> ValueId {
+		self.raw(ValueData::new(
+			Type::unit(),
+			ValueType::Branch(Branch::new(cond, true_basic_block, false_basic_block)),
+		))
+	}
+
+	fn branch_with_args(
+		self,
+		cond: ValueId,
+		true_basic_block: BasicBlockId,
+		true_args: Vec<ValueId>,
+		false_basic_block: BasicBlockId,
+		false_args: Vec<ValueId>,
+	) -> ValueId {
+		self.raw(ValueData::new(
+			Type::unit(),
+			ValueType::Branch(Branch::with_args(
+				cond,
+				true_basic_block,
+				true_args,
+				false_basic_block,
+				false_args,
+			)),
+		))
+	}
+
+	fn jump(self, target: BasicBlockId) -> ValueId {
+		self.raw(ValueData::new(
+			Type::unit(),
+			ValueType::Jump(Jump::new(target)),
+		))
+	}
+
+	fn jump_with_args(self, target: BasicBlockId, args: Vec<ValueId>) -> ValueId {
+		self.raw(ValueData::new(
+			Type::unit(),
+			ValueType::Jump(Jump::with_args(target, args)),
+		))
+	}
+
+	fn call(self, callee: FunctionId, args: Vec<ValueId>) -> ValueId {
+		let TypeKind::Function(_, ty) = TypeKind::from(self.function_type(callee)) else {
+			panic!("callee must be a function");
+		};
+
+		self.raw(ValueData::new(ty, ValueType::Call(Call::new(callee, args))))
+	}
+
+	fn ret(self, value: Option<ValueId>) -> ValueId {
+		self.raw(ValueData::new(
+			Type::unit(),
+			ValueType::Return(Return::new(value)),
+		))
+	}
+}
+
+/// Creates values that live outside of any function.
+pub trait GlobalInstBuilder: ValueBuilder {
+	fn global_alloc(self, init: ValueId) -> ValueId {
+		let ty = self.value_type(init).into_ptr();
+
+		self.raw(ValueData::new(
+			ty,
+			ValueType::GlobalAlloc(GlobalAlloc::new(init)),
+		))
+	}
+}
+
+/// Adds a new value to a [`DataFlowGraph`].
+pub struct LocalBuilder<'a> {
+	pub(crate) dfg: &'a mut DataFlowGraph,
+}
+
+impl ValueBuilder for LocalBuilder<'_> {
+	fn raw(self, data: ValueData) -> ValueId {
+		let value = ValueId::local();
+		self.dfg.insert_value(value, data);
+		value
+	}
+
+	fn value_type(&self, value: ValueId) -> Type {
+		self.dfg.value_type(value)
+	}
+}
+
+impl LocalInstBuilder for LocalBuilder<'_> {
+	fn function_type(&self, func: FunctionId) -> Type {
+		self.dfg.function_type(func)
+	}
+}
+
+/// Replaces the data of an existing value in a [`DataFlowGraph`], keeping its users.
+pub struct ReplaceBuilder<'a> {
+	pub(crate) dfg: &'a mut DataFlowGraph,
+	pub(crate) value: ValueId,
+}
+
+impl ValueBuilder for ReplaceBuilder<'_> {
+	fn raw(self, data: ValueData) -> ValueId {
+		self.dfg.replace_value(self.value, data);
+		self.value
+	}
+
+	fn value_type(&self, value: ValueId) -> Type {
+		self.dfg.value_type(value)
+	}
+}
+
+impl LocalInstBuilder for ReplaceBuilder<'_> {
+	fn function_type(&self, func: FunctionId) -> Type {
+		self.dfg.function_type(func)
+	}
+}
+
+/// Adds a new basic block to a [`DataFlowGraph`].
+pub struct BasicBlockBuilder<'a> {
+	pub(crate) dfg: &'a mut DataFlowGraph,
+}
+
+impl BasicBlockBuilder<'_> {
+	#[must_use]
+	pub fn basic_block(self, name: Option<String>) -> BasicBlockId {
+		let data = match name {
+			Some(name) => BasicBlockData::with_name(name),
+			None => BasicBlockData::new(),
+		};
+
+		self.dfg.insert_basic_block(data)
+	}
+
+	pub fn basic_block_with_params(
+		self,
+		name: Option<String>,
+		params_ty: impl IntoIterator<Item = Type>,
+	) -> BasicBlockId {
+		let params = params_ty
+			.into_iter()
+			.enumerate()
+			.map(|(i, ty)| {
+				LocalBuilder { dfg: self.dfg }.raw(ValueData::new(
+					ty,
+					ValueType::BlockArgRef(BlockArgRef::new(i)),
+				))
+			})
+			.collect();
+
+		let data = match name {
+			Some(name) => BasicBlockData::with_name_and_params(name, params),
+			None => BasicBlockData::with_params(params),
+		};
+
+		self.dfg.insert_basic_block(data)
+	}
+}
+
+/// Adds a new global value to a [`Program`].
+pub struct GlobalBuilder<'a> {
+	pub(crate) program: &'a mut Program,
+}
+
+impl ValueBuilder for GlobalBuilder<'_> {
+	fn raw(self, data: ValueData) -> ValueId {
+		self.program.insert_global(data)
+	}
+
+	fn value_type(&self, value: ValueId) -> Type {
+		self.program.borrow_value(value).ty().clone()
+	}
+}
+
+impl GlobalInstBuilder for GlobalBuilder<'_> {}
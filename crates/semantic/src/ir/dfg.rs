@@ -0,0 +1,233 @@
+use alloc::{
+	rc::{Rc, Weak},
+	string::String,
+	vec::Vec,
+};
+use core::cell::RefCell;
+
+use hashbrown::HashMap;
+
+use super::{
+	BasicBlockBuilder, BasicBlockData, BasicBlockId, FunctionId, LocalBuilder, ReplaceBuilder,
+	Type, ValueData, ValueId, ValueType,
+};
+
+/// The values and basic blocks of a function, along with who uses them.
+#[derive(Debug, Default)]
+pub struct DataFlowGraph {
+	values: HashMap<ValueId, ValueData>,
+	basic_blocks: HashMap<BasicBlockId, BasicBlockData>,
+	pub(crate) globals: Weak<RefCell<HashMap<ValueId, ValueData>>>,
+	pub(crate) function_types: Weak<RefCell<HashMap<FunctionId, Type>>>,
+}
+
+impl DataFlowGraph {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub const fn new_value(&mut self) -> LocalBuilder<'_> {
+		LocalBuilder { dfg: self }
+	}
+
+	pub const fn replace_value_with(&mut self, value: ValueId) -> ReplaceBuilder<'_> {
+		ReplaceBuilder { dfg: self, value }
+	}
+
+	pub fn remove_value(&mut self, value: ValueId) -> ValueData {
+		let data = self
+			.values
+			.remove(&value)
+			.expect("value does not exist in the function");
+		assert!(data.used_by().is_empty(), "cannot remove a value in use");
+
+		self.remove_uses(value, data.value_ty());
+
+		data
+	}
+
+	/// Makes every user of a local value use another value instead.
+	pub fn replace_uses(&mut self, value: ValueId, with: ValueId) {
+		let users = self
+			.value(value)
+			.used_by()
+			.iter()
+			.copied()
+			.collect::<Vec<_>>();
+
+		for user in users {
+			let mut data = self.value(user).clone();
+			data.value_ty_mut().for_each_value_mut(|operand| {
+				if *operand == value {
+					*operand = with;
+				}
+			});
+
+			self.replace_value(user, data);
+		}
+	}
+
+	pub fn set_value_name(&mut self, value: ValueId, name: impl Into<String>) {
+		self.values
+			.get_mut(&value)
+			.expect("value does not exist in the function")
+			.set_name(name);
+	}
+
+	#[must_use]
+	pub fn value(&self, value: ValueId) -> &ValueData {
+		self.values
+			.get(&value)
+			.expect("value does not exist in the function")
+	}
+
+	#[must_use]
+	pub const fn values(&self) -> &HashMap<ValueId, ValueData> {
+		&self.values
+	}
+
+	/// The type of a local or global value.
+	#[must_use]
+	pub fn value_type(&self, value: ValueId) -> Type {
+		if value.is_global() {
+			let globals = self.globals();
+			let globals = globals.borrow();
+
+			globals
+				.get(&value)
+				.expect("global value does not exist in the program")
+				.ty()
+				.clone()
+		} else {
+			self.value(value).ty().clone()
+		}
+	}
+
+	#[must_use]
+	pub fn function_type(&self, func: FunctionId) -> Type {
+		self.function_types
+			.upgrade()
+			.expect("function is not part of a program")
+			.borrow()
+			.get(&func)
+			.expect("function does not exist in the program")
+			.clone()
+	}
+
+	pub const fn new_basic_block(&mut self) -> BasicBlockBuilder<'_> {
+		BasicBlockBuilder { dfg: self }
+	}
+
+	pub fn remove_basic_block(&mut self, basic_block: BasicBlockId) -> BasicBlockData {
+		let data = self
+			.basic_blocks
+			.remove(&basic_block)
+			.expect("basic block does not exist in the function");
+		assert!(
+			data.used_by().is_empty(),
+			"cannot remove a basic block in use"
+		);
+
+		for param in data.params() {
+			self.remove_value(*param);
+		}
+
+		data
+	}
+
+	#[must_use]
+	pub fn basic_block(&self, basic_block: BasicBlockId) -> &BasicBlockData {
+		self.basic_blocks
+			.get(&basic_block)
+			.expect("basic block does not exist in the function")
+	}
+
+	pub fn basic_block_mut(&mut self, basic_block: BasicBlockId) -> &mut BasicBlockData {
+		self.basic_blocks
+			.get_mut(&basic_block)
+			.expect("basic block does not exist in the function")
+	}
+
+	#[must_use]
+	pub const fn basic_blocks(&self) -> &HashMap<BasicBlockId, BasicBlockData> {
+		&self.basic_blocks
+	}
+
+	pub(crate) fn insert_value(&mut self, value: ValueId, data: ValueData) {
+		self.add_uses(value, data.value_ty());
+		self.values.insert(value, data);
+	}
+
+	pub(crate) fn replace_value(&mut self, value: ValueId, mut data: ValueData) {
+		let old = self
+			.values
+			.remove(&value)
+			.expect("value does not exist in the function");
+
+		self.remove_uses(value, old.value_ty());
+		self.add_uses(value, data.value_ty());
+
+		if let (None, Some(name)) = (data.name(), old.name()) {
+			data.set_name(name.clone());
+		}
+		data.used_by = old.used_by;
+
+		self.values.insert(value, data);
+	}
+
+	pub(crate) fn insert_basic_block(&mut self, data: BasicBlockData) -> BasicBlockId {
+		let basic_block = BasicBlockId::next();
+		self.basic_blocks.insert(basic_block, data);
+		basic_block
+	}
+
+	fn globals(&self) -> Rc<RefCell<HashMap<ValueId, ValueData>>> {
+		self.globals
+			.upgrade()
+			.expect("function is not part of a program")
+	}
+
+	fn add_uses(&mut self, user: ValueId, ty: &ValueType) {
+		for value in ty.values() {
+			if value.is_global() {
+				self.globals()
+					.borrow_mut()
+					.get_mut(&value)
+					.expect("global value does not exist in the program")
+					.used_by
+					.insert(user);
+			} else {
+				self.values
+					.get_mut(&value)
+					.expect("operand does not exist in the function")
+					.used_by
+					.insert(user);
+			}
+		}
+
+		for basic_block in ty.basic_blocks() {
+			self.basic_block_mut(basic_block).used_by.insert(user);
+		}
+	}
+
+	fn remove_uses(&mut self, user: ValueId, ty: &ValueType) {
+		for value in ty.values() {
+			if value.is_global() {
+				if let Some(globals) = self.globals.upgrade()
+					&& let Some(data) = globals.borrow_mut().get_mut(&value)
+				{
+					data.used_by.remove(&user);
+				}
+			} else if let Some(data) = self.values.get_mut(&value) {
+				data.used_by.remove(&user);
+			}
+		}
+
+		for basic_block in ty.basic_blocks() {
+			if let Some(data) = self.basic_blocks.get_mut(&basic_block) {
+				data.used_by.remove(&user);
+			}
+		}
+	}
+}
@@ -40,7 +40,7 @@ impl Iterator for ValueIter<'_> {
 				if cur == $index {
 					Some($head)
 				} else {
-					field_use!(@expand $index + 4 $(,$tail)*)
+					field_use!(@expand $index + 1 $(,$tail)*)
 				}
 			};
 		}
@@ -7,8 +7,8 @@ use serde::{Deserialize, Serialize};
 
 pub use self::iter::*;
 use super::{
-	Aggregate, Alloc, Binary, BlockArgRef, Branch, Call, FuncArgRef, GetElementPtr, GetPtr,
-	GlobalAlloc, Integer, Jump, Load, Return, Store, Type, Undef, ValueId, ZeroInit,
+	Aggregate, Alloc, BasicBlockId, Binary, BlockArgRef, Branch, Call, FuncArgRef, GetElementPtr,
+	GetPtr, GlobalAlloc, Integer, Jump, Load, Return, Store, Type, Undef, ValueId, ZeroInit,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -35,6 +35,18 @@ impl BasicBlockData {
 		Self::create(Some(name.into()), params)
 	}
 
+	#[must_use]
+	pub const fn name(&self) -> Option<&String> {
+		self.name.as_ref()
+	}
+
+	pub fn set_name(&mut self, name: impl Into<String>) {
+		let name: String = name.into();
+		debug_assert!(name.len() > 1 && name.starts_with('%'));
+
+		self.name = Some(name);
+	}
+
 	#[must_use]
 	pub const fn params(&self) -> &Vec<ValueId> {
 		&self.params
@@ -164,12 +176,18 @@ impl ValueType {
 				| Self::GetPtr(..)
 				| Self::GetElementPtr(..)
 				| Self::Binary(..)
+				| Self::Branch(..)
 				| Self::Jump(..)
 				| Self::Call(..)
 				| Self::Return(..)
 		)
 	}
 
+	#[must_use]
+	pub const fn is_terminator(&self) -> bool {
+		matches!(self, Self::Branch(..) | Self::Jump(..) | Self::Return(..))
+	}
+
 	#[must_use]
 	pub const fn values(&self) -> ValueIter<'_> {
 		ValueIter::new(self)
@@ -179,4 +197,62 @@ impl ValueType {
 	pub const fn basic_blocks(&self) -> BasicBlockIter<'_> {
 		BasicBlockIter::new(self)
 	}
+
+	/// Calls `f` on every operand, in the same order as [`Self::values`].
+	pub fn for_each_value_mut(&mut self, mut f: impl FnMut(&mut ValueId)) {
+		match self {
+			Self::Aggregate(v) => v.values_mut().iter_mut().for_each(f),
+			Self::GlobalAlloc(v) => f(v.init_mut()),
+			Self::Load(v) => f(v.src_mut()),
+			Self::Store(v) => {
+				f(v.value_mut());
+				f(v.dest_mut());
+			}
+			Self::GetPtr(v) => {
+				f(v.src_mut());
+				f(v.index_mut());
+			}
+			Self::GetElementPtr(v) => {
+				f(v.src_mut());
+				f(v.index_mut());
+			}
+			Self::Binary(v) => {
+				f(v.lhs_mut());
+				f(v.rhs_mut());
+			}
+			Self::Branch(v) => {
+				f(v.cond_mut());
+				v.true_args_mut().iter_mut().for_each(&mut f);
+				v.false_args_mut().iter_mut().for_each(f);
+			}
+			Self::Jump(v) => v.args_mut().iter_mut().for_each(f),
+			Self::Call(v) => v.args_mut().iter_mut().for_each(f),
+			Self::Return(v) => {
+				if let Some(value) = v.value_mut() {
+					f(value);
+				}
+			}
+			_ => {}
+		}
+	}
+
+	/// The arguments passed to each target, if this is a branch or a jump.
+	pub fn target_args_mut(&mut self) -> impl Iterator<Item = (BasicBlockId, &mut Vec<ValueId>)> {
+		let (first, second) = match self {
+			Self::Branch(v) => {
+				let (true_basic_block, false_basic_block) =
+					(v.true_basic_block(), v.false_basic_block());
+				let (true_args, false_args) = v.args_mut();
+
+				(
+					Some((true_basic_block, true_args)),
+					Some((false_basic_block, false_args)),
+				)
+			}
+			Self::Jump(v) => (Some((v.target(), v.args_mut())), None),
+			_ => (None, None),
+		};
+
+		first.into_iter().chain(second)
+	}
 }
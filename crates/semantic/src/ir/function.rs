@@ -0,0 +1,110 @@
+use alloc::{string::String, vec::Vec};
+
+use super::{
+	DataFlowGraph, FuncArgRef, Layout, Type, TypeKind, ValueBuilder as _, ValueData, ValueId,
+	ValueType,
+};
+
+/// A function definition or declaration.
+#[derive(Debug)]
+pub struct FunctionData {
+	ty: Type,
+	name: String,
+	params: Vec<ValueId>,
+	dfg: DataFlowGraph,
+	layout: Layout,
+}
+
+impl FunctionData {
+	pub fn new(name: impl Into<String>, params_ty: Vec<Type>, ret_ty: Type) -> Self {
+		let mut dfg = DataFlowGraph::new();
+
+		let params = params_ty
+			.iter()
+			.enumerate()
+			.map(|(i, ty)| {
+				dfg.new_value().raw(ValueData::new(
+					ty.clone(),
+					ValueType::FuncArgRef(FuncArgRef::new(i)),
+				))
+			})
+			.collect();
+
+		Self::create(name.into(), params_ty, ret_ty, params, dfg)
+	}
+
+	/// A function without a body, which is provided by the environment.
+	pub fn new_decl(name: impl Into<String>, params_ty: Vec<Type>, ret_ty: Type) -> Self {
+		Self::create(
+			name.into(),
+			params_ty,
+			ret_ty,
+			Vec::new(),
+			DataFlowGraph::new(),
+		)
+	}
+
+	#[must_use]
+	pub const fn ty(&self) -> &Type {
+		&self.ty
+	}
+
+	#[must_use]
+	pub fn ret_ty(&self) -> &Type {
+		match &*self.ty {
+			TypeKind::Function(_, ret) => ret,
+			_ => unreachable!(),
+		}
+	}
+
+	#[must_use]
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	#[must_use]
+	pub fn params(&self) -> &[ValueId] {
+		&self.params
+	}
+
+	#[must_use]
+	pub const fn dfg(&self) -> &DataFlowGraph {
+		&self.dfg
+	}
+
+	pub const fn dfg_mut(&mut self) -> &mut DataFlowGraph {
+		&mut self.dfg
+	}
+
+	#[must_use]
+	pub const fn layout(&self) -> &Layout {
+		&self.layout
+	}
+
+	pub const fn layout_mut(&mut self) -> &mut Layout {
+		&mut self.layout
+	}
+
+	#[must_use]
+	pub fn is_decl(&self) -> bool {
+		self.layout.basic_blocks().is_empty()
+	}
+
+	fn create(
+		name: String,
+		params_ty: Vec<Type>,
+		ret_ty: Type,
+		params: Vec<ValueId>,
+		dfg: DataFlowGraph,
+	) -> Self {
+		debug_assert!(name.len() > 1 && name.starts_with('@'));
+
+		Self {
+			ty: Type::function(params_ty, ret_ty),
+			name,
+			params,
+			dfg,
+			layout: Layout::new(),
+		}
+	}
+}
@@ -0,0 +1,99 @@
+use alloc::vec::Vec;
+
+use hashbrown::HashMap;
+
+use super::{BasicBlockId, ValueId};
+
+/// The order of the basic blocks in a function, and of the instructions in each basic block.
+#[derive(Debug, Default, Clone)]
+pub struct Layout {
+	order: Vec<BasicBlockId>,
+	insts: HashMap<BasicBlockId, Vec<ValueId>>,
+	parents: HashMap<ValueId, BasicBlockId>,
+}
+
+impl Layout {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	#[must_use]
+	pub fn basic_blocks(&self) -> &[BasicBlockId] {
+		&self.order
+	}
+
+	#[must_use]
+	pub fn entry_basic_block(&self) -> Option<BasicBlockId> {
+		self.order.first().copied()
+	}
+
+	#[must_use]
+	pub fn contains_basic_block(&self, basic_block: BasicBlockId) -> bool {
+		self.insts.contains_key(&basic_block)
+	}
+
+	pub fn push_basic_block(&mut self, basic_block: BasicBlockId) {
+		assert!(
+			self.insts.insert(basic_block, Vec::new()).is_none(),
+			"basic block is already in the layout"
+		);
+
+		self.order.push(basic_block);
+	}
+
+	/// Removes a basic block, returning its instructions.
+	pub fn remove_basic_block(&mut self, basic_block: BasicBlockId) -> Option<Vec<ValueId>> {
+		let insts = self.insts.remove(&basic_block)?;
+
+		self.order.retain(|bb| *bb != basic_block);
+
+		for inst in &insts {
+			self.parents.remove(inst);
+		}
+
+		Some(insts)
+	}
+
+	#[must_use]
+	pub fn insts(&self, basic_block: BasicBlockId) -> &[ValueId] {
+		self.insts
+			.get(&basic_block)
+			.expect("basic block is not in the layout")
+	}
+
+	pub fn push_inst(&mut self, basic_block: BasicBlockId, inst: ValueId) {
+		let len = self.insts(basic_block).len();
+		self.insert_inst(basic_block, len, inst);
+	}
+
+	pub fn insert_inst(&mut self, basic_block: BasicBlockId, index: usize, inst: ValueId) {
+		let insts = self
+			.insts
+			.get_mut(&basic_block)
+			.expect("basic block is not in the layout");
+
+		assert!(
+			self.parents.insert(inst, basic_block).is_none(),
+			"instruction is already in the layout"
+		);
+
+		insts.insert(index, inst);
+	}
+
+	/// Removes an instruction, returning the basic block it was in.
+	pub fn remove_inst(&mut self, inst: ValueId) -> Option<BasicBlockId> {
+		let basic_block = self.parents.remove(&inst)?;
+
+		if let Some(insts) = self.insts.get_mut(&basic_block) {
+			insts.retain(|i| *i != inst);
+		}
+
+		Some(basic_block)
+	}
+
+	#[must_use]
+	pub fn parent_basic_block(&self, inst: ValueId) -> Option<BasicBlockId> {
+		self.parents.get(&inst).copied()
+	}
+}
@@ -1,6 +1,13 @@
+mod builder;
+mod dfg;
 mod entities;
+mod function;
 mod id;
+mod layout;
+mod program;
 mod types;
 mod values;
 
-pub use self::{entities::*, id::*, types::*, values::*};
+pub use self::{
+	builder::*, dfg::*, entities::*, function::*, id::*, layout::*, program::*, types::*, values::*,
+};
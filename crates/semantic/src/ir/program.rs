@@ -0,0 +1,134 @@
+use alloc::{rc::Rc, string::String, vec::Vec};
+use core::cell::{Ref, RefCell};
+
+use hashbrown::HashMap;
+
+use super::{FunctionData, FunctionId, GlobalBuilder, Type, ValueData, ValueId};
+
+/// A whole program, made of global values and functions.
+#[derive(Debug, Default)]
+pub struct Program {
+	values: Rc<RefCell<HashMap<ValueId, ValueData>>>,
+	inst_layout: Vec<ValueId>,
+	funcs: HashMap<FunctionId, FunctionData>,
+	func_types: Rc<RefCell<HashMap<FunctionId, Type>>>,
+	func_layout: Vec<FunctionId>,
+}
+
+impl Program {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub const fn new_value(&mut self) -> GlobalBuilder<'_> {
+		GlobalBuilder { program: self }
+	}
+
+	#[allow(
+		clippy::needless_pass_by_ref_mut,
+		reason = "names are part of the program"
+	)]
+	pub fn set_value_name(&mut self, value: ValueId, name: impl Into<String>) {
+		self.values
+			.borrow_mut()
+			.get_mut(&value)
+			.expect("global value does not exist in the program")
+			.set_name(name);
+	}
+
+	#[must_use]
+	pub fn borrow_value(&self, value: ValueId) -> Ref<'_, ValueData> {
+		Ref::map(self.values.borrow(), |values| {
+			values
+				.get(&value)
+				.expect("global value does not exist in the program")
+		})
+	}
+
+	#[must_use]
+	pub fn borrow_values(&self) -> Ref<'_, HashMap<ValueId, ValueData>> {
+		self.values.borrow()
+	}
+
+	/// The global allocations, in the order they were created.
+	#[must_use]
+	pub fn inst_layout(&self) -> &[ValueId] {
+		&self.inst_layout
+	}
+
+	pub fn new_func(&mut self, mut data: FunctionData) -> FunctionId {
+		let func = FunctionId::next();
+
+		data.dfg_mut().globals = Rc::downgrade(&self.values);
+		data.dfg_mut().function_types = Rc::downgrade(&self.func_types);
+
+		self.func_types.borrow_mut().insert(func, data.ty().clone());
+		self.funcs.insert(func, data);
+		self.func_layout.push(func);
+
+		func
+	}
+
+	pub fn remove_func(&mut self, func: FunctionId) -> Option<FunctionData> {
+		let data = self.funcs.remove(&func)?;
+
+		self.func_types.borrow_mut().remove(&func);
+		self.func_layout.retain(|f| *f != func);
+
+		Some(data)
+	}
+
+	#[must_use]
+	pub fn func(&self, func: FunctionId) -> &FunctionData {
+		self.funcs
+			.get(&func)
+			.expect("function does not exist in the program")
+	}
+
+	pub fn func_mut(&mut self, func: FunctionId) -> &mut FunctionData {
+		self.funcs
+			.get_mut(&func)
+			.expect("function does not exist in the program")
+	}
+
+	#[must_use]
+	pub const fn funcs(&self) -> &HashMap<FunctionId, FunctionData> {
+		&self.funcs
+	}
+
+	pub const fn funcs_mut(&mut self) -> &mut HashMap<FunctionId, FunctionData> {
+		&mut self.funcs
+	}
+
+	/// The functions, in the order they were created.
+	#[must_use]
+	pub fn func_layout(&self) -> &[FunctionId] {
+		&self.func_layout
+	}
+
+	pub(crate) fn insert_global(&mut self, data: ValueData) -> ValueId {
+		let value = ValueId::global();
+		let is_global_alloc = data.value_ty().is_global_alloc();
+
+		{
+			let mut values = self.values.borrow_mut();
+
+			for operand in data.value_ty().values() {
+				values
+					.get_mut(&operand)
+					.expect("operand does not exist in the program")
+					.used_by
+					.insert(value);
+			}
+
+			values.insert(value, data);
+		}
+
+		if is_global_alloc {
+			self.inst_layout.push(value);
+		}
+
+		value
+	}
+}
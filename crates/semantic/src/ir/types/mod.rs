@@ -117,7 +117,7 @@ impl Display for TypeKind {
 				f.write_char('[')?;
 
 				Display::fmt(&t, f)?;
-				f.write_char(';')?;
+				f.write_str(", ")?;
 				Display::fmt(&len, f)?;
 				f.write_char(']')?;
 			}
@@ -167,10 +167,10 @@ mod tests {
 	fn fmt() {
 		assert_eq!(Type::i32().to_string(), "i32");
 		assert_eq!(Type::unit().to_string(), "unit");
-		assert_eq!(Type::i32().into_array(10).to_string(), "[i32;10]");
+		assert_eq!(Type::i32().into_array(10).to_string(), "[i32, 10]");
 		assert_eq!(
 			Type::i32().into_array(2).into_array(3).to_string(),
-			"[[i32;2];3]"
+			"[[i32, 2], 3]"
 		);
 		assert_eq!(Type::i32().into_ptr().into_ptr().to_string(), "**i32");
 		assert_eq!(Type::function([], Type::unit()).to_string(), "()");
@@ -11,6 +11,15 @@ pub enum Integer {
 	I32(i32),
 }
 
+impl Integer {
+	#[must_use]
+	pub const fn value(self) -> i32 {
+		match self {
+			Self::I32(value) => value,
+		}
+	}
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct ZeroInit;
@@ -24,6 +33,11 @@ pub struct Undef;
 pub struct Aggregate(Vec<ValueId>);
 
 impl Aggregate {
+	#[must_use]
+	pub const fn new(values: Vec<ValueId>) -> Self {
+		Self(values)
+	}
+
 	#[must_use]
 	pub const fn values(&self) -> &Vec<ValueId> {
 		&self.0
@@ -42,6 +56,11 @@ pub struct FuncArgRef {
 }
 
 impl FuncArgRef {
+	#[must_use]
+	pub const fn new(index: usize) -> Self {
+		Self { index }
+	}
+
 	#[must_use]
 	pub const fn index(self) -> usize {
 		self.index
@@ -60,6 +79,11 @@ pub struct BlockArgRef {
 }
 
 impl BlockArgRef {
+	#[must_use]
+	pub const fn new(index: usize) -> Self {
+		Self { index }
+	}
+
 	#[must_use]
 	pub const fn index(self) -> usize {
 		self.index
@@ -82,6 +106,11 @@ pub struct GlobalAlloc {
 }
 
 impl GlobalAlloc {
+	#[must_use]
+	pub const fn new(init: ValueId) -> Self {
+		Self { init }
+	}
+
 	#[must_use]
 	pub const fn init(self) -> ValueId {
 		self.init
@@ -100,6 +129,11 @@ pub struct Load {
 }
 
 impl Load {
+	#[must_use]
+	pub const fn new(src: ValueId) -> Self {
+		Self { src }
+	}
+
 	#[must_use]
 	pub const fn src(self) -> ValueId {
 		self.src
@@ -117,6 +151,11 @@ pub struct Store {
 }
 
 impl Store {
+	#[must_use]
+	pub const fn new(value: ValueId, dest: ValueId) -> Self {
+		Self { value, dest }
+	}
+
 	#[must_use]
 	pub const fn value(self) -> ValueId {
 		self.value
@@ -143,6 +182,11 @@ pub struct GetPtr {
 }
 
 impl GetPtr {
+	#[must_use]
+	pub const fn new(src: ValueId, index: ValueId) -> Self {
+		Self { src, index }
+	}
+
 	#[must_use]
 	pub const fn src(self) -> ValueId {
 		self.src
@@ -169,6 +213,11 @@ pub struct GetElementPtr {
 }
 
 impl GetElementPtr {
+	#[must_use]
+	pub const fn new(src: ValueId, index: ValueId) -> Self {
+		Self { src, index }
+	}
+
 	#[must_use]
 	pub const fn src(self) -> ValueId {
 		self.src
@@ -196,6 +245,11 @@ pub struct Binary {
 }
 
 impl Binary {
+	#[must_use]
+	pub const fn new(op: BinaryOp, lhs: ValueId, rhs: ValueId) -> Self {
+		Self { lhs, op, rhs }
+	}
+
 	#[must_use]
 	pub const fn lhs(self) -> ValueId {
 		self.lhs
@@ -232,6 +286,36 @@ pub struct Branch {
 }
 
 impl Branch {
+	#[must_use]
+	pub const fn new(
+		cond: ValueId,
+		true_basic_block: BasicBlockId,
+		false_basic_block: BasicBlockId,
+	) -> Self {
+		Self::with_args(
+			cond,
+			true_basic_block,
+			Vec::new(),
+			false_basic_block,
+			Vec::new(),
+		)
+	}
+
+	#[must_use]
+	pub const fn with_args(
+		cond: ValueId,
+		true_basic_block: BasicBlockId,
+		true_args: Vec<ValueId>,
+		false_basic_block: BasicBlockId,
+		false_args: Vec<ValueId>,
+	) -> Self {
+		Self {
+			cond,
+			true_values: (true_basic_block, true_args),
+			false_values: (false_basic_block, false_args),
+		}
+	}
+
 	#[must_use]
 	pub const fn cond(&self) -> ValueId {
 		self.cond
@@ -276,6 +360,11 @@ impl Branch {
 	pub const fn false_args_mut(&mut self) -> &mut Vec<ValueId> {
 		&mut self.false_values.1
 	}
+
+	/// Both argument lists at once, true first.
+	pub const fn args_mut(&mut self) -> (&mut Vec<ValueId>, &mut Vec<ValueId>) {
+		(&mut self.true_values.1, &mut self.false_values.1)
+	}
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -285,6 +374,16 @@ pub struct Jump {
 }
 
 impl Jump {
+	#[must_use]
+	pub const fn new(target: BasicBlockId) -> Self {
+		Self::with_args(target, Vec::new())
+	}
+
+	#[must_use]
+	pub const fn with_args(target: BasicBlockId, args: Vec<ValueId>) -> Self {
+		Self { target, args }
+	}
+
 	#[must_use]
 	pub const fn target(&self) -> BasicBlockId {
 		self.target
@@ -311,6 +410,11 @@ pub struct Call {
 }
 
 impl Call {
+	#[must_use]
+	pub const fn new(callee: FunctionId, args: Vec<ValueId>) -> Self {
+		Self { callee, args }
+	}
+
 	#[must_use]
 	pub const fn callee(&self) -> FunctionId {
 		self.callee
@@ -336,6 +440,11 @@ impl Call {
 pub struct Return(Option<ValueId>);
 
 impl Return {
+	#[must_use]
+	pub const fn new(value: Option<ValueId>) -> Self {
+		Self(value)
+	}
+
 	#[must_use]
 	pub const fn value(self) -> Option<ValueId> {
 		self.0
@@ -367,6 +476,33 @@ pub enum BinaryOp {
 	Sar,
 }
 
+impl BinaryOp {
+	/// Applies the operation with wrapping arithmetic, or returns [`None`] on division by zero.
+	#[must_use]
+	pub const fn eval(self, lhs: i32, rhs: i32) -> Option<i32> {
+		Some(match self {
+			Self::NotEq => (lhs != rhs) as i32,
+			Self::Eq => (lhs == rhs) as i32,
+			Self::Gt => (lhs > rhs) as i32,
+			Self::Lt => (lhs < rhs) as i32,
+			Self::Ge => (lhs >= rhs) as i32,
+			Self::Le => (lhs <= rhs) as i32,
+			Self::Add => lhs.wrapping_add(rhs),
+			Self::Sub => lhs.wrapping_sub(rhs),
+			Self::Mul => lhs.wrapping_mul(rhs),
+			Self::Div | Self::Mod if matches!(rhs, 0) => return None,
+			Self::Div => lhs.wrapping_div(rhs),
+			Self::Mod => lhs.wrapping_rem(rhs),
+			Self::And => lhs & rhs,
+			Self::Or => lhs | rhs,
+			Self::Xor => lhs ^ rhs,
+			Self::Shl => lhs.wrapping_shl(rhs as u32),
+			Self::Shr => (lhs as u32).wrapping_shr(rhs as u32) as i32,
+			Self::Sar => lhs.wrapping_shr(rhs as u32),
+		})
+	}
+}
+
 impl Display for BinaryOp {
 	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
 		f.write_str(match self {
@@ -6,4 +6,11 @@ extern crate alloc;
 #[cfg(feature = "std")]
 extern crate std;
 
+mod eval;
 pub mod ir;
+mod lower;
+pub mod opt;
+mod text;
+mod verify;
+
+pub use self::{eval::*, lower::*, text::*, verify::*};
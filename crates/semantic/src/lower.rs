@@ -0,0 +1,639 @@
+use alloc::vec::Vec;
+use core::{
+	error::Error as CoreError,
+	fmt::{Debug, Display, Formatter, Result as FmtResult},
+};
+
+use vmm_ir::{
+	BlockInstruction, CellWidth, EofPolicy, Instruction, Offset, ScaleAnd, SuperInstruction,
+};
+use vmm_tape::TAPE_SIZE;
+use vmm_utils::GetOrZero as _;
+
+use crate::ir::{
+	BasicBlockId, BinaryOp, FunctionData, FunctionId, GlobalInstBuilder as _,
+	LocalInstBuilder as _, Program, Type, ValueBuilder as _, ValueId,
+};
+
+/// Lowers a program into a [`Program`] with a single `@main` function.
+///
+/// The tape is the global `@tape` array and the pointer is an index into it stored in `%ptr`.
+/// Input and output go through the declared `@getchar(): i32`, which returns a negative value at
+/// the end of input, and `@putchar(i32)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lowering {
+	tape_len: usize,
+	cell_width: CellWidth,
+	eof: EofPolicy,
+}
+
+impl Lowering {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			tape_len: TAPE_SIZE,
+			cell_width: CellWidth::U8,
+			eof: EofPolicy::Zero,
+		}
+	}
+
+	#[must_use]
+	pub const fn and_with_tape_len(mut self, tape_len: usize) -> Self {
+		self.tape_len = tape_len;
+		self
+	}
+
+	#[must_use]
+	pub const fn and_with_cell_width(mut self, cell_width: CellWidth) -> Self {
+		self.cell_width = cell_width;
+		self
+	}
+
+	#[must_use]
+	pub const fn and_with_eof_policy(mut self, eof: EofPolicy) -> Self {
+		self.eof = eof;
+		self
+	}
+
+	pub fn lower(self, instructions: &[Instruction]) -> Result<Program, LowerError> {
+		if matches!(self.tape_len, 0) {
+			return Err(LowerError::EmptyTape);
+		}
+
+		if self.tape_len > i32::MAX as usize {
+			return Err(LowerError::TapeTooLarge(self.tape_len));
+		}
+
+		let mut program = Program::new();
+
+		let zero = program
+			.new_value()
+			.zero_init(Type::i32().into_array(self.tape_len));
+		let tape = program.new_value().global_alloc(zero);
+		program.set_value_name(tape, "@tape");
+
+		let getchar = program.new_func(FunctionData::new_decl("@getchar", Vec::new(), Type::i32()));
+		let putchar = program.new_func(FunctionData::new_decl(
+			"@putchar",
+			alloc::vec![Type::i32()],
+			Type::unit(),
+		));
+		let main = program.new_func(FunctionData::new("@main", Vec::new(), Type::unit()));
+
+		let func = program.func_mut(main);
+		let entry = func
+			.dfg_mut()
+			.new_basic_block()
+			.basic_block(Some("%entry".into()));
+		func.layout_mut().push_basic_block(entry);
+
+		let ptr = func.dfg_mut().new_value().alloc(Type::i32());
+		func.dfg_mut().set_value_name(ptr, "%ptr");
+		func.layout_mut().push_inst(entry, ptr);
+
+		let mut lowerer = Lowerer {
+			func,
+			options: self,
+			tape,
+			ptr,
+			getchar,
+			putchar,
+			basic_block: entry,
+		};
+
+		let start = lowerer.integer(0);
+		lowerer.store(start, ptr);
+
+		lowerer.block(instructions)?;
+
+		let ret = lowerer.func.dfg_mut().new_value().ret(None);
+		lowerer.push(ret);
+
+		Ok(program)
+	}
+
+	const fn wrap_offset(self, offset: Offset) -> i32 {
+		offset.value().rem_euclid(self.tape_len as isize) as i32
+	}
+}
+
+impl Default for Lowering {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LowerError {
+	Unsupported(Instruction),
+	EmptyTape,
+	TapeTooLarge(usize),
+}
+
+impl Display for LowerError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::Unsupported(instr) => {
+				f.write_str("instruction ")?;
+				Debug::fmt(&instr, f)?;
+				f.write_str(" cannot be lowered")
+			}
+			Self::EmptyTape => f.write_str("cannot lower a program with an empty tape"),
+			Self::TapeTooLarge(len) => {
+				f.write_str("a tape of ")?;
+				Display::fmt(&len, f)?;
+				f.write_str(" cells is too large to index with an i32")
+			}
+		}
+	}
+}
+
+impl CoreError for LowerError {}
+
+struct Lowerer<'a> {
+	func: &'a mut FunctionData,
+	options: Lowering,
+	tape: ValueId,
+	ptr: ValueId,
+	getchar: FunctionId,
+	putchar: FunctionId,
+	basic_block: BasicBlockId,
+}
+
+impl Lowerer<'_> {
+	fn block(&mut self, instructions: &[Instruction]) -> Result<(), LowerError> {
+		instructions
+			.iter()
+			.try_for_each(|instr| self.instruction(instr))
+	}
+
+	fn instruction(&mut self, instr: &Instruction) -> Result<(), LowerError> {
+		match instr {
+			Instruction::Boundary => {}
+			Instruction::IncVal { value, offset } => {
+				let value = self.options.cell_width.wrap_signed(*value);
+
+				if !matches!(value, 0) {
+					let (cell_ptr, cell) = self.load_cell(*offset);
+					let value = self.integer(value);
+					let sum = self.binary(BinaryOp::Add, cell, value);
+
+					self.store_wrapped(sum, cell_ptr);
+				}
+			}
+			Instruction::SetVal { value, offset } => {
+				let cell_ptr = self.cell_ptr(*offset);
+				let value = self.constant(value.get_or_zero());
+
+				self.store(value, cell_ptr);
+			}
+			Instruction::SubCell { offset } => {
+				let (current_ptr, current) = self.load_cell(Offset(0));
+
+				if self.is_current(*offset) {
+					let zero = self.integer(0);
+					let negated = self.binary(BinaryOp::Sub, zero, current);
+
+					self.store_wrapped(negated, current_ptr);
+				} else {
+					let (cell_ptr, cell) = self.load_cell(*offset);
+					let difference = self.binary(BinaryOp::Sub, cell, current);
+
+					self.store_wrapped(difference, cell_ptr);
+					self.clear(current_ptr);
+				}
+			}
+			Instruction::ScaleVal { factor } => {
+				let (current_ptr, current) = self.load_cell(Offset(0));
+
+				if let Some(scaled) = self.scaled(current, *factor) {
+					self.store_wrapped(scaled, current_ptr);
+				}
+			}
+			Instruction::MoveVal(offset) => self.scale_and_move(*offset, 1),
+			Instruction::FetchVal(offset) => self.scale_and_fetch(*offset, 1),
+			Instruction::TakeVal(offset) => self.scale_and_take(*offset, 1),
+			Instruction::ReplaceVal(offset) => {
+				if !self.is_current(*offset) {
+					let (cell_ptr, cell) = self.load_cell(*offset);
+					let current_ptr = self.cell_ptr(Offset(0));
+
+					self.store(cell, current_ptr);
+					self.clear(cell_ptr);
+				}
+			}
+			Instruction::MovePtr(offset) => self.move_ptr(*offset),
+			Instruction::FindZero(jump_by) => self.find_zero(*jump_by)?,
+			Instruction::Read => self.read(),
+			Instruction::Write { offset } => {
+				let (_, cell) = self.load_cell(*offset);
+
+				let byte = if matches!(self.options.cell_width, CellWidth::U8) {
+					cell
+				} else {
+					let mask = self.integer(0xFF);
+					self.binary(BinaryOp::And, cell, mask)
+				};
+
+				let call = self
+					.func
+					.dfg_mut()
+					.new_value()
+					.call(self.putchar, alloc::vec![byte]);
+				self.push(call);
+			}
+			Instruction::Block(BlockInstruction::DynamicLoop(body)) => {
+				self.while_nz(|this| this.block(body))?;
+			}
+			Instruction::Block(BlockInstruction::IfNz(body)) => {
+				let (_, current) = self.load_cell(Offset(0));
+				let cond = self.not_zero(current);
+
+				let then_block = self.new_basic_block();
+				let end_block = self.new_basic_block();
+
+				let branch = self
+					.func
+					.dfg_mut()
+					.new_value()
+					.branch(cond, then_block, end_block);
+				self.push(branch);
+
+				self.switch_to(then_block);
+				self.block(body)?;
+
+				let current_ptr = self.cell_ptr(Offset(0));
+				self.clear(current_ptr);
+				self.jump(end_block);
+
+				self.switch_to(end_block);
+			}
+			Instruction::Super(s) => self.super_instruction(*s)?,
+			i => return Err(LowerError::Unsupported(i.clone())),
+		}
+
+		Ok(())
+	}
+
+	fn super_instruction(&mut self, instr: SuperInstruction) -> Result<(), LowerError> {
+		match instr {
+			SuperInstruction::ScaleAnd {
+				action,
+				offset,
+				factor,
+			} => match action {
+				ScaleAnd::Move => self.scale_and_move(offset, factor),
+				ScaleAnd::Fetch => self.scale_and_fetch(offset, factor),
+				ScaleAnd::Take => self.scale_and_take(offset, factor),
+				ScaleAnd::Set(value) => {
+					let (current_ptr, current) = self.load_cell(Offset(0));
+					let scaled = self.scaled(current, factor).unwrap_or(current);
+					let value = self.constant(value.get());
+
+					if self.is_current(offset) {
+						let sum = self.binary(BinaryOp::Add, scaled, value);
+
+						self.store_wrapped(sum, current_ptr);
+					} else {
+						let (cell_ptr, cell) = self.load_cell(offset);
+						let sum = self.binary(BinaryOp::Add, cell, scaled);
+
+						self.store_wrapped(sum, cell_ptr);
+						self.store(value, current_ptr);
+					}
+				}
+				_ => return Err(LowerError::Unsupported(instr.into())),
+			},
+			SuperInstruction::FindAndSetZero { offset, value } => {
+				self.find_zero(offset)?;
+
+				let current_ptr = self.cell_ptr(Offset(0));
+				let value = self.constant(value.get());
+				self.store(value, current_ptr);
+			}
+			SuperInstruction::SetUntilZero { value, offset } => {
+				self.while_nz(|this| {
+					let current_ptr = this.cell_ptr(Offset(0));
+					let value = this.constant(value.get_or_zero());
+
+					this.store(value, current_ptr);
+					this.move_ptr(offset);
+
+					Ok(())
+				})?;
+			}
+			SuperInstruction::FindCellByZero { jump_by, offset } => {
+				self.find_zero(jump_by)?;
+				self.move_ptr(offset);
+			}
+			SuperInstruction::ShiftVals { jump_by, offset } => {
+				self.while_nz(|this| {
+					this.scale_and_move(offset, 1);
+					this.move_ptr(jump_by);
+
+					Ok(())
+				})?;
+			}
+			i => return Err(LowerError::Unsupported(i.into())),
+		}
+
+		Ok(())
+	}
+
+	fn scale_and_move(&mut self, offset: Offset, factor: u32) {
+		let (current_ptr, current) = self.load_cell(Offset(0));
+		let scaled = self.scaled(current, factor);
+
+		if self.is_current(offset) {
+			if let Some(scaled) = scaled {
+				self.store_wrapped(scaled, current_ptr);
+			}
+		} else {
+			let (cell_ptr, cell) = self.load_cell(offset);
+			let sum = self.binary(BinaryOp::Add, cell, scaled.unwrap_or(current));
+
+			self.store_wrapped(sum, cell_ptr);
+			self.clear(current_ptr);
+		}
+	}
+
+	fn scale_and_fetch(&mut self, offset: Offset, factor: u32) {
+		if self.is_current(offset) {
+			return self.scale_and_move(offset, factor);
+		}
+
+		let (current_ptr, current) = self.load_cell(Offset(0));
+		let (cell_ptr, cell) = self.load_cell(offset);
+		let scaled = self.scaled(cell, factor).unwrap_or(cell);
+		let sum = self.binary(BinaryOp::Add, current, scaled);
+
+		self.store_wrapped(sum, current_ptr);
+		self.clear(cell_ptr);
+	}
+
+	fn scale_and_take(&mut self, offset: Offset, factor: u32) {
+		self.scale_and_move(offset, factor);
+		self.move_ptr(offset);
+	}
+
+	fn read(&mut self) {
+		let char = self
+			.func
+			.dfg_mut()
+			.new_value()
+			.call(self.getchar, Vec::new());
+		self.push(char);
+
+		let (current_ptr, current) = self.load_cell(Offset(0));
+		let zero = self.integer(0);
+
+		// getchar returns -1 at the end of input, which wraps to the maximum cell value.
+		let value = match self.options.eof {
+			EofPolicy::Max => char,
+			EofPolicy::Zero => {
+				let not_eof = self.binary(BinaryOp::Ge, char, zero);
+
+				self.binary(BinaryOp::Mul, char, not_eof)
+			}
+			EofPolicy::Unchanged => {
+				let not_eof = self.binary(BinaryOp::Ge, char, zero);
+				let eof = self.binary(BinaryOp::Lt, char, zero);
+				let new = self.binary(BinaryOp::Mul, char, not_eof);
+				let old = self.binary(BinaryOp::Mul, current, eof);
+
+				self.binary(BinaryOp::Add, new, old)
+			}
+		};
+
+		self.store_wrapped(value, current_ptr);
+	}
+
+	fn find_zero(&mut self, jump_by: Offset) -> Result<(), LowerError> {
+		self.while_nz(|this| {
+			this.move_ptr(jump_by);
+			Ok(())
+		})
+	}
+
+	fn move_ptr(&mut self, offset: Offset) {
+		if !self.is_current(offset) {
+			let index = self.index(offset);
+			self.store(index, self.ptr);
+		}
+	}
+
+	/// Emits `while (tape[ptr] != 0) { body }`, leaving the builder after the loop.
+	fn while_nz(
+		&mut self,
+		body: impl FnOnce(&mut Self) -> Result<(), LowerError>,
+	) -> Result<(), LowerError> {
+		let cond_block = self.new_basic_block();
+		let body_block = self.new_basic_block();
+		let end_block = self.new_basic_block();
+
+		self.jump(cond_block);
+
+		self.switch_to(cond_block);
+		let (_, current) = self.load_cell(Offset(0));
+		let cond = self.not_zero(current);
+
+		let branch = self
+			.func
+			.dfg_mut()
+			.new_value()
+			.branch(cond, body_block, end_block);
+		self.push(branch);
+
+		self.switch_to(body_block);
+		body(self)?;
+		self.jump(cond_block);
+
+		self.switch_to(end_block);
+
+		Ok(())
+	}
+
+	fn new_basic_block(&mut self) -> BasicBlockId {
+		self.func.dfg_mut().new_basic_block().basic_block(None)
+	}
+
+	fn switch_to(&mut self, basic_block: BasicBlockId) {
+		self.func.layout_mut().push_basic_block(basic_block);
+		self.basic_block = basic_block;
+	}
+
+	fn jump(&mut self, target: BasicBlockId) {
+		let jump = self.func.dfg_mut().new_value().jump(target);
+		self.push(jump);
+	}
+
+	/// The index of the cell at `offset`, wrapping around the tape.
+	fn index(&mut self, offset: Offset) -> ValueId {
+		let ptr = self.load(self.ptr);
+
+		match self.options.wrap_offset(offset) {
+			0 => ptr,
+			offset => {
+				let offset = self.integer(offset);
+				let len = self.integer(self.options.tape_len as i32);
+				let sum = self.binary(BinaryOp::Add, ptr, offset);
+
+				self.binary(BinaryOp::Mod, sum, len)
+			}
+		}
+	}
+
+	fn cell_ptr(&mut self, offset: Offset) -> ValueId {
+		let index = self.index(offset);
+		let cell_ptr = self
+			.func
+			.dfg_mut()
+			.new_value()
+			.get_element_ptr(self.tape, index);
+
+		self.push(cell_ptr)
+	}
+
+	fn load_cell(&mut self, offset: Offset) -> (ValueId, ValueId) {
+		let cell_ptr = self.cell_ptr(offset);
+
+		(cell_ptr, self.load(cell_ptr))
+	}
+
+	fn clear(&mut self, cell_ptr: ValueId) {
+		let zero = self.integer(0);
+		self.store(zero, cell_ptr);
+	}
+
+	/// Stores the result of arithmetic, truncated to the cell width.
+	fn store_wrapped(&mut self, value: ValueId, cell_ptr: ValueId) {
+		let value = match self.options.cell_width {
+			CellWidth::U32 => value,
+			width => {
+				let mask = self.integer(width.max_value() as i32);
+				self.binary(BinaryOp::And, value, mask)
+			}
+		};
+
+		self.store(value, cell_ptr);
+	}
+
+	fn scaled(&mut self, value: ValueId, factor: u32) -> Option<ValueId> {
+		match self.options.cell_width.wrap_unsigned(factor) {
+			1 => None,
+			factor => {
+				let factor = self.integer(factor as i32);
+				Some(self.binary(BinaryOp::Mul, value, factor))
+			}
+		}
+	}
+
+	fn not_zero(&mut self, value: ValueId) -> ValueId {
+		let zero = self.integer(0);
+		self.binary(BinaryOp::NotEq, value, zero)
+	}
+
+	fn constant(&mut self, value: u32) -> ValueId {
+		let value = self.options.cell_width.wrap_unsigned(value);
+		self.integer(value as i32)
+	}
+
+	fn integer(&mut self, value: i32) -> ValueId {
+		self.func.dfg_mut().new_value().integer(value)
+	}
+
+	fn load(&mut self, src: ValueId) -> ValueId {
+		let load = self.func.dfg_mut().new_value().load(src);
+		self.push(load)
+	}
+
+	fn store(&mut self, value: ValueId, dest: ValueId) {
+		let store = self.func.dfg_mut().new_value().store(value, dest);
+		self.push(store);
+	}
+
+	fn binary(&mut self, op: BinaryOp, lhs: ValueId, rhs: ValueId) -> ValueId {
+		let binary = self.func.dfg_mut().new_value().binary(op, lhs, rhs);
+		self.push(binary)
+	}
+
+	fn push(&mut self, inst: ValueId) -> ValueId {
+		self.func.layout_mut().push_inst(self.basic_block, inst);
+		inst
+	}
+
+	const fn is_current(&self, offset: Offset) -> bool {
+		matches!(self.options.wrap_offset(offset), 0)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use vmm_ir::Instruction;
+
+	use super::{LowerError, Lowering};
+	use crate::ir::{Type, TypeKind, ValueType};
+
+	#[test]
+	fn lowers_loops() {
+		let program = Lowering::new()
+			.and_with_tape_len(10)
+			.lower(&[
+				Instruction::inc_val(3),
+				Instruction::dynamic_loop([Instruction::inc_val(-1), Instruction::move_ptr(1)]),
+				Instruction::write_once(),
+			])
+			.unwrap();
+
+		assert_eq!(program.func_layout().len(), 3);
+
+		let main = program.func(program.func_layout()[2]);
+		assert_eq!(main.name(), "@main");
+		assert_eq!(main.layout().basic_blocks().len(), 4);
+
+		for bb in main.layout().basic_blocks() {
+			let last = *main.layout().insts(*bb).last().unwrap();
+
+			assert!(matches!(
+				main.dfg().value(last).value_ty(),
+				ValueType::Branch(..) | ValueType::Jump(..) | ValueType::Return(..)
+			));
+		}
+	}
+
+	#[test]
+	fn tracks_uses() {
+		let program = Lowering::new()
+			.lower(&[Instruction::move_ptr(2), Instruction::Read])
+			.unwrap();
+
+		let tape = program.inst_layout()[0];
+		assert_eq!(
+			&**program.borrow_value(tape).ty(),
+			&TypeKind::Ptr(Type::i32().into_array(5000))
+		);
+		assert!(!program.borrow_value(tape).used_by().is_empty());
+
+		let main = program.func(program.func_layout()[2]);
+		let entry = main.layout().entry_basic_block().unwrap();
+		let ptr = main.layout().insts(entry)[0];
+
+		let stores = main
+			.dfg()
+			.value(ptr)
+			.used_by()
+			.iter()
+			.filter(|user| matches!(main.dfg().value(**user).value_ty(), ValueType::Store(..)))
+			.count();
+
+		assert_eq!(stores, 2);
+	}
+
+	#[test]
+	fn rejects_empty_tape() {
+		assert_eq!(
+			Lowering::new().and_with_tape_len(0).lower(&[]).unwrap_err(),
+			LowerError::EmptyTape
+		);
+	}
+}
@@ -0,0 +1,183 @@
+use super::{
+	FunctionPass,
+	flow::{incoming_args, integer, remove_param},
+};
+use crate::ir::{BasicBlockId, FunctionData, FunctionId, ValueBuilder as _, ValueType};
+
+/// Evaluates binary operations on constants, and basic block parameters that are always passed
+/// the same constant.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ConstantFolding;
+
+impl ConstantFolding {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self
+	}
+
+	fn eval_const(data: &mut FunctionData) -> bool {
+		let mut progress = false;
+
+		for bb in data.layout().basic_blocks().to_vec() {
+			for inst in data.layout().insts(bb).to_vec() {
+				let ValueType::Binary(binary) = data.dfg().value(inst).value_ty() else {
+					continue;
+				};
+
+				let (Some(lhs), Some(rhs)) =
+					(integer(data, binary.lhs()), integer(data, binary.rhs()))
+				else {
+					continue;
+				};
+
+				// Division by zero is left for the program to hit.
+				let Some(value) = binary.op().eval(lhs, rhs) else {
+					continue;
+				};
+
+				data.layout_mut().remove_inst(inst);
+				data.dfg_mut().replace_value_with(inst).integer(value);
+
+				progress = true;
+			}
+		}
+
+		progress
+	}
+
+	fn eval_basic_block_params(data: &mut FunctionData) -> bool {
+		let mut progress = false;
+
+		for bb in data.layout().basic_blocks().to_vec() {
+			let mut index = 0;
+
+			while index < data.dfg().basic_block(bb).params().len() {
+				let param = data.dfg().basic_block(bb).params()[index];
+
+				let Some(value) = Self::incoming_constant(data, bb, index) else {
+					index += 1;
+					continue;
+				};
+
+				let value = data.dfg_mut().new_value().integer(value);
+				data.dfg_mut().replace_uses(param, value);
+				remove_param(data, bb, index);
+
+				progress = true;
+			}
+		}
+
+		progress
+	}
+
+	fn incoming_constant(data: &FunctionData, bb: BasicBlockId, index: usize) -> Option<i32> {
+		let param = data.dfg().basic_block(bb).params()[index];
+		let mut constant = None;
+
+		for arg in incoming_args(data, bb, index) {
+			if arg == param {
+				continue;
+			}
+
+			let value = integer(data, arg)?;
+
+			if constant.is_some_and(|constant| constant != value) {
+				return None;
+			}
+
+			constant = Some(value);
+		}
+
+		constant
+	}
+}
+
+impl FunctionPass for ConstantFolding {
+	fn run_on(&mut self, _: FunctionId, data: &mut FunctionData) -> bool {
+		let mut progress = false;
+
+		while Self::eval_const(data) {
+			progress = true;
+		}
+
+		Self::eval_basic_block_params(data) || progress
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::ToString as _;
+
+	use super::ConstantFolding;
+	use crate::{ir::Program, opt::PassManager};
+
+	fn fold(text: &str) -> Program {
+		let mut program = text.parse().unwrap();
+
+		PassManager::new()
+			.and_with_function_pass(ConstantFolding::new())
+			.run_passes(&mut program);
+		program.verify().unwrap();
+
+		program
+	}
+
+	#[test]
+	fn folds_binary_operations() {
+		let program = fold(
+			"fun @main(): i32 {
+%entry:
+  %a = add 1, 2
+  %b = mul %a, -3
+  %c = lt %b, 0
+  %d = div %c, 0
+  %e = sub %b, %d
+  ret %e
+}",
+		);
+
+		assert_eq!(
+			program.to_string(),
+			"fun @main(): i32 {\n%entry:\n  %d = div 1, 0\n  %e = sub -9, %d\n  ret %e\n}\n"
+		);
+	}
+
+	#[test]
+	fn folds_basic_block_params() {
+		let program = fold(
+			"fun @main(%n: i32): i32 {
+%entry:
+  br %n, %then, %else
+
+%then:
+  jump %end(1, %n)
+
+%else:
+  jump %end(1, 2)
+
+%end(%x: i32, %y: i32):
+  %z = add %x, %y
+  ret %z
+}",
+		);
+
+		assert_eq!(
+			program.to_string(),
+			"fun @main(%n: i32): i32 {
+%entry:
+  br %n, %then, %else
+
+%then:
+  jump %end(%n)
+
+%else:
+  jump %end(2)
+
+%end(%y: i32):
+  %z = add 1, %y
+  ret %z
+}
+"
+		);
+	}
+}
@@ -0,0 +1,239 @@
+use alloc::vec::Vec;
+
+use super::{
+	FunctionPass,
+	flow::{is_pure, remove_param},
+};
+use crate::ir::{FunctionData, FunctionId, ValueId, ValueType};
+
+/// Removes instructions, allocations, basic block parameters and constants that nothing uses.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DeadCodeElimination;
+
+impl DeadCodeElimination {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self
+	}
+
+	fn remove_unused_insts(data: &mut FunctionData) -> bool {
+		let mut worklist = data
+			.layout()
+			.basic_blocks()
+			.iter()
+			.flat_map(|bb| data.layout().insts(*bb))
+			.copied()
+			.collect::<Vec<_>>();
+
+		let mut progress = false;
+
+		while let Some(inst) = worklist.pop() {
+			if data.layout().parent_basic_block(inst).is_none()
+				|| !data.dfg().value(inst).used_by().is_empty()
+				|| !is_pure(data, inst)
+			{
+				continue;
+			}
+
+			worklist.extend(
+				data.dfg()
+					.value(inst)
+					.value_ty()
+					.values()
+					.filter(|value| !value.is_global()),
+			);
+
+			data.layout_mut().remove_inst(inst);
+			data.dfg_mut().remove_value(inst);
+
+			progress = true;
+		}
+
+		progress
+	}
+
+	/// Removes allocations that are only ever stored to, along with the stores.
+	fn remove_write_only_allocs(data: &mut FunctionData) -> bool {
+		let allocs = data
+			.layout()
+			.basic_blocks()
+			.iter()
+			.flat_map(|bb| data.layout().insts(*bb))
+			.copied()
+			.filter(|inst| matches!(data.dfg().value(*inst).value_ty(), ValueType::Alloc(..)))
+			.collect::<Vec<_>>();
+
+		let mut progress = false;
+
+		for alloc in allocs {
+			let Some(mut dead) = Self::write_only_uses(data, alloc) else {
+				continue;
+			};
+
+			dead.push(alloc);
+
+			// Stores come before the pointers they store to.
+			for inst in dead {
+				data.layout_mut().remove_inst(inst);
+				data.dfg_mut().remove_value(inst);
+			}
+
+			progress = true;
+		}
+
+		progress
+	}
+
+	/// The stores to `ptr` and the pointers derived from it, or [`None`] if it is ever read.
+	fn write_only_uses(data: &FunctionData, ptr: ValueId) -> Option<Vec<ValueId>> {
+		let mut dead = Vec::new();
+
+		for user in data.dfg().value(ptr).used_by() {
+			match data.dfg().value(*user).value_ty() {
+				ValueType::Store(store) if store.dest() == ptr && store.value() != ptr => {
+					dead.push(*user);
+				}
+				ValueType::GetPtr(..) | ValueType::GetElementPtr(..) => {
+					dead.extend(Self::write_only_uses(data, *user)?);
+					dead.push(*user);
+				}
+				_ => return None,
+			}
+		}
+
+		Some(dead)
+	}
+
+	fn remove_unused_params(data: &mut FunctionData) -> bool {
+		let mut progress = false;
+
+		// The entry basic block is never branched to, so its parameters are left alone.
+		for bb in data.layout().basic_blocks().to_vec().into_iter().skip(1) {
+			let mut index = 0;
+
+			while index < data.dfg().basic_block(bb).params().len() {
+				let param = data.dfg().basic_block(bb).params()[index];
+
+				if data.dfg().value(param).used_by().is_empty() {
+					remove_param(data, bb, index);
+					progress = true;
+				} else {
+					index += 1;
+				}
+			}
+		}
+
+		progress
+	}
+
+	fn remove_unused_constants(data: &mut FunctionData) -> bool {
+		let mut progress = false;
+
+		loop {
+			let unused = data
+				.dfg()
+				.values()
+				.iter()
+				.filter(|(_, value)| value.value_ty().is_const() && value.used_by().is_empty())
+				.map(|(id, _)| *id)
+				.collect::<Vec<_>>();
+
+			if unused.is_empty() {
+				return progress;
+			}
+
+			for value in unused {
+				data.dfg_mut().remove_value(value);
+			}
+
+			progress = true;
+		}
+	}
+}
+
+impl FunctionPass for DeadCodeElimination {
+	fn run_on(&mut self, _: FunctionId, data: &mut FunctionData) -> bool {
+		let mut progress = Self::remove_unused_insts(data);
+
+		progress |= Self::remove_write_only_allocs(data);
+		progress |= Self::remove_unused_insts(data);
+		progress |= Self::remove_unused_params(data);
+		progress |= Self::remove_unused_insts(data);
+		progress |= Self::remove_unused_constants(data);
+
+		progress
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::ToString as _;
+
+	use super::DeadCodeElimination;
+	use crate::{ir::Program, opt::PassManager};
+
+	fn eliminate(text: &str) -> Program {
+		let mut program = text.parse().unwrap();
+
+		PassManager::new()
+			.and_with_function_pass(DeadCodeElimination::new())
+			.run_passes(&mut program);
+		program.verify().unwrap();
+
+		program
+	}
+
+	#[test]
+	fn removes_unused_values() {
+		let program = eliminate(
+			"decl @putchar(i32)
+
+fun @main(%n: i32) {
+%entry:
+  %a = add %n, 1
+  %b = mul %a, 2
+  %c = div %n, %a
+  %d = mod %n, 3
+  %p = alloc [i32, 2]
+  %q = getelemptr %p, %n
+  store %b, %q
+  call @putchar(%n)
+  jump %end(%a)
+
+%end(%x: i32):
+  ret
+}",
+		);
+
+		assert_eq!(
+			program.to_string(),
+			"decl @putchar(i32)
+
+fun @main(%n: i32) {
+%entry:
+  %a = add %n, 1
+  %c = div %n, %a
+  call @putchar(%n)
+  jump %end
+
+%end:
+  ret
+}
+"
+		);
+	}
+
+	#[test]
+	fn keeps_memory_that_is_read() {
+		let text = "fun @main(): i32 {
+%entry:
+  %p = alloc i32
+  store 1, %p
+  %x = load %p
+  ret %x
+}
+";
+
+		assert_eq!(eliminate(text).to_string(), text);
+	}
+}
@@ -0,0 +1,213 @@
+use alloc::vec::Vec;
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::ir::{
+	BasicBlockId, BinaryOp, BlockArgRef, FunctionData, Type, ValueBuilder as _, ValueData, ValueId,
+	ValueType,
+};
+
+/// The edges between the basic blocks of a function.
+pub struct ControlFlow {
+	order: Vec<BasicBlockId>,
+	predecessors: HashMap<BasicBlockId, Vec<BasicBlockId>>,
+}
+
+impl ControlFlow {
+	pub fn new(data: &FunctionData) -> Self {
+		let mut predecessors = HashMap::<_, Vec<_>>::new();
+
+		for bb in data.layout().basic_blocks() {
+			for succ in successors(data, *bb) {
+				let preds = predecessors.entry(succ).or_default();
+
+				if !preds.contains(bb) {
+					preds.push(*bb);
+				}
+			}
+		}
+
+		let Some(entry) = data.layout().entry_basic_block() else {
+			return Self {
+				order: Vec::new(),
+				predecessors,
+			};
+		};
+
+		// Reverse postorder, computed without recursion.
+		let mut postorder = Vec::new();
+		let mut visited = HashSet::<_>::from_iter([entry]);
+		let mut stack = alloc::vec![(entry, successors(data, entry), 0)];
+
+		while let Some((bb, succs, next)) = stack.last_mut() {
+			if let Some(succ) = succs.get(*next).copied() {
+				*next += 1;
+
+				if visited.insert(succ) {
+					stack.push((succ, successors(data, succ), 0));
+				}
+			} else {
+				postorder.push(*bb);
+				stack.pop();
+			}
+		}
+
+		postorder.reverse();
+
+		Self {
+			order: postorder,
+			predecessors,
+		}
+	}
+
+	/// The reachable basic blocks, in reverse postorder.
+	pub fn order(&self) -> &[BasicBlockId] {
+		&self.order
+	}
+
+	/// The distinct basic blocks that branch or jump to `bb`.
+	pub fn predecessors(&self, bb: BasicBlockId) -> &[BasicBlockId] {
+		self.predecessors.get(&bb).map_or(&[], Vec::as_slice)
+	}
+}
+
+pub fn successors(data: &FunctionData, bb: BasicBlockId) -> Vec<BasicBlockId> {
+	data.layout()
+		.insts(bb)
+		.last()
+		.map_or_else(Vec::new, |inst| {
+			data.dfg().value(*inst).value_ty().basic_blocks().collect()
+		})
+}
+
+pub fn terminator(data: &FunctionData, bb: BasicBlockId) -> Option<ValueId> {
+	data.layout()
+		.insts(bb)
+		.last()
+		.copied()
+		.filter(|inst| data.dfg().value(*inst).value_ty().is_terminator())
+}
+
+/// Replaces what an instruction does, keeping its users.
+pub fn set_value_type(data: &mut FunctionData, inst: ValueId, value_ty: ValueType) {
+	let ty = data.dfg().value(inst).ty().clone();
+
+	data.dfg_mut()
+		.replace_value_with(inst)
+		.raw(ValueData::new(ty, value_ty));
+}
+
+/// The arguments passed to the parameter at `index` by every branch or jump to `bb`.
+pub fn incoming_args(data: &FunctionData, bb: BasicBlockId, index: usize) -> Vec<ValueId> {
+	let mut args = Vec::new();
+
+	for user in data.dfg().basic_block(bb).used_by() {
+		match data.dfg().value(*user).value_ty() {
+			ValueType::Branch(branch) => {
+				if branch.true_basic_block() == bb {
+					args.push(branch.true_args()[index]);
+				}
+
+				if branch.false_basic_block() == bb {
+					args.push(branch.false_args()[index]);
+				}
+			}
+			ValueType::Jump(jump) => args.push(jump.args()[index]),
+			_ => {}
+		}
+	}
+
+	args
+}
+
+/// Calls `f` on every branch or jump to `bb`, and the arguments it passes.
+pub fn for_each_incoming(
+	data: &mut FunctionData,
+	bb: BasicBlockId,
+	mut f: impl FnMut(ValueId, &mut Vec<ValueId>),
+) {
+	let users = data
+		.dfg()
+		.basic_block(bb)
+		.used_by()
+		.iter()
+		.copied()
+		.collect::<Vec<_>>();
+
+	for user in users {
+		let mut value_ty = data.dfg().value(user).value_ty().clone();
+
+		for (target, args) in value_ty.target_args_mut() {
+			if target == bb {
+				f(user, args);
+			}
+		}
+
+		set_value_type(data, user, value_ty);
+	}
+}
+
+/// Removes an unused basic block parameter, along with the arguments passed to it.
+pub fn remove_param(data: &mut FunctionData, bb: BasicBlockId, index: usize) {
+	for_each_incoming(data, bb, |_, args| {
+		args.remove(index);
+	});
+
+	let param = data
+		.dfg_mut()
+		.basic_block_mut(bb)
+		.params_mut()
+		.remove(index);
+	data.dfg_mut().remove_value(param);
+
+	let params = data.dfg().basic_block(bb).params().clone();
+
+	for (i, param) in params.into_iter().enumerate().skip(index) {
+		let ty = data.dfg().value(param).ty().clone();
+
+		data.dfg_mut().replace_value_with(param).raw(ValueData::new(
+			ty,
+			ValueType::BlockArgRef(BlockArgRef::new(i)),
+		));
+	}
+}
+
+/// Adds a parameter to a basic block, returning it. Branches and jumps to the block must be
+/// given the new argument with [`for_each_incoming`].
+pub fn push_param(data: &mut FunctionData, bb: BasicBlockId, ty: Type) -> ValueId {
+	let index = data.dfg().basic_block(bb).params().len();
+	let param = data.dfg_mut().new_value().raw(ValueData::new(
+		ty,
+		ValueType::BlockArgRef(BlockArgRef::new(index)),
+	));
+
+	data.dfg_mut().basic_block_mut(bb).params_mut().push(param);
+
+	param
+}
+
+/// Whether an instruction can be removed when nothing uses it.
+pub fn is_pure(data: &FunctionData, inst: ValueId) -> bool {
+	match data.dfg().value(inst).value_ty() {
+		ValueType::Binary(binary) => {
+			!matches!(binary.op(), BinaryOp::Div | BinaryOp::Mod)
+				|| integer(data, binary.rhs()).is_some_and(|rhs| rhs != 0)
+		}
+		ValueType::Alloc(..)
+		| ValueType::Load(..)
+		| ValueType::GetPtr(..)
+		| ValueType::GetElementPtr(..) => true,
+		_ => false,
+	}
+}
+
+pub fn integer(data: &FunctionData, value: ValueId) -> Option<i32> {
+	if value.is_global() {
+		return None;
+	}
+
+	match data.dfg().value(value).value_ty() {
+		ValueType::Integer(i) => Some(i.value()),
+		_ => None,
+	}
+}
@@ -0,0 +1,565 @@
+use alloc::{collections::BTreeSet, vec::Vec};
+
+use hashbrown::{HashMap, HashSet};
+
+use super::{
+	ModulePass,
+	flow::{ControlFlow, for_each_incoming, integer, push_param},
+};
+use crate::ir::{
+	BasicBlockId, FunctionData, FunctionId, LocalInstBuilder as _, Program, Type, TypeKind,
+	ValueBuilder as _, ValueId, ValueType,
+};
+
+/// Promotes memory that is only ever accessed at known addresses into SSA values.
+///
+/// This covers scalar allocations, like the tape pointer of a lowered program, and the cells of
+/// arrays that are only indexed by constants, like the tape of a program without loops. Global
+/// memory is loaded once on entry and stored back before returning, and is only promoted in
+/// functions that call nothing but declarations, which are assumed not to touch it.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Mem2Reg;
+
+impl Mem2Reg {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self
+	}
+}
+
+impl ModulePass for Mem2Reg {
+	fn run_on(&mut self, program: &mut Program) -> bool {
+		let decls = program
+			.funcs()
+			.iter()
+			.filter(|(_, data)| data.is_decl())
+			.map(|(func, _)| *func)
+			.collect::<HashSet<_>>();
+
+		let mut progress = false;
+
+		for func in program.func_layout().to_vec() {
+			let data = program.func_mut(func);
+
+			if !data.is_decl() {
+				progress |= promote(data, &decls);
+			}
+		}
+
+		progress
+	}
+}
+
+/// A scalar in memory: either a whole allocation or one element of an array.
+type Location = (ValueId, Option<i32>);
+
+#[derive(Clone, Copy)]
+enum Pointee {
+	Scalar,
+	Array(i32),
+}
+
+struct Accesses {
+	/// Where each pointer to a promotable location points.
+	pointers: HashMap<ValueId, Location>,
+	/// The loads from and stores to each location, in layout order.
+	insts: HashMap<Location, Vec<(BasicBlockId, usize, bool)>>,
+}
+
+fn promote(data: &mut FunctionData, decls: &HashSet<FunctionId>) -> bool {
+	let Some(entry) = data.layout().entry_basic_block() else {
+		return false;
+	};
+
+	// Values flowing back into the entry block would have to be merged with the initial ones.
+	if !data.dfg().basic_block(entry).used_by().is_empty() {
+		return false;
+	}
+
+	let calls_definitions = data.dfg().values().values().any(
+		|value| matches!(value.value_ty(), ValueType::Call(call) if !decls.contains(&call.callee())),
+	);
+
+	let accesses = accesses(data, !calls_definitions);
+
+	let locations = accesses
+		.insts
+		.iter()
+		.filter(|(location, insts)| !location.0.is_global() || !is_canonical(data, entry, insts))
+		.map(|(location, _)| *location)
+		.collect::<BTreeSet<_>>();
+
+	if locations.is_empty() {
+		return false;
+	}
+
+	rename(data, entry, &accesses.pointers, &locations);
+
+	true
+}
+
+/// Finds the locations whose every access is a load or store through a known pointer.
+fn accesses(data: &FunctionData, include_globals: bool) -> Accesses {
+	let dfg = data.dfg();
+
+	let mut bases = HashMap::new();
+
+	for (value, value_data) in dfg.values() {
+		if matches!(value_data.value_ty(), ValueType::Alloc(..)) {
+			bases.insert(*value, pointee(value_data.ty()));
+		}
+
+		if include_globals {
+			for operand in value_data.value_ty().values() {
+				if operand.is_global() {
+					bases.insert(operand, pointee(&dfg.value_type(operand)));
+				}
+			}
+		}
+	}
+
+	let mut pointers = HashMap::new();
+	let mut escaped = HashSet::new();
+
+	for (base, pointee) in &bases {
+		match pointee {
+			Some(Pointee::Scalar) => {
+				pointers.insert(*base, (*base, None));
+			}
+			Some(Pointee::Array(..)) => {}
+			None => {
+				escaped.insert(*base);
+			}
+		}
+	}
+
+	for (value, value_data) in dfg.values() {
+		let ValueType::GetElementPtr(gep) = value_data.value_ty() else {
+			continue;
+		};
+
+		let Some(pointee) = bases.get(&gep.src()) else {
+			continue;
+		};
+
+		match (pointee, integer(data, gep.index())) {
+			(Some(Pointee::Array(len)), Some(index)) if (0..*len).contains(&index) => {
+				pointers.insert(*value, (gep.src(), Some(index)));
+			}
+			_ => {
+				escaped.insert(gep.src());
+			}
+		}
+	}
+
+	for value_data in dfg.values().values() {
+		let allowed = match value_data.value_ty() {
+			ValueType::Load(load) => Some(load.src()),
+			ValueType::Store(store) => Some(store.dest()),
+			ValueType::GetElementPtr(gep) => Some(gep.src()),
+			_ => None,
+		};
+
+		for operand in value_data.value_ty().values() {
+			if Some(operand) == allowed {
+				continue;
+			}
+
+			if let Some((base, _)) = pointers.get(&operand) {
+				escaped.insert(*base);
+			} else if bases.contains_key(&operand) {
+				escaped.insert(operand);
+			}
+		}
+	}
+
+	// Loads and stores of whole arrays.
+	for value_data in dfg.values().values() {
+		let ptr = match value_data.value_ty() {
+			ValueType::Load(load) => load.src(),
+			ValueType::Store(store) => store.dest(),
+			_ => continue,
+		};
+
+		if bases
+			.get(&ptr)
+			.is_some_and(|pointee| !matches!(pointee, Some(Pointee::Scalar)))
+		{
+			escaped.insert(ptr);
+		}
+	}
+
+	pointers.retain(|_, (base, _)| !escaped.contains(base));
+
+	let mut insts = HashMap::<_, Vec<_>>::new();
+
+	for bb in data.layout().basic_blocks() {
+		for (index, inst) in data.layout().insts(*bb).iter().enumerate() {
+			let (ptr, is_load) = match dfg.value(*inst).value_ty() {
+				ValueType::Load(load) => (load.src(), true),
+				ValueType::Store(store) => (store.dest(), false),
+				_ => continue,
+			};
+
+			if let Some(location) = pointers.get(&ptr) {
+				insts
+					.entry(*location)
+					.or_default()
+					.push((*bb, index, is_load));
+			}
+		}
+	}
+
+	Accesses { pointers, insts }
+}
+
+/// What a pointer points to, if it is a scalar or an array of them.
+fn pointee(ty: &Type) -> Option<Pointee> {
+	let TypeKind::Ptr(pointee) = &**ty else {
+		return None;
+	};
+
+	match &**pointee {
+		TypeKind::Int(..) => Some(Pointee::Scalar),
+		TypeKind::Array(elem, len) if matches!(**elem, TypeKind::Int(..)) => {
+			i32::try_from(*len).ok().map(Pointee::Array)
+		}
+		_ => None,
+	}
+}
+
+/// Whether a global location is already only loaded at the start of the function and stored
+/// at the end, so promoting it again would change nothing.
+fn is_canonical(
+	data: &FunctionData,
+	entry: BasicBlockId,
+	insts: &[(BasicBlockId, usize, bool)],
+) -> bool {
+	let prefix = data
+		.layout()
+		.insts(entry)
+		.iter()
+		.take_while(|inst| {
+			matches!(
+				data.dfg().value(**inst).value_ty(),
+				ValueType::Alloc(..) | ValueType::GetElementPtr(..) | ValueType::Load(..)
+			)
+		})
+		.count();
+
+	let mut loads = 0;
+	let mut stored = HashSet::new();
+
+	for (bb, index, is_load) in insts {
+		if *is_load {
+			loads += 1;
+
+			if *bb != entry || *index >= prefix || loads > 1 {
+				return false;
+			}
+
+			continue;
+		}
+
+		let block = data.layout().insts(*bb);
+		let returns = block.last().is_some_and(|inst| {
+			matches!(data.dfg().value(*inst).value_ty(), ValueType::Return(..))
+		});
+
+		let at_end = block[*index..block.len() - 1]
+			.iter()
+			.all(|inst| matches!(data.dfg().value(*inst).value_ty(), ValueType::Store(..)));
+
+		if !returns || !at_end || !stored.insert(*bb) {
+			return false;
+		}
+	}
+
+	true
+}
+
+/// Replaces the loads and stores of the locations with the values they would see, passing them
+/// between basic blocks as parameters.
+fn rename(
+	data: &mut FunctionData,
+	entry: BasicBlockId,
+	pointers: &HashMap<ValueId, Location>,
+	locations: &BTreeSet<Location>,
+) {
+	let flow = ControlFlow::new(data);
+
+	let reachable = flow.order().iter().copied().collect::<HashSet<_>>();
+	let order = flow
+		.order()
+		.iter()
+		.copied()
+		.chain(
+			data.layout()
+				.basic_blocks()
+				.iter()
+				.copied()
+				.filter(|bb| !reachable.contains(bb)),
+		)
+		.collect::<Vec<_>>();
+
+	let (initial, entry_pointers) = initial_values(data, entry, locations);
+
+	let mut exits = HashMap::<BasicBlockId, HashMap<Location, ValueId>>::new();
+	let mut merges = Vec::new();
+
+	for bb in order {
+		let preds = flow.predecessors(bb);
+
+		let mut current = if bb == entry {
+			initial.clone()
+		} else if let [pred] = preds
+			&& let Some(exit) = exits.get(pred)
+		{
+			exit.clone()
+		} else if preds.is_empty() {
+			let mut values = HashMap::new();
+
+			for location in locations {
+				values.insert(*location, data.dfg_mut().new_value().undef(Type::i32()));
+			}
+
+			values
+		} else {
+			let mut values = HashMap::new();
+
+			for location in locations {
+				values.insert(*location, push_param(data, bb, Type::i32()));
+			}
+
+			merges.push(bb);
+
+			values
+		};
+
+		for inst in data.layout().insts(bb).to_vec() {
+			match data.dfg().value(inst).value_ty() {
+				ValueType::Load(load) => {
+					let Some(location) =
+						pointers.get(&load.src()).filter(|l| locations.contains(*l))
+					else {
+						continue;
+					};
+
+					let value = current[location];
+
+					data.dfg_mut().replace_uses(inst, value);
+					data.layout_mut().remove_inst(inst);
+					data.dfg_mut().remove_value(inst);
+				}
+				ValueType::Store(store) => {
+					let Some(location) = pointers
+						.get(&store.dest())
+						.filter(|l| locations.contains(*l))
+					else {
+						continue;
+					};
+
+					current.insert(*location, store.value());
+
+					data.layout_mut().remove_inst(inst);
+					data.dfg_mut().remove_value(inst);
+				}
+				ValueType::Return(..) => {
+					for (location, ptr) in &entry_pointers {
+						if current[location] == initial[location] {
+							continue;
+						}
+
+						let store = data.dfg_mut().new_value().store(current[location], *ptr);
+						let index = data.layout().insts(bb).len() - 1;
+						data.layout_mut().insert_inst(bb, index, store);
+					}
+				}
+				_ => {}
+			}
+		}
+
+		exits.insert(bb, current);
+	}
+
+	for bb in merges {
+		let preds = data
+			.dfg()
+			.basic_block(bb)
+			.used_by()
+			.iter()
+			.map(|user| (*user, data.layout().parent_basic_block(*user)))
+			.collect::<HashMap<_, _>>();
+
+		for_each_incoming(data, bb, |user, args| {
+			if let Some(exit) = preds[&user].and_then(|pred| exits.get(&pred)) {
+				args.extend(locations.iter().map(|location| exit[location]));
+			}
+		});
+	}
+}
+
+/// The value of each location on entry: loaded for globals, and undefined otherwise. Also
+/// returns the pointers to the global locations, created at the start of the entry block.
+fn initial_values(
+	data: &mut FunctionData,
+	entry: BasicBlockId,
+	locations: &BTreeSet<Location>,
+) -> (HashMap<Location, ValueId>, Vec<(Location, ValueId)>) {
+	let mut initial = HashMap::new();
+	let mut pointers = Vec::new();
+	let mut index = 0;
+
+	for location in locations {
+		let (base, element) = *location;
+
+		if !base.is_global() {
+			initial.insert(*location, data.dfg_mut().new_value().undef(Type::i32()));
+			continue;
+		}
+
+		let ptr = if let Some(element) = element {
+			let element = data.dfg_mut().new_value().integer(element);
+			let ptr = data.dfg_mut().new_value().get_element_ptr(base, element);
+
+			data.layout_mut().insert_inst(entry, index, ptr);
+			index += 1;
+
+			ptr
+		} else {
+			base
+		};
+
+		let load = data.dfg_mut().new_value().load(ptr);
+		data.layout_mut().insert_inst(entry, index, load);
+		index += 1;
+
+		initial.insert(*location, load);
+		pointers.push((*location, ptr));
+	}
+
+	(initial, pointers)
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::ToString as _;
+
+	use super::Mem2Reg;
+	use crate::{ir::Program, opt::PassManager};
+
+	fn promote(text: &str) -> Program {
+		let mut program = text.parse().unwrap();
+
+		PassManager::new()
+			.and_with_module_pass(Mem2Reg::new())
+			.run_passes(&mut program);
+		program.verify().unwrap();
+
+		program
+	}
+
+	#[test]
+	fn promotes_allocs() {
+		let program = promote(
+			"fun @main(%n: i32): i32 {
+%entry:
+  %p = alloc i32
+  store %n, %p
+  jump %loop
+
+%loop:
+  %x = load %p
+  %y = sub %x, 1
+  store %y, %p
+  br %y, %loop, %exit
+
+%exit:
+  %z = load %p
+  ret %z
+}",
+		);
+
+		assert_eq!(
+			program.to_string(),
+			"fun @main(%n: i32): i32 {
+%entry:
+  %p = alloc i32
+  jump %loop(%n)
+
+%loop(%0: i32):
+  %y = sub %0, 1
+  br %y, %loop(%y), %exit
+
+%exit:
+  ret %y
+}
+"
+		);
+	}
+
+	#[test]
+	fn promotes_global_cells() {
+		let program = promote(
+			"global @tape = alloc [i32, 2], zeroinit
+
+decl @putchar(i32)
+
+fun @main() {
+%entry:
+  %p = getelemptr @tape, 1
+  %x = load %p
+  %y = add %x, 1
+  store %y, %p
+  %z = load %p
+  call @putchar(%z)
+  ret
+}",
+		);
+
+		assert_eq!(
+			program.to_string(),
+			"global @tape = alloc [i32, 2], zeroinit
+
+decl @putchar(i32)
+
+fun @main() {
+%entry:
+  %0 = getelemptr @tape, 1
+  %1 = load %0
+  %p = getelemptr @tape, 1
+  %y = add %1, 1
+  call @putchar(%y)
+  store %y, %0
+  ret
+}
+"
+		);
+	}
+
+	#[test]
+	fn leaves_escaping_memory() {
+		let text = "global @tape = alloc [i32, 2], zeroinit
+
+fun @main(%n: i32): i32 {
+%entry:
+  %p = getelemptr @tape, 0
+  store 1, %p
+  %q = getelemptr @tape, %n
+  %x = load %q
+  ret %x
+}
+
+fun @f(): i32 {
+%entry:
+  %p = getelemptr @tape, 0
+  store 1, %p
+  %x = call @main(0)
+  %y = load %p
+  ret %y
+}
+";
+
+		assert_eq!(promote(text).to_string(), text);
+	}
+}
@@ -0,0 +1,151 @@
+mod constant_folding;
+mod dead_code;
+mod flow;
+mod mem2reg;
+mod simplify_cfg;
+
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt::Debug;
+
+pub use self::{constant_folding::*, dead_code::*, mem2reg::*, simplify_cfg::*};
+use crate::ir::{FunctionData, FunctionId, Program};
+
+/// A pass that rewrites one function at a time.
+pub trait FunctionPass: Debug {
+	/// Returns whether the function changed.
+	fn run_on(&mut self, func: FunctionId, data: &mut FunctionData) -> bool;
+}
+
+/// A pass that needs to see the whole program.
+pub trait ModulePass: Debug {
+	/// Returns whether the program changed.
+	fn run_on(&mut self, program: &mut Program) -> bool;
+}
+
+#[derive(Debug)]
+pub enum Pass {
+	Function(Box<dyn FunctionPass>),
+	Module(Box<dyn ModulePass>),
+}
+
+/// Runs passes over a [`Program`] until none of them make progress.
+#[derive(Debug, Default)]
+pub struct PassManager {
+	passes: Vec<Pass>,
+}
+
+impl PassManager {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self { passes: Vec::new() }
+	}
+
+	/// Promotes memory to SSA values, folds constants, and cleans up the leftovers.
+	#[must_use]
+	pub fn standard() -> Self {
+		Self::new()
+			.and_with_module_pass(Mem2Reg::new())
+			.and_with_function_pass(ConstantFolding::new())
+			.and_with_function_pass(DeadCodeElimination::new())
+			.and_with_function_pass(SimplifyCfg::new())
+	}
+
+	#[must_use]
+	pub fn and_with_function_pass(mut self, pass: impl FunctionPass + 'static) -> Self {
+		self.passes.push(Pass::Function(Box::new(pass)));
+		self
+	}
+
+	#[must_use]
+	pub fn and_with_module_pass(mut self, pass: impl ModulePass + 'static) -> Self {
+		self.passes.push(Pass::Module(Box::new(pass)));
+		self
+	}
+
+	#[must_use]
+	pub fn passes(&self) -> &[Pass] {
+		&self.passes
+	}
+
+	/// Runs every pass in order, and repeats until a whole iteration changes nothing.
+	///
+	/// Returns the number of iterations, including the last one.
+	pub fn run_passes(&mut self, program: &mut Program) -> usize {
+		let mut iteration = 1;
+
+		while self.run_iteration(program) {
+			iteration += 1;
+		}
+
+		iteration
+	}
+
+	fn run_iteration(&mut self, program: &mut Program) -> bool {
+		let mut progress = false;
+
+		for pass in &mut self.passes {
+			match pass {
+				Pass::Function(pass) => {
+					for func in program.func_layout().to_vec() {
+						let data = program.func_mut(func);
+
+						if !data.is_decl() {
+							progress |= pass.run_on(func, data);
+						}
+					}
+				}
+				Pass::Module(pass) => progress |= pass.run_on(program),
+			}
+		}
+
+		progress
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use vmm_ir::Instruction;
+
+	use super::PassManager;
+	use crate::{Evaluator, Lowering, Value};
+
+	#[test]
+	fn optimizes_lowered_programs() {
+		let instructions = [
+			Instruction::inc_val(3),
+			Instruction::write_once(),
+			Instruction::move_ptr(1),
+			Instruction::read(),
+			Instruction::dynamic_loop([
+				Instruction::inc_val(-1),
+				Instruction::move_ptr(-1),
+				Instruction::inc_val(2),
+				Instruction::move_ptr(1),
+			]),
+			Instruction::move_ptr(-1),
+			Instruction::write_once(),
+		];
+
+		let lowering = Lowering::new().and_with_tape_len(4);
+		let mut program = lowering.lower(&instructions).unwrap();
+
+		assert!(PassManager::standard().run_passes(&mut program) > 1);
+		assert_eq!(PassManager::standard().run_passes(&mut program), 1);
+
+		program.verify().unwrap();
+
+		let mut optimized = Evaluator::new(&program).and_with_input(*b"\x05");
+		optimized.run().unwrap();
+
+		let lowered = lowering.lower(&instructions).unwrap();
+		let mut evaluator = Evaluator::new(&lowered).and_with_input(*b"\x05");
+		evaluator.run().unwrap();
+
+		assert_eq!(optimized.output(), [3, 13]);
+		assert_eq!(optimized.output(), evaluator.output());
+		assert_eq!(
+			optimized.global(program.inst_layout()[0]),
+			Some(&[Value::Int(13), Value::Int(0), Value::Int(0), Value::Int(0)][..])
+		);
+	}
+}
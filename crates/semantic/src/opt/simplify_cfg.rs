@@ -0,0 +1,397 @@
+use alloc::vec::Vec;
+
+use hashbrown::HashSet;
+
+use super::{
+	FunctionPass,
+	flow::{ControlFlow, incoming_args, integer, remove_param, set_value_type, terminator},
+};
+use crate::ir::{FunctionData, FunctionId, Jump, ValueBuilder as _, ValueId, ValueType};
+
+/// Folds constant branches, threads jumps through empty basic blocks, merges basic blocks into
+/// their only predecessor, and removes unreachable basic blocks and redundant parameters.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SimplifyCfg;
+
+impl SimplifyCfg {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self
+	}
+
+	fn fold_branches(data: &mut FunctionData) -> bool {
+		let mut progress = false;
+
+		for bb in data.layout().basic_blocks().to_vec() {
+			let Some(inst) = terminator(data, bb) else {
+				continue;
+			};
+
+			let ValueType::Branch(branch) = data.dfg().value(inst).value_ty() else {
+				continue;
+			};
+
+			let jump = match integer(data, branch.cond()) {
+				Some(0) => Jump::with_args(branch.false_basic_block(), branch.false_args().clone()),
+				Some(_) => Jump::with_args(branch.true_basic_block(), branch.true_args().clone()),
+				None if branch.true_basic_block() == branch.false_basic_block()
+					&& branch.true_args() == branch.false_args() =>
+				{
+					Jump::with_args(branch.true_basic_block(), branch.true_args().clone())
+				}
+				None => continue,
+			};
+
+			set_value_type(data, inst, ValueType::Jump(jump));
+			progress = true;
+		}
+
+		progress
+	}
+
+	/// Makes branches and jumps to a basic block that only jumps somewhere else go there
+	/// directly.
+	fn thread_jumps(data: &mut FunctionData) -> bool {
+		let mut progress = false;
+		let entry = data.layout().entry_basic_block();
+
+		for bb in data.layout().basic_blocks().to_vec() {
+			if Some(bb) == entry
+				|| !data.dfg().basic_block(bb).params().is_empty()
+				|| data.layout().insts(bb).len() != 1
+			{
+				continue;
+			}
+
+			let inst = data.layout().insts(bb)[0];
+			let ValueType::Jump(jump) = data.dfg().value(inst).value_ty() else {
+				continue;
+			};
+
+			if jump.target() == bb || data.dfg().basic_block(bb).used_by().is_empty() {
+				continue;
+			}
+
+			let (target, args) = (jump.target(), jump.args().clone());
+
+			let users = data
+				.dfg()
+				.basic_block(bb)
+				.used_by()
+				.iter()
+				.copied()
+				.collect::<Vec<_>>();
+
+			for user in users {
+				let mut value_ty = data.dfg().value(user).value_ty().clone();
+
+				match &mut value_ty {
+					ValueType::Branch(branch) => {
+						if branch.true_basic_block() == bb {
+							*branch.true_basic_block_mut() = target;
+							branch.true_args_mut().clone_from(&args);
+						}
+
+						if branch.false_basic_block() == bb {
+							*branch.false_basic_block_mut() = target;
+							branch.false_args_mut().clone_from(&args);
+						}
+					}
+					ValueType::Jump(jump) => {
+						*jump.target_mut() = target;
+						jump.args_mut().clone_from(&args);
+					}
+					_ => continue,
+				}
+
+				set_value_type(data, user, value_ty);
+			}
+
+			progress = true;
+		}
+
+		progress
+	}
+
+	/// Appends a basic block to its only predecessor, when that predecessor jumps to it.
+	fn merge_basic_blocks(data: &mut FunctionData) -> bool {
+		let mut progress = false;
+		let entry = data.layout().entry_basic_block();
+
+		for bb in data.layout().basic_blocks().to_vec() {
+			if Some(bb) == entry || !data.layout().contains_basic_block(bb) {
+				continue;
+			}
+
+			let users = data.dfg().basic_block(bb).used_by();
+			let Some(jump) = users.iter().next().copied().filter(|_| users.len() == 1) else {
+				continue;
+			};
+
+			let ValueType::Jump(jump_data) = data.dfg().value(jump).value_ty() else {
+				continue;
+			};
+
+			let Some(pred) = data.layout().parent_basic_block(jump) else {
+				continue;
+			};
+
+			if pred == bb || terminator(data, pred) != Some(jump) {
+				continue;
+			}
+
+			let args = jump_data.args().clone();
+			let params = data.dfg().basic_block(bb).params().clone();
+
+			for (param, arg) in params.into_iter().zip(args) {
+				data.dfg_mut().replace_uses(param, arg);
+			}
+
+			data.layout_mut().remove_inst(jump);
+			data.dfg_mut().remove_value(jump);
+
+			for inst in data
+				.layout_mut()
+				.remove_basic_block(bb)
+				.into_iter()
+				.flatten()
+			{
+				data.layout_mut().push_inst(pred, inst);
+			}
+
+			data.dfg_mut().remove_basic_block(bb);
+
+			progress = true;
+		}
+
+		progress
+	}
+
+	fn remove_unreachable(data: &mut FunctionData) -> bool {
+		let reachable = ControlFlow::new(data)
+			.order()
+			.iter()
+			.copied()
+			.collect::<HashSet<_>>();
+
+		let unreachable = data
+			.layout()
+			.basic_blocks()
+			.iter()
+			.copied()
+			.filter(|bb| !reachable.contains(bb))
+			.collect::<Vec<_>>();
+
+		if unreachable.is_empty() {
+			return false;
+		}
+
+		let mut removed = Vec::new();
+
+		for bb in &unreachable {
+			removed.extend(
+				data.layout_mut()
+					.remove_basic_block(*bb)
+					.into_iter()
+					.flatten(),
+			);
+		}
+
+		// Cut every instruction loose first, as they may use each other.
+		for inst in &removed {
+			let ty = data.dfg().value(*inst).ty().clone();
+			data.dfg_mut().replace_value_with(*inst).undef(ty);
+		}
+
+		for bb in unreachable {
+			for param in data.dfg().basic_block(bb).params().clone() {
+				Self::replace_with_undef(data, param);
+			}
+
+			data.dfg_mut().remove_basic_block(bb);
+		}
+
+		for inst in removed {
+			Self::replace_with_undef(data, inst);
+			data.dfg_mut().remove_value(inst);
+		}
+
+		true
+	}
+
+	/// Replaces parameters that are always passed the same value, or themselves, with that value.
+	fn remove_redundant_params(data: &mut FunctionData) -> bool {
+		let mut progress = false;
+		let entry = data.layout().entry_basic_block();
+
+		for bb in data.layout().basic_blocks().to_vec() {
+			if Some(bb) == entry {
+				continue;
+			}
+
+			let mut index = 0;
+
+			while index < data.dfg().basic_block(bb).params().len() {
+				let param = data.dfg().basic_block(bb).params()[index];
+
+				let mut incoming = incoming_args(data, bb, index)
+					.into_iter()
+					.filter(|arg| *arg != param);
+
+				let Some(value) = incoming
+					.next()
+					.filter(|value| incoming.all(|v| v == *value))
+				else {
+					index += 1;
+					continue;
+				};
+
+				data.dfg_mut().replace_uses(param, value);
+				remove_param(data, bb, index);
+
+				progress = true;
+			}
+		}
+
+		progress
+	}
+
+	fn replace_with_undef(data: &mut FunctionData, value: ValueId) {
+		if data.dfg().value(value).used_by().is_empty() {
+			return;
+		}
+
+		let ty = data.dfg().value(value).ty().clone();
+		let undef = data.dfg_mut().new_value().undef(ty);
+		data.dfg_mut().replace_uses(value, undef);
+	}
+}
+
+impl FunctionPass for SimplifyCfg {
+	fn run_on(&mut self, _: FunctionId, data: &mut FunctionData) -> bool {
+		let mut progress = Self::fold_branches(data);
+
+		progress |= Self::thread_jumps(data);
+		progress |= Self::remove_unreachable(data);
+		progress |= Self::merge_basic_blocks(data);
+		progress |= Self::remove_redundant_params(data);
+
+		progress
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::ToString as _;
+
+	use super::SimplifyCfg;
+	use crate::{ir::Program, opt::PassManager};
+
+	fn simplify(text: &str) -> Program {
+		let mut program = text.parse().unwrap();
+
+		PassManager::new()
+			.and_with_function_pass(SimplifyCfg::new())
+			.run_passes(&mut program);
+		program.verify().unwrap();
+
+		program
+	}
+
+	#[test]
+	fn folds_constant_branches() {
+		let program = simplify(
+			"decl @putchar(i32)
+
+fun @main() {
+%entry:
+  br 0, %dead, %live
+
+%dead:
+  call @putchar(1)
+  jump %live
+
+%live:
+  call @putchar(2)
+  ret
+}",
+		);
+
+		assert_eq!(
+			program.to_string(),
+			"decl @putchar(i32)\n\nfun @main() {\n%entry:\n  call @putchar(2)\n  ret\n}\n"
+		);
+	}
+
+	#[test]
+	fn threads_jumps() {
+		let program = simplify(
+			"fun @main(%n: i32): i32 {
+%entry:
+  br %n, %a, %b
+
+%a:
+  jump %end(%n)
+
+%b:
+  jump %end(0)
+
+%end(%x: i32):
+  %y = add %x, 1
+  br %y, %end(%y), %exit
+
+%exit:
+  ret %x
+}",
+		);
+
+		assert_eq!(
+			program.to_string(),
+			"fun @main(%n: i32): i32 {
+%entry:
+  br %n, %end(%n), %end(0)
+
+%end(%x: i32):
+  %y = add %x, 1
+  br %y, %end(%y), %exit
+
+%exit:
+  ret %x
+}
+"
+		);
+	}
+
+	#[test]
+	fn removes_redundant_params() {
+		let program = simplify(
+			"fun @main(%n: i32): i32 {
+%entry:
+  jump %loop(%n)
+
+%loop(%x: i32):
+  %y = sub %n, 1
+  br %y, %loop(%x), %exit
+
+%exit:
+  ret %x
+}",
+		);
+
+		assert_eq!(
+			program.to_string(),
+			"fun @main(%n: i32): i32 {
+%entry:
+  jump %loop
+
+%loop:
+  %y = sub %n, 1
+  br %y, %loop, %exit
+
+%exit:
+  ret %n
+}
+"
+		);
+	}
+}
@@ -0,0 +1,142 @@
+use alloc::{format, string::String, vec::Vec};
+use core::{
+	fmt::{Display, Formatter, Result as FmtResult},
+	iter::Peekable,
+	str::Chars,
+};
+
+use super::ParseProgramError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+	Symbol(String),
+	Int(i32),
+	Word(String),
+	Punct(char),
+}
+
+impl Display for Token {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::Symbol(s) | Self::Word(s) => f.write_str(s),
+			Self::Int(i) => Display::fmt(&i, f),
+			Self::Punct(c) => Display::fmt(&c, f),
+		}
+	}
+}
+
+/// Splits the text into tokens, each tagged with the line it starts on.
+pub fn tokenize(s: &str) -> Result<Vec<(Token, usize)>, ParseProgramError> {
+	let mut lexer = Lexer {
+		chars: s.chars().peekable(),
+		line: 1,
+	};
+
+	let mut tokens = Vec::new();
+
+	while let Some(token) = lexer.next_token()? {
+		tokens.push((token, lexer.line));
+	}
+
+	Ok(tokens)
+}
+
+struct Lexer<'a> {
+	chars: Peekable<Chars<'a>>,
+	line: usize,
+}
+
+impl Lexer<'_> {
+	fn next_token(&mut self) -> Result<Option<Token>, ParseProgramError> {
+		self.skip_trivia()?;
+
+		let Some(c) = self.chars.next() else {
+			return Ok(None);
+		};
+
+		let token = match c {
+			'@' | '%' => {
+				let mut symbol = String::from(c);
+				self.take_while(&mut symbol, |c| c.is_ascii_alphanumeric() || c == '_');
+
+				if symbol.len() == 1 {
+					return Err(self.error("expected a symbol name"));
+				}
+
+				Token::Symbol(symbol)
+			}
+			'-' | '0'..='9' => {
+				let mut digits = String::from(c);
+				self.take_while(&mut digits, |c| c.is_ascii_digit());
+
+				let value = digits
+					.parse::<i32>()
+					.map_err(|_| self.error(format!("invalid integer {digits}")))?;
+
+				Token::Int(value)
+			}
+			c if c.is_ascii_alphabetic() || c == '_' => {
+				let mut word = String::from(c);
+				self.take_while(&mut word, |c| c.is_ascii_alphanumeric() || c == '_');
+
+				Token::Word(word)
+			}
+			'=' | ',' | ':' | '(' | ')' | '{' | '}' | '[' | ']' | '*' => Token::Punct(c),
+			c => return Err(self.error(format!("unexpected character {c:?}"))),
+		};
+
+		Ok(Some(token))
+	}
+
+	fn skip_trivia(&mut self) -> Result<(), ParseProgramError> {
+		loop {
+			match self.chars.peek() {
+				Some('\n') => {
+					self.line += 1;
+					self.chars.next();
+				}
+				Some(c) if c.is_whitespace() => {
+					self.chars.next();
+				}
+				Some('/') => {
+					self.chars.next();
+
+					match self.chars.next() {
+						Some('/') => while self.chars.next_if(|c| *c != '\n').is_some() {},
+						Some('*') => self.skip_block_comment()?,
+						_ => return Err(self.error("expected a comment")),
+					}
+				}
+				_ => return Ok(()),
+			}
+		}
+	}
+
+	fn skip_block_comment(&mut self) -> Result<(), ParseProgramError> {
+		let mut star = false;
+
+		loop {
+			match self.chars.next() {
+				Some('/') if star => return Ok(()),
+				Some(c) => {
+					if c == '\n' {
+						self.line += 1;
+					}
+
+					star = c == '*';
+				}
+				None => return Err(self.error("unterminated comment")),
+			}
+		}
+	}
+
+	fn take_while(&mut self, out: &mut String, f: impl Fn(char) -> bool) {
+		while let Some(c) = self.chars.next_if(|c| f(*c)) {
+			out.push(c);
+		}
+	}
+
+	fn error(&self, reason: impl Into<String>) -> ParseProgramError {
+		ParseProgramError::new(self.line, reason)
+	}
+}
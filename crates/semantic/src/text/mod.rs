@@ -0,0 +1,186 @@
+mod lexer;
+mod parse;
+mod print;
+
+use alloc::string::String;
+use core::{
+	error::Error as CoreError,
+	fmt::{Display, Formatter, Result as FmtResult},
+	str::FromStr,
+};
+
+use crate::ir::Program;
+
+/// Parses the Koopa-compatible text that the [`Display`] implementation of [`Program`] prints.
+impl FromStr for Program {
+	type Err = ParseProgramError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let tokens = self::lexer::tokenize(s)?;
+		let items = self::parse::Parser::new(tokens).items()?;
+
+		self::parse::build(&items)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseProgramError {
+	line: usize,
+	reason: String,
+}
+
+impl ParseProgramError {
+	pub(crate) fn new(line: usize, reason: impl Into<String>) -> Self {
+		Self {
+			line,
+			reason: reason.into(),
+		}
+	}
+
+	/// The line the error was found on, starting at 1.
+	#[must_use]
+	pub const fn line(&self) -> usize {
+		self.line
+	}
+
+	#[must_use]
+	pub fn reason(&self) -> &str {
+		&self.reason
+	}
+}
+
+impl Display for ParseProgramError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		f.write_str("line ")?;
+		Display::fmt(&self.line, f)?;
+		f.write_str(": ")?;
+		f.write_str(&self.reason)
+	}
+}
+
+impl CoreError for ParseProgramError {}
+
+#[cfg(test)]
+mod tests {
+	use alloc::{string::ToString as _, vec::Vec};
+
+	use super::ParseProgramError;
+	use crate::ir::{FunctionData, LocalInstBuilder as _, Program, Type};
+
+	const FIXTURE: &str = "global @tape = alloc [i32, 4], {1, 2, 3, 4}
+
+decl @getchar(): i32
+
+decl @putchar(i32)
+
+fun @sum(%n: i32): i32 {
+%entry:
+  jump %cond(0, %n)
+
+%cond(%acc: i32, %i: i32):
+  br %i, %body, %end
+
+%body:
+  %next = sub %i, 1
+  %elem = getelemptr @tape, %next
+  %x = load %elem
+  %total = add %acc, %x
+  jump %cond(%total, %next)
+
+%end:
+  ret %acc
+}
+
+fun @main() {
+%entry:
+  %c = call @getchar()
+  %s = call @sum(%c)
+  call @putchar(%s)
+  ret
+}
+";
+
+	fn round_trip(text: &str) -> Result<(), ParseProgramError> {
+		let program = text.parse::<Program>()?;
+		let printed = program.to_string();
+
+		assert_eq!(program.verify(), Ok(()));
+		assert_eq!(printed, text);
+		assert_eq!(printed.parse::<Program>()?.to_string(), text);
+
+		Ok(())
+	}
+
+	#[test]
+	fn round_trips() -> Result<(), ParseProgramError> {
+		round_trip(FIXTURE)
+	}
+
+	#[test]
+	fn skips_comments() -> Result<(), ParseProgramError> {
+		let program = "// the entry point
+fun @main(): i32 {
+%entry: /* nothing
+  happens here */
+  ret 0
+}"
+		.parse::<Program>()?;
+
+		assert_eq!(
+			program.to_string(),
+			"fun @main(): i32 {\n%entry:\n  ret 0\n}\n"
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn names_temporaries() {
+		let mut program = Program::new();
+		let main = program.new_func(FunctionData::new("@main", Vec::new(), Type::i32()));
+
+		let func = program.func_mut(main);
+		let entry = func.dfg_mut().new_basic_block().basic_block(None);
+		func.layout_mut().push_basic_block(entry);
+
+		let ptr = func.dfg_mut().new_value().alloc(Type::i32());
+		let value = func.dfg_mut().new_value().load(ptr);
+		func.dfg_mut().set_value_name(value, "%0");
+		let ret = func.dfg_mut().new_value().ret(Some(value));
+
+		for inst in [ptr, value, ret] {
+			func.layout_mut().push_inst(entry, inst);
+		}
+
+		assert_eq!(
+			program.to_string(),
+			"fun @main(): i32 {\n%1:\n  %2 = alloc i32\n  %0 = load %2\n  ret %0\n}\n"
+		);
+	}
+
+	#[test]
+	fn reports_errors() {
+		let error = |text: &str| text.parse::<Program>().unwrap_err();
+
+		assert_eq!(
+			error("fun @main() {\n%entry:\n  ret %x\n}"),
+			ParseProgramError::new(3, "function does not return a value")
+		);
+		assert_eq!(
+			error("fun @main(): i32 {\n%entry:\n\n  ret %x\n}").to_string(),
+			"line 4: %x is not defined"
+		);
+		assert_eq!(
+			error(
+				"fun @main() {\n%entry:\n  %p = alloc i32\n  %x = load %p\n  store %x, %x\n  ret\n}"
+			)
+			.reason(),
+			"expected a pointer, found i32"
+		);
+		assert_eq!(
+			error("fun @main() {\n%entry:\n  %x = add %x, 1\n  ret\n}").reason(),
+			"%x depends on itself"
+		);
+		assert_eq!(error("fun @main() {\n%entry:\n}").line(), 3);
+	}
+}
@@ -0,0 +1,940 @@
+use alloc::{
+	borrow::ToOwned as _,
+	format,
+	string::{String, ToString as _},
+	vec::Vec,
+};
+
+use hashbrown::{HashMap, HashSet};
+
+use super::{ParseProgramError, lexer::Token};
+use crate::ir::{
+	Aggregate, BasicBlockId, BinaryOp, FunctionData, FunctionId, GlobalInstBuilder as _, Integer,
+	LocalInstBuilder as _, Program, Type, TypeKind, Undef, ValueBuilder as _, ValueData, ValueId,
+	ValueType, ZeroInit,
+};
+
+type Result<T> = core::result::Result<T, ParseProgramError>;
+
+pub enum Item {
+	Global {
+		name: String,
+		ty: Type,
+		init: Operand,
+		line: usize,
+	},
+	Decl {
+		name: String,
+		params: Vec<Type>,
+		ret: Type,
+		line: usize,
+	},
+	Fun {
+		name: String,
+		params: Vec<(String, Type)>,
+		ret: Type,
+		blocks: Vec<Block>,
+		line: usize,
+	},
+}
+
+pub struct Block {
+	name: String,
+	params: Vec<(String, Type)>,
+	stmts: Vec<Stmt>,
+	line: usize,
+}
+
+pub struct Stmt {
+	result: Option<String>,
+	inst: Inst,
+	line: usize,
+}
+
+pub enum Inst {
+	Alloc(Type),
+	Load(Operand),
+	Store(Operand, Operand),
+	GetPtr(Operand, Operand),
+	GetElementPtr(Operand, Operand),
+	Binary(BinaryOp, Operand, Operand),
+	Branch(Operand, Target, Target),
+	Jump(Target),
+	Call(String, Vec<Operand>),
+	Return(Option<Operand>),
+}
+
+impl Inst {
+	const fn is_terminator(&self) -> bool {
+		matches!(self, Self::Branch(..) | Self::Jump(..) | Self::Return(..))
+	}
+}
+
+pub struct Target {
+	name: String,
+	args: Vec<Operand>,
+}
+
+pub enum Operand {
+	Symbol(String),
+	Int(i32),
+	Undef,
+	ZeroInit,
+	Aggregate(Vec<Operand>),
+}
+
+pub struct Parser {
+	tokens: Vec<(Token, usize)>,
+	pos: usize,
+}
+
+impl Parser {
+	pub const fn new(tokens: Vec<(Token, usize)>) -> Self {
+		Self { tokens, pos: 0 }
+	}
+
+	pub fn items(mut self) -> Result<Vec<Item>> {
+		let mut items = Vec::new();
+
+		while self.peek().is_some() {
+			items.push(self.item()?);
+		}
+
+		Ok(items)
+	}
+
+	fn item(&mut self) -> Result<Item> {
+		let line = self.line();
+
+		match self.word()?.as_str() {
+			"global" => {
+				let name = self.symbol()?;
+				self.punct('=')?;
+				self.keyword("alloc")?;
+				let ty = self.ty()?;
+				self.punct(',')?;
+				let init = self.operand()?;
+
+				Ok(Item::Global {
+					name,
+					ty,
+					init,
+					line,
+				})
+			}
+			"decl" => {
+				let name = self.symbol()?;
+				let params = self.list('(', ')', Self::ty)?;
+				let ret = self.ret_ty()?;
+
+				Ok(Item::Decl {
+					name,
+					params,
+					ret,
+					line,
+				})
+			}
+			"fun" => {
+				let name = self.symbol()?;
+				let params = self.list('(', ')', Self::param)?;
+				let ret = self.ret_ty()?;
+				self.punct('{')?;
+
+				let mut blocks = Vec::new();
+				while !self.eat_punct('}') {
+					blocks.push(self.block()?);
+				}
+
+				Ok(Item::Fun {
+					name,
+					params,
+					ret,
+					blocks,
+					line,
+				})
+			}
+			word => Err(self.error(format!(
+				"expected `global`, `decl` or `fun`, found `{word}`"
+			))),
+		}
+	}
+
+	fn block(&mut self) -> Result<Block> {
+		let line = self.line();
+		let name = self.symbol()?;
+
+		let params = if self.is_punct('(') {
+			self.list('(', ')', Self::param)?
+		} else {
+			Vec::new()
+		};
+
+		self.punct(':')?;
+
+		let mut stmts = Vec::<Stmt>::new();
+
+		while stmts.last().is_none_or(|stmt| !stmt.inst.is_terminator()) {
+			stmts.push(self.stmt()?);
+		}
+
+		Ok(Block {
+			name,
+			params,
+			stmts,
+			line,
+		})
+	}
+
+	fn stmt(&mut self) -> Result<Stmt> {
+		let line = self.line();
+
+		let result = if matches!(self.peek(), Some(Token::Symbol(..))) {
+			let name = self.symbol()?;
+			self.punct('=')?;
+			Some(name)
+		} else {
+			None
+		};
+
+		let inst = match self.word()?.as_str() {
+			"alloc" if result.is_some() => Inst::Alloc(self.ty()?),
+			"load" if result.is_some() => Inst::Load(self.operand()?),
+			"getptr" if result.is_some() => {
+				let (src, index) = self.operands()?;
+				Inst::GetPtr(src, index)
+			}
+			"getelemptr" if result.is_some() => {
+				let (src, index) = self.operands()?;
+				Inst::GetElementPtr(src, index)
+			}
+			"call" => {
+				let callee = self.symbol()?;
+				let args = self.list('(', ')', Self::operand)?;
+				Inst::Call(callee, args)
+			}
+			"store" if result.is_none() => {
+				let (value, dest) = self.operands()?;
+				Inst::Store(value, dest)
+			}
+			"br" if result.is_none() => {
+				let cond = self.operand()?;
+				self.punct(',')?;
+				let true_target = self.target()?;
+				self.punct(',')?;
+				let false_target = self.target()?;
+				Inst::Branch(cond, true_target, false_target)
+			}
+			"jump" if result.is_none() => Inst::Jump(self.target()?),
+			"ret" if result.is_none() => {
+				let value = if self.starts_operand() {
+					Some(self.operand()?)
+				} else {
+					None
+				};
+
+				Inst::Return(value)
+			}
+			word if result.is_some() => match binary_op(word) {
+				Some(op) => {
+					let (lhs, rhs) = self.operands()?;
+					Inst::Binary(op, lhs, rhs)
+				}
+				None => return Err(self.error(format!("`{word}` does not produce a value"))),
+			},
+			word => return Err(self.error(format!("expected an instruction, found `{word}`"))),
+		};
+
+		Ok(Stmt { result, inst, line })
+	}
+
+	fn target(&mut self) -> Result<Target> {
+		let name = self.symbol()?;
+
+		let args = if self.is_punct('(') {
+			self.list('(', ')', Self::operand)?
+		} else {
+			Vec::new()
+		};
+
+		Ok(Target { name, args })
+	}
+
+	fn operands(&mut self) -> Result<(Operand, Operand)> {
+		let lhs = self.operand()?;
+		self.punct(',')?;
+		let rhs = self.operand()?;
+
+		Ok((lhs, rhs))
+	}
+
+	fn starts_operand(&self) -> bool {
+		match self.peek() {
+			Some(Token::Int(..) | Token::Punct('{')) => true,
+			Some(Token::Word(word)) => matches!(word.as_str(), "undef" | "zeroinit"),
+			// A symbol followed by a `:` or `=` starts the next block or statement.
+			Some(Token::Symbol(..)) => !matches!(
+				self.tokens.get(self.pos + 1),
+				Some((Token::Punct(':' | '=' | '('), _))
+			),
+			_ => false,
+		}
+	}
+
+	fn operand(&mut self) -> Result<Operand> {
+		match self.next()? {
+			Token::Symbol(name) => Ok(Operand::Symbol(name)),
+			Token::Int(i) => Ok(Operand::Int(i)),
+			Token::Word(word) if word == "undef" => Ok(Operand::Undef),
+			Token::Word(word) if word == "zeroinit" => Ok(Operand::ZeroInit),
+			Token::Punct('{') => {
+				self.pos -= 1;
+				self.list('{', '}', Self::operand).map(Operand::Aggregate)
+			}
+			token => Err(self.error(format!("expected a value, found `{token}`"))),
+		}
+	}
+
+	fn param(&mut self) -> Result<(String, Type)> {
+		let name = self.symbol()?;
+		self.punct(':')?;
+		let ty = self.ty()?;
+
+		Ok((name, ty))
+	}
+
+	fn ret_ty(&mut self) -> Result<Type> {
+		if self.eat_punct(':') {
+			self.ty()
+		} else {
+			Ok(Type::unit())
+		}
+	}
+
+	fn ty(&mut self) -> Result<Type> {
+		match self.next()? {
+			Token::Word(word) if word == "i32" => Ok(Type::i32()),
+			Token::Punct('*') => self.ty().map(Type::into_ptr),
+			Token::Punct('[') => {
+				let base = self.ty()?;
+				self.punct(',')?;
+
+				let len = match self.next()? {
+					Token::Int(len) if len > 0 => len as usize,
+					token => {
+						return Err(self
+							.error(format!("expected a positive array length, found `{token}`")));
+					}
+				};
+
+				self.punct(']')?;
+
+				Ok(base.into_array(len))
+			}
+			Token::Punct('(') => {
+				self.pos -= 1;
+				let params = self.list('(', ')', Self::ty)?;
+				let ret = self.ret_ty()?;
+
+				Ok(Type::function(params, ret))
+			}
+			token => Err(self.error(format!("expected a type, found `{token}`"))),
+		}
+	}
+
+	fn list<T>(
+		&mut self,
+		open: char,
+		close: char,
+		mut element: impl FnMut(&mut Self) -> Result<T>,
+	) -> Result<Vec<T>> {
+		self.punct(open)?;
+
+		let mut elements = Vec::new();
+
+		if self.eat_punct(close) {
+			return Ok(elements);
+		}
+
+		loop {
+			elements.push(element(self)?);
+
+			if self.eat_punct(close) {
+				return Ok(elements);
+			}
+
+			self.punct(',')?;
+		}
+	}
+
+	fn symbol(&mut self) -> Result<String> {
+		match self.next()? {
+			Token::Symbol(name) => Ok(name),
+			token => Err(self.error(format!("expected a symbol, found `{token}`"))),
+		}
+	}
+
+	fn word(&mut self) -> Result<String> {
+		match self.next()? {
+			Token::Word(word) => Ok(word),
+			token => Err(self.error(format!("expected a keyword, found `{token}`"))),
+		}
+	}
+
+	fn keyword(&mut self, keyword: &str) -> Result<()> {
+		match self.next()? {
+			Token::Word(word) if word == keyword => Ok(()),
+			token => Err(self.error(format!("expected `{keyword}`, found `{token}`"))),
+		}
+	}
+
+	fn punct(&mut self, c: char) -> Result<()> {
+		match self.next()? {
+			Token::Punct(p) if p == c => Ok(()),
+			token => Err(self.error(format!("expected `{c}`, found `{token}`"))),
+		}
+	}
+
+	fn is_punct(&self, c: char) -> bool {
+		matches!(self.peek(), Some(Token::Punct(p)) if *p == c)
+	}
+
+	fn eat_punct(&mut self, c: char) -> bool {
+		let is_punct = self.is_punct(c);
+
+		if is_punct {
+			self.pos += 1;
+		}
+
+		is_punct
+	}
+
+	fn peek(&self) -> Option<&Token> {
+		self.tokens.get(self.pos).map(|(token, _)| token)
+	}
+
+	fn next(&mut self) -> Result<Token> {
+		let token = self
+			.peek()
+			.cloned()
+			.ok_or_else(|| self.error("unexpected end of input"))?;
+
+		self.pos += 1;
+
+		Ok(token)
+	}
+
+	fn line(&self) -> usize {
+		self.tokens
+			.get(self.pos)
+			.or_else(|| self.tokens.last())
+			.map_or(1, |(_, line)| *line)
+	}
+
+	/// An error at the most recently consumed token.
+	fn error(&self, reason: impl Into<String>) -> ParseProgramError {
+		let line = self
+			.tokens
+			.get(self.pos.saturating_sub(1))
+			.map_or(1, |(_, line)| *line);
+
+		ParseProgramError::new(line, reason)
+	}
+}
+
+/// Builds the [`Program`] the items describe.
+pub fn build(items: &[Item]) -> Result<Program> {
+	let mut program = Program::new();
+	let mut globals = HashMap::new();
+	let mut funcs = HashMap::new();
+
+	for item in items {
+		let (name, line) = match item {
+			Item::Global { name, line, .. }
+			| Item::Decl { name, line, .. }
+			| Item::Fun { name, line, .. } => (name, *line),
+		};
+
+		if !name.starts_with('@') {
+			return Err(ParseProgramError::new(
+				line,
+				format!("global symbol {name} must start with `@`"),
+			));
+		}
+
+		if globals.contains_key(name) || funcs.contains_key(name) {
+			return Err(ParseProgramError::new(
+				line,
+				format!("{name} is defined more than once"),
+			));
+		}
+
+		match item {
+			Item::Global { ty, init, .. } => {
+				if ty.is_unit() {
+					return Err(ParseProgramError::new(line, "cannot allocate unit"));
+				}
+
+				let init = constant(&mut |data| program.new_value().raw(data), init, ty, line)?;
+				let value = program.new_value().global_alloc(init);
+				program.set_value_name(value, name.clone());
+
+				globals.insert(name.clone(), value);
+			}
+			Item::Decl { params, ret, .. } => {
+				let func = program.new_func(FunctionData::new_decl(
+					name.clone(),
+					params.clone(),
+					ret.clone(),
+				));
+
+				funcs.insert(name.clone(), func);
+			}
+			Item::Fun {
+				params,
+				ret,
+				blocks,
+				..
+			} => {
+				if blocks.is_empty() {
+					return Err(ParseProgramError::new(
+						line,
+						format!("function {name} has no basic blocks"),
+					));
+				}
+
+				let func = program.new_func(FunctionData::new(
+					name.clone(),
+					params.iter().map(|(_, ty)| ty.clone()).collect(),
+					ret.clone(),
+				));
+
+				funcs.insert(name.clone(), func);
+			}
+		}
+	}
+
+	for item in items {
+		let Item::Fun {
+			name,
+			params,
+			blocks,
+			..
+		} = item
+		else {
+			continue;
+		};
+
+		Body {
+			func: program.func_mut(funcs[name]),
+			globals: &globals,
+			funcs: &funcs,
+			basic_blocks: HashMap::new(),
+			values: HashMap::new(),
+			defs: HashMap::new(),
+			building: HashSet::new(),
+		}
+		.build(params, blocks)?;
+	}
+
+	Ok(program)
+}
+
+struct Body<'a, 'b> {
+	func: &'a mut FunctionData,
+	globals: &'a HashMap<String, ValueId>,
+	funcs: &'a HashMap<String, FunctionId>,
+	basic_blocks: HashMap<&'b str, BasicBlockId>,
+	values: HashMap<&'b str, ValueId>,
+	defs: HashMap<&'b str, &'b Stmt>,
+	building: HashSet<&'b str>,
+}
+
+impl<'b> Body<'_, 'b> {
+	fn build(mut self, params: &'b [(String, Type)], blocks: &'b [Block]) -> Result<()> {
+		let func_params = self.func.params().to_vec();
+
+		for ((name, _), value) in params.iter().zip(func_params) {
+			self.name_value(name, value, blocks[0].line)?;
+		}
+
+		let mut basic_blocks = Vec::with_capacity(blocks.len());
+
+		for block in blocks {
+			if self.basic_blocks.contains_key(block.name.as_str()) {
+				return Err(ParseProgramError::new(
+					block.line,
+					format!("basic block {} is defined more than once", block.name),
+				));
+			}
+
+			let bb = self
+				.func
+				.dfg_mut()
+				.new_basic_block()
+				.basic_block_with_params(
+					Some(block.name.clone()),
+					block.params.iter().map(|(_, ty)| ty.clone()),
+				);
+
+			let bb_params = self.func.dfg().basic_block(bb).params().clone();
+
+			for ((name, _), value) in block.params.iter().zip(bb_params) {
+				self.name_value(name, value, block.line)?;
+			}
+
+			self.func.layout_mut().push_basic_block(bb);
+			self.basic_blocks.insert(&block.name, bb);
+			basic_blocks.push(bb);
+		}
+
+		for stmt in blocks.iter().flat_map(|block| &block.stmts) {
+			let Some(name) = &stmt.result else {
+				continue;
+			};
+
+			if self.values.contains_key(name.as_str()) || self.defs.insert(name, stmt).is_some() {
+				return Err(ParseProgramError::new(
+					stmt.line,
+					format!("{name} is defined more than once"),
+				));
+			}
+		}
+
+		for (block, bb) in blocks.iter().zip(basic_blocks) {
+			for stmt in &block.stmts {
+				let value = match &stmt.result {
+					Some(name) => self.define(name, stmt.line)?,
+					None => self.inst(stmt)?,
+				};
+
+				self.func.layout_mut().push_inst(bb, value);
+			}
+		}
+
+		Ok(())
+	}
+
+	fn name_value(&mut self, name: &'b str, value: ValueId, line: usize) -> Result<()> {
+		if self.values.insert(name, value).is_some() {
+			return Err(ParseProgramError::new(
+				line,
+				format!("{name} is defined more than once"),
+			));
+		}
+
+		self.func.dfg_mut().set_value_name(value, name);
+
+		Ok(())
+	}
+
+	/// Builds the instruction that defines `name`, building its operands first if they come later
+	/// in the text.
+	fn define(&mut self, name: &'b str, line: usize) -> Result<ValueId> {
+		if let Some(value) = self.values.get(name) {
+			return Ok(*value);
+		}
+
+		let Some(stmt) = self.defs.get(name).copied() else {
+			return match self.globals.get(name) {
+				Some(value) => Ok(*value),
+				None => Err(ParseProgramError::new(
+					line,
+					format!("{name} is not defined"),
+				)),
+			};
+		};
+
+		if !self.building.insert(name) {
+			return Err(ParseProgramError::new(
+				stmt.line,
+				format!("{name} depends on itself"),
+			));
+		}
+
+		let value = self.inst(stmt)?;
+
+		if self.func.dfg().value(value).ty().is_unit() {
+			return Err(ParseProgramError::new(
+				stmt.line,
+				format!("{name} is assigned a value of type unit"),
+			));
+		}
+
+		self.func.dfg_mut().set_value_name(value, name);
+		self.values.insert(name, value);
+		self.building.remove(name);
+
+		Ok(value)
+	}
+
+	fn inst(&mut self, stmt: &'b Stmt) -> Result<ValueId> {
+		let line = stmt.line;
+
+		let value = match &stmt.inst {
+			Inst::Alloc(ty) => {
+				if ty.is_unit() {
+					return Err(ParseProgramError::new(line, "cannot allocate unit"));
+				}
+
+				self.func.dfg_mut().new_value().alloc(ty.clone())
+			}
+			Inst::Load(src) => {
+				let src = self.pointer(src, line)?;
+				self.func.dfg_mut().new_value().load(src)
+			}
+			Inst::Store(value, dest) => {
+				let dest = self.pointer(dest, line)?;
+				let TypeKind::Ptr(ty) = TypeKind::from(self.func.dfg().value_type(dest)) else {
+					unreachable!()
+				};
+
+				let value = self.operand(value, &ty, line)?;
+				self.func.dfg_mut().new_value().store(value, dest)
+			}
+			Inst::GetPtr(src, index) => {
+				let src = self.pointer(src, line)?;
+				let index = self.operand(index, &Type::i32(), line)?;
+				self.func.dfg_mut().new_value().get_ptr(src, index)
+			}
+			Inst::GetElementPtr(src, index) => {
+				let src = self.pointer(src, line)?;
+
+				let TypeKind::Ptr(base) = TypeKind::from(self.func.dfg().value_type(src)) else {
+					unreachable!()
+				};
+
+				if !matches!(*base, TypeKind::Array(..)) {
+					return Err(ParseProgramError::new(
+						line,
+						format!("expected a pointer to an array, found *{base}"),
+					));
+				}
+
+				let index = self.operand(index, &Type::i32(), line)?;
+				self.func.dfg_mut().new_value().get_element_ptr(src, index)
+			}
+			Inst::Binary(op, lhs, rhs) => {
+				let lhs = self.operand(lhs, &Type::i32(), line)?;
+				let rhs = self.operand(rhs, &Type::i32(), line)?;
+				self.func.dfg_mut().new_value().binary(*op, lhs, rhs)
+			}
+			Inst::Branch(cond, true_target, false_target) => {
+				let cond = self.operand(cond, &Type::i32(), line)?;
+				let (true_bb, true_args) = self.target(true_target, line)?;
+				let (false_bb, false_args) = self.target(false_target, line)?;
+
+				self.func
+					.dfg_mut()
+					.new_value()
+					.branch_with_args(cond, true_bb, true_args, false_bb, false_args)
+			}
+			Inst::Jump(target) => {
+				let (bb, args) = self.target(target, line)?;
+				self.func.dfg_mut().new_value().jump_with_args(bb, args)
+			}
+			Inst::Call(callee, args) => {
+				let Some(func) = self.funcs.get(callee).copied() else {
+					return Err(ParseProgramError::new(
+						line,
+						format!("function {callee} is not defined"),
+					));
+				};
+
+				let TypeKind::Function(params, _) =
+					TypeKind::from(self.func.dfg().function_type(func))
+				else {
+					unreachable!()
+				};
+
+				if params.len() != args.len() {
+					return Err(ParseProgramError::new(
+						line,
+						format!(
+							"{callee} takes {} arguments but {} were given",
+							params.len(),
+							args.len()
+						),
+					));
+				}
+
+				let args = args
+					.iter()
+					.zip(&params)
+					.map(|(arg, ty)| self.operand(arg, ty, line))
+					.collect::<Result<_>>()?;
+
+				self.func.dfg_mut().new_value().call(func, args)
+			}
+			Inst::Return(value) => {
+				let ret = self.func.ret_ty().clone();
+
+				let value = match (value, ret.is_unit()) {
+					(None, true) => None,
+					(Some(value), false) => Some(self.operand(value, &ret, line)?),
+					(None, false) => {
+						return Err(ParseProgramError::new(
+							line,
+							format!("expected a return value of type {ret}"),
+						));
+					}
+					(Some(..), true) => {
+						return Err(ParseProgramError::new(
+							line,
+							"function does not return a value",
+						));
+					}
+				};
+
+				self.func.dfg_mut().new_value().ret(value)
+			}
+		};
+
+		Ok(value)
+	}
+
+	fn target(&mut self, target: &'b Target, line: usize) -> Result<(BasicBlockId, Vec<ValueId>)> {
+		let Some(bb) = self.basic_blocks.get(target.name.as_str()).copied() else {
+			return Err(ParseProgramError::new(
+				line,
+				format!("basic block {} is not defined", target.name),
+			));
+		};
+
+		let params = self.func.dfg().basic_block(bb).params().clone();
+
+		if params.len() != target.args.len() {
+			return Err(ParseProgramError::new(
+				line,
+				format!(
+					"{} takes {} arguments but {} were given",
+					target.name,
+					params.len(),
+					target.args.len()
+				),
+			));
+		}
+
+		let args = target
+			.args
+			.iter()
+			.zip(params)
+			.map(|(arg, param)| {
+				let ty = self.func.dfg().value(param).ty().clone();
+				self.operand(arg, &ty, line)
+			})
+			.collect::<Result<_>>()?;
+
+		Ok((bb, args))
+	}
+
+	fn pointer(&mut self, operand: &'b Operand, line: usize) -> Result<ValueId> {
+		let Operand::Symbol(name) = operand else {
+			return Err(ParseProgramError::new(line, "expected a pointer"));
+		};
+
+		let value = self.define(name, line)?;
+		let ty = self.func.dfg().value_type(value);
+
+		if !matches!(*ty, TypeKind::Ptr(..)) {
+			return Err(ParseProgramError::new(
+				line,
+				format!("expected a pointer, found {ty}"),
+			));
+		}
+
+		Ok(value)
+	}
+
+	fn operand(&mut self, operand: &'b Operand, ty: &Type, line: usize) -> Result<ValueId> {
+		let Operand::Symbol(name) = operand else {
+			let dfg = self.func.dfg_mut();
+			return constant(&mut |data| dfg.new_value().raw(data), operand, ty, line);
+		};
+
+		let value = self.define(name, line)?;
+		let found = self.func.dfg().value_type(value);
+
+		if found != *ty {
+			return Err(ParseProgramError::new(
+				line,
+				format!("expected {name} to be {ty}, found {found}"),
+			));
+		}
+
+		Ok(value)
+	}
+}
+
+fn constant(
+	new: &mut impl FnMut(ValueData) -> ValueId,
+	operand: &Operand,
+	ty: &Type,
+	line: usize,
+) -> Result<ValueId> {
+	let value_ty = match (operand, &**ty) {
+		(_, TypeKind::Unit | TypeKind::Function(..)) => {
+			return Err(ParseProgramError::new(
+				line,
+				format!("cannot create a constant of type {ty}"),
+			));
+		}
+		(Operand::Symbol(name), _) => {
+			return Err(ParseProgramError::new(
+				line,
+				format!("expected a constant, found {name}"),
+			));
+		}
+		(Operand::Int(i), TypeKind::Int(..)) => {
+			return Ok(new(ValueData::new(
+				Type::i32(),
+				ValueType::Integer(Integer::I32(*i)),
+			)));
+		}
+		(Operand::Undef, _) => ValueType::Undef(Undef),
+		(Operand::ZeroInit, _) => ValueType::ZeroInit(ZeroInit),
+		(Operand::Aggregate(elements), TypeKind::Array(base, len)) if elements.len() == *len => {
+			let elements = elements
+				.iter()
+				.map(|element| constant(new, element, base, line))
+				.collect::<Result<_>>()?;
+
+			ValueType::Aggregate(Aggregate::new(elements))
+		}
+		(operand, _) => {
+			let found = match operand {
+				Operand::Int(i) => i.to_string(),
+				_ => "an aggregate".to_owned(),
+			};
+
+			return Err(ParseProgramError::new(
+				line,
+				format!("expected a constant of type {ty}, found {found}"),
+			));
+		}
+	};
+
+	Ok(new(ValueData::new(ty.clone(), value_ty)))
+}
+
+fn binary_op(word: &str) -> Option<BinaryOp> {
+	Some(match word {
+		"ne" => BinaryOp::NotEq,
+		"eq" => BinaryOp::Eq,
+		"gt" => BinaryOp::Gt,
+		"lt" => BinaryOp::Lt,
+		"ge" => BinaryOp::Ge,
+		"le" => BinaryOp::Le,
+		"add" => BinaryOp::Add,
+		"sub" => BinaryOp::Sub,
+		"mul" => BinaryOp::Mul,
+		"div" => BinaryOp::Div,
+		"mod" => BinaryOp::Mod,
+		"and" => BinaryOp::And,
+		"or" => BinaryOp::Or,
+		"xor" => BinaryOp::Xor,
+		"shl" => BinaryOp::Shl,
+		"shr" => BinaryOp::Shr,
+		"sar" => BinaryOp::Sar,
+		_ => return None,
+	})
+}
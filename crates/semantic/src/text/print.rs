@@ -0,0 +1,392 @@
+use alloc::{
+	borrow::ToOwned as _,
+	format,
+	string::{String, ToString as _},
+	vec::Vec,
+};
+use core::fmt::{Display, Formatter, Result as FmtResult, Write as _};
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::ir::{
+	BasicBlockId, FunctionData, FunctionId, Program, TypeKind, ValueData, ValueId, ValueType,
+};
+
+impl Display for Program {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		let mut globals = Names::new('@');
+
+		let global_names = self
+			.inst_layout()
+			.iter()
+			.map(|value| {
+				let name = globals.reserve(self.borrow_value(*value).name().map(String::as_str));
+				(*value, name)
+			})
+			.collect::<Vec<_>>();
+
+		let func_names = self
+			.func_layout()
+			.iter()
+			.map(|func| (*func, globals.reserve(Some(self.func(*func).name()))))
+			.collect::<HashMap<_, _>>();
+
+		let mut printer = Printer {
+			program: self,
+			global_names: global_names.iter().cloned().collect(),
+			func_names,
+			locals: HashMap::new(),
+			basic_blocks: HashMap::new(),
+		};
+
+		let mut first = true;
+
+		for (value, name) in &global_names {
+			if !first {
+				f.write_char('\n')?;
+			}
+
+			printer.global(f, *value, name)?;
+			first = false;
+		}
+
+		for func in self.func_layout() {
+			if !first {
+				f.write_char('\n')?;
+			}
+
+			printer.function(f, *func)?;
+			first = false;
+		}
+
+		Ok(())
+	}
+}
+
+struct Printer<'a> {
+	program: &'a Program,
+	global_names: HashMap<ValueId, String>,
+	func_names: HashMap<FunctionId, String>,
+	locals: HashMap<ValueId, String>,
+	basic_blocks: HashMap<BasicBlockId, String>,
+}
+
+impl Printer<'_> {
+	fn global(&self, f: &mut Formatter<'_>, value: ValueId, name: &str) -> FmtResult {
+		let data = self.program.borrow_value(value);
+
+		let ValueType::GlobalAlloc(alloc) = data.value_ty() else {
+			unreachable!("only global allocations are laid out");
+		};
+
+		let TypeKind::Ptr(ty) = &**data.ty() else {
+			unreachable!("global allocations are pointers");
+		};
+
+		let init = self.global_init(alloc.init());
+
+		writeln!(f, "global {name} = alloc {ty}, {init}")
+	}
+
+	fn global_init(&self, value: ValueId) -> String {
+		let values = self.program.borrow_values();
+
+		constant(&values[&value], |value| self.global_init(value))
+	}
+
+	fn function(&mut self, f: &mut Formatter<'_>, func: FunctionId) -> FmtResult {
+		let data = self.program.func(func);
+		let name = self.func_names[&func].clone();
+
+		let TypeKind::Function(params, ret) = &**data.ty() else {
+			unreachable!("functions have function types");
+		};
+
+		if data.is_decl() {
+			f.write_str("decl ")?;
+			f.write_str(&name)?;
+			f.write_char('(')?;
+			write_list(f, params.iter())?;
+			f.write_char(')')?;
+		} else {
+			self.name_locals(data);
+
+			f.write_str("fun ")?;
+			f.write_str(&name)?;
+			f.write_char('(')?;
+			write_list(
+				f,
+				data.params().iter().map(|param| self.typed(data, *param)),
+			)?;
+			f.write_char(')')?;
+		}
+
+		if !ret.is_unit() {
+			f.write_str(": ")?;
+			Display::fmt(ret, f)?;
+		}
+
+		if data.is_decl() {
+			return f.write_char('\n');
+		}
+
+		f.write_str(" {\n")?;
+
+		for (i, bb) in data.layout().basic_blocks().iter().enumerate() {
+			if i > 0 {
+				f.write_char('\n')?;
+			}
+
+			f.write_str(&self.basic_blocks[bb])?;
+
+			let params = data.dfg().basic_block(*bb).params();
+			if !params.is_empty() {
+				f.write_char('(')?;
+				write_list(f, params.iter().map(|param| self.typed(data, *param)))?;
+				f.write_char(')')?;
+			}
+
+			f.write_str(":\n")?;
+
+			for inst in data.layout().insts(*bb) {
+				f.write_str("  ")?;
+				self.inst(f, data, *inst)?;
+				f.write_char('\n')?;
+			}
+		}
+
+		f.write_str("}\n")
+	}
+
+	fn inst(&self, f: &mut Formatter<'_>, func: &FunctionData, inst: ValueId) -> FmtResult {
+		let data = func.dfg().value(inst);
+
+		if !data.ty().is_unit() {
+			write!(f, "{} = ", self.locals[&inst])?;
+		}
+
+		let operand = |value| self.operand(func, value);
+
+		match data.value_ty() {
+			ValueType::Alloc(..) => {
+				let TypeKind::Ptr(ty) = &**data.ty() else {
+					unreachable!("allocations are pointers");
+				};
+
+				write!(f, "alloc {ty}")
+			}
+			ValueType::Load(load) => write!(f, "load {}", operand(load.src())),
+			ValueType::Store(store) => write!(
+				f,
+				"store {}, {}",
+				operand(store.value()),
+				operand(store.dest())
+			),
+			ValueType::GetPtr(get) => {
+				write!(f, "getptr {}, {}", operand(get.src()), operand(get.index()))
+			}
+			ValueType::GetElementPtr(get) => write!(
+				f,
+				"getelemptr {}, {}",
+				operand(get.src()),
+				operand(get.index())
+			),
+			ValueType::Binary(binary) => write!(
+				f,
+				"{} {}, {}",
+				binary.op(),
+				operand(binary.lhs()),
+				operand(binary.rhs())
+			),
+			ValueType::Branch(branch) => write!(
+				f,
+				"br {}, {}, {}",
+				operand(branch.cond()),
+				self.target(func, branch.true_basic_block(), branch.true_args()),
+				self.target(func, branch.false_basic_block(), branch.false_args())
+			),
+			ValueType::Jump(jump) => {
+				write!(f, "jump {}", self.target(func, jump.target(), jump.args()))
+			}
+			ValueType::Call(call) => {
+				write!(f, "call {}(", self.func_names[&call.callee()])?;
+				write_list(f, call.args().iter().map(|arg| operand(*arg)))?;
+				f.write_char(')')
+			}
+			ValueType::Return(ret) => match ret.value() {
+				Some(value) => write!(f, "ret {}", operand(value)),
+				None => f.write_str("ret"),
+			},
+			_ => unreachable!("{:?} is not an instruction", data.value_ty()),
+		}
+	}
+
+	fn target(&self, func: &FunctionData, bb: BasicBlockId, args: &[ValueId]) -> String {
+		let mut out = self.basic_blocks[&bb].clone();
+
+		if !args.is_empty() {
+			out.push('(');
+			out.push_str(
+				&args
+					.iter()
+					.map(|arg| self.operand(func, *arg))
+					.collect::<Vec<_>>()
+					.join(", "),
+			);
+			out.push(')');
+		}
+
+		out
+	}
+
+	fn operand(&self, func: &FunctionData, value: ValueId) -> String {
+		if value.is_global() {
+			return match self.global_names.get(&value) {
+				Some(name) => name.clone(),
+				None => self.global_init(value),
+			};
+		}
+
+		match self.locals.get(&value) {
+			Some(name) => name.clone(),
+			None => constant(func.dfg().value(value), |value| self.operand(func, value)),
+		}
+	}
+
+	fn typed(&self, func: &FunctionData, value: ValueId) -> String {
+		format!("{}: {}", self.locals[&value], func.dfg().value(value).ty())
+	}
+
+	/// Names every parameter, basic block and instruction result, keeping the names they already
+	/// have where possible.
+	fn name_locals(&mut self, func: &FunctionData) {
+		let mut names = Names::new('%');
+		names.used.extend(
+			self.global_names
+				.values()
+				.chain(self.func_names.values())
+				.cloned(),
+		);
+
+		let dfg = func.dfg();
+		let layout = func.layout();
+
+		let mut values = func.params().to_vec();
+		let mut basic_blocks = Vec::new();
+
+		for bb in layout.basic_blocks() {
+			basic_blocks.push((*bb, dfg.basic_block(*bb).name()));
+			values.extend(dfg.basic_block(*bb).params());
+			values.extend(
+				layout
+					.insts(*bb)
+					.iter()
+					.filter(|inst| !dfg.value(**inst).ty().is_unit()),
+			);
+		}
+
+		self.locals.clear();
+		self.basic_blocks.clear();
+
+		// Reserve the existing names first, so generated ones never steal them.
+		for (bb, name) in &basic_blocks {
+			if let Some(name) = name {
+				self.basic_blocks
+					.insert(*bb, names.reserve(Some(name.as_str())));
+			}
+		}
+
+		for value in &values {
+			if let Some(name) = dfg.value(*value).name() {
+				self.locals
+					.insert(*value, names.reserve(Some(name.as_str())));
+			}
+		}
+
+		for (bb, _) in &basic_blocks {
+			if !self.basic_blocks.contains_key(bb) {
+				self.basic_blocks.insert(*bb, names.reserve(None));
+			}
+		}
+
+		for value in &values {
+			if !self.locals.contains_key(value) {
+				self.locals.insert(*value, names.reserve(None));
+			}
+		}
+	}
+}
+
+/// Allocates unique names within a scope.
+struct Names {
+	prefix: char,
+	used: HashSet<String>,
+	next: usize,
+}
+
+impl Names {
+	fn new(prefix: char) -> Self {
+		Self {
+			prefix,
+			used: HashSet::new(),
+			next: 0,
+		}
+	}
+
+	fn reserve(&mut self, preferred: Option<&str>) -> String {
+		if let Some(name) = preferred {
+			if self.used.insert(name.to_owned()) {
+				return name.to_owned();
+			}
+
+			if name[1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+				for i in 1.. {
+					let name = format!("{name}_{i}");
+
+					if self.used.insert(name.clone()) {
+						return name;
+					}
+				}
+			}
+		}
+
+		loop {
+			let name = format!("{}{}", self.prefix, self.next);
+			self.next += 1;
+
+			if self.used.insert(name.clone()) {
+				return name;
+			}
+		}
+	}
+}
+
+fn constant(data: &ValueData, element: impl Fn(ValueId) -> String) -> String {
+	match data.value_ty() {
+		ValueType::Integer(i) => i.value().to_string(),
+		ValueType::ZeroInit(..) => "zeroinit".to_owned(),
+		ValueType::Undef(..) => "undef".to_owned(),
+		ValueType::Aggregate(aggregate) => {
+			let elements = aggregate
+				.values()
+				.iter()
+				.map(|value| element(*value))
+				.collect::<Vec<_>>();
+
+			format!("{{{}}}", elements.join(", "))
+		}
+		value_ty => unreachable!("{value_ty:?} is not a constant"),
+	}
+}
+
+fn write_list<T: Display>(f: &mut Formatter<'_>, items: impl Iterator<Item = T>) -> FmtResult {
+	for (i, item) in items.enumerate() {
+		if i > 0 {
+			f.write_str(", ")?;
+		}
+
+		Display::fmt(&item, f)?;
+	}
+
+	Ok(())
+}
@@ -0,0 +1,898 @@
+use alloc::vec::Vec;
+use core::{
+	error::Error as CoreError,
+	fmt::{Debug, Display, Formatter, Result as FmtResult},
+};
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::ir::{
+	BasicBlockId, FunctionData, FunctionId, Program, Type, TypeKind, ValueId, ValueType,
+};
+
+impl Program {
+	/// Checks that the program is well formed, returning the first problem found.
+	///
+	/// Every basic block must end in exactly one terminator, arguments must refer to a parameter
+	/// that exists, operands must have the types their users expect, `used_by` sets must match the
+	/// actual uses, and every value must be defined before it is used.
+	pub fn verify(&self) -> Result<(), VerifyError> {
+		self.verify_globals()?;
+
+		for func in self.func_layout() {
+			let data = self.func(*func);
+
+			if !data.is_decl() {
+				FunctionVerifier::new(self, data).verify()?;
+			}
+		}
+
+		Ok(())
+	}
+
+	fn verify_globals(&self) -> Result<(), VerifyError> {
+		let values = self.borrow_values();
+		let mut users = HashMap::<ValueId, HashSet<ValueId>>::new();
+
+		for (value, data) in values.iter() {
+			for operand in data.value_ty().values() {
+				if !values.contains_key(&operand) {
+					return Err(VerifyError::UndefinedValue {
+						user: *value,
+						value: operand,
+					});
+				}
+
+				users.entry(operand).or_default().insert(*value);
+			}
+
+			match data.value_ty() {
+				ValueType::GlobalAlloc(alloc) => {
+					let init = &values[&alloc.init()];
+
+					if !init.value_ty().is_const() {
+						return Err(VerifyError::NotAConstant(alloc.init()));
+					}
+
+					expect_type(*value, &init.ty().clone().into_ptr(), data.ty())?;
+				}
+				ValueType::Aggregate(aggregate) => {
+					let TypeKind::Array(base, len) = &**data.ty() else {
+						return Err(VerifyError::InvalidOperand {
+							user: *value,
+							value: *value,
+						});
+					};
+
+					if aggregate.values().len() != *len {
+						return Err(VerifyError::ArgCountMismatch {
+							user: *value,
+							expected: *len,
+							found: aggregate.values().len(),
+						});
+					}
+
+					for element in aggregate.values() {
+						expect_type(*element, base, values[element].ty())?;
+					}
+				}
+				value_ty if value_ty.is_const() => {}
+				_ => return Err(VerifyError::NotAnInstruction(*value)),
+			}
+		}
+
+		for func in self.func_layout() {
+			for (value, data) in self.func(*func).dfg().values() {
+				for operand in data.value_ty().values().filter(|v| v.is_global()) {
+					if !values.contains_key(&operand) {
+						return Err(VerifyError::UndefinedValue {
+							user: *value,
+							value: operand,
+						});
+					}
+
+					users.entry(operand).or_default().insert(*value);
+				}
+			}
+		}
+
+		let none = HashSet::new();
+
+		for (value, data) in values.iter() {
+			if users.get(value).unwrap_or(&none) != data.used_by() {
+				return Err(VerifyError::InconsistentUses(*value));
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+	MissingTerminator(BasicBlockId),
+	UnexpectedTerminator(ValueId),
+	NotAnInstruction(ValueId),
+	NotAConstant(ValueId),
+	ArgOutOfRange(ValueId),
+	UndefinedValue {
+		user: ValueId,
+		value: ValueId,
+	},
+	UndefinedBasicBlock {
+		user: ValueId,
+		basic_block: BasicBlockId,
+	},
+	UndefinedFunction {
+		user: ValueId,
+		func: FunctionId,
+	},
+	TypeMismatch {
+		value: ValueId,
+		expected: Type,
+		found: Type,
+	},
+	InvalidOperand {
+		user: ValueId,
+		value: ValueId,
+	},
+	ArgCountMismatch {
+		user: ValueId,
+		expected: usize,
+		found: usize,
+	},
+	InconsistentUses(ValueId),
+	InconsistentBasicBlockUses(BasicBlockId),
+	UseBeforeDefinition {
+		user: ValueId,
+		value: ValueId,
+	},
+}
+
+impl Display for VerifyError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::MissingTerminator(bb) => {
+				f.write_str("basic block ")?;
+				Debug::fmt(&bb, f)?;
+				f.write_str(" does not end in a terminator")
+			}
+			Self::UnexpectedTerminator(value) => {
+				f.write_str("terminator ")?;
+				Debug::fmt(&value, f)?;
+				f.write_str(" is not at the end of its basic block")
+			}
+			Self::NotAnInstruction(value) => {
+				Debug::fmt(&value, f)?;
+				f.write_str(" is not an instruction")
+			}
+			Self::NotAConstant(value) => {
+				Debug::fmt(&value, f)?;
+				f.write_str(" is not a constant")
+			}
+			Self::ArgOutOfRange(value) => {
+				f.write_str("argument reference ")?;
+				Debug::fmt(&value, f)?;
+				f.write_str(" is out of range")
+			}
+			Self::UndefinedValue { user, value } => {
+				Debug::fmt(&user, f)?;
+				f.write_str(" uses undefined value ")?;
+				Debug::fmt(&value, f)
+			}
+			Self::UndefinedBasicBlock { user, basic_block } => {
+				Debug::fmt(&user, f)?;
+				f.write_str(" targets undefined basic block ")?;
+				Debug::fmt(&basic_block, f)
+			}
+			Self::UndefinedFunction { user, func } => {
+				Debug::fmt(&user, f)?;
+				f.write_str(" calls undefined function ")?;
+				Debug::fmt(&func, f)
+			}
+			Self::TypeMismatch {
+				value,
+				expected,
+				found,
+			} => {
+				f.write_str("expected ")?;
+				Debug::fmt(&value, f)?;
+				f.write_str(" to be ")?;
+				Display::fmt(&expected, f)?;
+				f.write_str(", found ")?;
+				Display::fmt(&found, f)
+			}
+			Self::InvalidOperand { user, value } => {
+				Debug::fmt(&value, f)?;
+				f.write_str(" is not a valid operand for ")?;
+				Debug::fmt(&user, f)
+			}
+			Self::ArgCountMismatch {
+				user,
+				expected,
+				found,
+			} => {
+				Debug::fmt(&user, f)?;
+				f.write_str(" passes ")?;
+				Display::fmt(&found, f)?;
+				f.write_str(" arguments, expected ")?;
+				Display::fmt(&expected, f)
+			}
+			Self::InconsistentUses(value) => {
+				f.write_str("users of ")?;
+				Debug::fmt(&value, f)?;
+				f.write_str(" do not match its uses")
+			}
+			Self::InconsistentBasicBlockUses(bb) => {
+				f.write_str("users of basic block ")?;
+				Debug::fmt(&bb, f)?;
+				f.write_str(" do not match its uses")
+			}
+			Self::UseBeforeDefinition { user, value } => {
+				Debug::fmt(&user, f)?;
+				f.write_str(" uses ")?;
+				Debug::fmt(&value, f)?;
+				f.write_str(" before it is defined")
+			}
+		}
+	}
+}
+
+impl CoreError for VerifyError {}
+
+struct FunctionVerifier<'a> {
+	program: &'a Program,
+	func: &'a FunctionData,
+	block_params: HashMap<ValueId, BasicBlockId>,
+}
+
+impl<'a> FunctionVerifier<'a> {
+	fn new(program: &'a Program, func: &'a FunctionData) -> Self {
+		let block_params = func
+			.dfg()
+			.basic_blocks()
+			.iter()
+			.flat_map(|(bb, data)| data.params().iter().map(|param| (*param, *bb)))
+			.collect();
+
+		Self {
+			program,
+			func,
+			block_params,
+		}
+	}
+
+	fn verify(&self) -> Result<(), VerifyError> {
+		self.verify_references()?;
+		self.verify_uses()?;
+		self.verify_terminators()?;
+
+		for bb in self.func.layout().basic_blocks() {
+			for inst in self.func.layout().insts(*bb) {
+				self.verify_types(*inst)?;
+			}
+		}
+
+		self.verify_dominance()
+	}
+
+	/// Checks that operands, targets, callees and argument references all exist.
+	fn verify_references(&self) -> Result<(), VerifyError> {
+		let dfg = self.func.dfg();
+		let globals = self.program.borrow_values();
+
+		for (value, data) in dfg.values() {
+			for operand in data.value_ty().values() {
+				let exists = if operand.is_global() {
+					globals.contains_key(&operand)
+				} else {
+					dfg.values().contains_key(&operand)
+				};
+
+				if !exists {
+					return Err(VerifyError::UndefinedValue {
+						user: *value,
+						value: operand,
+					});
+				}
+			}
+
+			for basic_block in data.value_ty().basic_blocks() {
+				if !self.func.layout().contains_basic_block(basic_block) {
+					return Err(VerifyError::UndefinedBasicBlock {
+						user: *value,
+						basic_block,
+					});
+				}
+			}
+
+			match data.value_ty() {
+				ValueType::FuncArgRef(arg) => {
+					if self.func.params().get(arg.index()) != Some(value) {
+						return Err(VerifyError::ArgOutOfRange(*value));
+					}
+				}
+				ValueType::BlockArgRef(arg) => {
+					let in_range = self.block_params.get(value).is_some_and(|bb| {
+						dfg.basic_block(*bb).params().get(arg.index()) == Some(value)
+					});
+
+					if !in_range {
+						return Err(VerifyError::ArgOutOfRange(*value));
+					}
+				}
+				ValueType::Call(call) if !self.program.funcs().contains_key(&call.callee()) => {
+					return Err(VerifyError::UndefinedFunction {
+						user: *value,
+						func: call.callee(),
+					});
+				}
+				_ => {}
+			}
+		}
+
+		Ok(())
+	}
+
+	fn verify_uses(&self) -> Result<(), VerifyError> {
+		let dfg = self.func.dfg();
+
+		let mut users = HashMap::<ValueId, HashSet<ValueId>>::new();
+		let mut basic_block_users = HashMap::<BasicBlockId, HashSet<ValueId>>::new();
+
+		for (value, data) in dfg.values() {
+			for operand in data.value_ty().values().filter(|v| !v.is_global()) {
+				users.entry(operand).or_default().insert(*value);
+			}
+
+			for basic_block in data.value_ty().basic_blocks() {
+				basic_block_users
+					.entry(basic_block)
+					.or_default()
+					.insert(*value);
+			}
+		}
+
+		let none = HashSet::new();
+
+		for (value, data) in dfg.values() {
+			if users.get(value).unwrap_or(&none) != data.used_by() {
+				return Err(VerifyError::InconsistentUses(*value));
+			}
+		}
+
+		for (bb, data) in dfg.basic_blocks() {
+			if basic_block_users.get(bb).unwrap_or(&none) != data.used_by() {
+				return Err(VerifyError::InconsistentBasicBlockUses(*bb));
+			}
+		}
+
+		Ok(())
+	}
+
+	fn verify_terminators(&self) -> Result<(), VerifyError> {
+		let dfg = self.func.dfg();
+
+		for bb in self.func.layout().basic_blocks() {
+			let insts = self.func.layout().insts(*bb);
+
+			let Some((last, rest)) = insts.split_last() else {
+				return Err(VerifyError::MissingTerminator(*bb));
+			};
+
+			for inst in insts {
+				if !dfg.values().contains_key(inst) {
+					return Err(VerifyError::NotAnInstruction(*inst));
+				}
+
+				if !dfg.value(*inst).value_ty().is_local_instruction() {
+					return Err(VerifyError::NotAnInstruction(*inst));
+				}
+			}
+
+			if let Some(inst) = rest
+				.iter()
+				.find(|inst| dfg.value(**inst).value_ty().is_terminator())
+			{
+				return Err(VerifyError::UnexpectedTerminator(*inst));
+			}
+
+			if !dfg.value(*last).value_ty().is_terminator() {
+				return Err(VerifyError::MissingTerminator(*bb));
+			}
+		}
+
+		Ok(())
+	}
+
+	fn verify_types(&self, inst: ValueId) -> Result<(), VerifyError> {
+		let dfg = self.func.dfg();
+		let data = dfg.value(inst);
+		let i32 = Type::i32();
+		let unit = Type::unit();
+
+		let expected = match data.value_ty() {
+			ValueType::Alloc(..) => match &**data.ty() {
+				TypeKind::Ptr(base) if !base.is_unit() => return Ok(()),
+				_ => {
+					return Err(VerifyError::InvalidOperand {
+						user: inst,
+						value: inst,
+					});
+				}
+			},
+			ValueType::Load(load) => self.pointee(inst, load.src())?,
+			ValueType::Store(store) => {
+				let ty = self.pointee(inst, store.dest())?;
+				self.expect(store.value(), &ty)?;
+				unit
+			}
+			ValueType::GetPtr(get) => {
+				self.pointee(inst, get.src())?;
+				self.expect(get.index(), &i32)?;
+				self.ty(get.src())
+			}
+			ValueType::GetElementPtr(get) => {
+				let TypeKind::Array(base, _) = TypeKind::from(self.pointee(inst, get.src())?)
+				else {
+					return Err(VerifyError::InvalidOperand {
+						user: inst,
+						value: get.src(),
+					});
+				};
+
+				self.expect(get.index(), &i32)?;
+				base.into_ptr()
+			}
+			ValueType::Binary(binary) => {
+				self.expect(binary.lhs(), &i32)?;
+				self.expect(binary.rhs(), &i32)?;
+				i32
+			}
+			ValueType::Branch(branch) => {
+				self.expect(branch.cond(), &i32)?;
+				self.expect_args(inst, branch.true_basic_block(), branch.true_args())?;
+				self.expect_args(inst, branch.false_basic_block(), branch.false_args())?;
+				unit
+			}
+			ValueType::Jump(jump) => {
+				self.expect_args(inst, jump.target(), jump.args())?;
+				unit
+			}
+			ValueType::Call(call) => {
+				let TypeKind::Function(params, ret) = &**self.program.func(call.callee()).ty()
+				else {
+					unreachable!("functions have function types");
+				};
+
+				if params.len() != call.args().len() {
+					return Err(VerifyError::ArgCountMismatch {
+						user: inst,
+						expected: params.len(),
+						found: call.args().len(),
+					});
+				}
+
+				for (arg, ty) in call.args().iter().zip(params) {
+					self.expect(*arg, ty)?;
+				}
+
+				ret.clone()
+			}
+			ValueType::Return(ret) => {
+				match (ret.value(), self.func.ret_ty()) {
+					(Some(value), ty) => self.expect(value, ty)?,
+					(None, ty) if ty.is_unit() => {}
+					(None, _) => {
+						return Err(VerifyError::ArgCountMismatch {
+							user: inst,
+							expected: 1,
+							found: 0,
+						});
+					}
+				}
+
+				unit
+			}
+			_ => return Err(VerifyError::NotAnInstruction(inst)),
+		};
+
+		expect_type(inst, &expected, data.ty())
+	}
+
+	/// Checks that every use is dominated by the definition of the value it uses.
+	fn verify_dominance(&self) -> Result<(), VerifyError> {
+		let dfg = self.func.dfg();
+		let layout = self.func.layout();
+		let dominators = Dominators::new(self.func);
+
+		for bb in layout.basic_blocks() {
+			if !dominators.is_reachable(*bb) {
+				continue;
+			}
+
+			for (index, inst) in layout.insts(*bb).iter().enumerate() {
+				for value in dfg.value(*inst).value_ty().values() {
+					if value.is_global() {
+						continue;
+					}
+
+					let defined = match dfg.value(value).value_ty() {
+						value_ty if value_ty.is_const() => true,
+						ValueType::FuncArgRef(..) => true,
+						ValueType::BlockArgRef(..) => {
+							let def = self.block_params[&value];
+
+							layout.contains_basic_block(def) && dominators.dominates(def, *bb)
+						}
+						_ => match layout.parent_basic_block(value) {
+							Some(def) if def == *bb => layout.insts(def)[..index].contains(&value),
+							Some(def) => dominators.dominates(def, *bb),
+							None => false,
+						},
+					};
+
+					if !defined {
+						return Err(VerifyError::UseBeforeDefinition { user: *inst, value });
+					}
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	fn expect_args(
+		&self,
+		user: ValueId,
+		target: BasicBlockId,
+		args: &[ValueId],
+	) -> Result<(), VerifyError> {
+		let params = self.func.dfg().basic_block(target).params();
+
+		if params.len() != args.len() {
+			return Err(VerifyError::ArgCountMismatch {
+				user,
+				expected: params.len(),
+				found: args.len(),
+			});
+		}
+
+		for (arg, param) in args.iter().zip(params) {
+			self.expect(*arg, &self.ty(*param))?;
+		}
+
+		Ok(())
+	}
+
+	fn pointee(&self, user: ValueId, value: ValueId) -> Result<Type, VerifyError> {
+		match TypeKind::from(self.ty(value)) {
+			TypeKind::Ptr(base) => Ok(base),
+			_ => Err(VerifyError::InvalidOperand { user, value }),
+		}
+	}
+
+	fn expect(&self, value: ValueId, expected: &Type) -> Result<(), VerifyError> {
+		expect_type(value, expected, &self.ty(value))
+	}
+
+	fn ty(&self, value: ValueId) -> Type {
+		if value.is_global() {
+			self.program.borrow_value(value).ty().clone()
+		} else {
+			self.func.dfg().value(value).ty().clone()
+		}
+	}
+}
+
+fn expect_type(value: ValueId, expected: &Type, found: &Type) -> Result<(), VerifyError> {
+	if expected == found {
+		Ok(())
+	} else {
+		Err(VerifyError::TypeMismatch {
+			value,
+			expected: expected.clone(),
+			found: found.clone(),
+		})
+	}
+}
+
+/// The immediate dominators of the reachable basic blocks of a function.
+struct Dominators {
+	idom: HashMap<BasicBlockId, BasicBlockId>,
+	order: HashMap<BasicBlockId, usize>,
+}
+
+impl Dominators {
+	fn new(func: &FunctionData) -> Self {
+		let successors = |bb: BasicBlockId| {
+			func.layout()
+				.insts(bb)
+				.last()
+				.map_or_else(Vec::new, |inst| {
+					func.dfg().value(*inst).value_ty().basic_blocks().collect()
+				})
+		};
+
+		let Some(entry) = func.layout().entry_basic_block() else {
+			return Self {
+				idom: HashMap::new(),
+				order: HashMap::new(),
+			};
+		};
+
+		// Reverse postorder, computed without recursion.
+		let mut postorder = Vec::new();
+		let mut visited = HashSet::<_>::from_iter([entry]);
+		let mut stack = alloc::vec![(entry, successors(entry), 0)];
+
+		while let Some((bb, succs, next)) = stack.last_mut() {
+			if let Some(succ) = succs.get(*next).copied() {
+				*next += 1;
+
+				if visited.insert(succ) {
+					stack.push((succ, successors(succ), 0));
+				}
+			} else {
+				postorder.push(*bb);
+				stack.pop();
+			}
+		}
+
+		let order = postorder
+			.iter()
+			.rev()
+			.enumerate()
+			.map(|(i, bb)| (*bb, i))
+			.collect::<HashMap<_, _>>();
+
+		let mut predecessors = HashMap::<BasicBlockId, Vec<BasicBlockId>>::new();
+		for bb in &postorder {
+			for succ in successors(*bb) {
+				predecessors.entry(succ).or_default().push(*bb);
+			}
+		}
+
+		let mut idom = HashMap::<_, _>::from_iter([(entry, entry)]);
+		let mut changed = true;
+
+		while changed {
+			changed = false;
+
+			for bb in postorder.iter().rev().skip(1) {
+				let mut new_idom = None;
+
+				for pred in predecessors.get(bb).into_iter().flatten() {
+					if !idom.contains_key(pred) {
+						continue;
+					}
+
+					new_idom = Some(match new_idom {
+						None => *pred,
+						Some(other) => intersect(&idom, &order, *pred, other),
+					});
+				}
+
+				if let Some(new_idom) = new_idom
+					&& idom.insert(*bb, new_idom) != Some(new_idom)
+				{
+					changed = true;
+				}
+			}
+		}
+
+		Self { idom, order }
+	}
+
+	fn is_reachable(&self, bb: BasicBlockId) -> bool {
+		self.order.contains_key(&bb)
+	}
+
+	fn dominates(&self, a: BasicBlockId, mut b: BasicBlockId) -> bool {
+		if !self.is_reachable(a) {
+			return false;
+		}
+
+		loop {
+			if a == b {
+				return true;
+			}
+
+			match self.idom.get(&b) {
+				Some(idom) if *idom != b => b = *idom,
+				_ => return false,
+			}
+		}
+	}
+}
+
+fn intersect(
+	idom: &HashMap<BasicBlockId, BasicBlockId>,
+	order: &HashMap<BasicBlockId, usize>,
+	mut a: BasicBlockId,
+	mut b: BasicBlockId,
+) -> BasicBlockId {
+	while a != b {
+		while order[&a] > order[&b] {
+			a = idom[&a];
+		}
+
+		while order[&b] > order[&a] {
+			b = idom[&b];
+		}
+	}
+
+	a
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec::Vec;
+
+	use vmm_ir::Instruction;
+
+	use super::VerifyError;
+	use crate::{
+		Lowering,
+		ir::{BinaryOp, FunctionData, LocalInstBuilder as _, Program, Type, ValueBuilder as _},
+	};
+
+	fn parse(text: &str) -> Program {
+		text.parse().unwrap()
+	}
+
+	#[test]
+	fn accepts_lowered_programs() {
+		let program = Lowering::new()
+			.lower(&[
+				Instruction::inc_val(1),
+				Instruction::dynamic_loop([Instruction::inc_val(-1), Instruction::move_ptr(1)]),
+				Instruction::write_once(),
+			])
+			.unwrap();
+
+		assert_eq!(program.verify(), Ok(()));
+	}
+
+	#[test]
+	fn checks_terminators() {
+		let mut program = Program::new();
+		let main = program.new_func(FunctionData::new("@main", Vec::new(), Type::unit()));
+
+		let func = program.func_mut(main);
+		let entry = func.dfg_mut().new_basic_block().basic_block(None);
+		func.layout_mut().push_basic_block(entry);
+
+		let ptr = func.dfg_mut().new_value().alloc(Type::i32());
+		func.layout_mut().push_inst(entry, ptr);
+
+		assert_eq!(program.verify(), Err(VerifyError::MissingTerminator(entry)));
+
+		let func = program.func_mut(main);
+		let ret = func.dfg_mut().new_value().ret(None);
+		func.layout_mut().insert_inst(entry, 0, ret);
+		let end = func.dfg_mut().new_value().ret(None);
+		func.layout_mut().push_inst(entry, end);
+
+		assert_eq!(
+			program.verify(),
+			Err(VerifyError::UnexpectedTerminator(ret))
+		);
+	}
+
+	#[test]
+	fn checks_types() {
+		let mut program = Program::new();
+		let main = program.new_func(FunctionData::new("@main", Vec::new(), Type::unit()));
+
+		let func = program.func_mut(main);
+		let entry = func.dfg_mut().new_basic_block().basic_block(None);
+		func.layout_mut().push_basic_block(entry);
+
+		let ptr = func.dfg_mut().new_value().alloc(Type::i32());
+		let one = func.dfg_mut().new_value().integer(1);
+		let sum = func.dfg_mut().new_value().binary(BinaryOp::Add, ptr, one);
+		let ret = func.dfg_mut().new_value().ret(None);
+
+		for inst in [ptr, sum, ret] {
+			func.layout_mut().push_inst(entry, inst);
+		}
+
+		assert_eq!(
+			program.verify(),
+			Err(VerifyError::TypeMismatch {
+				value: ptr,
+				expected: Type::i32(),
+				found: Type::i32().into_ptr(),
+			})
+		);
+	}
+
+	#[test]
+	fn checks_definitions() {
+		let program = parse(
+			"fun @main() {
+%entry:
+  %x = add %y, 1
+  %y = add 1, 2
+  ret
+}",
+		);
+
+		assert!(matches!(
+			program.verify(),
+			Err(VerifyError::UseBeforeDefinition { .. })
+		));
+
+		let program = parse(
+			"fun @main(): i32 {
+%entry:
+  br 1, %then, %else
+
+%then:
+  %x = add 1, 2
+  jump %else
+
+%else:
+  ret %x
+}",
+		);
+
+		assert!(matches!(
+			program.verify(),
+			Err(VerifyError::UseBeforeDefinition { .. })
+		));
+
+		let program = parse(
+			"fun @main(): i32 {
+%entry:
+  %x = add 1, 2
+  jump %loop(%x)
+
+%loop(%i: i32):
+  %next = sub %i, 1
+  br %next, %loop(%next), %end
+
+%end:
+  ret %x
+}",
+		);
+
+		assert_eq!(program.verify(), Ok(()));
+	}
+
+	#[test]
+	fn checks_args_and_uses() {
+		let mut program = parse(
+			"fun @main() {
+%entry:
+  jump %next(1, 2)
+
+%next(%a: i32, %b: i32):
+  ret
+}",
+		);
+
+		let main = program.func_layout()[0];
+		let func = program.func_mut(main);
+		let next = func.layout().basic_blocks()[1];
+		let params = func.dfg_mut().basic_block_mut(next).params_mut();
+		params.swap(0, 1);
+		let params = params.clone();
+
+		// Both parameters are out of place, and either may be found first.
+		assert!(matches!(
+			program.verify(),
+			Err(VerifyError::ArgOutOfRange(param)) if params.contains(&param)
+		));
+
+		let func = program.func_mut(main);
+		func.dfg_mut().basic_block_mut(next).params_mut().swap(0, 1);
+		func.dfg_mut().basic_block_mut(next).used_by.clear();
+
+		assert_eq!(
+			program.verify(),
+			Err(VerifyError::InconsistentBasicBlockUses(next))
+		);
+	}
+}
@@ -1,28 +1,29 @@
 mod ops;
+mod value;
 
 use core::{
 	fmt::{Debug, Display, Formatter, Result as FmtResult},
-	num::NonZeroU8,
+	num::NonZero,
 };
 
-use vmm_utils::GetOrZero as _;
+pub use self::value::*;
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub struct Cell {
-	value: Option<NonZeroU8>,
+pub struct Cell<V = u8> {
+	value: V,
 	index: Option<usize>,
 }
 
-impl Cell {
+impl<V: CellValue> Cell<V> {
 	#[inline]
 	#[must_use]
-	pub const fn new(value: u8) -> Self {
+	pub const fn new(value: V) -> Self {
 		Self::create(value, None)
 	}
 
 	#[inline]
 	#[must_use]
-	pub const fn with_index(value: u8, index: usize) -> Self {
+	pub const fn with_index(value: V, index: usize) -> Self {
 		Self::create(value, Some(index))
 	}
 
@@ -36,46 +37,40 @@ impl Cell {
 	}
 
 	#[must_use]
-	pub const fn as_u8(&self) -> &u8 {
-		unsafe { &*(&raw const self.value).cast::<u8>() }
+	pub const fn as_value(&self) -> &V {
+		&self.value
 	}
 
-	pub fn as_mut_u8(&mut self) -> &mut u8 {
-		unsafe { &mut *(&raw mut self.value).cast::<u8>() }
+	pub const fn as_mut_value(&mut self) -> &mut V {
+		&mut self.value
 	}
 
 	#[must_use]
-	pub fn value(self) -> u8 {
-		self.value.get_or_zero()
+	pub const fn value(self) -> V {
+		self.value
 	}
 
-	pub const fn set_value(&mut self, value: u8) {
-		self.value = NonZeroU8::new(value);
+	pub const fn set_value(&mut self, value: V) {
+		self.value = value;
 	}
 
 	pub const fn clear_value(&mut self) {
-		self.set_value(0);
+		self.set_value(V::ZERO);
 	}
 
 	#[must_use]
 	#[allow(clippy::trivially_copy_pass_by_ref)]
 	pub fn is_zero(&self) -> bool {
-		matches!(self.value(), 0)
+		self.value == V::ZERO
 	}
 
 	#[inline]
-	const fn create(value: u8, index: Option<usize>) -> Self {
-		Self {
-			value: match value {
-				0 => None,
-				i => Some(unsafe { NonZeroU8::new_unchecked(i) }),
-			},
-			index,
-		}
+	const fn create(value: V, index: Option<usize>) -> Self {
+		Self { value, index }
 	}
 }
 
-impl Debug for Cell {
+impl<V: CellValue> Debug for Cell<V> {
 	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
 		f.debug_struct("Cell")
 			.field("value", &self.value())
@@ -83,72 +78,97 @@ impl Debug for Cell {
 	}
 }
 
-impl Default for Cell {
+impl<V: CellValue> Default for Cell<V> {
 	fn default() -> Self {
-		Self::new(0)
+		Self::new(V::ZERO)
 	}
 }
 
-impl Display for Cell {
+impl<V: CellValue> Display for Cell<V> {
 	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
 		Display::fmt(&self.value(), f)
 	}
 }
 
-impl From<Cell> for Option<NonZeroU8> {
-	fn from(value: Cell) -> Self {
-		value.value
-	}
-}
-
-impl From<Cell> for u8 {
-	fn from(value: Cell) -> Self {
-		value.value()
-	}
-}
-
-impl From<NonZeroU8> for Cell {
-	fn from(value: NonZeroU8) -> Self {
-		Self::new(value.get())
-	}
-}
-
-impl From<Option<NonZeroU8>> for Cell {
-	fn from(value: Option<NonZeroU8>) -> Self {
-		Self::new(value.get_or_zero())
-	}
-}
-
-impl From<u8> for Cell {
-	fn from(value: u8) -> Self {
+impl<V: CellValue> From<V> for Cell<V> {
+	fn from(value: V) -> Self {
 		Self::new(value)
 	}
 }
 
-impl PartialEq<u8> for Cell {
-	fn eq(&self, other: &u8) -> bool {
+impl<V: CellValue> PartialEq<V> for Cell<V> {
+	fn eq(&self, other: &V) -> bool {
 		PartialEq::eq(&self.value(), other)
 	}
 }
 
-impl PartialOrd<u8> for Cell {
-	fn partial_cmp(&self, other: &u8) -> Option<core::cmp::Ordering> {
+impl<V: CellValue> PartialOrd<V> for Cell<V> {
+	fn partial_cmp(&self, other: &V) -> Option<core::cmp::Ordering> {
 		Some(Ord::cmp(&self.value(), other))
 	}
 }
 
+macro_rules! impl_from_cell {
+	($($ty:ty)*) => {
+		$(
+			impl From<Cell<$ty>> for $ty {
+				fn from(value: Cell<$ty>) -> Self {
+					value.value()
+				}
+			}
+
+			impl From<Cell<$ty>> for Option<NonZero<$ty>> {
+				fn from(value: Cell<$ty>) -> Self {
+					NonZero::new(value.value())
+				}
+			}
+
+			impl From<NonZero<$ty>> for Cell<$ty> {
+				fn from(value: NonZero<$ty>) -> Self {
+					Self::new(value.get())
+				}
+			}
+
+			impl From<Option<NonZero<$ty>>> for Cell<$ty> {
+				fn from(value: Option<NonZero<$ty>>) -> Self {
+					Self::new(value.map_or(0, NonZero::get))
+				}
+			}
+		)*
+	};
+}
+
+impl_from_cell!(u8 u16 u32);
+
 #[cfg(test)]
 mod tests {
+	use vmm_num::ops::WrappingAddAssign as _;
+
 	use super::Cell;
 
 	#[test]
-	fn as_u8_works() {
-		let mut value = Cell::new(8);
+	fn as_value_works() {
+		let mut value = Cell::new(8u8);
+
+		assert_eq!(*value.as_value(), 8);
+
+		*value.as_mut_value() = 0;
+
+		assert_eq!(*value.as_value(), 0);
+	}
+
+	#[test]
+	fn wide_cells_wrap_at_their_width() {
+		let mut value = Cell::new(u16::MAX);
+
+		value.wrapping_add_assign(2u16);
+
+		assert_eq!(value.value(), 1);
 
-		assert_eq!(*value.as_u8(), 8);
+		let mut value = Cell::new(255u32);
 
-		*value.as_mut_u8() = 0;
+		value.wrapping_add_assign(1u32);
 
-		assert_eq!(*value.as_u8(), 0);
+		assert_eq!(value.value(), 256);
 	}
 }
@@ -4,9 +4,9 @@ use vmm_num::ops::{
 	WrappingAdd, WrappingAddAssign, WrappingMul, WrappingMulAssign, WrappingSub, WrappingSubAssign,
 };
 
-use super::Cell;
+use super::{Cell, CellValue};
 
-impl Add for Cell {
+impl<V: CellValue> Add for Cell<V> {
 	type Output = Self;
 
 	fn add(self, rhs: Self) -> Self::Output {
@@ -14,7 +14,7 @@ impl Add for Cell {
 	}
 }
 
-impl Add<&Self> for Cell {
+impl<V: CellValue> Add<&Self> for Cell<V> {
 	type Output = Self;
 
 	fn add(self, rhs: &Self) -> Self::Output {
@@ -22,67 +22,67 @@ impl Add<&Self> for Cell {
 	}
 }
 
-impl Add<Cell> for &Cell {
-	type Output = Cell;
+impl<V: CellValue> Add<Cell<V>> for &Cell<V> {
+	type Output = Cell<V>;
 
-	fn add(self, rhs: Cell) -> Self::Output {
+	fn add(self, rhs: Cell<V>) -> Self::Output {
 		Add::add(*self, rhs)
 	}
 }
 
-impl Add for &Cell {
-	type Output = Cell;
+impl<V: CellValue> Add for &Cell<V> {
+	type Output = Cell<V>;
 
 	fn add(self, rhs: Self) -> Self::Output {
 		Add::add(*self, *rhs)
 	}
 }
 
-impl Add<u8> for Cell {
+impl<V: CellValue> Add<V> for Cell<V> {
 	type Output = Self;
 
-	fn add(self, rhs: u8) -> Self::Output {
+	fn add(self, rhs: V) -> Self::Output {
 		Self::create(Add::add(self.value(), rhs), self.index)
 	}
 }
 
-impl Add<&u8> for Cell {
+impl<V: CellValue> Add<&V> for Cell<V> {
 	type Output = Self;
 
-	fn add(self, rhs: &u8) -> Self::Output {
+	fn add(self, rhs: &V) -> Self::Output {
 		Add::add(self, *rhs)
 	}
 }
 
-impl Add<u8> for &Cell {
-	type Output = Cell;
+impl<V: CellValue> Add<V> for &Cell<V> {
+	type Output = Cell<V>;
 
-	fn add(self, rhs: u8) -> Self::Output {
+	fn add(self, rhs: V) -> Self::Output {
 		Add::add(*self, rhs)
 	}
 }
 
-impl Add<&u8> for &Cell {
-	type Output = Cell;
+impl<V: CellValue> Add<&V> for &Cell<V> {
+	type Output = Cell<V>;
 
-	fn add(self, rhs: &u8) -> Self::Output {
+	fn add(self, rhs: &V) -> Self::Output {
 		Add::add(*self, *rhs)
 	}
 }
 
-impl AddAssign for Cell {
+impl<V: CellValue> AddAssign for Cell<V> {
 	fn add_assign(&mut self, rhs: Self) {
-		AddAssign::add_assign(self.as_mut_u8(), rhs.value());
+		AddAssign::add_assign(self.as_mut_value(), rhs.value());
 	}
 }
 
-impl AddAssign<&Self> for Cell {
+impl<V: CellValue> AddAssign<&Self> for Cell<V> {
 	fn add_assign(&mut self, rhs: &Self) {
 		AddAssign::add_assign(self, *rhs);
 	}
 }
 
-impl WrappingAdd for Cell {
+impl<V: CellValue> WrappingAdd for Cell<V> {
 	type Output = Self;
 
 	fn wrapping_add(self, rhs: Self) -> Self::Output {
@@ -93,7 +93,7 @@ impl WrappingAdd for Cell {
 	}
 }
 
-impl WrappingAdd<&Self> for Cell {
+impl<V: CellValue> WrappingAdd<&Self> for Cell<V> {
 	type Output = Self;
 
 	fn wrapping_add(self, rhs: &Self) -> Self::Output {
@@ -101,137 +101,93 @@ impl WrappingAdd<&Self> for Cell {
 	}
 }
 
-impl WrappingAdd<Cell> for &Cell {
-	type Output = Cell;
+impl<V: CellValue> WrappingAdd<Cell<V>> for &Cell<V> {
+	type Output = Cell<V>;
 
-	fn wrapping_add(self, rhs: Cell) -> Self::Output {
+	fn wrapping_add(self, rhs: Cell<V>) -> Self::Output {
 		WrappingAdd::wrapping_add(*self, rhs)
 	}
 }
 
-impl WrappingAdd for &Cell {
-	type Output = Cell;
+impl<V: CellValue> WrappingAdd for &Cell<V> {
+	type Output = Cell<V>;
 
 	fn wrapping_add(self, rhs: Self) -> Self::Output {
 		WrappingAdd::wrapping_add(*self, *rhs)
 	}
 }
 
-impl WrappingAdd<i8> for Cell {
+impl<V: CellValue> WrappingAdd<V> for Cell<V> {
 	type Output = Self;
 
-	fn wrapping_add(self, rhs: i8) -> Self::Output {
+	fn wrapping_add(self, rhs: V) -> Self::Output {
 		Self::create(WrappingAdd::wrapping_add(self.value(), rhs), self.index)
 	}
 }
 
-impl WrappingAdd<&i8> for Cell {
+impl<V: CellValue> WrappingAdd<&V> for Cell<V> {
 	type Output = Self;
 
-	fn wrapping_add(self, rhs: &i8) -> Self::Output {
+	fn wrapping_add(self, rhs: &V) -> Self::Output {
 		WrappingAdd::wrapping_add(self, *rhs)
 	}
 }
 
-impl WrappingAdd<i8> for &Cell {
-	type Output = Cell;
+impl<V: CellValue> WrappingAdd<V> for &Cell<V> {
+	type Output = Cell<V>;
 
-	fn wrapping_add(self, rhs: i8) -> Self::Output {
+	fn wrapping_add(self, rhs: V) -> Self::Output {
 		WrappingAdd::wrapping_add(*self, rhs)
 	}
 }
 
-impl WrappingAdd<&i8> for &Cell {
-	type Output = Cell;
+impl<V: CellValue> WrappingAdd<&V> for &Cell<V> {
+	type Output = Cell<V>;
 
-	fn wrapping_add(self, rhs: &i8) -> Self::Output {
+	fn wrapping_add(self, rhs: &V) -> Self::Output {
 		WrappingAdd::wrapping_add(*self, *rhs)
 	}
 }
 
-impl WrappingAdd<u8> for Cell {
-	type Output = Self;
-
-	fn wrapping_add(self, rhs: u8) -> Self::Output {
-		Self::create(WrappingAdd::wrapping_add(self.value(), rhs), self.index)
-	}
-}
-
-impl WrappingAdd<&u8> for Cell {
-	type Output = Self;
-
-	fn wrapping_add(self, rhs: &u8) -> Self::Output {
-		WrappingAdd::wrapping_add(self, *rhs)
-	}
-}
-
-impl WrappingAdd<u8> for &Cell {
-	type Output = Cell;
-
-	fn wrapping_add(self, rhs: u8) -> Self::Output {
-		WrappingAdd::wrapping_add(*self, rhs)
-	}
-}
-
-impl WrappingAdd<&u8> for &Cell {
-	type Output = Cell;
-
-	fn wrapping_add(self, rhs: &u8) -> Self::Output {
-		WrappingAdd::wrapping_add(*self, *rhs)
-	}
-}
-
-impl WrappingAddAssign for Cell {
+impl<V: CellValue> WrappingAddAssign for Cell<V> {
 	fn wrapping_add_assign(&mut self, rhs: Self) {
 		*self = WrappingAdd::wrapping_add(*self, rhs);
 	}
 }
 
-impl WrappingAddAssign<&Self> for Cell {
+impl<V: CellValue> WrappingAddAssign<&Self> for Cell<V> {
 	fn wrapping_add_assign(&mut self, rhs: &Self) {
 		WrappingAddAssign::wrapping_add_assign(self, *rhs);
 	}
 }
 
-impl WrappingAddAssign<i8> for Cell {
-	fn wrapping_add_assign(&mut self, rhs: i8) {
-		*self = WrappingAdd::wrapping_add(*self, rhs);
-	}
-}
-
-impl WrappingAddAssign<&i8> for Cell {
-	fn wrapping_add_assign(&mut self, rhs: &i8) {
-		WrappingAddAssign::wrapping_add_assign(self, *rhs);
-	}
-}
-
-impl WrappingAddAssign<u8> for Cell {
-	fn wrapping_add_assign(&mut self, rhs: u8) {
+impl<V: CellValue> WrappingAddAssign<V> for Cell<V> {
+	fn wrapping_add_assign(&mut self, rhs: V) {
 		*self = WrappingAdd::wrapping_add(*self, rhs);
 	}
 }
 
-impl WrappingAddAssign<&u8> for Cell {
-	fn wrapping_add_assign(&mut self, rhs: &u8) {
+impl<V: CellValue> WrappingAddAssign<&V> for Cell<V> {
+	fn wrapping_add_assign(&mut self, rhs: &V) {
 		WrappingAddAssign::wrapping_add_assign(self, *rhs);
 	}
 }
 
-impl WrappingMul<u8> for Cell {
+impl<V: CellValue> WrappingMul<V> for Cell<V> {
 	type Output = Self;
 
-	fn wrapping_mul(self, rhs: u8) -> Self::Output {
+	fn wrapping_mul(self, rhs: V) -> Self::Output {
 		Self::create(WrappingMul::wrapping_mul(self.value(), rhs), self.index)
 	}
 }
 
-impl WrappingMulAssign<u8> for Cell {
-	fn wrapping_mul_assign(&mut self, rhs: u8) {
+impl<V: CellValue> WrappingMulAssign<V> for Cell<V> {
+	fn wrapping_mul_assign(&mut self, rhs: V) {
 		*self = WrappingMul::wrapping_mul(*self, rhs);
 	}
 }
 
-impl WrappingSub for Cell {
+impl<V: CellValue> WrappingSub for Cell<V> {
 	type Output = Self;
 
 	fn wrapping_sub(self, rhs: Self) -> Self::Output {
@@ -242,22 +198,22 @@ impl WrappingSub for Cell {
 	}
 }
 
-impl WrappingSub<u8> for Cell {
+impl<V: CellValue> WrappingSub<V> for Cell<V> {
 	type Output = Self;
 
-	fn wrapping_sub(self, rhs: u8) -> Self::Output {
+	fn wrapping_sub(self, rhs: V) -> Self::Output {
 		Self::create(WrappingSub::wrapping_sub(self.value(), rhs), self.index)
 	}
 }
 
-impl WrappingSubAssign for Cell {
+impl<V: CellValue> WrappingSubAssign for Cell<V> {
 	fn wrapping_sub_assign(&mut self, rhs: Self) {
 		*self = WrappingSub::wrapping_sub(*self, rhs);
 	}
 }
 
-impl WrappingSubAssign<u8> for Cell {
-	fn wrapping_sub_assign(&mut self, rhs: u8) {
+impl<V: CellValue> WrappingSubAssign<V> for Cell<V> {
+	fn wrapping_sub_assign(&mut self, rhs: V) {
 		*self = WrappingSub::wrapping_sub(*self, rhs);
 	}
 }
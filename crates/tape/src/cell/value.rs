@@ -0,0 +1,76 @@
+use core::{
+	fmt::{Debug, Display},
+	hash::Hash,
+	ops::{Add, AddAssign},
+};
+
+use vmm_num::ops::{
+	WrappingAdd, WrappingAddAssign, WrappingFrom, WrappingMul, WrappingMulAssign, WrappingSub,
+	WrappingSubAssign,
+};
+
+/// The integer type stored in each cell of a tape, which decides the cell width.
+pub trait CellValue:
+	self::sealed::Sealed
+	+ Copy
+	+ Debug
+	+ Default
+	+ Display
+	+ Eq
+	+ Hash
+	+ Ord
+	+ Send
+	+ Sync
+	+ Unpin
+	+ 'static
+	+ Add<Output = Self>
+	+ AddAssign
+	+ WrappingAdd<Output = Self>
+	+ WrappingAddAssign
+	+ WrappingSub<Output = Self>
+	+ WrappingSubAssign
+	+ WrappingMul<Output = Self>
+	+ WrappingMulAssign
+	+ WrappingFrom<u8>
+	+ WrappingFrom<i32>
+	+ WrappingFrom<u32>
+{
+	const BITS: u32;
+
+	const ZERO: Self;
+
+	/// Truncate the value to a byte, used for output.
+	fn to_u8(self) -> u8;
+
+	fn to_u32(self) -> u32;
+}
+
+macro_rules! impl_cell_value {
+	($($ty:ty)*) => {
+		$(
+			impl self::sealed::Sealed for $ty {}
+
+			impl CellValue for $ty {
+				const BITS: u32 = <$ty>::BITS;
+
+				const ZERO: Self = 0;
+
+				#[inline]
+				fn to_u8(self) -> u8 {
+					WrappingFrom::wrapping_from(self)
+				}
+
+				#[inline]
+				fn to_u32(self) -> u32 {
+					WrappingFrom::wrapping_from(self)
+				}
+			}
+		)*
+	};
+}
+
+impl_cell_value!(u8 u16 u32);
+
+mod sealed {
+	pub trait Sealed {}
+}
@@ -1,37 +1,42 @@
-use alloc::boxed::Box;
+use alloc::{boxed::Box, vec};
 
-use crate::{Cell, TAPE_SIZE, Tape, TapePointer};
+use crate::{Cell, CellValue, TAPE_SIZE, Tape, TapePointer};
 
 #[derive(Clone, PartialEq, Eq)]
-pub struct BoxTape {
-	cells: Box<[Cell; TAPE_SIZE]>,
+pub struct BoxTape<V = u8> {
+	cells: Box<[Cell<V>]>,
 	ptr: TapePointer,
 }
 
-impl BoxTape {
+impl<V: CellValue> BoxTape<V> {
 	#[must_use]
-	#[allow(clippy::large_stack_frames)]
 	pub fn new() -> Self {
-		Self {
-			cells: Box::new([Cell::new(0); TAPE_SIZE]),
-			ptr: TapePointer::zero(),
-		}
+		Self::with_len(TAPE_SIZE)
 	}
 }
 
-impl Default for BoxTape {
+impl<V: CellValue> Default for BoxTape<V> {
 	fn default() -> Self {
 		Self::new()
 	}
 }
 
-impl Tape for BoxTape {
-	fn as_slice(&self) -> &[Cell] {
-		&*self.cells
+impl<V: CellValue> Tape for BoxTape<V> {
+	type Value = V;
+
+	fn with_len(len: usize) -> Self {
+		Self {
+			cells: vec![Cell::new(V::ZERO); len].into_boxed_slice(),
+			ptr: TapePointer::zero(len),
+		}
+	}
+
+	fn as_slice(&self) -> &[Cell<V>] {
+		&self.cells
 	}
 
-	fn as_mut_slice(&mut self) -> &mut [Cell] {
-		&mut *self.cells
+	fn as_mut_slice(&mut self) -> &mut [Cell<V>] {
+		&mut self.cells
 	}
 
 	fn ptr(&self) -> &TapePointer {
@@ -42,11 +47,11 @@ impl Tape for BoxTape {
 		&mut self.ptr
 	}
 
-	unsafe fn get_unchecked(&self, ptr: usize) -> &Cell {
+	unsafe fn get_unchecked(&self, ptr: usize) -> &Cell<V> {
 		unsafe { self.cells.get_unchecked(ptr) }
 	}
 
-	unsafe fn get_unchecked_mut(&mut self, ptr: usize) -> &mut Cell {
+	unsafe fn get_unchecked_mut(&mut self, ptr: usize) -> &mut Cell<V> {
 		unsafe { self.cells.get_unchecked_mut(ptr) }
 	}
 }
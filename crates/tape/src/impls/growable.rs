@@ -0,0 +1,106 @@
+use alloc::vec::Vec;
+use core::iter;
+
+use crate::{Cell, CellValue, TAPE_SIZE, Tape, TapePointer};
+
+/// A tape that grows in either direction when the pointer leaves it, instead of wrapping around.
+#[derive(Clone, PartialEq, Eq)]
+pub struct GrowableTape<V = u8> {
+	cells: Vec<Cell<V>>,
+	ptr: TapePointer,
+}
+
+impl<V: CellValue> GrowableTape<V> {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::with_len(TAPE_SIZE)
+	}
+
+	fn grow_left(&mut self, by: usize) {
+		let by = by.max(self.cells.len());
+
+		self.cells.splice(0..0, iter::repeat_n(Cell::new(V::ZERO), by));
+
+		self.reindex(0);
+
+		self.ptr = unsafe { TapePointer::new_unchecked(self.ptr.value() + by, self.cells.len()) };
+	}
+
+	fn grow_right(&mut self, to: usize) {
+		let old_len = self.cells.len();
+
+		self.cells.resize(to.max(old_len * 2), Cell::new(V::ZERO));
+
+		self.reindex(old_len);
+
+		self.ptr = unsafe { TapePointer::new_unchecked(self.ptr.value(), self.cells.len()) };
+	}
+
+	fn reindex(&mut self, start: usize) {
+		self.cells[start..]
+			.iter_mut()
+			.zip(start..)
+			.for_each(|(cell, i)| cell.set_index(i));
+	}
+}
+
+impl<V: CellValue> Default for GrowableTape<V> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<V: CellValue> Tape for GrowableTape<V> {
+	type Value = V;
+
+	fn with_len(len: usize) -> Self {
+		Self {
+			cells: (0..len).map(|i| Cell::with_index(V::ZERO, i)).collect(),
+			ptr: TapePointer::zero(len),
+		}
+	}
+
+	fn init(&mut self) {}
+
+	fn as_slice(&self) -> &[Cell<V>] {
+		self.cells.as_slice()
+	}
+
+	fn as_mut_slice(&mut self) -> &mut [Cell<V>] {
+		self.cells.as_mut_slice()
+	}
+
+	fn ptr(&self) -> &TapePointer {
+		&self.ptr
+	}
+
+	fn ptr_mut(&mut self) -> &mut TapePointer {
+		&mut self.ptr
+	}
+
+	fn move_ptr(&mut self, offset: isize) {
+		let idx = self.index_of(offset);
+
+		unsafe { self.ptr.set_unchecked(idx) };
+	}
+
+	fn index_of(&mut self, offset: isize) -> usize {
+		let ptr = self.ptr.value();
+
+		match ptr.checked_add_signed(offset) {
+			Some(idx) if idx < self.cells.len() => idx,
+			Some(idx) => {
+				self.grow_right(idx + 1);
+
+				idx
+			}
+			None => {
+				let missing = offset.unsigned_abs() - ptr;
+
+				self.grow_left(missing);
+
+				self.ptr.value() - offset.unsigned_abs()
+			}
+		}
+	}
+}
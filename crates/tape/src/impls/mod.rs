@@ -1,13 +1,14 @@
 mod boxed;
+mod growable;
 mod ptr;
 mod stack;
 mod vec;
 
-pub use self::{boxed::*, ptr::*, stack::*, vec::*};
+pub use self::{boxed::*, growable::*, ptr::*, stack::*, vec::*};
 
 #[cfg(test)]
 mod tests {
-	use crate::{BoxTape, PtrTape, StackTape, TAPE_SIZE, Tape, VecTape};
+	use crate::{BoxTape, GrowableTape, PtrTape, StackTape, TAPE_SIZE, Tape, VecTape};
 
 	fn check_impl<T: Tape>() {
 		let mut tape = T::default();
@@ -15,29 +16,92 @@ mod tests {
 		tape.init();
 
 		assert_eq!(tape.as_slice().len(), TAPE_SIZE);
+		assert_eq!(tape.ptr().len(), TAPE_SIZE);
 
 		for (i, cell) in tape.as_slice().iter().copied().enumerate() {
 			assert_eq!(cell.index(), Some(i));
 		}
 	}
 
+	fn check_len<T: Tape>(len: usize) {
+		let mut tape = T::with_len(len);
+
+		tape.init();
+
+		assert_eq!(tape.len(), len);
+		assert_eq!(tape.ptr().len(), len);
+
+		*tape.ptr_mut() -= 1usize;
+
+		assert_eq!(tape.ptr().value(), len - 1);
+		assert_eq!(tape.current_cell().index(), Some(len - 1));
+
+		*tape.ptr_mut() += 1usize;
+
+		assert_eq!(tape.ptr().value(), 0);
+	}
+
 	#[test]
 	fn is_box_correct_impl() {
 		check_impl::<BoxTape>();
+		check_len::<BoxTape>(30_000);
 	}
 
 	#[test]
 	fn is_ptr_correct_impl() {
 		check_impl::<PtrTape>();
+		check_len::<PtrTape>(30_000);
 	}
 
 	#[test]
 	fn is_vec_correct_impl() {
 		check_impl::<VecTape>();
+		check_len::<VecTape>(30_000);
 	}
 
 	#[test]
 	fn is_stack_correct_impl() {
 		check_impl::<StackTape>();
+		check_len::<StackTape<300>>(300);
+	}
+
+	#[test]
+	fn is_growable_correct_impl() {
+		check_impl::<GrowableTape>();
+	}
+
+	#[test]
+	fn wide_cells_are_correct_impls() {
+		check_impl::<VecTape<u16>>();
+		check_impl::<PtrTape<u32>>();
+		check_len::<BoxTape<u32>>(300);
+		check_len::<StackTape<300, u16>>(300);
+	}
+
+	#[test]
+	fn growable_tape_grows_instead_of_wrapping() {
+		let mut tape = GrowableTape::<u8>::with_len(4);
+
+		tape.current_cell_mut().set_value(1);
+
+		tape.move_ptr(-3);
+
+		assert_eq!(tape.len(), 8);
+		assert_eq!(tape.ptr().value(), 1);
+		assert!(tape.current_cell().is_zero());
+
+		tape.move_ptr(3);
+
+		assert_eq!(tape.current_cell().value(), 1);
+
+		let idx = tape.index_of(10);
+
+		assert_eq!(idx, 14);
+		assert_eq!(tape.len(), 16);
+		assert_eq!(tape.ptr().len(), 16);
+
+		for (i, cell) in tape.as_slice().iter().copied().enumerate() {
+			assert_eq!(cell.index(), Some(i));
+		}
 	}
 }
@@ -6,22 +6,29 @@ use core::{
 };
 
 use self::unique::Unique;
-use crate::{Cell, TAPE_SIZE, Tape, TapePointer};
+use crate::{Cell, CellValue, TAPE_SIZE, Tape, TapePointer};
 
 #[derive(Clone, PartialEq, Eq)]
-pub struct PtrTape {
-	cells: Unique<Cell>,
+pub struct PtrTape<V = u8> {
+	cells: Unique<Cell<V>>,
+	len: usize,
 	ptr: TapePointer,
 }
 
-impl PtrTape {
+impl<V: CellValue> PtrTape<V> {
 	#[must_use]
 	pub fn new() -> Self {
 		Self::try_new().unwrap()
 	}
 
 	pub fn try_new() -> Result<Self, LayoutError> {
-		let layout = Layout::array::<Cell>(TAPE_SIZE)?;
+		Self::try_with_len(TAPE_SIZE)
+	}
+
+	pub fn try_with_len(len: usize) -> Result<Self, LayoutError> {
+		assert!(len > 0, "tape length must be non-zero");
+
+		let layout = Layout::array::<Cell<V>>(len)?;
 
 		let ptr = unsafe {
 			let raw = alloc::alloc::alloc(layout);
@@ -30,45 +37,52 @@ impl PtrTape {
 				alloc::alloc::handle_alloc_error(layout);
 			}
 
-			let p = Unique::new_unchecked(raw.cast::<Cell>());
+			let p = Unique::new_unchecked(raw.cast::<Cell<V>>());
 
-			(0..TAPE_SIZE).for_each(|i| p.add(i).write(Cell::with_index(0, i)));
+			(0..len).for_each(|i| p.add(i).write(Cell::with_index(V::ZERO, i)));
 			p
 		};
 
 		Ok(Self {
 			cells: ptr,
-			ptr: TapePointer::zero(),
+			len,
+			ptr: TapePointer::zero(len),
 		})
 	}
 }
 
-impl Default for PtrTape {
+impl<V: CellValue> Default for PtrTape<V> {
 	fn default() -> Self {
 		Self::new()
 	}
 }
 
-impl Drop for PtrTape {
+impl<V> Drop for PtrTape<V> {
 	fn drop(&mut self) {
-		let layout = Layout::array::<Cell>(TAPE_SIZE).unwrap();
+		let layout = Layout::array::<Cell<V>>(self.len).unwrap();
 
 		unsafe { alloc::alloc::dealloc(self.cells.as_ptr().cast(), layout) }
 	}
 }
 
-unsafe impl Send for PtrTape {}
-unsafe impl Sync for PtrTape {}
+unsafe impl<V: Send> Send for PtrTape<V> {}
+unsafe impl<V: Sync> Sync for PtrTape<V> {}
+
+impl<V: CellValue> Tape for PtrTape<V> {
+	type Value = V;
+
+	fn with_len(len: usize) -> Self {
+		Self::try_with_len(len).unwrap()
+	}
 
-impl Tape for PtrTape {
 	fn init(&mut self) {}
 
-	fn as_slice(&self) -> &[Cell] {
-		unsafe { slice::from_raw_parts(self.cells.as_ptr(), TAPE_SIZE) }
+	fn as_slice(&self) -> &[Cell<V>] {
+		unsafe { slice::from_raw_parts(self.cells.as_ptr(), self.len) }
 	}
 
-	fn as_mut_slice(&mut self) -> &mut [Cell] {
-		unsafe { slice::from_raw_parts_mut(self.cells.as_ptr(), TAPE_SIZE) }
+	fn as_mut_slice(&mut self) -> &mut [Cell<V>] {
+		unsafe { slice::from_raw_parts_mut(self.cells.as_ptr(), self.len) }
 	}
 
 	fn ptr(&self) -> &TapePointer {
@@ -5,36 +5,48 @@ use core::{
 	pin::Pin,
 };
 
-use crate::{Cell, TAPE_SIZE, Tape, TapePointer};
+use crate::{Cell, CellValue, TAPE_SIZE, Tape, TapePointer};
 
 #[derive(Clone, PartialEq, Eq)]
-pub struct StackTape {
-	cells: Pin<Stack>,
+pub struct StackTape<const N: usize = TAPE_SIZE, V = u8> {
+	cells: Pin<Stack<N, V>>,
 	ptr: TapePointer,
 }
 
-impl StackTape {
+impl<const N: usize, V: CellValue> StackTape<N, V> {
 	#[must_use]
 	pub const fn new() -> Self {
 		Self {
 			cells: Pin::new(Stack::new()),
-			ptr: TapePointer::zero(),
+			ptr: TapePointer::zero(N),
 		}
 	}
 }
 
-impl Default for StackTape {
+impl<const N: usize, V: CellValue> Default for StackTape<N, V> {
 	fn default() -> Self {
 		Self::new()
 	}
 }
 
-impl Tape for StackTape {
-	fn as_slice(&self) -> &[Cell] {
+impl<const N: usize, V: CellValue> Tape for StackTape<N, V> {
+	type Value = V;
+
+	fn with_len(len: usize) -> Self {
+		assert_eq!(len, N, "stack tapes have a fixed length of {N} cells");
+
+		Self::new()
+	}
+
+	fn len(&self) -> usize {
+		N
+	}
+
+	fn as_slice(&self) -> &[Cell<V>] {
 		&*self.cells
 	}
 
-	fn as_mut_slice(&mut self) -> &mut [Cell] {
+	fn as_mut_slice(&mut self) -> &mut [Cell<V>] {
 		&mut *self.cells
 	}
 
@@ -48,27 +60,27 @@ impl Tape for StackTape {
 }
 
 #[derive(Clone, PartialEq, Eq)]
-pub struct Stack {
-	inner: [Cell; TAPE_SIZE],
+pub struct Stack<const N: usize = TAPE_SIZE, V = u8> {
+	inner: [Cell<V>; N],
 }
 
-impl Stack {
+impl<const N: usize, V: CellValue> Stack<N, V> {
 	const fn new() -> Self {
 		Self {
-			inner: [Cell::new(0); TAPE_SIZE],
+			inner: [Cell::new(V::ZERO); N],
 		}
 	}
 }
 
-impl Deref for Stack {
-	type Target = [Cell; TAPE_SIZE];
+impl<const N: usize, V> Deref for Stack<N, V> {
+	type Target = [Cell<V>; N];
 
 	fn deref(&self) -> &Self::Target {
 		&self.inner
 	}
 }
 
-impl DerefMut for Stack {
+impl<const N: usize, V> DerefMut for Stack<N, V> {
 	fn deref_mut(&mut self) -> &mut Self::Target {
 		&mut self.inner
 	}
@@ -1,37 +1,43 @@
 use alloc::vec::Vec;
 
-use crate::{Cell, TAPE_SIZE, Tape, TapePointer};
+use crate::{Cell, CellValue, TAPE_SIZE, Tape, TapePointer};
 
 #[derive(Clone, PartialEq, Eq)]
-pub struct VecTape {
-	cells: Vec<Cell>,
+pub struct VecTape<V = u8> {
+	cells: Vec<Cell<V>>,
 	ptr: TapePointer,
 }
 
-impl VecTape {
+impl<V: CellValue> VecTape<V> {
 	#[must_use]
 	pub fn new() -> Self {
-		Self {
-			cells: (0..TAPE_SIZE).map(|i| Cell::with_index(0, i)).collect(),
-			ptr: TapePointer::zero(),
-		}
+		Self::with_len(TAPE_SIZE)
 	}
 }
 
-impl Default for VecTape {
+impl<V: CellValue> Default for VecTape<V> {
 	fn default() -> Self {
 		Self::new()
 	}
 }
 
-impl Tape for VecTape {
+impl<V: CellValue> Tape for VecTape<V> {
+	type Value = V;
+
+	fn with_len(len: usize) -> Self {
+		Self {
+			cells: (0..len).map(|i| Cell::with_index(V::ZERO, i)).collect(),
+			ptr: TapePointer::zero(len),
+		}
+	}
+
 	fn init(&mut self) {}
 
-	fn as_slice(&self) -> &[Cell] {
+	fn as_slice(&self) -> &[Cell<V>] {
 		self.cells.as_slice()
 	}
 
-	fn as_mut_slice(&mut self) -> &mut [Cell] {
+	fn as_mut_slice(&mut self) -> &mut [Cell<V>] {
 		self.cells.as_mut_slice()
 	}
 
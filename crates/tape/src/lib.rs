@@ -12,72 +12,113 @@ use core::ops::IndexMut;
 
 pub use self::{cell::*, impls::*, ptr::*};
 
+/// The number of cells a tape has when created through [`Default`].
 pub const TAPE_SIZE: usize = 5000;
 
 pub trait Tape: Default {
+	type Value: CellValue;
+
+	/// Create a tape with `len` cells.
+	///
+	/// # Panics
+	///
+	/// Panics if `len` is zero, or if the implementation has a fixed length other than `len`.
+	fn with_len(len: usize) -> Self;
+
 	/// Initialize the tape, setting all cells indices and values.
 	fn init(&mut self) {
 		for (i, cell) in self.as_mut_slice().iter_mut().enumerate() {
-			cell.set_value(0);
+			cell.clear_value();
 
 			cell.set_index(i);
 		}
 	}
 
-	fn as_slice(&self) -> &[Cell];
+	fn as_slice(&self) -> &[Cell<Self::Value>];
+
+	fn len(&self) -> usize {
+		self.as_slice().len()
+	}
+
+	fn is_empty(&self) -> bool {
+		self.as_slice().is_empty()
+	}
 
-	fn as_mut_slice(&mut self) -> &mut [Cell];
+	fn as_mut_slice(&mut self) -> &mut [Cell<Self::Value>];
 
 	fn ptr(&self) -> &TapePointer;
 
 	fn ptr_mut(&mut self) -> &mut TapePointer;
 
-	fn get(&self, ptr: usize) -> &Cell {
+	/// Move the pointer by `offset` cells.
+	fn move_ptr(&mut self, offset: isize) {
+		*self.ptr_mut() += offset;
+	}
+
+	/// Get the index of the cell `offset` cells away from the pointer.
+	///
+	/// Fixed-size tapes wrap around, while growable tapes make sure the cell exists first, so the
+	/// returned index is always in bounds.
+	fn index_of(&mut self, offset: isize) -> usize {
+		(self.ptr() + offset).value()
+	}
+
+	fn get(&self, ptr: usize) -> &Cell<Self::Value> {
 		self.as_slice().get(ptr).unwrap()
 	}
 
-	fn get_mut(&mut self, ptr: usize) -> &mut Cell {
+	fn get_mut(&mut self, ptr: usize) -> &mut Cell<Self::Value> {
 		self.as_mut_slice().get_mut(ptr).unwrap()
 	}
 
-	fn current_cell(&self) -> &Cell {
+	fn current_cell(&self) -> &Cell<Self::Value> {
 		self.get(self.ptr().value())
 	}
 
-	fn current_cell_mut(&mut self) -> &mut Cell {
+	fn current_cell_mut(&mut self) -> &mut Cell<Self::Value> {
 		self.get_mut(self.ptr().value())
 	}
 
-	unsafe fn current_cell_unchecked(&self) -> &Cell {
+	unsafe fn current_cell_unchecked(&self) -> &Cell<Self::Value> {
 		unsafe { self.get_unchecked(self.ptr().value()) }
 	}
 
-	unsafe fn current_cell_unchecked_mut(&mut self) -> &mut Cell {
+	unsafe fn current_cell_unchecked_mut(&mut self) -> &mut Cell<Self::Value> {
 		unsafe { self.get_unchecked_mut(self.ptr().value()) }
 	}
 
-	unsafe fn get_unchecked(&self, ptr: usize) -> &Cell {
+	unsafe fn get_unchecked(&self, ptr: usize) -> &Cell<Self::Value> {
 		unsafe { self.as_slice().get_unchecked(ptr) }
 	}
 
-	unsafe fn get_unchecked_mut(&mut self, ptr: usize) -> &mut Cell {
+	unsafe fn get_unchecked_mut(&mut self, ptr: usize) -> &mut Cell<Self::Value> {
 		unsafe { self.as_mut_slice().get_unchecked_mut(ptr) }
 	}
 }
 
 impl<T: Tape> Tape for Box<T>
 where
-	Self: IndexMut<usize, Output = Cell>,
+	Self: IndexMut<usize, Output = Cell<T::Value>>,
 {
+	type Value = T::Value;
+
+	fn with_len(len: usize) -> Self {
+		Self::new(T::with_len(len))
+	}
+
 	fn init(&mut self) {
 		(**self).init();
 	}
 
-	fn as_slice(&self) -> &[Cell] {
+	fn as_slice(&self) -> &[Cell<Self::Value>] {
 		(**self).as_slice()
 	}
 
-	fn as_mut_slice(&mut self) -> &mut [Cell] {
+	fn len(&self) -> usize {
+		(**self).len()
+	}
+
+	fn as_mut_slice(&mut self) -> &mut [Cell<Self::Value>] {
 		(**self).as_mut_slice()
 	}
 
@@ -89,19 +130,27 @@ where
 		(**self).ptr_mut()
 	}
 
-	fn current_cell(&self) -> &Cell {
+	fn move_ptr(&mut self, offset: isize) {
+		(**self).move_ptr(offset);
+	}
+
+	fn index_of(&mut self, offset: isize) -> usize {
+		(**self).index_of(offset)
+	}
+
+	fn current_cell(&self) -> &Cell<Self::Value> {
 		(**self).current_cell()
 	}
 
-	fn current_cell_mut(&mut self) -> &mut Cell {
+	fn current_cell_mut(&mut self) -> &mut Cell<Self::Value> {
 		(**self).current_cell_mut()
 	}
 
-	unsafe fn current_cell_unchecked(&self) -> &Cell {
+	unsafe fn current_cell_unchecked(&self) -> &Cell<Self::Value> {
 		unsafe { (**self).current_cell_unchecked() }
 	}
 
-	unsafe fn current_cell_unchecked_mut(&mut self) -> &mut Cell {
+	unsafe fn current_cell_unchecked_mut(&mut self) -> &mut Cell<Self::Value> {
 		unsafe { (**self).current_cell_unchecked_mut() }
 	}
 }
@@ -5,50 +5,100 @@ use serde::{Deserialize, Serialize};
 use super::TAPE_SIZE;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-#[repr(transparent)]
-#[serde(transparent)]
-pub struct TapePointer(usize);
+#[serde(try_from = "RawTapePointer")]
+pub struct TapePointer {
+	value: usize,
+	len: usize,
+}
 
 impl TapePointer {
 	#[must_use]
-	pub const fn new(value: usize) -> Option<Self> {
-		if value >= TAPE_SIZE {
+	pub const fn new(value: usize, len: usize) -> Option<Self> {
+		if value >= len {
 			None
 		} else {
-			Some(unsafe { Self::new_unchecked(value) })
+			Some(unsafe { Self::new_unchecked(value, len) })
 		}
 	}
 
 	#[must_use]
-	pub const fn zero() -> Self {
-		unsafe { Self::new_unchecked(0) }
+	pub const fn zero(len: usize) -> Self {
+		assert!(len > 0, "tape length must be non-zero");
+
+		unsafe { Self::new_unchecked(0, len) }
 	}
 
 	#[must_use]
-	pub const unsafe fn new_unchecked(value: usize) -> Self {
-		Self(value)
+	pub const unsafe fn new_unchecked(value: usize, len: usize) -> Self {
+		Self { value, len }
 	}
 
 	#[must_use]
 	pub const fn value(self) -> usize {
-		self.0
+		self.value
 	}
 
-	pub const fn set(&mut self, mut value: usize) {
-		while value >= TAPE_SIZE {
-			value -= TAPE_SIZE;
-		}
+	/// The length of the tape this pointer wraps around.
+	#[must_use]
+	pub const fn len(self) -> usize {
+		self.len
+	}
+
+	#[must_use]
+	pub const fn is_empty(self) -> bool {
+		matches!(self.len, 0)
+	}
 
-		unsafe { self.set_unchecked(value) };
+	pub const fn set(&mut self, value: usize) {
+		unsafe { self.set_unchecked(value % self.len) };
 	}
 
 	pub const unsafe fn set_unchecked(&mut self, value: usize) {
-		self.0 = value;
+		self.value = value;
+	}
+}
+
+impl TryFrom<RawTapePointer> for TapePointer {
+	type Error = &'static str;
+
+	fn try_from(RawTapePointer { value, len }: RawTapePointer) -> Result<Self, Self::Error> {
+		if matches!(len, 0) {
+			return Err("tape length must be non-zero");
+		}
+
+		Self::new(value, len).ok_or("tape pointer must be within the tape")
 	}
 }
 
 impl Default for TapePointer {
 	fn default() -> Self {
-		Self::zero()
+		Self::zero(TAPE_SIZE)
+	}
+}
+
+// What a pointer is read as before checking it points into a tape.
+#[derive(Deserialize)]
+struct RawTapePointer {
+	value: usize,
+	len: usize,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::TapePointer;
+
+	#[test]
+	fn round_trips() {
+		let ptr = TapePointer::new(3, 8).unwrap();
+		let json = serde_json::to_string(&ptr).unwrap();
+
+		assert_eq!(json, r#"{"value":3,"len":8}"#);
+		assert_eq!(serde_json::from_str::<TapePointer>(&json).unwrap(), ptr);
+	}
+
+	#[test]
+	fn rejects_pointers_off_the_tape() {
+		assert!(serde_json::from_str::<TapePointer>(r#"{"value":0,"len":0}"#).is_err());
+		assert!(serde_json::from_str::<TapePointer>(r#"{"value":8,"len":8}"#).is_err());
 	}
 }
@@ -1,16 +1,14 @@
 use core::ops::{Add, AddAssign, Neg, Sub, SubAssign};
 
-use crate::{TAPE_SIZE, TapePointer};
+use crate::TapePointer;
 
 impl Add<usize> for TapePointer {
 	type Output = Self;
 
 	fn add(self, rhs: usize) -> Self::Output {
-		let mut out = Self(self.0 + rhs);
+		let mut out = self;
 
-		while out.0 >= TAPE_SIZE {
-			out.0 -= TAPE_SIZE;
-		}
+		out.set(self.value % self.len + rhs % self.len);
 
 		out
 	}
@@ -104,11 +102,11 @@ impl Sub<usize> for TapePointer {
 	type Output = Self;
 
 	fn sub(self, rhs: usize) -> Self::Output {
-		Self(if self.0.wrapping_sub(rhs) >= TAPE_SIZE {
-			TAPE_SIZE - rhs
-		} else {
-			self.0 - rhs
-		})
+		let mut out = self;
+
+		out.set(self.value % self.len + (self.len - rhs % self.len));
+
+		out
 	}
 }
 
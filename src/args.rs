@@ -1,28 +1,147 @@
 use std::{
 	fmt::{Display, Formatter, Result as FmtResult},
 	path::PathBuf,
+	time::Duration,
 };
 
 use clap::{
 	Arg, ArgAction, ArgGroup, ArgMatches, Args as ClapArgs, Command, CommandFactory,
-	Error as ClapError, FromArgMatches, Parser, ValueEnum,
-	builder::{EnumValueParser, PossibleValue, ValueParser},
+	Error as ClapError, FromArgMatches, Parser, Subcommand, ValueEnum,
+	builder::{
+		EnumValueParser, PossibleValue, PossibleValuesParser, RangedU64ValueParser,
+		TypedValueParser as _, ValueParser,
+	},
 	error::ErrorKind as ClapErrorKind,
 };
+use vmm::{
+	interpret::{BoundsPolicy, ExecutionBudget},
+	ir::{CellWidth, EofPolicy},
+	opt::{OptLevel, PassName},
+	tape::TAPE_SIZE,
+};
+
+#[derive(Debug, Parser)]
+#[command(name = "vmm", args_conflicts_with_subcommands = true)]
+pub struct Cli {
+	#[command(subcommand)]
+	pub command: Option<Mode>,
+	#[command(flatten)]
+	pub args: Option<Args>,
+}
+
+impl Cli {
+	pub fn into_mode(self) -> Result<Mode, ClapError> {
+		match (self.command, self.args) {
+			(Some(mode), _) => Ok(mode),
+			(None, Some(args)) => Ok(Mode::Run(args)),
+			(None, None) => Err(ClapError::raw(
+				ClapErrorKind::MissingRequiredArgument,
+				"The following required argument was not provided: file",
+			)),
+		}
+	}
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Mode {
+	/// Run a program (the default)
+	Run(Args),
+	/// Step through a program interactively
+	Debug(Args),
+	/// Generate source code for a program instead of running it
+	Compile {
+		#[command(flatten)]
+		args: Args,
+		#[arg(long, default_value_t = Target::C)]
+		target: Target,
+		/// Where to write the generated code, stdout if not given
+		#[arg(long)]
+		output: Option<PathBuf>,
+	},
+	/// Find the optimization that changes a program's output
+	Bisect {
+		#[command(flatten)]
+		args: Args,
+		/// What the program reads, empty if not given
+		#[arg(long)]
+		input: Option<PathBuf>,
+	},
+}
+
+impl Mode {
+	pub const fn args(&self) -> &Args {
+		match self {
+			Self::Run(args)
+			| Self::Debug(args)
+			| Self::Compile { args, .. }
+			| Self::Bisect { args, .. } => args,
+		}
+	}
+}
 
 #[derive(Debug)]
 pub struct Args {
 	pub file: PathBuf,
 	pub optimize: bool,
+	pub opt_level: Option<OptLevel>,
+	pub passes: Option<PathBuf>,
+	pub disable_passes: Vec<PassName>,
+	pub opt_fuel: Option<u64>,
+	pub validate: bool,
 	pub tape: TapeType,
+	pub tape_len: usize,
+	pub bounds: BoundsPolicy,
+	pub cell_width: CellWidth,
+	pub eof: EofPolicy,
+	pub fuel: Option<u64>,
+	pub loop_limit: Option<usize>,
+	pub time_limit: Option<Duration>,
+	pub backend: Backend,
+}
+
+impl Args {
+	/// Whether to optimize, asking for a level or changing which passes run also implies it.
+	pub const fn should_optimize(&self) -> bool {
+		self.optimize
+			|| self.opt_level.is_some()
+			|| self.passes.is_some()
+			|| !self.disable_passes.is_empty()
+			|| self.opt_fuel.is_some()
+			|| self.validate
+	}
+
+	pub const fn budget(&self) -> ExecutionBudget {
+		ExecutionBudget {
+			fuel: self.fuel,
+			loop_limit: self.loop_limit,
+			time_limit: self.time_limit,
+		}
+	}
 }
 
 impl ClapArgs for Args {
+	fn group_id() -> Option<clap::Id> {
+		Some(clap::Id::from("Args"))
+	}
+
 	fn augment_args(cmd: Command) -> Command {
 		cmd.group(ArgGroup::new("Args").multiple(true).args([
 			clap::Id::from("file"),
 			clap::Id::from("optimize"),
+			clap::Id::from("opt_level"),
+			clap::Id::from("passes"),
+			clap::Id::from("disable_passes"),
+			clap::Id::from("opt_fuel"),
+			clap::Id::from("validate"),
 			clap::Id::from("tape_type"),
+			clap::Id::from("tape_len"),
+			clap::Id::from("bounds"),
+			clap::Id::from("cell_width"),
+			clap::Id::from("eof"),
+			clap::Id::from("fuel"),
+			clap::Id::from("loop_limit"),
+			clap::Id::from("time_limit"),
+			clap::Id::from("backend"),
 		]))
 		.arg(
 			Arg::new("file")
@@ -40,6 +159,54 @@ impl ClapArgs for Args {
 				.short('o')
 				.long("optimize"),
 		)
+		.arg(
+			Arg::new("opt_level")
+				.value_name("LEVEL")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(
+					PossibleValuesParser::new(["0", "1", "2", "s"])
+						.map(|s| s.parse::<OptLevel>().unwrap()),
+				)
+				.conflicts_with("passes")
+				.short('O')
+				.long("opt-level"),
+		)
+		.arg(
+			Arg::new("passes")
+				.value_name("PIPELINE")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(ValueParser::path_buf())
+				.long("passes"),
+		)
+		.arg(
+			Arg::new("disable_passes")
+				.value_name("PASS")
+				.required(false)
+				.action(ArgAction::Append)
+				.value_parser(
+					PossibleValuesParser::new(PassName::ALL.iter().map(|pass| pass.as_str()))
+						.map(|s| s.parse::<PassName>().unwrap()),
+				)
+				.long("disable-pass"),
+		)
+		.arg(
+			Arg::new("opt_fuel")
+				.value_name("OPT_FUEL")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(RangedU64ValueParser::<u64>::new())
+				.long("opt-fuel"),
+		)
+		.arg(
+			Arg::new("validate")
+				.value_name("VALIDATE")
+				.required(ArgAction::SetTrue.takes_values())
+				.value_parser(ValueParser::bool())
+				.action(ArgAction::SetTrue)
+				.long("validate"),
+		)
 		.arg(
 			Arg::new("tape_type")
 				.value_name("TAPE_TYPE")
@@ -50,13 +217,104 @@ impl ClapArgs for Args {
 				.default_value("ptr")
 				.long("tape"),
 		)
+		.arg(
+			Arg::new("tape_len")
+				.value_name("TAPE_LEN")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(RangedU64ValueParser::<usize>::new().range(1..))
+				.short('l')
+				.long("tape-len"),
+		)
+		.arg(
+			Arg::new("bounds")
+				.value_name("BOUNDS")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(
+					PossibleValuesParser::new(["wrap", "error", "saturate"])
+						.map(|s| s.parse::<BoundsPolicy>().unwrap()),
+				)
+				.short('b')
+				.long("bounds"),
+		)
+		.arg(
+			Arg::new("cell_width")
+				.value_name("CELL_WIDTH")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(
+					PossibleValuesParser::new(["8", "16", "32"])
+						.map(|s| CellWidth::from_bits(s.parse().unwrap()).unwrap()),
+				)
+				.short('w')
+				.long("cell-width"),
+		)
+		.arg(
+			Arg::new("eof")
+				.value_name("EOF")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(
+					PossibleValuesParser::new(["zero", "max", "unchanged"])
+						.map(|s| s.parse::<EofPolicy>().unwrap()),
+				)
+				.short('e')
+				.long("eof"),
+		)
+		.arg(
+			Arg::new("fuel")
+				.value_name("FUEL")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(RangedU64ValueParser::<u64>::new())
+				.long("fuel"),
+		)
+		.arg(
+			Arg::new("loop_limit")
+				.value_name("LOOP_LIMIT")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(RangedU64ValueParser::<usize>::new())
+				.long("loop-limit"),
+		)
+		.arg(
+			Arg::new("time_limit")
+				.value_name("SECONDS")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(parse_seconds)
+				.long("time-limit"),
+		)
+		.arg(
+			Arg::new("backend")
+				.value_name("BACKEND")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(EnumValueParser::<Backend>::new())
+				.default_value("bytecode")
+				.long("backend"),
+		)
 	}
 
 	fn augment_args_for_update(cmd: Command) -> Command {
 		cmd.group(ArgGroup::new("Args").multiple(true).args([
 			clap::Id::from("file"),
 			clap::Id::from("optimize"),
+			clap::Id::from("opt_level"),
+			clap::Id::from("passes"),
+			clap::Id::from("disable_passes"),
+			clap::Id::from("opt_fuel"),
+			clap::Id::from("validate"),
 			clap::Id::from("tape_type"),
+			clap::Id::from("tape_len"),
+			clap::Id::from("bounds"),
+			clap::Id::from("cell_width"),
+			clap::Id::from("eof"),
+			clap::Id::from("fuel"),
+			clap::Id::from("loop_limit"),
+			clap::Id::from("time_limit"),
+			clap::Id::from("backend"),
 		]))
 		.arg(
 			Arg::new("file")
@@ -74,6 +332,54 @@ impl ClapArgs for Args {
 				.short('o')
 				.long("optimize"),
 		)
+		.arg(
+			Arg::new("opt_level")
+				.value_name("LEVEL")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(
+					PossibleValuesParser::new(["0", "1", "2", "s"])
+						.map(|s| s.parse::<OptLevel>().unwrap()),
+				)
+				.conflicts_with("passes")
+				.short('O')
+				.long("opt-level"),
+		)
+		.arg(
+			Arg::new("passes")
+				.value_name("PIPELINE")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(ValueParser::path_buf())
+				.long("passes"),
+		)
+		.arg(
+			Arg::new("disable_passes")
+				.value_name("PASS")
+				.required(false)
+				.action(ArgAction::Append)
+				.value_parser(
+					PossibleValuesParser::new(PassName::ALL.iter().map(|pass| pass.as_str()))
+						.map(|s| s.parse::<PassName>().unwrap()),
+				)
+				.long("disable-pass"),
+		)
+		.arg(
+			Arg::new("opt_fuel")
+				.value_name("OPT_FUEL")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(RangedU64ValueParser::<u64>::new())
+				.long("opt-fuel"),
+		)
+		.arg(
+			Arg::new("validate")
+				.value_name("VALIDATE")
+				.required(false)
+				.value_parser(ValueParser::bool())
+				.action(ArgAction::SetTrue)
+				.long("validate"),
+		)
 		.arg(
 			Arg::new("tape_type")
 				.value_name("TAPE_TYPE")
@@ -83,6 +389,83 @@ impl ClapArgs for Args {
 				.short('t')
 				.long("tape"),
 		)
+		.arg(
+			Arg::new("tape_len")
+				.value_name("TAPE_LEN")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(RangedU64ValueParser::<usize>::new().range(1..))
+				.short('l')
+				.long("tape-len"),
+		)
+		.arg(
+			Arg::new("bounds")
+				.value_name("BOUNDS")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(
+					PossibleValuesParser::new(["wrap", "error", "saturate"])
+						.map(|s| s.parse::<BoundsPolicy>().unwrap()),
+				)
+				.short('b')
+				.long("bounds"),
+		)
+		.arg(
+			Arg::new("cell_width")
+				.value_name("CELL_WIDTH")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(
+					PossibleValuesParser::new(["8", "16", "32"])
+						.map(|s| CellWidth::from_bits(s.parse().unwrap()).unwrap()),
+				)
+				.short('w')
+				.long("cell-width"),
+		)
+		.arg(
+			Arg::new("eof")
+				.value_name("EOF")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(
+					PossibleValuesParser::new(["zero", "max", "unchanged"])
+						.map(|s| s.parse::<EofPolicy>().unwrap()),
+				)
+				.short('e')
+				.long("eof"),
+		)
+		.arg(
+			Arg::new("fuel")
+				.value_name("FUEL")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(RangedU64ValueParser::<u64>::new())
+				.long("fuel"),
+		)
+		.arg(
+			Arg::new("loop_limit")
+				.value_name("LOOP_LIMIT")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(RangedU64ValueParser::<usize>::new())
+				.long("loop-limit"),
+		)
+		.arg(
+			Arg::new("time_limit")
+				.value_name("SECONDS")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(parse_seconds)
+				.long("time-limit"),
+		)
+		.arg(
+			Arg::new("backend")
+				.value_name("BACKEND")
+				.required(false)
+				.action(ArgAction::Set)
+				.value_parser(EnumValueParser::<Backend>::new())
+				.long("backend"),
+		)
 	}
 }
 
@@ -115,12 +498,33 @@ impl FromArgMatches for Args {
 					"The following required argument was not provided: optimize",
 				)
 			})?,
+			opt_level: matches.remove_one("opt_level"),
+			passes: matches.remove_one("passes"),
+			disable_passes: matches
+				.remove_many("disable_passes")
+				.map(Iterator::collect)
+				.unwrap_or_default(),
+			opt_fuel: matches.remove_one("opt_fuel"),
+			validate: matches.remove_one("validate").ok_or_else(|| {
+				ClapError::raw(
+					ClapErrorKind::MissingRequiredArgument,
+					"The following required argument was not provided: validate",
+				)
+			})?,
 			tape: matches.remove_one("tape_type").ok_or_else(|| {
 				ClapError::raw(
 					ClapErrorKind::MissingRequiredArgument,
 					"The following required argument was not provided: tape",
 				)
 			})?,
+			tape_len: matches.remove_one("tape_len").unwrap_or(TAPE_SIZE),
+			bounds: matches.remove_one("bounds").unwrap_or_default(),
+			cell_width: matches.remove_one("cell_width").unwrap_or_default(),
+			eof: matches.remove_one("eof").unwrap_or_default(),
+			fuel: matches.remove_one("fuel"),
+			loop_limit: matches.remove_one("loop_limit"),
+			time_limit: matches.remove_one("time_limit"),
+			backend: matches.remove_one("backend").unwrap_or_default(),
 		})
 	}
 
@@ -149,6 +553,26 @@ impl FromArgMatches for Args {
 			})?;
 		}
 
+		if let Some(opt_level) = matches.remove_one("opt_level") {
+			self.opt_level = Some(opt_level);
+		}
+
+		if let Some(passes) = matches.remove_one("passes") {
+			self.passes = Some(passes);
+		}
+
+		if let Some(disable_passes) = matches.remove_many("disable_passes") {
+			self.disable_passes = disable_passes.collect();
+		}
+
+		if let Some(opt_fuel) = matches.remove_one("opt_fuel") {
+			self.opt_fuel = Some(opt_fuel);
+		}
+
+		if let Some(validate) = matches.remove_one("validate") {
+			self.validate = validate;
+		}
+
 		if matches.contains_id("tape_type") {
 			let tape_type = &mut self.tape;
 			*tape_type = matches.remove_one("tape_type").ok_or_else(|| {
@@ -159,18 +583,57 @@ impl FromArgMatches for Args {
 			})?;
 		}
 
+		if let Some(tape_len) = matches.remove_one("tape_len") {
+			self.tape_len = tape_len;
+		}
+
+		if let Some(bounds) = matches.remove_one("bounds") {
+			self.bounds = bounds;
+		}
+
+		if let Some(cell_width) = matches.remove_one("cell_width") {
+			self.cell_width = cell_width;
+		}
+
+		if let Some(eof) = matches.remove_one("eof") {
+			self.eof = eof;
+		}
+
+		if let Some(fuel) = matches.remove_one("fuel") {
+			self.fuel = Some(fuel);
+		}
+
+		if let Some(loop_limit) = matches.remove_one("loop_limit") {
+			self.loop_limit = Some(loop_limit);
+		}
+
+		if let Some(time_limit) = matches.remove_one("time_limit") {
+			self.time_limit = Some(time_limit);
+		}
+
+		if let Some(backend) = matches.remove_one("backend") {
+			self.backend = backend;
+		}
+
 		Ok(())
 	}
 }
 
 impl Parser for Args {}
 
+fn parse_seconds(s: &str) -> Result<Duration, String> {
+	s.parse::<f64>()
+		.map_err(|e| e.to_string())
+		.and_then(|secs| Duration::try_from_secs_f64(secs).map_err(|e| e.to_string()))
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum TapeType {
 	Box,
 	Vec,
 	Ptr,
 	Stack,
+	Growable,
 }
 
 impl Display for TapeType {
@@ -180,13 +643,14 @@ impl Display for TapeType {
 			Self::Vec => "vec",
 			Self::Ptr => "ptr",
 			Self::Stack => "stack",
+			Self::Growable => "growable",
 		})
 	}
 }
 
 impl ValueEnum for TapeType {
 	fn value_variants<'a>() -> &'a [Self] {
-		&[Self::Box, Self::Vec, Self::Ptr, Self::Stack]
+		&[Self::Box, Self::Vec, Self::Ptr, Self::Stack, Self::Growable]
 	}
 
 	fn to_possible_value(&self) -> Option<PossibleValue> {
@@ -195,6 +659,86 @@ impl ValueEnum for TapeType {
 			Self::Vec => "vec",
 			Self::Ptr => "ptr",
 			Self::Stack => "stack",
+			Self::Growable => "growable",
+		}))
+	}
+}
+
+/// How programs are executed when running them.
+#[derive(Debug, Default, Clone, Copy)]
+pub enum Backend {
+	/// Walk the instruction tree directly, the only backend that fills in the profiler
+	Tree,
+	/// Compile to flat bytecode and run it in a dispatch loop
+	#[default]
+	Bytecode,
+	/// Compile to native code with Cranelift, only supports wrapping fixed-size tapes
+	Jit,
+}
+
+impl Display for Backend {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		f.write_str(match self {
+			Self::Tree => "tree",
+			Self::Bytecode => "bytecode",
+			Self::Jit => "jit",
+		})
+	}
+}
+
+impl ValueEnum for Backend {
+	fn value_variants<'a>() -> &'a [Self] {
+		&[Self::Tree, Self::Bytecode, Self::Jit]
+	}
+
+	fn to_possible_value(&self) -> Option<PossibleValue> {
+		Some(PossibleValue::new(match self {
+			Self::Tree => "tree",
+			Self::Bytecode => "bytecode",
+			Self::Jit => "jit",
+		}))
+	}
+}
+
+/// The language programs are compiled to.
+#[derive(Debug, Default, Clone, Copy)]
+pub enum Target {
+	#[default]
+	C,
+	/// A standalone Rust `main.rs`
+	Rust,
+	/// A Rust library exposing `run(input, output)`
+	RustLib,
+	/// A WebAssembly text module
+	Wat,
+	/// A binary WebAssembly module
+	Wasm,
+}
+
+impl Display for Target {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		f.write_str(match self {
+			Self::C => "c",
+			Self::Rust => "rust",
+			Self::RustLib => "rust-lib",
+			Self::Wat => "wat",
+			Self::Wasm => "wasm",
+		})
+	}
+}
+
+impl ValueEnum for Target {
+	fn value_variants<'a>() -> &'a [Self] {
+		&[Self::C, Self::Rust, Self::RustLib, Self::Wat, Self::Wasm]
+	}
+
+	fn to_possible_value(&self) -> Option<PossibleValue> {
+		Some(PossibleValue::new(match self {
+			Self::C => "c",
+			Self::Rust => "rust",
+			Self::RustLib => "rust-lib",
+			Self::Wat => "wat",
+			Self::Wasm => "wasm",
 		}))
 	}
 }
@@ -0,0 +1,183 @@
+use std::{
+	fs,
+	io::Cursor,
+	mem::{self, Discriminant},
+	path::Path,
+};
+
+use color_eyre::eyre::{Result, bail, eyre};
+use vmm::{
+	interpret::{ExecutionBudget, Interpreter, RuntimeError},
+	ir::CellWidth,
+	opt::{AppliedChange, NoopStore, Optimizer},
+	parse::Parser as BfParser,
+	program::Program,
+	tape::{CellValue, VecTape},
+};
+
+use crate::{args::Args, load_pipeline, source::Source};
+
+/// What a run of a program did, everything that has to stay the same after optimizing.
+#[derive(Debug, PartialEq, Eq)]
+struct Outcome {
+	output: Vec<u8>,
+	error: Option<Discriminant<RuntimeError>>,
+}
+
+struct Run {
+	output: Vec<u8>,
+	result: Result<(), RuntimeError>,
+	fuel_used: u64,
+}
+
+impl Run {
+	fn outcome(self) -> Outcome {
+		Outcome {
+			output: self.output,
+			error: self.result.as_ref().err().map(mem::discriminant),
+		}
+	}
+
+	const fn ran_out_of_budget(&self) -> bool {
+		matches!(
+			self.result,
+			Err(RuntimeError::OutOfFuel(_)
+				| RuntimeError::OutOfTime(_)
+				| RuntimeError::TooManyIterations { .. })
+		)
+	}
+}
+
+/// Finds the first change the optimizer makes that alters what the program does, by optimizing
+/// with more and more fuel until the output no longer matches an unoptimized run.
+pub fn bisect(args: &Args, input: Option<&Path>) -> Result<()> {
+	match args.cell_width {
+		CellWidth::U8 => bisect_with::<u8>(args, input),
+		CellWidth::U16 => bisect_with::<u16>(args, input),
+		CellWidth::U32 => bisect_with::<u32>(args, input),
+	}
+}
+
+fn bisect_with<V: CellValue>(args: &Args, input: Option<&Path>) -> Result<()> {
+	let raw_data = fs::read_to_string(&args.file)?;
+
+	let (program, source_map) = BfParser::new(&raw_data)
+		.scan_with_source_map()
+		.map_err(|e| eyre!("in {}: {}", args.file.display(), e.snippet()))?;
+
+	let program = program.into_iter().collect::<Program>();
+	let source = Source::new(args.file.clone(), raw_data, source_map);
+	let input = input.map(fs::read).transpose()?.unwrap_or_default();
+
+	// Fuel is only counted when there is a limit on it.
+	let reference_budget = ExecutionBudget {
+		fuel: Some(args.fuel.unwrap_or(u64::MAX)),
+		..args.budget()
+	};
+
+	let reference = execute::<V>(program.clone(), &input, args, reference_budget);
+
+	if reference.ran_out_of_budget() {
+		bail!("the unoptimized program ran out of budget, so it can't be used as a reference");
+	}
+
+	let fuel_used = reference.fuel_used;
+	let expected = reference.outcome();
+
+	// Optimizing shouldn't ever make a program do more work, so a run that takes twice as long as
+	// the reference has gone wrong.
+	let budget = ExecutionBudget {
+		fuel: Some(fuel_used.saturating_mul(2)),
+		..args.budget()
+	};
+
+	let optimize = |fuel: Option<u64>| -> Result<(Program, Option<AppliedChange>, u64)> {
+		let mut optimizer = Optimizer::new(program.clone(), NoopStore::new())
+			.and_with_cell_width(args.cell_width)
+			.and_with_tape_len(args.tape_len)
+			.and_with_eof_policy(args.eof)
+			.and_with_pipeline(load_pipeline(args)?)
+			.and_with_source_map(source.map().clone())
+			// The whole point is to find programs that don't match.
+			.and_without_validation();
+
+		if let Some(fuel) = fuel {
+			optimizer = optimizer.and_with_fuel(fuel);
+		}
+
+		let program = optimizer.optimize()?;
+		let fuel = optimizer.fuel();
+
+		Ok((program, fuel.last_change().cloned(), fuel.consumed()))
+	};
+
+	let matches =
+		|program: Program| execute::<V>(program, &input, args, budget).outcome() == expected;
+
+	let (optimized, _, total) = optimize(args.opt_fuel)?;
+
+	if matches(optimized) {
+		println!("the optimized program matches the unoptimized one after {total} changes");
+		return Ok(());
+	}
+
+	if total == 0 {
+		bail!("the output changed without the optimizer applying any changes");
+	}
+
+	// Without any changes the program is the reference, so the first bad change is in `good..bad`.
+	let (mut good, mut bad) = (0, total);
+
+	while bad - good > 1 {
+		let fuel = good + (bad - good) / 2;
+
+		if matches(optimize(Some(fuel))?.0) {
+			good = fuel;
+		} else {
+			bad = fuel;
+		}
+	}
+
+	let Some(change) = optimize(Some(bad))?.1 else {
+		unreachable!("limited fuel keeps the last change");
+	};
+
+	print!(
+		"change {bad} of {total} alters the output, made by {}",
+		change.pass()
+	);
+
+	if let Some(span) = change.span() {
+		print!(" at {}", source.locate(span));
+	}
+
+	println!();
+	print!("{change}");
+
+	Ok(())
+}
+
+fn execute<V: CellValue>(
+	program: Program,
+	input: &[u8],
+	args: &Args,
+	budget: ExecutionBudget,
+) -> Run {
+	let mut interpreter = Interpreter::<VecTape<V>, _, _>::with_tape_len(
+		program,
+		Cursor::new(input.to_vec()),
+		Vec::new(),
+		args.tape_len,
+	)
+	.and_with_bounds_policy(args.bounds)
+	.and_with_eof_policy(args.eof)
+	.and_with_budget(budget);
+
+	let result = interpreter.run();
+
+	Run {
+		output: interpreter.output().clone(),
+		result,
+		fuel_used: interpreter.fuel_used(),
+	}
+}
@@ -0,0 +1,173 @@
+use std::io::{Empty, Stdout, Write as _, empty, prelude::*, stdin, stdout};
+
+use color_eyre::eyre::Result;
+use vmm::{
+	interpret::{Breakpoint, DebugEvent, Debugger, Interpreter},
+	program::Program,
+	tape::Tape,
+};
+
+use crate::{args::Args, source::Source};
+
+const DEFAULT_TAPE_RADIUS: usize = 8;
+
+const HELP: &str = "\
+commands:
+  s, step [count]      execute the next instruction, entering blocks
+  n, next              execute the next instruction, running blocks to completion
+  c, continue          run until a breakpoint is hit or the program finishes
+  b, break <bp>        add a breakpoint: <path> (e.g. 3.0.1), #<cell>=<value> or *=<value>
+  d, delete <index>    remove a breakpoint
+  bl, breakpoints      list breakpoints
+  t, tape [radius]     show the tape around the pointer
+  w, where             show the current position, instruction and source location
+  q, quit              exit the debugger
+the program reads from an empty input while debugging";
+
+pub fn repl<T: Tape>(program: Program, source: &Source, args: &Args) -> Result<()> {
+	let mut debugger =
+		Interpreter::<T, _, _>::with_tape_len(program, empty(), stdout(), args.tape_len)
+			.and_with_bounds_policy(args.bounds)
+			.and_with_eof_policy(args.eof)
+			.and_with_budget(args.budget())
+			.and_with_source_map(source.map().clone())
+			.into_debugger();
+
+	println!("{HELP}");
+	print_position(&debugger, source);
+
+	let mut lines = stdin().lock().lines();
+
+	loop {
+		print!("(vmm) ");
+		stdout().flush()?;
+
+		let Some(line) = lines.next().transpose()? else {
+			break;
+		};
+
+		let mut words = line.split_whitespace();
+
+		let Some(command) = words.next() else {
+			continue;
+		};
+
+		let arg = words.next();
+
+		let event = match command {
+			"s" | "step" => {
+				let count = arg.map_or(Ok(1), str::parse::<usize>);
+
+				let Ok(count) = count else {
+					println!("invalid step count");
+					continue;
+				};
+
+				let mut event = DebugEvent::Stepped;
+
+				for _ in 0..count {
+					event = debugger
+						.step()
+						.map_err(|e| source.locate_error(e, debugger.interpreter().error_span()))?;
+
+					if !matches!(event, DebugEvent::Stepped) {
+						break;
+					}
+				}
+
+				event
+			}
+			"n" | "next" => debugger
+				.step_over()
+				.map_err(|e| source.locate_error(e, debugger.interpreter().error_span()))?,
+			"c" | "continue" => debugger
+				.resume()
+				.map_err(|e| source.locate_error(e, debugger.interpreter().error_span()))?,
+			"b" | "break" => {
+				match arg.unwrap_or_default().parse::<Breakpoint>() {
+					Ok(breakpoint) => {
+						let index = debugger.add_breakpoint(breakpoint);
+
+						println!("breakpoint {index} added");
+					}
+					Err(e) => println!("{e}"),
+				}
+
+				continue;
+			}
+			"d" | "delete" => {
+				match arg
+					.and_then(|a| a.parse().ok())
+					.and_then(|i| debugger.remove_breakpoint(i))
+				{
+					Some(breakpoint) => println!("removed breakpoint {breakpoint}"),
+					None => println!("no such breakpoint"),
+				}
+
+				continue;
+			}
+			"bl" | "breakpoints" => {
+				for (i, breakpoint) in debugger.breakpoints() {
+					println!("{i}: {breakpoint}");
+				}
+
+				continue;
+			}
+			"t" | "tape" => {
+				let radius = arg
+					.and_then(|a| a.parse().ok())
+					.unwrap_or(DEFAULT_TAPE_RADIUS);
+
+				println!("{}", debugger.interpreter().tape_window(radius));
+
+				continue;
+			}
+			"w" | "where" => {
+				print_position(&debugger, source);
+
+				continue;
+			}
+			"h" | "help" => {
+				println!("{HELP}");
+
+				continue;
+			}
+			"q" | "quit" => break,
+			_ => {
+				println!("unknown command {command:?}, try \"help\"");
+
+				continue;
+			}
+		};
+
+		match event {
+			DebugEvent::Stepped => print_position(&debugger, source),
+			DebugEvent::Breakpoint(i) => {
+				println!("hit breakpoint {i}");
+				print_position(&debugger, source);
+			}
+			DebugEvent::Finished => println!("program finished"),
+		}
+	}
+
+	Ok(())
+}
+
+fn print_position<T: Tape>(debugger: &Debugger<T, Empty, Stdout>, source: &Source) {
+	let Some(instr) = debugger.current_instruction() else {
+		println!("program finished");
+		return;
+	};
+
+	print!(
+		"{}: {} (ptr: {})",
+		debugger.position(),
+		instr.to_string().lines().next().unwrap_or_default(),
+		debugger.interpreter().ptr().value()
+	);
+
+	match debugger.current_span() {
+		Some(span) => println!(" at {}", source.locate(span)),
+		None => println!(),
+	}
+}
@@ -1,5 +1,6 @@
 #[doc(inline)]
 pub use {
-	vmm_alloc_stats as alloc_stats, vmm_interpret as interpret, vmm_ir as ir, vmm_opt as opt,
-	vmm_parse as parse, vmm_program as program, vmm_tape as tape, vmm_utils as utils,
+	vmm_alloc_stats as alloc_stats, vmm_codegen as codegen, vmm_interpret as interpret,
+	vmm_ir as ir, vmm_jit as jit, vmm_opt as opt, vmm_parse as parse, vmm_program as program,
+	vmm_semantic as semantic, vmm_tape as tape, vmm_utils as utils,
 };
@@ -1,17 +1,20 @@
 #![allow(clippy::large_stack_frames)]
 
 mod args;
+mod bisect;
+mod debug;
+mod source;
 
 #[cfg(any(miri, not(feature = "mimalloc")))]
 use std::alloc::System as Alloc;
 use std::{
 	fs,
-	io::{Stdout, empty, stdin, stdout},
-	path::PathBuf,
+	io::{Read, Stdout, Write as _, empty, stdin, stdout},
+	path::{Path, PathBuf},
 };
 
 use clap::Parser as _;
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{Result, bail, eyre};
 use serde_binary::{Config, to_writer_with_config};
 use serde_reflection::{Tracer, TracerConfig};
 use tracing::{debug, debug_span, info};
@@ -24,18 +27,25 @@ use tracing_subscriber::{
 };
 use vmm::{
 	alloc_stats::{Region, StatsAlloc},
-	interpret::{Interpreter, Profiler},
-	ir::{BlockInstruction, Instruction, MinimumOutputs as _, ScaleAnd, SuperInstruction},
-	opt::{HashMetadataStore, Optimizer, OutputMetadataStore},
+	codegen::{CCodegen, Codegen, CodegenOptions, RustCodegen, RustEntry, WasmCodegen},
+	interpret::{BoundsPolicy, Interpreter, Profiler},
+	ir::{
+		BlockInstruction, CellWidth, Instruction, MinimumOutputs as _, ScaleAnd, SuperInstruction,
+	},
+	jit::JitProgram,
+	opt::{HashMetadataStore, Optimizer, OutputMetadataStore, PassPipeline, Validation},
 	parse::Parser as BfParser,
 	program::Program,
-	tape::{BoxTape, PtrTape, StackTape, Tape, VecTape},
+	tape::{BoxTape, CellValue, GrowableTape, PtrTape, StackTape, TAPE_SIZE, Tape, VecTape},
 	utils::{CopyWriter, HeapSize as _},
 };
 #[cfg(all(not(miri), feature = "mimalloc"))]
 use vmm_mimalloc::MiMalloc as Alloc;
 
-use self::args::{Args, TapeType};
+use self::{
+	args::{Args, Backend, Cli, Mode, TapeType, Target},
+	source::Source,
+};
 
 #[global_allocator]
 static ALLOC: StatsAlloc<Alloc> = StatsAlloc::new(Alloc);
@@ -53,12 +63,8 @@ fn main() -> Result<()> {
 
 	debug_span!("after_install").in_scope(|| report_alloc_stats(&mut region));
 
-	let Args {
-		file,
-		optimize,
-		tape,
-	} = match Args::try_parse() {
-		Ok(args) => args,
+	let mode = match Cli::try_parse().and_then(Cli::into_mode) {
+		Ok(mode) => mode,
 		Err(e) => {
 			eprintln!("{e}");
 			return Ok(());
@@ -67,45 +73,21 @@ fn main() -> Result<()> {
 
 	region.reset();
 
-	let raw_data = fs::read_to_string(file)?;
-
-	let filtered_data = raw_data
-		.chars()
-		.filter(|c| matches!(c, '+'..='.' | '>' | '<' | '[' | ']'))
-		.collect::<String>();
-
-	debug_span!("after_read_and_filter").in_scope(|| report_alloc_stats(&mut region));
-
-	let program = {
-		let unoptimized = BfParser::new(&filtered_data)
-			.scan()?
-			.into_iter()
-			.collect::<Program>();
+	let args = mode.args();
 
-		debug_span!("after_parse").in_scope(|| report_alloc_stats(&mut region));
+	let Args { tape, tape_len, .. } = args;
 
-		info!(
-			"size of raw: {} bytes (len: {})",
-			unoptimized.heap_size(),
-			unoptimized.len()
+	if matches!(tape, TapeType::Stack) && *tape_len != TAPE_SIZE {
+		bail!(
+			"stack tapes are fixed at {TAPE_SIZE} cells, use another tape type for a length of {tape_len}"
 		);
-		if optimize {
-			region.reset();
-
-			let mut optimizer = Optimizer::new(
-				unoptimized,
-				OutputMetadataStore::new(HashMetadataStore::new(), PathBuf::new().join("./out"))?,
-			);
-
-			let out = optimizer.optimize()?;
+	}
 
-			debug_span!("after_optimize").in_scope(|| report_alloc_stats(&mut region));
+	if let Mode::Bisect { input, .. } = &mode {
+		return bisect::bisect(args, input.as_deref());
+	}
 
-			out
-		} else {
-			unoptimized
-		}
-	};
+	let (program, source) = load_program(args, &mut region)?;
 
 	write_binary(&program)?;
 
@@ -122,80 +104,246 @@ fn main() -> Result<()> {
 
 	fs::write("./out/ir.txt", ir)?;
 
+	if let Mode::Compile { target, output, .. } = &mode {
+		return compile(&program, args, *target, output.as_deref());
+	}
+
+	let Some((profiler, output)) = (match args.cell_width {
+		CellWidth::U8 => execute::<u8>(&mode, program, &source, &mut region)?,
+		CellWidth::U16 => execute::<u16>(&mode, program, &source, &mut region)?,
+		CellWidth::U32 => execute::<u32>(&mode, program, &source, &mut region)?,
+	}) else {
+		return Ok(());
+	};
+
+	if !matches!(output.last(), Some(b'\n')) {
+		println!();
+	}
+
+	debug_span!("after_run").in_scope(|| report_alloc_stats(&mut region));
+
+	write_profiler(profiler)?;
+
+	debug_span!("total").in_scope(|| report_alloc_stats(&mut total));
+
+	Ok(())
+}
+
+fn execute<V: CellValue>(
+	mode: &Mode,
+	program: Program,
+	source: &Source,
+	region: &mut Region<'_, Alloc>,
+) -> Result<Option<(Profiler, Vec<u8>)>> {
+	let args = mode.args();
+
+	if matches!(mode, Mode::Debug(..)) {
+		match args.tape {
+			TapeType::Ptr => debug::repl::<PtrTape<V>>(program, source, args),
+			TapeType::Box => debug::repl::<BoxTape<V>>(program, source, args),
+			TapeType::Vec => debug::repl::<VecTape<V>>(program, source, args),
+			TapeType::Stack => debug::repl::<StackTape<TAPE_SIZE, V>>(program, source, args),
+			TapeType::Growable => debug::repl::<GrowableTape<V>>(program, source, args),
+		}?;
+
+		return Ok(None);
+	}
+
 	let output = CopyWriter::new(stdout(), Vec::<u8>::with_capacity(program.min_outputs()));
 
 	region.reset();
 
-	let (profiler, output) = match (program.needs_input(), tape) {
-		(true, TapeType::Ptr) => {
-			let mut vm = Interpreter::<PtrTape, _, _>::with_profiler(program, stdin(), output);
+	let result = match (program.needs_input(), args.tape) {
+		(true, TapeType::Ptr) => run::<PtrTape<V>, _>(program, stdin(), output, source, args)?,
+		(true, TapeType::Box) => run::<BoxTape<V>, _>(program, stdin(), output, source, args)?,
+		(true, TapeType::Vec) => run::<VecTape<V>, _>(program, stdin(), output, source, args)?,
+		(true, TapeType::Stack) => {
+			run::<StackTape<TAPE_SIZE, V>, _>(program, stdin(), output, source, args)?
+		}
+		(true, TapeType::Growable) => {
+			run::<GrowableTape<V>, _>(program, stdin(), output, source, args)?
+		}
+		(false, TapeType::Ptr) => run::<PtrTape<V>, _>(program, empty(), output, source, args)?,
+		(false, TapeType::Box) => run::<BoxTape<V>, _>(program, empty(), output, source, args)?,
+		(false, TapeType::Vec) => run::<VecTape<V>, _>(program, empty(), output, source, args)?,
+		(false, TapeType::Stack) => {
+			run::<StackTape<TAPE_SIZE, V>, _>(program, empty(), output, source, args)?
+		}
+		(false, TapeType::Growable) => {
+			run::<GrowableTape<V>, _>(program, empty(), output, source, args)?
+		}
+	};
 
-			vm.run()?;
+	Ok(Some(result))
+}
 
-			(vm.profiler(), get_interpreter_output(&vm))
-		}
-		(true, TapeType::Box) => {
-			let mut vm = Interpreter::<BoxTape, _, _>::with_profiler(program, stdin(), output);
+fn run<T: Tape, R: Read + 'static>(
+	program: Program,
+	input: R,
+	output: CopyWriter<Stdout, Vec<u8>>,
+	source: &Source,
+	args: &Args,
+) -> Result<(Profiler, Vec<u8>)> {
+	if matches!(args.backend, Backend::Jit) {
+		return run_jit::<T, _>(&program, input, output, args);
+	}
 
-			vm.run()?;
+	let mut vm = Interpreter::<T, _, _>::with_tape_len(program, input, output, args.tape_len)
+		.and_with_bounds_policy(args.bounds)
+		.and_with_eof_policy(args.eof)
+		.and_with_budget(args.budget())
+		.and_with_profiler()
+		.and_with_source_map(source.map().clone());
+
+	match args.backend {
+		Backend::Tree => vm.run(),
+		Backend::Bytecode => vm.run_bytecode(),
+		Backend::Jit => unreachable!(),
+	}
+	.map_err(|e| source.locate_error(e, vm.error_span()))?;
 
-			(vm.profiler(), get_interpreter_output(&vm))
-		}
-		(true, TapeType::Vec) => {
-			let mut vm = Interpreter::<VecTape, _, _>::with_profiler(program, stdin(), output);
+	if let Some(profile) = vm.source_profile() {
+		source.write_profile(profile)?;
+	}
 
-			vm.run()?;
+	Ok((vm.profiler(), get_interpreter_output(&vm)))
+}
 
-			(vm.profiler(), get_interpreter_output(&vm))
-		}
-		(true, TapeType::Stack) => {
-			let mut vm = Interpreter::<StackTape, _, _>::with_profiler(program, stdin(), output);
+fn run_jit<T: Tape, R: Read>(
+	program: &Program,
+	input: R,
+	mut output: CopyWriter<Stdout, Vec<u8>>,
+	args: &Args,
+) -> Result<(Profiler, Vec<u8>)> {
+	if matches!(args.tape, TapeType::Growable) {
+		bail!("the jit backend doesn't support growable tapes");
+	}
 
-			vm.run()?;
+	if !matches!(args.bounds, BoundsPolicy::Wrap) {
+		bail!("the jit backend only supports the wrap bounds policy");
+	}
 
-			(vm.profiler(), get_interpreter_output(&vm))
-		}
-		(false, TapeType::Ptr) => {
-			let mut vm = Interpreter::<PtrTape, _, _>::with_profiler(program, empty(), output);
+	if !args.budget().is_unlimited() {
+		bail!("the jit backend doesn't support execution budgets");
+	}
 
-			vm.run()?;
+	let mut tape = T::with_len(args.tape_len);
+	tape.init();
 
-			(vm.profiler(), get_interpreter_output(&vm))
-		}
-		(false, TapeType::Box) => {
-			let mut vm = Interpreter::<BoxTape, _, _>::with_profiler(program, empty(), output);
+	let jit = JitProgram::<T::Value>::compile(program, tape.len())?;
 
-			vm.run()?;
+	jit.run(&mut tape, input, &mut output, args.eof)?;
 
-			(vm.profiler(), get_interpreter_output(&vm))
-		}
-		(false, TapeType::Vec) => {
-			let mut vm = Interpreter::<VecTape, _, _>::with_profiler(program, empty(), output);
+	let mut out = output.into_inner().1;
 
-			vm.run()?;
+	out.shrink_to_fit();
 
-			(vm.profiler(), get_interpreter_output(&vm))
-		}
-		(false, TapeType::Stack) => {
-			let mut vm = Interpreter::<StackTape, _, _>::with_profiler(program, empty(), output);
+	Ok((Profiler::default(), out))
+}
 
-			vm.run()?;
+fn compile(program: &Program, args: &Args, target: Target, output: Option<&Path>) -> Result<()> {
+	let options = CodegenOptions::new()
+		.and_with_tape_len(args.tape_len)
+		.and_with_cell_width(args.cell_width)
+		.and_with_eof_policy(args.eof);
+
+	let code = match target {
+		Target::C => CCodegen::new(options).generate(program)?,
+		Target::Rust => RustCodegen::new(options).generate(program)?,
+		Target::RustLib => RustCodegen::new(options)
+			.and_with_entry(RustEntry::Library)
+			.generate(program)?,
+		Target::Wat | Target::Wasm => WasmCodegen::new(options).generate(program)?,
+	};
 
-			(vm.profiler(), get_interpreter_output(&vm))
-		}
+	let code = match target {
+		Target::Wasm => wat::parse_str(&code)?,
+		_ => code.into_bytes(),
 	};
 
-	if !matches!(output.last(), Some(b'\n')) {
-		println!();
+	match output {
+		Some(path) => fs::write(path, code)?,
+		None => stdout().write_all(&code)?,
 	}
 
-	debug_span!("after_run").in_scope(|| report_alloc_stats(&mut region));
+	Ok(())
+}
 
-	write_profiler(profiler)?;
+fn load_program<T>(args: &Args, region: &mut Region<'_, T>) -> Result<(Program, Source)> {
+	let raw_data = fs::read_to_string(&args.file)?;
 
-	debug_span!("total").in_scope(|| report_alloc_stats(&mut total));
+	debug_span!("after_read").in_scope(|| report_alloc_stats(region));
 
-	Ok(())
+	// Parsing the unfiltered source keeps error locations pointing into the file.
+	let (unoptimized, source_map) = BfParser::new(&raw_data)
+		.scan_with_source_map()
+		.map_err(|e| eyre!("in {}: {}", args.file.display(), e.snippet()))?;
+
+	let unoptimized = unoptimized.into_iter().collect::<Program>();
+	let mut source = Source::new(args.file.clone(), raw_data, source_map);
+
+	debug_span!("after_parse").in_scope(|| report_alloc_stats(region));
+
+	info!(
+		"size of raw: {} bytes (len: {})",
+		unoptimized.heap_size(),
+		unoptimized.len()
+	);
+
+	if !args.should_optimize() {
+		return Ok((unoptimized, source));
+	}
+
+	region.reset();
+
+	let mut optimizer = Optimizer::new(
+		unoptimized,
+		OutputMetadataStore::new(HashMetadataStore::new(), PathBuf::new().join("./out"))?,
+	)
+	.and_with_cell_width(args.cell_width)
+	.and_with_tape_len(args.tape_len)
+	.and_with_eof_policy(args.eof)
+	.and_with_pipeline(load_pipeline(args)?)
+	.and_with_source_map(source.map().clone());
+
+	if let Some(limit) = args.opt_fuel {
+		optimizer = optimizer.and_with_fuel(limit);
+	}
+
+	if args.validate {
+		optimizer = optimizer.and_with_validation(Validation::new());
+	}
+
+	let out = optimizer.optimize()?;
+
+	if let Some(source_map) = optimizer.source_map() {
+		source.set_map(source_map.clone());
+	}
+
+	debug_span!("after_optimize").in_scope(|| report_alloc_stats(region));
+
+	Ok((out, source))
+}
+
+fn load_pipeline(args: &Args) -> Result<PassPipeline> {
+	let pipeline = match &args.passes {
+		Some(path) => {
+			let raw = fs::read_to_string(path)?;
+
+			match path.extension().and_then(|ext| ext.to_str()) {
+				Some("ron") => ron::from_str(&raw)?,
+				Some("toml") => toml::from_str(&raw)?,
+				_ => bail!("pipeline {} must be a .ron or .toml file", path.display()),
+			}
+		}
+		None => PassPipeline::level(args.opt_level.unwrap_or_default()),
+	};
+
+	Ok(args
+		.disable_passes
+		.iter()
+		.copied()
+		.fold(pipeline, PassPipeline::and_without_pass))
 }
 
 fn install_tracing() -> impl Drop {
@@ -217,7 +365,7 @@ fn install_tracing() -> impl Drop {
 
 	let file_layer = fmt::layer().with_ansi(false).with_writer(log_file);
 
-	let filter_layer = EnvFilter::new("info");
+	let filter_layer = EnvFilter::new("info,cranelift_jit=warn,cranelift_codegen=warn");
 	let fmt_layer = fmt::layer().with_target(false).with_filter(filter_layer);
 
 	let json_file_layer = fmt::layer()
@@ -287,7 +435,11 @@ fn write_binary(program: &Program) -> Result<()> {
 }
 
 fn write_format() -> Result<()> {
-	let mut tracer = Tracer::new(TracerConfig::default().default_u8_value(1));
+	let mut tracer = Tracer::new(
+		TracerConfig::default()
+			.default_u8_value(1)
+			.default_u32_value(1),
+	);
 
 	tracer.trace_simple_type::<BlockInstruction>().unwrap();
 
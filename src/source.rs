@@ -0,0 +1,66 @@
+use std::{fmt::Write as _, fs, path::PathBuf};
+
+use color_eyre::eyre::{Report, Result};
+use vmm::{
+	interpret::{RuntimeError, SourceProfile},
+	parse::SourceLocation,
+	program::{SourceMap, SourceSpan},
+};
+
+const HOT_SPOT_COUNT: usize = 32;
+
+/// The file a program was loaded from, and where each of its instructions came from.
+#[derive(Debug, Clone)]
+pub struct Source {
+	path: PathBuf,
+	text: String,
+	map: SourceMap,
+}
+
+impl Source {
+	pub const fn new(path: PathBuf, text: String, map: SourceMap) -> Self {
+		Self { path, text, map }
+	}
+
+	pub const fn map(&self) -> &SourceMap {
+		&self.map
+	}
+
+	pub fn set_map(&mut self, map: SourceMap) {
+		self.map = map;
+	}
+
+	pub fn locate(&self, span: SourceSpan) -> SourceLocation {
+		SourceLocation::new(&self.text, span.start())
+	}
+
+	/// Points the error at the instruction that caused it, when it is known.
+	pub fn locate_error(&self, error: RuntimeError, span: Option<SourceSpan>) -> Report {
+		match span {
+			Some(span) => Report::new(error).wrap_err(format!(
+				"in {} at {}",
+				self.path.display(),
+				self.locate(span)
+			)),
+			None => error.into(),
+		}
+	}
+
+	pub fn write_profile(&self, profile: &SourceProfile) -> Result<()> {
+		let mut out = String::new();
+
+		for (span, count) in profile.hottest(HOT_SPOT_COUNT) {
+			let snippet = self.text[span.as_range()]
+				.chars()
+				.filter(|c| "+-<>[],.".contains(*c))
+				.take(40)
+				.collect::<String>();
+
+			writeln!(out, "{}\t{count}\t{snippet}", self.locate(span))?;
+		}
+
+		fs::write("./out/source_profile.txt", out)?;
+
+		Ok(())
+	}
+}
@@ -0,0 +1,54 @@
+mod program_utils;
+
+use std::io;
+
+use program_utils::{Result, get_program};
+use vmm::{
+	interpret::{BoundsPolicy, Interpreter, RuntimeError},
+	ir::Instruction,
+	tape::VecTape,
+};
+
+fn run(program: &str, bounds: BoundsPolicy) -> Result<Vec<u8>, RuntimeError> {
+	let mut interpreter = Interpreter::<VecTape, _, _>::with_tape_len(
+		get_program(program).unwrap(),
+		io::empty(),
+		Vec::new(),
+		8,
+	)
+	.and_with_bounds_policy(bounds);
+
+	interpreter.run()?;
+
+	Ok(interpreter.output().clone())
+}
+
+#[test]
+fn wrap() -> Result<(), RuntimeError> {
+	assert_eq!(run("+++<+.", BoundsPolicy::Wrap)?, [1]);
+	assert_eq!(run("+++>>>>>>>>.", BoundsPolicy::Wrap)?, [3]);
+
+	Ok(())
+}
+
+#[test]
+fn saturate() -> Result<(), RuntimeError> {
+	assert_eq!(run("+++<+.", BoundsPolicy::Saturate)?, [4]);
+	assert_eq!(run(">>>>>>>+++>>>.", BoundsPolicy::Saturate)?, [3]);
+
+	Ok(())
+}
+
+#[test]
+fn error() {
+	let err = run("+++>>><<<<+.", BoundsPolicy::Error).unwrap_err();
+
+	assert!(matches!(
+		err,
+		RuntimeError::PointerOutOfBounds {
+			ptr: 0,
+			offset: -1,
+			instruction: Some(Instruction::MovePtr(_)),
+		}
+	));
+}
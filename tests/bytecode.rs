@@ -0,0 +1,91 @@
+mod program_utils;
+
+use std::io;
+
+use program_utils::{Result, get_program};
+use vmm::{
+	interpret::{ExecutionBudget, Interpreter, RuntimeError},
+	opt::{NoopStore, Optimizer},
+	program::Program,
+	tape::{Tape as _, VecTape},
+};
+
+fn load(raw: &str, optimized: bool) -> Result<Program> {
+	let program = get_program(raw)?;
+
+	Ok(if optimized {
+		Optimizer::new(program, NoopStore::new()).optimize()?
+	} else {
+		program
+	})
+}
+
+fn compare(raw: &str, optimized: bool) -> Result<Vec<u8>> {
+	let program = load(raw, optimized)?;
+
+	let mut tree = Interpreter::<VecTape, _, _>::new(program.clone(), io::empty(), Vec::new());
+	let mut bytecode = Interpreter::<VecTape, _, _>::new(program, io::empty(), Vec::new());
+
+	tree.run()?;
+	bytecode.run_bytecode()?;
+
+	assert_eq!(tree.output(), bytecode.output());
+	assert_eq!(tree.tape().as_slice(), bytecode.tape().as_slice());
+	assert_eq!(tree.ptr(), bytecode.ptr());
+
+	Ok(bytecode.output().clone())
+}
+
+#[test]
+fn hello_world() -> Result<()> {
+	let program = include_str!("../programs/hello_world.bf");
+
+	assert_eq!(compare(program, false)?, b"Hello World!\n");
+	assert_eq!(compare(program, true)?, b"Hello World!\n");
+
+	Ok(())
+}
+
+#[test]
+fn cell_size() -> Result<()> {
+	let program = include_str!("../programs/cell_size.bf");
+
+	assert_eq!(compare(program, false)?, b"8 bit cells\n");
+	assert_eq!(compare(program, true)?, b"8 bit cells\n");
+
+	Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn bench() -> Result<()> {
+	let program = include_str!("../programs/bench.bf");
+
+	assert_eq!(compare(program, true)?, b"ZYXWVUTSRQPONMLKJIHGFEDCBA\n");
+
+	Ok(())
+}
+
+#[test]
+fn respects_budget() -> Result<()> {
+	let budget = ExecutionBudget::unlimited().and_with_loop_limit(254);
+
+	let mut interpreter =
+		Interpreter::<VecTape, _, _>::new(load("-[-]", false)?, io::empty(), Vec::new())
+			.and_with_budget(budget);
+
+	assert!(matches!(
+		interpreter.run_bytecode(),
+		Err(RuntimeError::TooManyIterations { limit: 254, .. })
+	));
+
+	let mut interpreter =
+		Interpreter::<VecTape, _, _>::new(load("+++.", false)?, io::empty(), Vec::new())
+			.and_with_budget(ExecutionBudget::unlimited().and_with_fuel(4));
+
+	interpreter.run_bytecode()?;
+
+	assert_eq!(interpreter.output(), &[3]);
+
+	Ok(())
+}
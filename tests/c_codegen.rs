@@ -0,0 +1,114 @@
+#![cfg(not(miri))]
+
+mod program_utils;
+
+use std::{
+	env,
+	fs::{self, File},
+	process::Command,
+};
+
+use program_utils::{Result, get_program};
+use vmm::{
+	codegen::{CCodegen, Codegen as _, CodegenOptions},
+	interpret::Interpreter,
+	ir::CellWidth,
+	opt::{NoopStore, Optimizer},
+	tape::{Tape, VecTape},
+};
+
+const INPUT: &[u8] = b"Hello, world!\n";
+
+// Compile the generated code with the system C compiler (or `$CC`) and run it.
+fn run_c(name: &str, code: &str) -> Vec<u8> {
+	let dir = env::temp_dir().join("vmm_c_codegen");
+	fs::create_dir_all(&dir).unwrap();
+
+	let source = dir.join(format!("{name}.c"));
+	let binary = dir.join(name);
+
+	fs::write(&source, code).unwrap();
+
+	let compiled = Command::new(env::var_os("CC").unwrap_or_else(|| "cc".into()))
+		.arg("-O1")
+		.arg("-o")
+		.arg(&binary)
+		.arg(&source)
+		.status()
+		.expect("failed to run the C compiler, set CC to one");
+
+	assert!(compiled.success(), "failed to compile {}", source.display());
+
+	let input = dir.join(format!("{name}.in"));
+
+	fs::write(&input, INPUT).unwrap();
+
+	let output = Command::new(binary)
+		.stdin(File::open(input).unwrap())
+		.output()
+		.unwrap();
+
+	output.stdout
+}
+
+fn compare<T: Tape>(name: &str, raw: &str, cell_width: CellWidth) -> Result<()> {
+	for optimized in [false, true] {
+		let program = get_program(raw)?;
+
+		let program = if optimized {
+			Optimizer::new(program, NoopStore::new())
+				.and_with_cell_width(cell_width)
+				.optimize()?
+		} else {
+			program
+		};
+
+		let mut interpreter = Interpreter::<T, _, _>::new(program.clone(), INPUT, Vec::new());
+
+		interpreter.run()?;
+
+		let options = CodegenOptions::new()
+			.and_with_tape_len(interpreter.tape().len())
+			.and_with_cell_width(cell_width);
+
+		let code = CCodegen::new(options).generate(&program).unwrap();
+
+		let output = run_c(&format!("{name}_{cell_width}_{optimized}"), &code);
+
+		assert_eq!(interpreter.output(), &output);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn hello_world() -> Result<()> {
+	compare::<VecTape>(
+		"hello_world",
+		include_str!("../programs/hello_world.bf"),
+		CellWidth::U8,
+	)
+}
+
+#[test]
+fn reads_input() -> Result<()> {
+	compare::<VecTape>("rot13", include_str!("../programs/rot13.bf"), CellWidth::U8)
+}
+
+#[test]
+fn cell_width() -> Result<()> {
+	let program = include_str!("../programs/cell_size.bf");
+
+	compare::<VecTape<u8>>("cell_size", program, CellWidth::U8)?;
+	compare::<VecTape<u16>>("cell_size", program, CellWidth::U16)?;
+	compare::<VecTape<u32>>("cell_size", program, CellWidth::U32)
+}
+
+#[test]
+fn super_instructions() -> Result<()> {
+	compare::<VecTape>(
+		"numwarp",
+		include_str!("../programs/numwarp.bf"),
+		CellWidth::U8,
+	)
+}
@@ -0,0 +1,84 @@
+mod program_utils;
+
+use program_utils::{Result, run_program_with_cell_width};
+use vmm::{ir::CellWidth, tape::VecTape};
+
+const CELL_SIZE: &str = include_str!("../programs/cell_size.bf");
+
+fn overflow_byte() -> String {
+	"+".repeat(256) + "[>+<[-]]>."
+}
+
+#[test]
+fn unoptimized_8_bit() -> Result<()> {
+	assert_eq!(
+		run_program_with_cell_width::<VecTape<u8>>(CELL_SIZE, false, CellWidth::U8)?,
+		b"8 bit cells\n"
+	);
+
+	Ok(())
+}
+
+#[test]
+fn optimized_8_bit() -> Result<()> {
+	assert_eq!(
+		run_program_with_cell_width::<VecTape<u8>>(CELL_SIZE, true, CellWidth::U8)?,
+		b"8 bit cells\n"
+	);
+
+	Ok(())
+}
+
+#[test]
+fn unoptimized_16_bit() -> Result<()> {
+	assert_eq!(
+		run_program_with_cell_width::<VecTape<u16>>(CELL_SIZE, false, CellWidth::U16)?,
+		b"16 bit cells\n"
+	);
+
+	Ok(())
+}
+
+#[test]
+fn optimized_16_bit() -> Result<()> {
+	assert_eq!(
+		run_program_with_cell_width::<VecTape<u16>>(CELL_SIZE, true, CellWidth::U16)?,
+		b"16 bit cells\n"
+	);
+
+	Ok(())
+}
+
+#[test]
+fn unoptimized_32_bit() -> Result<()> {
+	assert_eq!(
+		run_program_with_cell_width::<VecTape<u32>>(CELL_SIZE, false, CellWidth::U32)?,
+		b"32 bit cells\n"
+	);
+
+	Ok(())
+}
+
+#[test]
+fn optimized_32_bit() -> Result<()> {
+	assert_eq!(
+		run_program_with_cell_width::<VecTape<u32>>(CELL_SIZE, true, CellWidth::U32)?,
+		b"32 bit cells\n"
+	);
+
+	Ok(())
+}
+
+#[test]
+fn optimizer_respects_cell_width() -> Result<()> {
+	assert_eq!(
+		run_program_with_cell_width::<VecTape<u8>>(&overflow_byte(), true, CellWidth::U8)?,
+		b"\0"
+	);
+	assert_eq!(
+		run_program_with_cell_width::<VecTape<u16>>(&overflow_byte(), true, CellWidth::U16)?,
+		b"\x01"
+	);
+
+	Ok(())
+}
@@ -0,0 +1,70 @@
+mod program_utils;
+
+use std::io;
+
+use program_utils::{Result, get_program};
+use vmm::{
+	interpret::Interpreter,
+	ir::EofPolicy,
+	opt::{NoopStore, Optimizer},
+	tape::VecTape,
+};
+
+const READ_AFTER_CHANGE: &str = "+++,.";
+
+fn run(program: &str, optimized: bool, eof: EofPolicy) -> Result<Vec<u8>> {
+	let program = get_program(program)?;
+
+	let program = if optimized {
+		Optimizer::new(program, NoopStore::new())
+			.and_with_eof_policy(eof)
+			.optimize()?
+	} else {
+		program
+	};
+
+	let mut interpreter = Interpreter::<VecTape, _, _>::new(program, io::empty(), Vec::new())
+		.and_with_eof_policy(eof);
+
+	interpreter.run()?;
+
+	Ok(interpreter.output().clone())
+}
+
+#[test]
+fn zero() -> Result<()> {
+	assert_eq!(run(READ_AFTER_CHANGE, false, EofPolicy::Zero)?, [0]);
+	assert_eq!(run(READ_AFTER_CHANGE, true, EofPolicy::Zero)?, [0]);
+
+	Ok(())
+}
+
+#[test]
+fn max() -> Result<()> {
+	assert_eq!(run(READ_AFTER_CHANGE, false, EofPolicy::Max)?, [255]);
+	assert_eq!(run(READ_AFTER_CHANGE, true, EofPolicy::Max)?, [255]);
+
+	Ok(())
+}
+
+#[test]
+fn unchanged() -> Result<()> {
+	assert_eq!(run(READ_AFTER_CHANGE, false, EofPolicy::Unchanged)?, [3]);
+	assert_eq!(run(READ_AFTER_CHANGE, true, EofPolicy::Unchanged)?, [3]);
+
+	Ok(())
+}
+
+#[test]
+fn input_is_read_regardless_of_policy() -> Result<()> {
+	let program = get_program(",.,.")?;
+
+	let mut interpreter = Interpreter::<VecTape, _, _>::new(program, &b"a"[..], Vec::new())
+		.and_with_eof_policy(EofPolicy::Unchanged);
+
+	interpreter.run()?;
+
+	assert_eq!(interpreter.output(), b"aa");
+
+	Ok(())
+}
@@ -0,0 +1,85 @@
+mod program_utils;
+
+use std::{io, time::Duration};
+
+use program_utils::get_program;
+use vmm::{
+	interpret::{ExecutionBudget, Interpreter, RuntimeError},
+	ir::Instruction,
+	program::Program,
+	tape::VecTape,
+};
+
+fn run(program: Program, budget: ExecutionBudget) -> Result<Vec<u8>, RuntimeError> {
+	let mut interpreter =
+		Interpreter::<VecTape, _, _>::new(program, io::empty(), Vec::new()).and_with_budget(budget);
+
+	interpreter.run()?;
+
+	Ok(interpreter.output().clone())
+}
+
+fn parse(program: &str) -> Program {
+	get_program(program).unwrap()
+}
+
+#[test]
+fn unlimited_by_default() -> Result<(), RuntimeError> {
+	assert_eq!(run(parse("-[-]+."), ExecutionBudget::default())?, [1]);
+
+	Ok(())
+}
+
+#[test]
+fn fuel() {
+	let budget = ExecutionBudget::unlimited().and_with_fuel(4);
+
+	assert_eq!(run(parse("+++."), budget).unwrap(), [3]);
+	assert!(matches!(
+		run(parse("++++."), budget),
+		Err(RuntimeError::OutOfFuel(4))
+	));
+	assert!(matches!(
+		run(parse("+[]"), budget),
+		Err(RuntimeError::OutOfFuel(4))
+	));
+}
+
+#[test]
+fn loop_limit() {
+	let budget = ExecutionBudget::unlimited().and_with_loop_limit(255);
+
+	assert_eq!(run(parse("-[-]+."), budget).unwrap(), [1]);
+	assert!(matches!(
+		run(parse("-[-]"), budget.and_with_loop_limit(254)),
+		Err(RuntimeError::TooManyIterations { ptr: 0, limit: 254 })
+	));
+}
+
+#[test]
+fn scans_respect_the_budget() {
+	let budget = ExecutionBudget::unlimited().and_with_loop_limit(1_000);
+
+	for scan in [
+		Instruction::find_zero(0),
+		Instruction::set_until_zero(1, 0),
+		Instruction::shift_vals(0, 0),
+	] {
+		let program = [Instruction::inc_val(1), scan].into_iter().collect();
+
+		assert!(matches!(
+			run(program, budget),
+			Err(RuntimeError::TooManyIterations { limit: 1_000, .. })
+		));
+	}
+}
+
+#[test]
+fn time_limit() {
+	let budget = ExecutionBudget::unlimited().and_with_time_limit(Duration::from_millis(10));
+
+	assert!(matches!(
+		run(parse("+[]"), budget),
+		Err(RuntimeError::OutOfTime(_))
+	));
+}
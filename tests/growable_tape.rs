@@ -0,0 +1,58 @@
+mod program_utils;
+
+use program_utils::{Result, run_program};
+use vmm::tape::{GrowableTape, PtrTape, TAPE_SIZE};
+
+fn walk_past_end() -> String {
+	"+".repeat(65) + &">".repeat(TAPE_SIZE) + "." + &"<".repeat(TAPE_SIZE) + "."
+}
+
+fn walk_before_start() -> String {
+	"+".repeat(65) + "<++[-<+>]<." + ">>."
+}
+
+#[test]
+fn fixed_tape_wraps() -> Result<()> {
+	assert_eq!(run_program::<PtrTape>(&walk_past_end(), false)?, b"AA");
+
+	Ok(())
+}
+
+#[test]
+fn unoptimized_walk_past_end() -> Result<()> {
+	assert_eq!(
+		run_program::<GrowableTape>(&walk_past_end(), false)?,
+		b"\0A"
+	);
+
+	Ok(())
+}
+
+#[test]
+fn unoptimized_walk_before_start() -> Result<()> {
+	assert_eq!(
+		run_program::<GrowableTape>(&walk_before_start(), false)?,
+		b"\x02A"
+	);
+
+	Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn optimized_walk_past_end() -> Result<()> {
+	assert_eq!(run_program::<GrowableTape>(&walk_past_end(), true)?, b"\0A");
+
+	Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn optimized_walk_before_start() -> Result<()> {
+	assert_eq!(
+		run_program::<GrowableTape>(&walk_before_start(), true)?,
+		b"\x02A"
+	);
+
+	Ok(())
+}
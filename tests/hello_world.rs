@@ -1,7 +1,7 @@
 mod program_utils;
 
 use program_utils::{Result, run_program};
-use vmm::tape::{BoxTape, PtrTape, StackTape, Tape, VecTape};
+use vmm::tape::{BoxTape, GrowableTape, PtrTape, StackTape, Tape, VecTape};
 
 const PROGRAM: &str = include_str!("../programs/hello_world.bf");
 
@@ -31,6 +31,11 @@ fn unoptimized_stack_tape() -> Result<()> {
 	run::<StackTape>(false)
 }
 
+#[test]
+fn unoptimized_growable_tape() -> Result<()> {
+	run::<GrowableTape>(false)
+}
+
 #[test]
 #[cfg_attr(miri, ignore)]
 fn optimized_box_tape() -> Result<()> {
@@ -54,3 +59,9 @@ fn optimized_vec_tape() -> Result<()> {
 fn optimized_stack_tape() -> Result<()> {
 	run::<StackTape>(true)
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn optimized_growable_tape() -> Result<()> {
+	run::<GrowableTape>(true)
+}
@@ -0,0 +1,225 @@
+#![cfg(not(miri))]
+
+mod program_utils;
+
+use std::io::{self, prelude::*};
+
+use program_utils::{Result, get_program};
+use vmm::{
+	interpret::{Interpreter, RuntimeError},
+	ir::EofPolicy,
+	jit::{JitError, JitProgram},
+	opt::{NoopStore, Optimizer},
+	program::Program,
+	tape::{Tape as _, VecTape},
+};
+
+const INPUT: &[u8] = b"Hello, world!\n";
+
+// Fails once it holds `limit` bytes, which is how programs that never finish are stopped.
+#[derive(Debug)]
+struct LimitedOutput {
+	bytes: Vec<u8>,
+	limit: usize,
+}
+
+impl LimitedOutput {
+	const fn new(limit: usize) -> Self {
+		Self {
+			bytes: Vec::new(),
+			limit,
+		}
+	}
+
+	const fn is_full(&self) -> bool {
+		self.bytes.len() >= self.limit
+	}
+}
+
+impl Write for LimitedOutput {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		if self.is_full() && !buf.is_empty() {
+			return Err(io::Error::other("output limit reached"));
+		}
+
+		let len = buf.len().min(self.limit - self.bytes.len());
+
+		self.bytes.extend_from_slice(&buf[..len]);
+
+		Ok(len)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+fn load(raw: &str, optimized: bool) -> Result<Program> {
+	let program = get_program(raw)?;
+
+	Ok(if optimized {
+		Optimizer::new(program, NoopStore::new()).optimize()?
+	} else {
+		program
+	})
+}
+
+fn compare(raw: &str, optimized: bool) -> Result<Vec<u8>> {
+	compare_limited(raw, optimized, INPUT, usize::MAX)
+}
+
+// Compares the interpreter and the compiled program until they've written `limit` bytes.
+fn compare_limited(
+	raw: &str,
+	optimized: bool,
+	input: &'static [u8],
+	limit: usize,
+) -> Result<Vec<u8>> {
+	let program = load(raw, optimized)?;
+
+	let mut interpreter =
+		Interpreter::<VecTape, _, _>::new(program.clone(), input, LimitedOutput::new(limit));
+
+	match interpreter.run_bytecode() {
+		Err(RuntimeError::Io(_)) if interpreter.output().is_full() => {}
+		result => result?,
+	}
+
+	let mut tape = VecTape::default();
+	tape.init();
+
+	let mut output = LimitedOutput::new(limit);
+
+	match JitProgram::compile(&program, tape.len())?.run(
+		&mut tape,
+		input,
+		&mut output,
+		EofPolicy::Zero,
+	) {
+		Err(JitError::Io(_)) if output.is_full() => {}
+		result => result?,
+	}
+
+	let output = output.bytes;
+
+	assert_eq!(interpreter.output().bytes, output);
+	assert_eq!(interpreter.tape().as_slice(), tape.as_slice());
+	assert_eq!(interpreter.ptr(), tape.ptr());
+
+	Ok(output)
+}
+
+fn compare_both(raw: &str) -> Result<Vec<u8>> {
+	compare_both_limited(raw, INPUT, usize::MAX)
+}
+
+fn compare_both_limited(raw: &str, input: &'static [u8], limit: usize) -> Result<Vec<u8>> {
+	let unoptimized = compare_limited(raw, false, input, limit)?;
+
+	assert_eq!(unoptimized, compare_limited(raw, true, input, limit)?);
+
+	Ok(unoptimized)
+}
+
+#[test]
+fn hello_world() -> Result<()> {
+	let output = compare_both(include_str!("../programs/hello_world.bf"))?;
+
+	assert_eq!(output, b"Hello World!\n");
+
+	Ok(())
+}
+
+#[test]
+fn cell_size() -> Result<()> {
+	let output = compare_both(include_str!("../programs/cell_size.bf"))?;
+
+	assert_eq!(output, b"8 bit cells\n");
+
+	Ok(())
+}
+
+#[test]
+fn reads_input() -> Result<()> {
+	let program = include_str!("../programs/rot13.bf");
+
+	assert_eq!(compare(program, false)?, b"Uryyb, jbeyq!\n");
+
+	compare(program, true)?;
+
+	Ok(())
+}
+
+#[test]
+fn turing() -> Result<()> {
+	let output = compare_both_limited(
+		include_str!("../programs/turing.bf"),
+		b"b1b1bbb1c1c11111d\n",
+		usize::MAX,
+	)?;
+
+	assert_eq!(output, b"1c11111\n");
+
+	Ok(())
+}
+
+#[test]
+fn endless_programs() -> Result<()> {
+	for (raw, limit) in [
+		(include_str!("../programs/e.bf"), 50),
+		(include_str!("../programs/fib.bf"), 1000),
+		(include_str!("../programs/golden_ratio.bf"), 10),
+		// Reading zero at the end of the input doesn't stop it.
+		(include_str!("../programs/head.bf"), 1000),
+	] {
+		assert_eq!(compare_both_limited(raw, INPUT, limit)?.len(), limit);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn short_programs() -> Result<()> {
+	for raw in [
+		include_str!("../programs/a_to_z.bf"),
+		include_str!("../programs/array_size.bf"),
+		include_str!("../programs/ascii.bf"),
+		include_str!("../programs/bottles.bf"),
+		include_str!("../programs/collatz.bf"),
+		include_str!("../programs/diamond.bf"),
+		include_str!("../programs/factor.bf"),
+		include_str!("../programs/h.bf"),
+		include_str!("../programs/hello_world_color.bf"),
+		include_str!("../programs/jabh.bf"),
+		include_str!("../programs/newline_test.bf"),
+		include_str!("../programs/numwarp.bf"),
+		include_str!("../programs/serptri.bf"),
+		include_str!("../programs/squares.bf"),
+		include_str!("../programs/test.bf"),
+		include_str!("../programs/yapi.bf"),
+	] {
+		compare_both(raw)?;
+	}
+
+	Ok(())
+}
+
+#[test]
+#[ignore = "takes too long"]
+fn long_programs() -> Result<()> {
+	for raw in [
+		// Compiling awib takes most of a minute without optimizations turned on.
+		include_str!("../programs/awib.bf"),
+		include_str!("../programs/bench.bf"),
+		include_str!("../programs/chess.bf"),
+		include_str!("../programs/hello_world_test.bf"),
+		include_str!("../programs/long.bf"),
+		include_str!("../programs/loops.bf"),
+		include_str!("../programs/mandlebrot.bf"),
+		include_str!("../programs/oobrain.bf"),
+	] {
+		compare_both(raw)?;
+	}
+
+	Ok(())
+}
@@ -0,0 +1,103 @@
+mod program_utils;
+
+use std::io;
+
+use program_utils::{Result, get_program};
+use vmm::{
+	interpret::Interpreter,
+	opt::{NoopStore, OptLevel, Optimizer, PassName, PassPipeline},
+	program::Program,
+	tape::VecTape,
+};
+
+const LEVELS: [OptLevel; 4] = [OptLevel::O0, OptLevel::O1, OptLevel::O2, OptLevel::Os];
+
+fn optimize(raw: &str, pipeline: PassPipeline) -> Result<Program> {
+	Ok(Optimizer::new(get_program(raw)?, NoopStore::new())
+		.and_with_pipeline(pipeline)
+		.optimize()?)
+}
+
+fn run(program: Program) -> Result<Vec<u8>> {
+	let mut interpreter =
+		Interpreter::<VecTape, _, _>::new(program, io::empty(), Vec::<u8>::new());
+
+	interpreter.run()?;
+
+	Ok(interpreter.output().clone())
+}
+
+#[test]
+fn levels_keep_semantics() -> Result<()> {
+	for raw in [
+		include_str!("../programs/hello_world.bf"),
+		include_str!("../programs/squares.bf"),
+	] {
+		let expected = run(get_program(raw)?)?;
+
+		for level in LEVELS {
+			assert_eq!(
+				run(optimize(raw, PassPipeline::level(level))?)?,
+				expected,
+				"output changed at O{level}"
+			);
+		}
+	}
+
+	Ok(())
+}
+
+#[test]
+fn levels_trade_size() -> Result<()> {
+	let raw = include_str!("../programs/squares.bf");
+
+	let [o0, o1, o2, os] = LEVELS.map(|level| {
+		optimize(raw, PassPipeline::level(level))
+			.unwrap()
+			.rough_estimate()
+	});
+
+	assert_eq!(o0, get_program(raw)?.rough_estimate());
+	assert!(o1 < o0);
+	assert!(o2 < o1);
+	assert!(os <= o2);
+
+	Ok(())
+}
+
+#[test]
+fn single_passes_can_be_disabled() -> Result<()> {
+	let raw = "+++[->++<]>.";
+
+	let program = optimize(
+		raw,
+		PassPipeline::default().and_without_pass(PassName::OptimizeScaleAndMoveVal),
+	)?;
+
+	assert!(program.rough_estimate() < get_program(raw)?.rough_estimate());
+	assert_eq!(run(program)?, [6]);
+
+	Ok(())
+}
+
+#[test]
+fn pipelines_load_from_toml() {
+	let pipeline = toml::from_str::<PassPipeline>(
+		r#"
+		level = "O1"
+		enable = ["optimize_find_zero"]
+		disable = ["optimize_clear_cell"]
+		"#,
+	)
+	.unwrap();
+
+	assert_eq!(
+		pipeline.passes(),
+		[
+			PassName::CollapseStackedInstr,
+			PassName::CollapseRelativeInstr,
+			PassName::OptimizeClearLoop,
+			PassName::OptimizeFindZero,
+		]
+	);
+}
@@ -0,0 +1,75 @@
+mod program_utils;
+
+use program_utils::{Result, get_program};
+use vmm::{
+	ir::Instruction,
+	opt::{NoopStore, Optimizer},
+};
+
+const RAW: &str = "++>++[-]<[->+<]>.";
+
+fn optimizer(raw: &str) -> Result<Optimizer<NoopStore>> {
+	Ok(Optimizer::new(get_program(raw)?, NoopStore::new()))
+}
+
+#[test]
+fn no_fuel_makes_no_changes() -> Result<()> {
+	let mut optimizer = optimizer(RAW)?.and_with_fuel(0);
+	let program = optimizer.optimize()?;
+
+	assert_eq!(program.len(), get_program(RAW)?.len());
+	assert_eq!(optimizer.fuel().consumed(), 0);
+	assert!(optimizer.fuel().last_change().is_none());
+
+	Ok(())
+}
+
+#[test]
+fn fuel_limits_changes() -> Result<()> {
+	let mut unlimited = optimizer(RAW)?;
+	let expected = unlimited.optimize()?;
+	let total = unlimited.fuel().consumed();
+
+	assert!(total > 1);
+
+	let mut limited = optimizer(RAW)?.and_with_fuel(total);
+
+	assert_eq!(
+		limited.optimize()?.iter().collect::<Vec<_>>(),
+		expected.iter().collect::<Vec<_>>()
+	);
+
+	let mut previous = get_program(RAW)?.rough_estimate();
+
+	for fuel in 1..total {
+		let mut optimizer = optimizer(RAW)?.and_with_fuel(fuel);
+		let program = optimizer.optimize()?;
+
+		assert_eq!(optimizer.fuel().consumed(), fuel);
+		assert!(optimizer.fuel().is_exhausted());
+		assert!(program.rough_estimate() <= previous);
+
+		previous = program.rough_estimate();
+	}
+
+	Ok(())
+}
+
+#[test]
+fn last_change_is_kept() -> Result<()> {
+	let mut optimizer = optimizer("++")?.and_with_fuel(1);
+	let program = optimizer.optimize()?;
+
+	let change = optimizer.fuel().last_change().unwrap();
+
+	assert_eq!(change.pass(), "collapse_stacked_instr");
+	assert_eq!(
+		change.before(),
+		[Instruction::inc_val(1), Instruction::inc_val(1)]
+	);
+	assert_eq!(change.after(), [Instruction::inc_val(2)]);
+	assert_eq!(change.to_string(), "- inc 1\n- inc 1\n+ inc 2\n");
+	assert!(program.iter().any(|i| *i == Instruction::inc_val(2)));
+
+	Ok(())
+}
@@ -0,0 +1,53 @@
+mod program_utils;
+
+use program_utils::{Result, get_program};
+use vmm::opt::{
+	HashMetadataStore, MetadataStore as _, Optimizer, OptimizerError, OptimizerStats, PassName,
+};
+
+#[test]
+fn records_what_each_pass_did() -> Result<()> {
+	let mut optimizer = Optimizer::new(get_program("++>++[-]<[->+<]>.")?, HashMetadataStore::new());
+
+	optimizer.optimize()?;
+
+	let stats = optimizer.stats();
+	let collapse = stats.get("collapse_stacked_instr").unwrap();
+
+	assert!(collapse.runs() > 0);
+	assert!(collapse.windows() >= collapse.should_run_hits());
+	assert!(collapse.changes() > 0);
+	assert!(collapse.instruction_delta() < 0);
+
+	assert!(
+		stats
+			.iter()
+			.all(|stats| stats.should_run_hits() <= stats.windows())
+	);
+	assert!(
+		stats
+			.iter()
+			.all(|stats| stats.pass().parse::<PassName>().is_ok()),
+		"passes are recorded under the names pipelines use"
+	);
+
+	Ok(())
+}
+
+#[test]
+fn stores_report() -> Result<()> {
+	let mut optimizer = Optimizer::new(get_program("++[-]")?, HashMetadataStore::new());
+
+	optimizer.optimize()?;
+
+	let stored = optimizer
+		.store()
+		.get::<OptimizerStats>(0)
+		.map_err(OptimizerError::from)?
+		.unwrap();
+
+	assert_eq!(&stored, optimizer.stats());
+	assert!(!stored.is_empty());
+
+	Ok(())
+}
@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+
 use std::{
 	error::Error as StdError,
 	fmt::{Display, Formatter, Result as FmtResult},
@@ -6,27 +8,35 @@ use std::{
 
 use vmm::{
 	interpret::{Interpreter, RuntimeError},
+	ir::CellWidth,
+	jit::JitError,
 	opt::{NoopStore, Optimizer, OptimizerError},
 	parse::{ParseError, Parser},
 	program::Program,
+	semantic::{EvalError, LowerError, ParseProgramError, VerifyError},
 	tape::Tape,
 };
 
 pub fn get_program(raw: &str) -> Result<Program, ParseError> {
-	let raw = raw
-		.chars()
-		.filter(|c| matches!(c, '+'..='.' | '>' | '<' | '[' | ']'))
-		.collect::<String>();
-
-	Parser::new(&raw).scan().map(|v| v.into_iter().collect())
+	Parser::new(raw).scan().map(|v| v.into_iter().collect())
 }
 
 pub fn run_program<T: Tape>(program: &str, optimized: bool) -> Result<Vec<u8>, TestError> {
+	run_program_with_cell_width::<T>(program, optimized, CellWidth::U8)
+}
+
+pub fn run_program_with_cell_width<T: Tape>(
+	program: &str,
+	optimized: bool,
+	cell_width: CellWidth,
+) -> Result<Vec<u8>, TestError> {
 	let program = get_program(program)?;
 
 	let output = {
 		let program = if optimized {
-			Optimizer::new(program, NoopStore::new()).optimize()?
+			Optimizer::new(program, NoopStore::new())
+				.and_with_cell_width(cell_width)
+				.optimize()?
 		} else {
 			program
 		};
@@ -47,6 +57,11 @@ pub enum TestError {
 	Parse(ParseError),
 	Optimizer(OptimizerError),
 	Runtime(RuntimeError),
+	Jit(JitError),
+	Lower(LowerError),
+	ParseProgram(ParseProgramError),
+	Verify(VerifyError),
+	Eval(EvalError),
 }
 
 impl Display for TestError {
@@ -55,6 +70,11 @@ impl Display for TestError {
 			Self::Parse(e) => Display::fmt(&e, f),
 			Self::Optimizer(e) => Display::fmt(&e, f),
 			Self::Runtime(e) => Display::fmt(&e, f),
+			Self::Jit(e) => Display::fmt(&e, f),
+			Self::Lower(e) => Display::fmt(&e, f),
+			Self::ParseProgram(e) => Display::fmt(&e, f),
+			Self::Verify(e) => Display::fmt(&e, f),
+			Self::Eval(e) => Display::fmt(&e, f),
 		}
 	}
 }
@@ -65,6 +85,11 @@ impl StdError for TestError {
 			Self::Parse(e) => Some(e),
 			Self::Optimizer(e) => Some(e),
 			Self::Runtime(e) => Some(e),
+			Self::Jit(e) => Some(e),
+			Self::Lower(e) => Some(e),
+			Self::ParseProgram(e) => Some(e),
+			Self::Verify(e) => Some(e),
+			Self::Eval(e) => Some(e),
 		}
 	}
 }
@@ -87,4 +112,34 @@ impl From<RuntimeError> for TestError {
 	}
 }
 
+impl From<JitError> for TestError {
+	fn from(value: JitError) -> Self {
+		Self::Jit(value)
+	}
+}
+
+impl From<LowerError> for TestError {
+	fn from(value: LowerError) -> Self {
+		Self::Lower(value)
+	}
+}
+
+impl From<ParseProgramError> for TestError {
+	fn from(value: ParseProgramError) -> Self {
+		Self::ParseProgram(value)
+	}
+}
+
+impl From<VerifyError> for TestError {
+	fn from(value: VerifyError) -> Self {
+		Self::Verify(value)
+	}
+}
+
+impl From<EvalError> for TestError {
+	fn from(value: EvalError) -> Self {
+		Self::Eval(value)
+	}
+}
+
 pub type Result<T, E = TestError> = std::result::Result<T, E>;
@@ -0,0 +1,150 @@
+#![cfg(not(miri))]
+
+mod program_utils;
+
+use std::{
+	env,
+	ffi::OsString,
+	fs::{self, File},
+	path::PathBuf,
+	process::Command,
+};
+
+use program_utils::{Result, get_program};
+use vmm::{
+	codegen::{Codegen as _, CodegenOptions, RustCodegen, RustEntry},
+	interpret::Interpreter,
+	ir::CellWidth,
+	opt::{NoopStore, Optimizer},
+	tape::{Tape, VecTape},
+};
+
+const INPUT: &[u8] = b"Hello, world!\n";
+
+fn rustc() -> Command {
+	Command::new(env::var_os("RUSTC").unwrap_or_else(|| OsString::from("rustc")))
+}
+
+fn out_dir() -> PathBuf {
+	let dir = env::temp_dir().join("vmm_rust_codegen");
+	fs::create_dir_all(&dir).unwrap();
+
+	dir
+}
+
+fn run_rust(name: &str, code: &str) -> Vec<u8> {
+	let dir = out_dir();
+
+	let source = dir.join(format!("{name}.rs"));
+	let binary = dir.join(name);
+	let input = dir.join(format!("{name}.in"));
+
+	fs::write(&source, code).unwrap();
+	fs::write(&input, INPUT).unwrap();
+
+	let compiled = rustc()
+		.args(["--edition", "2024", "-D", "warnings", "-o"])
+		.arg(&binary)
+		.arg(&source)
+		.status()
+		.unwrap();
+
+	assert!(compiled.success(), "failed to compile {}", source.display());
+
+	Command::new(binary)
+		.stdin(File::open(input).unwrap())
+		.output()
+		.unwrap()
+		.stdout
+}
+
+fn compare<T: Tape>(name: &str, raw: &str, cell_width: CellWidth) -> Result<()> {
+	for optimized in [false, true] {
+		let program = get_program(raw)?;
+
+		let program = if optimized {
+			Optimizer::new(program, NoopStore::new())
+				.and_with_cell_width(cell_width)
+				.optimize()?
+		} else {
+			program
+		};
+
+		let mut interpreter = Interpreter::<T, _, _>::new(program.clone(), INPUT, Vec::new());
+
+		interpreter.run()?;
+
+		let options = CodegenOptions::new()
+			.and_with_tape_len(interpreter.tape().len())
+			.and_with_cell_width(cell_width);
+
+		let code = RustCodegen::new(options).generate(&program).unwrap();
+
+		let output = run_rust(&format!("{name}_{cell_width}_{optimized}"), &code);
+
+		assert_eq!(interpreter.output(), &output);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn hello_world() -> Result<()> {
+	compare::<VecTape>(
+		"hello_world",
+		include_str!("../programs/hello_world.bf"),
+		CellWidth::U8,
+	)
+}
+
+#[test]
+fn reads_input() -> Result<()> {
+	compare::<VecTape>("rot13", include_str!("../programs/rot13.bf"), CellWidth::U8)
+}
+
+#[test]
+fn cell_width() -> Result<()> {
+	let program = include_str!("../programs/cell_size.bf");
+
+	compare::<VecTape<u16>>("cell_size", program, CellWidth::U16)?;
+	compare::<VecTape<u32>>("cell_size", program, CellWidth::U32)
+}
+
+#[test]
+fn library() -> Result<()> {
+	let program = Optimizer::new(
+		get_program(include_str!("../programs/rot13.bf"))?,
+		NoopStore::new(),
+	)
+	.optimize()?;
+
+	let code = RustCodegen::new(CodegenOptions::new())
+		.and_with_entry(RustEntry::Library)
+		.generate(&program)
+		.unwrap();
+
+	let source = out_dir().join("rot13_lib.rs");
+
+	fs::write(&source, code).unwrap();
+
+	let compiled = rustc()
+		.args([
+			"--edition",
+			"2024",
+			"--crate-type",
+			"lib",
+			"--emit",
+			"metadata",
+			"-D",
+			"warnings",
+			"--out-dir",
+		])
+		.arg(out_dir())
+		.arg(&source)
+		.status()
+		.unwrap();
+
+	assert!(compiled.success(), "failed to compile {}", source.display());
+
+	Ok(())
+}
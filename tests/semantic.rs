@@ -0,0 +1,53 @@
+mod program_utils;
+
+use program_utils::{Result, get_program};
+use vmm::{
+	opt::{NoopStore, Optimizer},
+	semantic::{Lowering, ir::Program},
+};
+
+fn lower(raw: &str) -> Result<()> {
+	let program = get_program(raw)?;
+	let optimized = Optimizer::new(program.clone(), NoopStore::new()).optimize()?;
+
+	for program in [program, optimized] {
+		let lowered = Lowering::new().lower(&program)?;
+
+		lowered.verify()?;
+
+		let text = lowered.to_string();
+		let parsed = text.parse::<Program>()?;
+		parsed.verify()?;
+		assert_eq!(parsed.to_string(), text);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn golden() -> Result<()> {
+	let program = get_program("+[->+<]>.,")?;
+	let lowered = Lowering::new().and_with_tape_len(8).lower(&program)?;
+
+	assert_eq!(lowered.to_string(), include_str!("golden/move_cell.koopa"));
+
+	Ok(())
+}
+
+#[test]
+fn hello_world() -> Result<()> {
+	lower(include_str!("../programs/hello_world.bf"))
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn super_instructions() -> Result<()> {
+	lower(include_str!("../programs/numwarp.bf"))?;
+	lower(include_str!("../programs/factor.bf"))
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn reads_input() -> Result<()> {
+	lower(include_str!("../programs/rot13.bf"))
+}
@@ -0,0 +1,133 @@
+#![cfg(not(miri))]
+
+mod program_utils;
+
+use program_utils::{Result, get_program};
+use vmm::{
+	interpret::Interpreter,
+	opt::{NoopStore, Optimizer},
+	semantic::{Evaluator, Lowering, opt::PassManager},
+	tape::VecTape,
+};
+
+const INPUT: &[u8] = b"Hello, world!\n";
+
+fn compare(raw: &str, optimized: bool) -> Result<Vec<u8>> {
+	let program = get_program(raw)?;
+	let program = if optimized {
+		Optimizer::new(program, NoopStore::new()).optimize()?
+	} else {
+		program
+	};
+
+	let mut interpreter = Interpreter::<VecTape, _, _>::new(program.clone(), INPUT, Vec::new());
+
+	interpreter.run_bytecode()?;
+
+	let mut lowered = Lowering::new().lower(&program)?;
+	let mut evaluator = Evaluator::new(&lowered).and_with_input(INPUT);
+
+	evaluator.run()?;
+
+	let output = evaluator.output().to_vec();
+
+	assert_eq!(*interpreter.output(), output);
+
+	PassManager::standard().run_passes(&mut lowered);
+	lowered.verify()?;
+
+	let mut evaluator = Evaluator::new(&lowered).and_with_input(INPUT);
+
+	evaluator.run()?;
+
+	assert_eq!(evaluator.output(), output);
+
+	Ok(output)
+}
+
+fn compare_both(raw: &str) -> Result<Vec<u8>> {
+	let unoptimized = compare(raw, false)?;
+
+	assert_eq!(unoptimized, compare(raw, true)?);
+
+	Ok(unoptimized)
+}
+
+#[test]
+fn hello_world() -> Result<()> {
+	let output = compare_both(include_str!("../programs/hello_world.bf"))?;
+
+	assert_eq!(output, b"Hello World!\n");
+
+	Ok(())
+}
+
+#[test]
+fn cell_size() -> Result<()> {
+	let output = compare_both(include_str!("../programs/cell_size.bf"))?;
+
+	assert_eq!(output, b"8 bit cells\n");
+
+	Ok(())
+}
+
+#[test]
+fn reads_input() -> Result<()> {
+	let raw = include_str!("../programs/rot13.bf");
+
+	assert_eq!(compare(raw, false)?, b"Uryyb, jbeyq!\n");
+
+	compare(raw, true)?;
+
+	Ok(())
+}
+
+// `e.bf`, `fib.bf`, `golden_ratio.bf` and `head.bf` never finish with this input, so they are
+// left out.
+#[test]
+fn short_programs() -> Result<()> {
+	for raw in [
+		include_str!("../programs/a_to_z.bf"),
+		include_str!("../programs/ascii.bf"),
+		include_str!("../programs/bottles.bf"),
+		include_str!("../programs/collatz.bf"),
+		include_str!("../programs/diamond.bf"),
+		include_str!("../programs/factor.bf"),
+		include_str!("../programs/h.bf"),
+		include_str!("../programs/hello_world_color.bf"),
+		include_str!("../programs/hello_world_test.bf"),
+		include_str!("../programs/jabh.bf"),
+		include_str!("../programs/newline_test.bf"),
+		include_str!("../programs/numwarp.bf"),
+		include_str!("../programs/serptri.bf"),
+		include_str!("../programs/squares.bf"),
+		include_str!("../programs/test.bf"),
+		include_str!("../programs/turing.bf"),
+		include_str!("../programs/yapi.bf"),
+	] {
+		compare_both(raw)?;
+	}
+
+	Ok(())
+}
+
+#[test]
+#[ignore = "takes too long"]
+fn long_programs() -> Result<()> {
+	for raw in [
+		include_str!("../programs/array_size.bf"),
+		include_str!("../programs/bench.bf"),
+		include_str!("../programs/chess.bf"),
+		include_str!("../programs/long.bf"),
+		include_str!("../programs/loops.bf"),
+		include_str!("../programs/mandlebrot.bf"),
+		include_str!("../programs/oobrain.bf"),
+	] {
+		compare_both(raw)?;
+	}
+
+	// Optimizing awib takes minutes on its own.
+	compare(include_str!("../programs/awib.bf"), false)?;
+
+	Ok(())
+}
@@ -0,0 +1,153 @@
+use std::{fs, io};
+
+use vmm::{
+	interpret::{BoundsPolicy, Interpreter, RuntimeError},
+	ir::{BlockInstruction, Instruction, SuperInstruction},
+	opt::{NoopStore, Optimizer},
+	parse::Parser,
+	program::{Program, SourceMap, SourceSpan},
+	tape::VecTape,
+};
+
+fn optimize(raw: &str) -> (Program, SourceMap) {
+	let (program, source_map) = Parser::new(raw).scan_with_source_map().unwrap();
+
+	let mut optimizer = Optimizer::new(program.into_iter().collect(), NoopStore::new())
+		.and_with_source_map(source_map);
+
+	let program = optimizer.optimize().unwrap();
+
+	(program, optimizer.source_map().unwrap().clone())
+}
+
+fn find(program: &[Instruction], f: impl Fn(&Instruction) -> bool) -> Option<usize> {
+	program.iter().position(f)
+}
+
+#[test]
+fn optimized_programs_keep_their_source_maps() {
+	for path in ["hello_world.bf", "rot13.bf", "factor.bf", "mandlebrot.bf"] {
+		let raw = fs::read_to_string(format!("./programs/{path}")).unwrap();
+		let (program, source_map) = optimize(&raw);
+
+		assert!(
+			source_map.matches(&program),
+			"source map of {path} is out of step"
+		);
+
+		for entry in &source_map {
+			assert!(entry.span().end() <= raw.len());
+		}
+	}
+}
+
+#[test]
+fn super_instructions_point_at_their_loops() {
+	let raw = ">>+ shift [[-<+>]>]";
+	let (program, source_map) = optimize(raw);
+
+	let index = find(&program, |i| {
+		matches!(i, Instruction::Super(SuperInstruction::ShiftVals { .. }))
+	})
+	.unwrap();
+
+	assert_eq!(
+		source_map.span(&[index]),
+		Some(SourceSpan::new(raw.find('[').unwrap(), raw.len()))
+	);
+}
+
+#[test]
+fn moved_instructions_keep_their_spans() {
+	let raw = "[->+<]>.";
+	let (program, source_map) = optimize(raw);
+
+	let index = find(&program, |i| matches!(i, Instruction::Write { .. })).unwrap();
+
+	assert_eq!(source_map.span(&[index]), Some(SourceSpan::new(7, 8)));
+}
+
+#[test]
+fn nested_instructions_are_mapped() {
+	let raw = ",[>,[-<+>]<.]";
+	let (program, source_map) = optimize(raw);
+
+	let outer = find(&program, |i| {
+		matches!(i, Instruction::Block(BlockInstruction::DynamicLoop(_)))
+	})
+	.unwrap();
+
+	assert_eq!(source_map.span(&[outer]), Some(SourceSpan::new(1, 13)));
+
+	let Instruction::Block(block) = &program[outer] else {
+		unreachable!();
+	};
+
+	let write = find(block, |i| matches!(i, Instruction::Write { .. })).unwrap();
+
+	assert_eq!(
+		source_map.span(&[outer, write]),
+		Some(SourceSpan::new(11, 12))
+	);
+}
+
+#[test]
+fn errors_point_at_the_instruction() {
+	let raw = "+[>+]";
+	let (program, source_map) = optimize(raw);
+
+	let interpreter =
+		Interpreter::<VecTape, _, _>::with_tape_len(program, io::empty(), Vec::new(), 8)
+			.and_with_bounds_policy(BoundsPolicy::Error)
+			.and_with_source_map(source_map);
+
+	let mut tree = interpreter.clone();
+
+	assert!(matches!(
+		tree.run(),
+		Err(RuntimeError::PointerOutOfBounds { .. })
+	));
+	assert_eq!(tree.error_span(), Some(SourceSpan::new(2, 3)));
+
+	let mut bytecode = interpreter;
+
+	assert!(matches!(
+		bytecode.run_bytecode(),
+		Err(RuntimeError::PointerOutOfBounds { .. })
+	));
+	assert_eq!(bytecode.error_span(), Some(SourceSpan::new(2, 3)));
+}
+
+#[test]
+fn profiles_source_spans() {
+	let raw = "+++[->++<]";
+	let (program, source_map) = Parser::new(raw).scan_with_source_map().unwrap();
+
+	let mut interpreter =
+		Interpreter::<VecTape, _, _>::new(program.into_iter().collect(), io::empty(), Vec::new())
+			.and_with_profiler()
+			.and_with_source_map(source_map);
+
+	interpreter.run().unwrap();
+
+	let profile = interpreter.source_profile().unwrap();
+
+	assert_eq!(profile.get(SourceSpan::new(0, 1)), 1);
+	assert_eq!(profile.get(SourceSpan::new(3, 10)), 1);
+	assert_eq!(profile.get(SourceSpan::new(4, 5)), 3);
+	assert_eq!(
+		profile.hottest(1),
+		[(SourceSpan::new(4, 5), 3)],
+		"ties are broken by position"
+	);
+}
+
+#[test]
+#[should_panic(expected = "source map must match the program")]
+fn interpreter_rejects_mismatched_source_maps() {
+	let (program, _) = optimize("+[>+]");
+	let (_, source_map) = Parser::new("+>.").scan_with_source_map().unwrap();
+
+	_ = Interpreter::<VecTape, _, _>::new(program, io::empty(), Vec::<u8>::new())
+		.and_with_source_map(source_map);
+}
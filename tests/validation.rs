@@ -0,0 +1,126 @@
+mod program_utils;
+
+use std::{fs, path::Path};
+
+use program_utils::{Result, get_program, run_program};
+use vmm::{
+	ir::{CellWidth, EofPolicy, Instruction},
+	opt::{NoopStore, Optimizer, Validation, ValidationError},
+	tape::{PtrTape, TAPE_SIZE},
+};
+
+// Optimizing awib takes minutes without optimizations turned on.
+const SLOW: &[&str] = &["awib.bf"];
+
+fn validate(path: &Path) -> Result<()> {
+	let raw = fs::read_to_string(path).unwrap();
+
+	Optimizer::new(get_program(&raw)?, NoopStore::new())
+		.and_with_validation(Validation::new().and_with_input(*b"12\nHello\n"))
+		.optimize()?;
+
+	Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn validates_every_program() -> Result<()> {
+	for entry in fs::read_dir("./programs").unwrap() {
+		let path = entry.unwrap().path();
+
+		if SLOW.iter().any(|slow| path.ends_with(slow)) {
+			continue;
+		}
+
+		validate(&path).inspect_err(|e| eprintln!("{} failed: {e}", path.display()))?;
+	}
+
+	Ok(())
+}
+
+#[test]
+#[ignore = "takes too long"]
+fn validates_slow_programs() -> Result<()> {
+	for slow in SLOW {
+		validate(&Path::new("./programs").join(slow))?;
+	}
+
+	Ok(())
+}
+
+fn check(original: &str, optimized: &str, validation: &Validation) -> Result<(), ValidationError> {
+	validation.validate(
+		&get_program(original).unwrap(),
+		&get_program(optimized).unwrap(),
+		CellWidth::U8,
+		EofPolicy::Zero,
+	)
+}
+
+#[test]
+fn catches_differences() {
+	let validation = Validation::new();
+
+	assert_eq!(check("++.", "++.", &validation), Ok(()));
+	assert!(matches!(
+		check("+.", "++.", &validation),
+		Err(ValidationError::Output { .. })
+	));
+	assert_eq!(
+		check("+>+", "+>", &validation),
+		Err(ValidationError::Tape { cell: 1 })
+	);
+	assert_eq!(
+		check("+>+", "+>", &validation.clone().and_without_tape()),
+		Ok(())
+	);
+	assert_eq!(
+		check(">", "", &validation),
+		Err(ValidationError::Pointer {
+			original: 1,
+			optimized: 0
+		})
+	);
+}
+
+#[test]
+fn compares_what_ran_before_fuel_ran_out() {
+	const LONG: &str = "+.+.+.+.+.+.+.+.+.+.";
+
+	let validation = Validation::new().and_with_fuel(6);
+
+	assert_eq!(check(LONG, LONG, &validation), Ok(()));
+	assert_eq!(check("+-+.+-+.+-+.", "+.+.+.", &validation), Ok(()));
+	assert!(matches!(
+		check(LONG, "+.+.", &validation),
+		Err(ValidationError::Output { .. })
+	));
+	assert!(check(LONG, "+.++.", &validation).is_err());
+}
+
+#[test]
+fn is_opt_in() -> Result<()> {
+	let optimizer = Optimizer::new(get_program("+.")?, NoopStore::new());
+
+	assert!(optimizer.validation().is_none());
+
+	Ok(())
+}
+
+#[test]
+fn keeps_cells_reached_by_wrapping() -> Result<()> {
+	let raw = "+".to_owned() + &">".repeat(TAPE_SIZE) + "[.-]";
+
+	let program = Optimizer::new(get_program(&raw)?, NoopStore::new())
+		.and_with_validation(Validation::new())
+		.optimize()?;
+
+	assert!(
+		program
+			.iter()
+			.any(|instr| matches!(instr, Instruction::Block(..)))
+	);
+	assert_eq!(run_program::<PtrTape>(&raw, true)?, [1]);
+
+	Ok(())
+}
@@ -0,0 +1,110 @@
+#![cfg(not(miri))]
+
+mod program_utils;
+
+use program_utils::{Result, get_program};
+use vmm::{
+	codegen::{Codegen as _, CodegenOptions, WasmCodegen},
+	interpret::Interpreter,
+	ir::CellWidth,
+	opt::{NoopStore, Optimizer},
+	tape::{Tape, VecTape},
+};
+use wasmi::{Caller, Engine, Linker, Module, Store};
+
+const INPUT: &[u8] = b"Hello, world!\n";
+
+#[derive(Default)]
+struct Host {
+	read: usize,
+	output: Vec<u8>,
+}
+
+fn run_wasm(wat: &str) -> Vec<u8> {
+	let engine = Engine::default();
+	let module = Module::new(&engine, &wat::parse_str(wat).unwrap()).unwrap();
+
+	let mut store = Store::new(&engine, Host::default());
+	let mut linker = Linker::new(&engine);
+
+	linker
+		.func_wrap("env", "read", |mut caller: Caller<'_, Host>| {
+			let host = caller.data_mut();
+
+			INPUT.get(host.read).map_or(-1, |&byte| {
+				host.read += 1;
+				i32::from(byte)
+			})
+		})
+		.unwrap();
+	linker
+		.func_wrap("env", "write", |mut caller: Caller<'_, Host>, byte: i32| {
+			caller.data_mut().output.push(byte as u8);
+		})
+		.unwrap();
+
+	let instance = linker
+		.instantiate(&mut store, &module)
+		.unwrap()
+		.start(&mut store)
+		.unwrap();
+
+	instance
+		.get_typed_func::<(), ()>(&store, "run")
+		.unwrap()
+		.call(&mut store, ())
+		.unwrap();
+
+	store.into_data().output
+}
+
+fn compare<T: Tape>(raw: &str, cell_width: CellWidth) -> Result<()> {
+	for optimized in [false, true] {
+		let program = get_program(raw)?;
+
+		let program = if optimized {
+			Optimizer::new(program, NoopStore::new())
+				.and_with_cell_width(cell_width)
+				.optimize()?
+		} else {
+			program
+		};
+
+		let mut interpreter = Interpreter::<T, _, _>::new(program.clone(), INPUT, Vec::new());
+
+		interpreter.run()?;
+
+		let options = CodegenOptions::new()
+			.and_with_tape_len(interpreter.tape().len())
+			.and_with_cell_width(cell_width);
+
+		let code = WasmCodegen::new(options).generate(&program).unwrap();
+
+		assert_eq!(interpreter.output(), &run_wasm(&code));
+	}
+
+	Ok(())
+}
+
+#[test]
+fn hello_world() -> Result<()> {
+	compare::<VecTape>(include_str!("../programs/hello_world.bf"), CellWidth::U8)
+}
+
+#[test]
+fn reads_input() -> Result<()> {
+	compare::<VecTape>(include_str!("../programs/rot13.bf"), CellWidth::U8)
+}
+
+#[test]
+fn cell_width() -> Result<()> {
+	let program = include_str!("../programs/cell_size.bf");
+
+	compare::<VecTape<u16>>(program, CellWidth::U16)?;
+	compare::<VecTape<u32>>(program, CellWidth::U32)
+}
+
+#[test]
+fn super_instructions() -> Result<()> {
+	compare::<VecTape>(include_str!("../programs/numwarp.bf"), CellWidth::U8)
+}